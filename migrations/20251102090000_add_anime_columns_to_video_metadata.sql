@@ -0,0 +1,6 @@
+-- Add migration script here
+-- Anime-specific metadata stored alongside movies and TV shows
+ALTER TABLE video_metadata ADD COLUMN anilist_id INTEGER;
+ALTER TABLE video_metadata ADD COLUMN mal_id INTEGER;
+ALTER TABLE video_metadata ADD COLUMN bangumi_id INTEGER;
+ALTER TABLE video_metadata ADD COLUMN episode_count INTEGER;
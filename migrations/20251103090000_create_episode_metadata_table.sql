@@ -0,0 +1,20 @@
+-- Add migration script here
+-- Episode metadata table (per-episode details for TV shows and anime)
+CREATE TABLE IF NOT EXISTS episode_metadata (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    media_item_id INTEGER NOT NULL,
+    season_number INTEGER NOT NULL,
+    episode_number INTEGER NOT NULL,
+    name TEXT NOT NULL,
+    overview TEXT,
+    still_path TEXT,
+    air_date TEXT,
+    runtime INTEGER,
+    vote_average REAL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (media_item_id) REFERENCES media_items(id) ON DELETE CASCADE,
+    UNIQUE (media_item_id, season_number, episode_number)
+);
+
+CREATE INDEX IF NOT EXISTS idx_episode_metadata_media_item ON episode_metadata(media_item_id);
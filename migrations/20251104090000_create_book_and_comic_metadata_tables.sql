@@ -0,0 +1,44 @@
+-- Add migration script here
+-- Book metadata table
+CREATE TABLE IF NOT EXISTS book_metadata (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    media_item_id INTEGER NOT NULL UNIQUE,
+    isbn TEXT,
+    authors TEXT, -- JSON array
+    publisher TEXT,
+    published_date TEXT,
+    page_count INTEGER,
+    language TEXT,
+    overview TEXT,
+    cover_path TEXT,
+    genres TEXT, -- JSON array
+    rating REAL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (media_item_id) REFERENCES media_items(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_book_metadata_media_item ON book_metadata(media_item_id);
+CREATE INDEX IF NOT EXISTS idx_book_metadata_isbn ON book_metadata(isbn);
+
+-- Comic metadata table
+CREATE TABLE IF NOT EXISTS comic_metadata (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    media_item_id INTEGER NOT NULL UNIQUE,
+    series TEXT,
+    volume INTEGER,
+    issue_number TEXT,
+    authors TEXT, -- JSON array
+    publisher TEXT,
+    published_date TEXT,
+    page_count INTEGER,
+    overview TEXT,
+    cover_path TEXT,
+    genres TEXT, -- JSON array
+    rating REAL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (media_item_id) REFERENCES media_items(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_comic_metadata_media_item ON comic_metadata(media_item_id);
@@ -0,0 +1,11 @@
+-- Add migration script here
+-- User preferences table, one row per user
+CREATE TABLE IF NOT EXISTS user_preferences (
+    user_id INTEGER PRIMARY KEY,
+    language TEXT,
+    default_provider TEXT,
+    items_per_page INTEGER NOT NULL DEFAULT 50,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
@@ -0,0 +1,13 @@
+-- Add migration script here
+-- External subtitle files found next to media items
+CREATE TABLE IF NOT EXISTS subtitles (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    media_item_id INTEGER NOT NULL,
+    file_path TEXT NOT NULL UNIQUE,
+    language TEXT,
+    format TEXT NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (media_item_id) REFERENCES media_items(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_subtitles_media_item ON subtitles(media_item_id);
@@ -0,0 +1,5 @@
+-- Add migration script here
+-- Size plus partial xxHash of the file, used to recognize moved files
+ALTER TABLE media_items ADD COLUMN content_hash TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_media_items_content_hash ON media_items(content_hash);
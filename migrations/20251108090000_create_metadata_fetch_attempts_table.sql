@@ -0,0 +1,13 @@
+-- Add migration script here
+-- Failed metadata lookups, kept until one succeeds. A NULL next_retry_at
+-- means retries are used up and the item needs a manual match.
+CREATE TABLE IF NOT EXISTS metadata_fetch_attempts (
+    media_item_id INTEGER PRIMARY KEY,
+    attempts INTEGER NOT NULL DEFAULT 0,
+    last_error TEXT NOT NULL,
+    next_retry_at TIMESTAMP,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (media_item_id) REFERENCES media_items(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_metadata_fetch_attempts_next_retry ON metadata_fetch_attempts(next_retry_at);
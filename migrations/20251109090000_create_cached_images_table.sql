@@ -0,0 +1,10 @@
+-- Add migration script here
+-- Remote images referenced by metadata, keyed by a hash of their URL. A NULL
+-- downloaded_at means the image has not been fetched yet.
+CREATE TABLE IF NOT EXISTS cached_images (
+    hash TEXT PRIMARY KEY,
+    url TEXT NOT NULL,
+    content_type TEXT,
+    downloaded_at TIMESTAMP,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
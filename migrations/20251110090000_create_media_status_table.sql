@@ -0,0 +1,14 @@
+-- Add migration script here
+-- Watch or read status of a media item, one row per user and item
+CREATE TABLE IF NOT EXISTS media_status (
+    user_id INTEGER NOT NULL,
+    media_item_id INTEGER NOT NULL,
+    status TEXT NOT NULL DEFAULT 'unwatched',
+    position INTEGER NOT NULL DEFAULT 0,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (user_id, media_item_id),
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE,
+    FOREIGN KEY (media_item_id) REFERENCES media_items(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_media_status_media_item ON media_status(media_item_id);
@@ -0,0 +1,4 @@
+-- Add migration script here
+-- Items whose metadata was matched by hand and must not be overwritten by
+-- automatic refreshes
+ALTER TABLE media_items ADD COLUMN metadata_locked BOOLEAN NOT NULL DEFAULT 0;
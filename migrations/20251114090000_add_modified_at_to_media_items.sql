@@ -0,0 +1,5 @@
+-- Add migration script here
+-- File modification time as of the last scan, and whether the file changed
+-- since its metadata was fetched
+ALTER TABLE media_items ADD COLUMN modified_at TIMESTAMP;
+ALTER TABLE media_items ADD COLUMN metadata_stale BOOLEAN NOT NULL DEFAULT 0;
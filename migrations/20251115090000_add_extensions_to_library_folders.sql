@@ -0,0 +1,3 @@
+-- Add migration script here
+-- JSON array of file extensions to scan instead of the media type's defaults
+ALTER TABLE library_folders ADD COLUMN extensions TEXT;
@@ -0,0 +1,12 @@
+-- Add migration script here
+-- Titles pinned to a provider ID, consulted before searching. Titles are
+-- stored normalized (cleaned of release noise, lowercase)
+CREATE TABLE IF NOT EXISTS match_overrides (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    title TEXT NOT NULL,
+    media_type TEXT NOT NULL,
+    provider TEXT NOT NULL,
+    media_id TEXT NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (title, media_type)
+);
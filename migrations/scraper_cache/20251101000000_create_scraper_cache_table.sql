@@ -0,0 +1,10 @@
+-- Add migration script here
+-- Disk tier of the scraper cache, kept in its own database file
+CREATE TABLE IF NOT EXISTS scraper_cache (
+    provider TEXT NOT NULL,
+    media_type TEXT NOT NULL,
+    query TEXT NOT NULL,
+    value BLOB NOT NULL,
+    expires_at INTEGER NOT NULL,
+    PRIMARY KEY (provider, media_type, query)
+);
@@ -0,0 +1,22 @@
+-- Add migration script here
+-- Key cache entries by year and language too; unset values are stored as
+-- NULL so they can't clash with a real year or language. SQLite doesn't treat
+-- NULLs as equal in a primary key, so entries are replaced by the cache itself.
+CREATE TABLE scraper_cache_keyed (
+    provider TEXT NOT NULL,
+    media_type TEXT NOT NULL,
+    query TEXT NOT NULL,
+    year INTEGER,
+    language TEXT,
+    value BLOB NOT NULL,
+    expires_at INTEGER NOT NULL
+);
+
+INSERT INTO scraper_cache_keyed (provider, media_type, query, value, expires_at)
+SELECT provider, media_type, query, value, expires_at FROM scraper_cache;
+
+DROP TABLE scraper_cache;
+
+ALTER TABLE scraper_cache_keyed RENAME TO scraper_cache;
+
+CREATE INDEX idx_scraper_cache_key ON scraper_cache (provider, media_type, query);
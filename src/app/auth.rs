@@ -0,0 +1,155 @@
+//! HS256 access tokens signed with `auth.jwt_secret`
+//!
+//! The server has no login flow yet, so tokens are minted by whoever shares
+//! the secret. Their subject must be a numeric user ID.
+
+use axum::{
+    extract::{FromRequestParts, OptionalFromRequestParts},
+    http::{header::AUTHORIZATION, request::Parts},
+};
+use chrono::{Duration, Utc};
+use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey, Header, Validation};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    Ctx,
+    error::{AuthError, AyiahError},
+};
+
+/// Claims carried by an access token
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Claims {
+    /// Who the token was issued to
+    pub sub: String,
+    /// Expiry as a Unix timestamp
+    pub exp: i64,
+    /// Start of validity as a Unix timestamp, if the issuer set one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nbf: Option<i64>,
+}
+
+/// Sign a token for `subject`, valid for `ttl`
+pub fn issue_token(secret: &str, subject: &str, ttl: Duration) -> Result<String, AuthError> {
+    let now = Utc::now();
+    let claims = Claims {
+        sub: subject.to_string(),
+        exp: (now + ttl).timestamp(),
+        nbf: Some(now.timestamp()),
+    };
+
+    jsonwebtoken::encode(
+        &Header::new(Algorithm::HS256),
+        &claims,
+        &EncodingKey::from_secret(secret.as_bytes()),
+    )
+    .map_err(|_| AuthError::TokenCreation)
+}
+
+/// Check a token's algorithm, signature, expiry and start of validity,
+/// returning its claims
+///
+/// Only HS256 is accepted, `none` in particular is refused.
+pub fn verify_token(secret: &str, token: &str) -> Result<Claims, AuthError> {
+    let mut validation = Validation::new(Algorithm::HS256);
+    validation.validate_nbf = true;
+    validation.leeway = 0;
+
+    jsonwebtoken::decode::<Claims>(
+        token,
+        &DecodingKey::from_secret(secret.as_bytes()),
+        &validation,
+    )
+    .map(|data| data.claims)
+    .map_err(|_| AuthError::InvalidToken)
+}
+
+/// The user whose token authorized a request, read from an
+/// `Authorization: Bearer` header
+///
+/// Tokens carry the user ID as their subject. Extract `Option<AuthUser>` for
+/// routes that also serve anonymous requests; a token that is present but
+/// invalid is still rejected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AuthUser {
+    pub id: i64,
+}
+
+impl AuthUser {
+    fn from_parts(parts: &Parts, ctx: &Ctx) -> Result<Option<Self>, AuthError> {
+        let Some(header) = parts.headers.get(AUTHORIZATION) else {
+            return Ok(None);
+        };
+        let token = header
+            .to_str()
+            .ok()
+            .and_then(|header| header.strip_prefix("Bearer "))
+            .ok_or(AuthError::InvalidToken)?;
+        let claims = verify_token(&ctx.config.read().auth.jwt_secret, token.trim())?;
+        let id = claims.sub.parse().map_err(|_| AuthError::InvalidToken)?;
+
+        Ok(Some(Self { id }))
+    }
+}
+
+impl FromRequestParts<Ctx> for AuthUser {
+    type Rejection = AyiahError;
+
+    async fn from_request_parts(parts: &mut Parts, ctx: &Ctx) -> Result<Self, Self::Rejection> {
+        Ok(Self::from_parts(parts, ctx)?.ok_or(AuthError::MissingAuth)?)
+    }
+}
+
+impl OptionalFromRequestParts<Ctx> for AuthUser {
+    type Rejection = AyiahError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        ctx: &Ctx,
+    ) -> Result<Option<Self>, Self::Rejection> {
+        Ok(Self::from_parts(parts, ctx)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tokens_are_verified() {
+        let token = issue_token("secret", "42", Duration::hours(1)).unwrap();
+        assert_eq!(verify_token("secret", &token).unwrap().sub, "42");
+
+        assert!(verify_token("other-secret", &token).is_err());
+        let expired = issue_token("secret", "42", Duration::seconds(-1)).unwrap();
+        assert!(verify_token("secret", &expired).is_err());
+        let (signing_input, _) = token.rsplit_once('.').unwrap();
+        assert!(verify_token("secret", &format!("{signing_input}.")).is_err());
+        assert!(verify_token("secret", "not-a-token").is_err());
+    }
+
+    #[test]
+    fn test_other_algorithms_and_future_tokens_are_refused() {
+        let key = EncodingKey::from_secret(b"secret");
+        let now = Utc::now().timestamp();
+        let claims = |nbf| Claims {
+            sub: "42".to_string(),
+            exp: now + 3600,
+            nbf,
+        };
+
+        let hs512 =
+            jsonwebtoken::encode(&Header::new(Algorithm::HS512), &claims(None), &key).unwrap();
+        assert!(verify_token("secret", &hs512).is_err());
+
+        // An unsigned token reusing a valid payload
+        let valid = jsonwebtoken::encode(&Header::default(), &claims(None), &key).unwrap();
+        assert_eq!(verify_token("secret", &valid).unwrap().sub, "42");
+        let payload = valid.split('.').nth(1).unwrap();
+        let unsigned = format!("eyJhbGciOiJub25lIiwidHlwIjoiSldUIn0.{payload}.");
+        assert!(verify_token("secret", &unsigned).is_err());
+
+        let future =
+            jsonwebtoken::encode(&Header::default(), &claims(Some(now + 600)), &key).unwrap();
+        assert!(verify_token("secret", &future).is_err());
+    }
+}
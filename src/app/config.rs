@@ -3,15 +3,23 @@ use std::{
     net::SocketAddr,
     path::{Path, PathBuf},
     sync::Arc,
+    time::Duration,
 };
 
+use axum::http::{HeaderName, Method};
 use config::{Config as ConfigBuilder, Environment, File as ConfigFile};
+use notify::{RecursiveMode, Watcher};
 use once_cell::sync::OnceCell;
 use parking_lot::RwLock;
 use serde::{Deserialize, Serialize};
-use tracing::info;
+use tokio::{sync::watch, task::JoinHandle};
+use tracing::{info, warn};
 
-use crate::error::ConfigError;
+use crate::{
+    error::ConfigError,
+    scraper::{CacheStrategy, RateLimitConfig, provider::HttpClientConfig},
+    services::{ImageCacheMode, webhook_notifier::WebhookEvent},
+};
 
 // Global configuration manager instance
 static CONFIG_MANAGER: OnceCell<ConfigManager> = OnceCell::new();
@@ -32,11 +40,28 @@ fn default_config_path() -> PathBuf {
 
 const ENVIRONMENT_PREFIX: &str = "AYIAH";
 
+/// JWT secret shipped in the default configuration
+pub const DEFAULT_JWT_SECRET: &str = "ayiah";
+
+/// Lowest accepted PBKDF2 iteration count
+const MIN_PBKDF2_ITERATIONS: u32 = 10_000;
+
+/// Quiet period after the last file event before the config is reloaded
+const RELOAD_DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// Largest request body accepted by default
+pub const DEFAULT_MAX_BODY_BYTES: usize = 4 * 1024 * 1024;
+
+/// How long a request may take by default before it's answered with 408
+pub const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(120);
+
 /// Configuration manager
 #[derive(Debug, Clone)]
 pub struct ConfigManager {
     config: Arc<RwLock<AppConfig>>,
     config_path: PathBuf,
+    /// Bumped after every successful reload
+    reloads: Arc<watch::Sender<u64>>,
 }
 
 // Application configuration structure
@@ -45,6 +70,9 @@ pub struct AppConfig {
     #[serde(default)]
     pub server: ServerConfig,
 
+    #[serde(default)]
+    pub database: DatabaseConfig,
+
     #[serde(default)]
     pub auth: AuthConfig,
 
@@ -53,15 +81,163 @@ pub struct AppConfig {
 
     #[serde(default)]
     pub scraper: ScraperConfig,
+
+    #[serde(default)]
+    pub providers: ProvidersConfig,
+
+    #[serde(default)]
+    pub webhooks: WebhooksConfig,
+
+    #[serde(default)]
+    pub scanner: ScannerConfig,
+
+    #[serde(default)]
+    pub images: ImagesConfig,
+}
+
+/// Deployment mode, read from `AYIAH_ENV`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RunMode {
+    Development,
+    Production,
+}
+
+impl RunMode {
+    /// `production` (case-insensitive) selects production; anything else is development
+    #[must_use]
+    pub fn from_env() -> Self {
+        match std::env::var("AYIAH_ENV") {
+            Ok(env) if env.eq_ignore_ascii_case("production") => Self::Production,
+            _ => Self::Development,
+        }
+    }
+}
+
+impl AppConfig {
+    /// Check values that deserialize fine but would fail at runtime
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        self.validate_for(RunMode::from_env())
+    }
+
+    /// Validate for an explicit run mode
+    pub fn validate_for(&self, mode: RunMode) -> Result<(), ConfigError> {
+        if self.server.port == 0 {
+            return Err(ConfigError::ParseError(
+                "server.port must be between 1 and 65535".to_string(),
+            ));
+        }
+
+        if self.auth.jwt_secret.trim().is_empty() {
+            return Err(ConfigError::ParseError(
+                "auth.jwt_secret must not be empty".to_string(),
+            ));
+        }
+
+        if mode == RunMode::Production && self.auth.jwt_secret == DEFAULT_JWT_SECRET {
+            return Err(ConfigError::ParseError(
+                "auth.jwt_secret must be changed from the default in production".to_string(),
+            ));
+        }
+
+        self.server.validate_cors()?;
+
+        if self.auth.pbkdf2_iterations < MIN_PBKDF2_ITERATIONS {
+            return Err(ConfigError::ParseError(format!(
+                "auth.pbkdf2_iterations must be at least {MIN_PBKDF2_ITERATIONS}"
+            )));
+        }
+
+        let providers = &self.providers;
+        for (name, settings) in [
+            ("tmdb", &providers.tmdb),
+            ("tvdb", &providers.tvdb),
+            ("anilist", &providers.anilist),
+            ("bangumi", &providers.bangumi),
+            ("kitsu", &providers.kitsu),
+        ] {
+            settings
+                .http
+                .validate()
+                .map_err(|e| ConfigError::ParseError(format!("providers.{name}.http: {e}")))?;
+        }
+
+        Ok(())
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct ServerConfig {
-    #[serde(default)]
     pub host: String,
 
-    #[serde(default)]
     pub port: u16,
+
+    /// Runtime worker threads; defaults to one per CPU core
+    pub workers: Option<usize>,
+
+    /// Origins allowed by CORS, such as `https://ayiah.example`, or `*` for
+    /// any; when empty, any origin is allowed in development and none in
+    /// production
+    pub cors_origins: Vec<String>,
+
+    /// Methods allowed by CORS, or `*` for any; common REST methods when empty
+    pub cors_methods: Vec<String>,
+
+    /// Request headers allowed by CORS, or `*` for any; `Authorization` and
+    /// `Content-Type` when empty
+    pub cors_headers: Vec<String>,
+
+    /// How long shutdown waits for running background jobs; 30 seconds
+    /// when unset
+    pub shutdown_timeout_seconds: Option<u64>,
+
+    /// Largest request body in bytes; `DEFAULT_MAX_BODY_BYTES` when unset
+    pub max_body_bytes: Option<usize>,
+
+    /// Seconds a request may take; `DEFAULT_REQUEST_TIMEOUT` when unset
+    pub request_timeout_seconds: Option<u64>,
+}
+
+impl ServerConfig {
+    /// Check that CORS origins, methods and headers parse
+    fn validate_cors(&self) -> Result<(), ConfigError> {
+        let invalid = |field: &str, value: &str| {
+            ConfigError::ParseError(format!("server.{field}: invalid entry {value:?}"))
+        };
+
+        for origin in self.cors_origins.iter().filter(|origin| *origin != "*") {
+            // An origin is scheme, host and port only, e.g. no trailing slash
+            let parsed =
+                reqwest::Url::parse(origin).map_err(|_| invalid("cors_origins", origin))?;
+            if !matches!(parsed.scheme(), "http" | "https")
+                || parsed.origin().ascii_serialization() != *origin
+            {
+                return Err(invalid("cors_origins", origin));
+            }
+        }
+        for method in self.cors_methods.iter().filter(|method| *method != "*") {
+            Method::from_bytes(method.as_bytes()).map_err(|_| invalid("cors_methods", method))?;
+        }
+        for header in self.cors_headers.iter().filter(|header| *header != "*") {
+            HeaderName::from_bytes(header.as_bytes())
+                .map_err(|_| invalid("cors_headers", header))?;
+        }
+
+        Ok(())
+    }
+
+    /// Largest request body in bytes
+    #[must_use]
+    pub fn max_body_bytes(&self) -> usize {
+        self.max_body_bytes.unwrap_or(DEFAULT_MAX_BODY_BYTES)
+    }
+
+    /// How long a request may take before it's answered with 408
+    #[must_use]
+    pub fn request_timeout(&self) -> Duration {
+        self.request_timeout_seconds
+            .map_or(DEFAULT_REQUEST_TIMEOUT, Duration::from_secs)
+    }
 }
 
 impl Default for ServerConfig {
@@ -69,29 +245,57 @@ impl Default for ServerConfig {
         Self {
             host: "127.0.0.1".to_string(),
             port: 7590,
+            workers: None,
+            cors_origins: Vec::new(),
+            cors_methods: Vec::new(),
+            cors_headers: Vec::new(),
+            shutdown_timeout_seconds: None,
+            max_body_bytes: None,
+            request_timeout_seconds: None,
+        }
+    }
+}
+
+/// SQLite connection pool settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DatabaseConfig {
+    /// Maximum number of pooled connections, at least 1
+    pub pool_size: u32,
+
+    /// Connections kept open even when idle
+    pub min_connections: u32,
+
+    /// How long to wait for a free connection, and for a locked database
+    pub timeout_seconds: u64,
+}
+
+impl Default for DatabaseConfig {
+    fn default() -> Self {
+        Self {
+            pool_size: 5,
+            min_connections: 0,
+            timeout_seconds: 30,
         }
     }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct AuthConfig {
-    #[serde(default)]
     pub jwt_secret: String,
 
-    #[serde(default)]
     pub jwt_expiry_hours: u64,
 
-    #[serde(default)]
     pub pbkdf2_iterations: u32,
 
-    #[serde(default)]
     pub refresh_token_expiry_days: u64,
 }
 
 impl Default for AuthConfig {
     fn default() -> Self {
         Self {
-            jwt_secret: "ayiah".to_string(),
+            jwt_secret: DEFAULT_JWT_SECRET.to_string(),
             jwt_expiry_hours: 24,
             pbkdf2_iterations: 100000,
             refresh_token_expiry_days: 7,
@@ -100,11 +304,10 @@ impl Default for AuthConfig {
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct LoggingConfig {
-    #[serde(default)]
     pub level: String,
 
-    #[serde(default)]
     pub file_path: Option<String>,
 }
 
@@ -118,15 +321,48 @@ impl Default for LoggingConfig {
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct ScraperConfig {
-    #[serde(default)]
+    /// TMDB v3 API key or v4 read access token
     pub tmdb_api_key: Option<String>,
 
-    #[serde(default)]
     pub tvdb_api_key: Option<String>,
 
-    #[serde(default)]
     pub cache_ttl_seconds: u64,
+
+    /// Keep scraper results in memory only, or also on disk
+    pub cache: CacheStrategy,
+
+    /// Metadata language as a BCP 47 tag, e.g. `zh-CN` or `en-US`, used by
+    /// provider requests that don't ask for one
+    #[serde(alias = "language")]
+    pub default_language: Option<String>,
+
+    /// Allow adult titles in search results
+    pub include_adult: bool,
+
+    /// Return every provider's copy of a title instead of collapsing them
+    pub keep_duplicates: bool,
+
+    /// Provider whose match is preferred when several find a title
+    pub default_provider: Option<String>,
+
+    /// Providers preferred next, in order, when the default finds nothing
+    pub fallback_providers: Vec<String>,
+}
+
+impl ScraperConfig {
+    /// The default provider followed by the fallbacks
+    #[must_use]
+    pub fn provider_order(&self) -> Vec<String> {
+        let mut order: Vec<String> = Vec::new();
+        for provider in self.default_provider.iter().chain(&self.fallback_providers) {
+            if !order.contains(provider) {
+                order.push(provider.clone());
+            }
+        }
+        order
+    }
 }
 
 impl Default for ScraperConfig {
@@ -135,10 +371,124 @@ impl Default for ScraperConfig {
             tmdb_api_key: None,
             tvdb_api_key: None,
             cache_ttl_seconds: 86400, // 24 hours
+            cache: CacheStrategy::Memory,
+            default_language: None,
+            include_adult: false,
+            keep_duplicates: false,
+            default_provider: None,
+            fallback_providers: Vec::new(),
         }
     }
 }
 
+/// Per-provider settings, keyed by provider name in the `[providers]` table
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+pub struct ProvidersConfig {
+    #[serde(default)]
+    pub tmdb: ProviderSettings,
+
+    #[serde(default)]
+    pub tvdb: ProviderSettings,
+
+    #[serde(default)]
+    pub anilist: ProviderSettings,
+
+    #[serde(default)]
+    pub bangumi: ProviderSettings,
+
+    #[serde(default)]
+    pub kitsu: ProviderSettings,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProviderSettings {
+    /// Register the provider at startup; on by default
+    #[serde(default = "enabled_by_default")]
+    pub enabled: bool,
+
+    /// Overrides the provider's built-in rate limit
+    #[serde(default)]
+    pub rate_limit: Option<RateLimitConfig>,
+
+    /// Timeouts, user agent, proxy and extra CA for the provider's requests
+    #[serde(default)]
+    pub http: HttpClientConfig,
+}
+
+impl Default for ProviderSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            rate_limit: None,
+            http: HttpClientConfig::default(),
+        }
+    }
+}
+
+const fn enabled_by_default() -> bool {
+    true
+}
+
+/// Webhooks notified of library events
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+pub struct WebhooksConfig {
+    /// URLs each event is POSTed to
+    #[serde(default)]
+    pub urls: Vec<String>,
+
+    /// Events to send; every event is sent when empty
+    #[serde(default)]
+    pub events: Vec<WebhookEvent>,
+}
+
+impl WebhooksConfig {
+    /// Whether `event` should be delivered at all
+    #[must_use]
+    pub fn subscribes_to(&self, event: WebhookEvent) -> bool {
+        !self.urls.is_empty() && (self.events.is_empty() || self.events.contains(&event))
+    }
+}
+
+/// Local copies of poster and backdrop images
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+pub struct ImagesConfig {
+    /// Whether images are downloaded when metadata is saved (`eager`), on
+    /// first request (`lazy`), or not at all (`off`)
+    #[serde(default)]
+    pub mode: ImageCacheMode,
+}
+
+/// Library scanning settings
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+pub struct ScannerConfig {
+    /// Gitignore-style patterns skipped in every library folder, on top of
+    /// the built-in defaults and each folder's `.ayiahignore`
+    #[serde(default)]
+    pub ignore_patterns: Vec<String>,
+
+    /// Fingerprint media files so moved files keep their library entry;
+    /// costs extra reads on every new file
+    #[serde(default)]
+    pub hash_files: bool,
+
+    /// Periodic rescans of every enabled library folder
+    #[serde(default)]
+    pub schedule: ScanScheduleConfig,
+}
+
+/// When libraries are rescanned automatically
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+pub struct ScanScheduleConfig {
+    /// Minutes between scheduled rescans; disabled when unset
+    #[serde(default)]
+    pub interval_minutes: Option<u64>,
+
+    /// Random delay of up to this many seconds added to each run, so several
+    /// instances don't hit shared storage and providers at the same moment
+    #[serde(default)]
+    pub jitter_seconds: u64,
+}
+
 impl ConfigManager {
     /// Create a new configuration manager instance
     pub fn new<P: AsRef<Path>>(config_path: Option<P>) -> Result<Self, ConfigError> {
@@ -149,6 +499,7 @@ impl ConfigManager {
         Ok(Self {
             config: Arc::new(RwLock::new(config)),
             config_path,
+            reloads: Arc::new(watch::Sender::new(0)),
         })
     }
 
@@ -159,14 +510,13 @@ impl ConfigManager {
 
         info!("Initializing configuration from {:?}", config_path);
 
-        let manager = CONFIG_MANAGER.get_or_init(|| match Self::new(Some(&config_path)) {
-            Ok(manager) => manager,
-            Err(e) => {
-                panic!("Failed to initialize configuration: {e}");
-            }
-        });
+        if let Some(manager) = CONFIG_MANAGER.get() {
+            return Ok(manager);
+        }
 
-        Ok(manager)
+        // Surface invalid configuration as an error so startup fails cleanly
+        let manager = Self::new(Some(&config_path))?;
+        Ok(CONFIG_MANAGER.get_or_init(|| manager))
     }
 
     pub fn socket_addr(&self) -> Result<SocketAddr, ConfigError> {
@@ -178,6 +528,11 @@ impl ConfigManager {
         Ok(addr)
     }
 
+    /// Path the configuration was loaded from
+    pub fn config_path(&self) -> &Path {
+        &self.config_path
+    }
+
     /// Get the global configuration manager instance
     pub fn instance() -> Result<&'static Self, ConfigError> {
         CONFIG_MANAGER.get().ok_or(ConfigError::NotInitialized)
@@ -194,21 +549,69 @@ impl ConfigManager {
     }
 
     /// Reload the configuration
+    ///
+    /// The running configuration is only replaced if the new one loads cleanly.
     pub fn reload(&self) -> Result<(), ConfigError> {
-        let new_config = Self::load_config(&self.config_path)?;
-        *self.config.write() = new_config;
-        info!("Configuration reloaded successfully");
-        Ok(())
+        self.reload_from(&self.config_path)
     }
 
     /// Reload the configuration from a specific path
     pub fn reload_from<P: AsRef<Path>>(&self, config_path: P) -> Result<(), ConfigError> {
         let new_config = Self::load_config(config_path)?;
         *self.config.write() = new_config;
+        self.reloads.send_modify(|generation| *generation += 1);
         info!("Configuration reloaded successfully");
         Ok(())
     }
 
+    /// Subscribe to reload events; the value is the reload count
+    pub fn subscribe(&self) -> watch::Receiver<u64> {
+        self.reloads.subscribe()
+    }
+
+    /// Reload the configuration whenever its file changes
+    ///
+    /// Bursts of writes are debounced into a single reload. A config that
+    /// fails to load is logged and the previous one stays active.
+    pub fn watch(&self) -> Result<JoinHandle<()>, ConfigError> {
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let file_name = self.config_path.file_name().map(ToOwned::to_owned);
+        let mut watcher =
+            notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+                let Ok(event) = event else { return };
+                let ours = event
+                    .paths
+                    .iter()
+                    .any(|path| path.file_name() == file_name.as_deref());
+                if ours && !event.kind.is_access() {
+                    let _ = tx.send(());
+                }
+            })
+            .map_err(|e| ConfigError::WatchError(e.to_string()))?;
+
+        // Watch the directory, since editors often replace the file instead of writing it
+        let dir = self
+            .config_path
+            .parent()
+            .filter(|dir| !dir.as_os_str().is_empty())
+            .unwrap_or_else(|| Path::new("."));
+        watcher
+            .watch(dir, RecursiveMode::NonRecursive)
+            .map_err(|e| ConfigError::WatchError(e.to_string()))?;
+
+        let manager = self.clone();
+        Ok(tokio::spawn(async move {
+            let _watcher = watcher;
+            while rx.recv().await.is_some() {
+                while let Ok(Some(())) = tokio::time::timeout(RELOAD_DEBOUNCE, rx.recv()).await {}
+
+                if let Err(e) = manager.reload() {
+                    warn!("Ignoring invalid configuration change: {e}");
+                }
+            }
+        }))
+    }
+
     /// Load configuration from file and environment variables
     fn load_config<P: AsRef<Path>>(config_path: P) -> Result<AppConfig, ConfigError> {
         let config_path = config_path.as_ref();
@@ -252,6 +655,229 @@ impl ConfigManager {
 
         // Deserialize the configuration
         let app_config: AppConfig = config.try_deserialize()?;
+        app_config.validate()?;
         Ok(app_config)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_full_config_deserializes() {
+        let config: AppConfig = toml::from_str(
+            r#"
+            [server]
+            host = "0.0.0.0"
+            port = 8080
+            workers = 2
+            cors_origins = ["https://ayiah.example"]
+
+            [database]
+            pool_size = 8
+
+            [scraper]
+            tmdb_api_key = "key"
+            cache = "persistent"
+
+            [providers.tmdb.rate_limit]
+            max_requests = 20
+            daily_quota = 1000
+
+            [webhooks]
+            urls = ["https://hooks.example/ayiah"]
+            events = ["scan_complete"]
+
+            [scanner]
+            ignore_patterns = ["*.nfo.bak", "Samples/"]
+
+            [scanner.schedule]
+            interval_minutes = 360
+            jitter_seconds = 600
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(config.server.port, 8080);
+        assert_eq!(config.server.workers, Some(2));
+        assert_eq!(config.server.cors_origins, vec!["https://ayiah.example"]);
+        assert_eq!(config.database.pool_size, 8);
+        assert_eq!(config.database.timeout_seconds, 30);
+        assert_eq!(config.scraper.tmdb_api_key.as_deref(), Some("key"));
+        assert_eq!(config.scraper.cache, CacheStrategy::Persistent);
+        assert_eq!(
+            config
+                .providers
+                .tmdb
+                .rate_limit
+                .as_ref()
+                .map(|r| r.max_requests),
+            Some(20)
+        );
+        assert_eq!(
+            config
+                .providers
+                .tmdb
+                .rate_limit
+                .as_ref()
+                .and_then(|r| r.daily_quota),
+            Some(1000)
+        );
+        assert!(config.providers.tvdb.rate_limit.is_none());
+        assert!(config.webhooks.subscribes_to(WebhookEvent::ScanComplete));
+        assert!(
+            !config
+                .webhooks
+                .subscribes_to(WebhookEvent::MetadataComplete)
+        );
+        assert_eq!(config.scanner.ignore_patterns, ["*.nfo.bak", "Samples/"]);
+        assert_eq!(config.scanner.schedule.interval_minutes, Some(360));
+        assert_eq!(config.scanner.schedule.jitter_seconds, 600);
+    }
+
+    #[tokio::test]
+    async fn test_file_change_triggers_reload() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        let manager = ConfigManager::new(Some(&path)).unwrap();
+        let mut reloads = manager.subscribe();
+        let watcher = manager.watch().unwrap();
+
+        let mut config = manager.read().clone();
+        config.server.port = 9123;
+        fs::write(&path, toml::to_string_pretty(&config).unwrap()).unwrap();
+
+        tokio::time::timeout(Duration::from_secs(5), reloads.changed())
+            .await
+            .expect("config was not reloaded")
+            .unwrap();
+        assert_eq!(manager.socket_addr().unwrap().port(), 9123);
+
+        // A malformed edit keeps the running configuration
+        fs::write(&path, "[server\nport = ").unwrap();
+        tokio::time::sleep(RELOAD_DEBOUNCE * 3).await;
+        assert_eq!(manager.socket_addr().unwrap().port(), 9123);
+
+        watcher.abort();
+    }
+
+    #[test]
+    fn test_default_config_is_valid() {
+        assert!(
+            AppConfig::default()
+                .validate_for(RunMode::Development)
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn test_zero_port_is_rejected() {
+        let mut config = AppConfig::default();
+        config.server.port = 0;
+        assert!(matches!(
+            config.validate_for(RunMode::Development),
+            Err(ConfigError::ParseError(msg)) if msg.contains("server.port")
+        ));
+    }
+
+    #[test]
+    fn test_empty_jwt_secret_is_rejected() {
+        let mut config = AppConfig::default();
+        config.auth.jwt_secret = "  ".to_string();
+        assert!(matches!(
+            config.validate_for(RunMode::Development),
+            Err(ConfigError::ParseError(msg)) if msg.contains("jwt_secret")
+        ));
+    }
+
+    #[test]
+    fn test_invalid_provider_proxy_is_rejected() {
+        let mut config = AppConfig::default();
+        config.providers.tvdb.http.proxy = Some("socks5://127.0.0.1:1080".to_string());
+        assert!(matches!(
+            config.validate_for(RunMode::Development),
+            Err(ConfigError::ParseError(msg)) if msg.contains("providers.tvdb.http")
+        ));
+    }
+
+    #[test]
+    fn test_unparseable_cors_origins_are_rejected() {
+        let mut config = AppConfig::default();
+        config.server.cors_origins =
+            vec!["*".to_string(), "https://ayiah.example:8443".to_string()];
+        assert!(config.validate_for(RunMode::Development).is_ok());
+
+        for origin in [
+            "ayiah.example",
+            "https://ayiah.example/",
+            "ftp://ayiah.example",
+        ] {
+            config.server.cors_origins = vec![origin.to_string()];
+            assert!(matches!(
+                config.validate_for(RunMode::Development),
+                Err(ConfigError::ParseError(msg)) if msg.contains("cors_origins")
+            ));
+        }
+    }
+
+    #[test]
+    fn test_low_pbkdf2_iterations_are_rejected() {
+        let mut config = AppConfig::default();
+        config.auth.pbkdf2_iterations = 1;
+        assert!(matches!(
+            config.validate_for(RunMode::Development),
+            Err(ConfigError::ParseError(msg)) if msg.contains("pbkdf2_iterations")
+        ));
+    }
+
+    #[test]
+    fn test_partial_sections_fall_back_to_defaults() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        fs::write(
+            &path,
+            "[server]\nhost = \"0.0.0.0\"\n\n[auth]\njwt_secret = \"a-long-unique-secret\"\n\n[scraper]\ninclude_adult = true\n",
+        )
+        .unwrap();
+
+        let manager = ConfigManager::new(Some(&path)).unwrap();
+        let config = manager.read();
+        assert!(config.validate_for(RunMode::Production).is_ok());
+        assert_eq!(config.server.host, "0.0.0.0");
+        assert_eq!(config.server.port, ServerConfig::default().port);
+        assert_eq!(config.auth.jwt_secret, "a-long-unique-secret");
+        assert_eq!(
+            config.auth.pbkdf2_iterations,
+            AuthConfig::default().pbkdf2_iterations
+        );
+        assert_eq!(
+            config.scraper.cache_ttl_seconds,
+            ScraperConfig::default().cache_ttl_seconds
+        );
+    }
+
+    #[test]
+    fn test_invalid_file_fails_to_load() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        fs::write(&path, "[server]\nhost = \"127.0.0.1\"\nport = 0\n").unwrap();
+
+        assert!(matches!(
+            ConfigManager::new(Some(&path)),
+            Err(ConfigError::ParseError(_))
+        ));
+    }
+
+    #[test]
+    fn test_default_jwt_secret_is_rejected_in_production() {
+        let mut config = AppConfig::default();
+        assert!(matches!(
+            config.validate_for(RunMode::Production),
+            Err(ConfigError::ParseError(msg)) if msg.contains("jwt_secret")
+        ));
+
+        config.auth.jwt_secret = "a-long-unique-secret".to_string();
+        assert!(config.validate_for(RunMode::Production).is_ok());
+    }
+}
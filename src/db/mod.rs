@@ -1,6 +1,9 @@
-use crate::error::AyiahError;
-use sqlx::{Pool, Sqlite, SqlitePool};
-use std::path::PathBuf;
+use crate::{app::config::DatabaseConfig, error::AyiahError};
+use sqlx::{
+    Pool, Sqlite,
+    sqlite::{SqliteConnectOptions, SqlitePoolOptions},
+};
+use std::path::{Path, PathBuf};
 use std::time::Duration;
 
 pub type Database = Pool<Sqlite>;
@@ -19,7 +22,7 @@ fn get_db_path() -> PathBuf {
     )
 }
 
-pub async fn init() -> Result<Database, AyiahError> {
+pub async fn init(config: &DatabaseConfig) -> Result<Database, AyiahError> {
     let db_path = get_db_path();
 
     // Ensure the parent directory exists
@@ -29,16 +32,7 @@ pub async fn init() -> Result<Database, AyiahError> {
         })?;
     }
 
-    let pool = SqlitePool::connect_with(
-        sqlx::sqlite::SqliteConnectOptions::new()
-            .filename(&db_path)
-            .create_if_missing(true)
-            .journal_mode(sqlx::sqlite::SqliteJournalMode::Wal)
-            .synchronous(sqlx::sqlite::SqliteSynchronous::Normal)
-            .busy_timeout(Duration::from_secs(30)),
-    )
-    .await
-    .map_err(|e| AyiahError::DatabaseError(e.to_string()))?;
+    let pool = connect(&db_path, config).await?;
 
     // Run migrations
     sqlx::migrate!("./migrations")
@@ -48,3 +42,83 @@ pub async fn init() -> Result<Database, AyiahError> {
 
     Ok(pool)
 }
+
+/// Open a connection pool to the SQLite file at `path`
+async fn connect(path: &Path, config: &DatabaseConfig) -> Result<Database, AyiahError> {
+    if config.pool_size == 0 {
+        return Err(AyiahError::DatabaseError(
+            "database.pool_size must be at least 1".to_string(),
+        ));
+    }
+
+    let timeout = Duration::from_secs(config.timeout_seconds);
+    SqlitePoolOptions::new()
+        .max_connections(config.pool_size)
+        .min_connections(config.min_connections.min(config.pool_size))
+        .acquire_timeout(timeout)
+        .connect_with(
+            SqliteConnectOptions::new()
+                .filename(path)
+                .create_if_missing(true)
+                .journal_mode(sqlx::sqlite::SqliteJournalMode::Wal)
+                .synchronous(sqlx::sqlite::SqliteSynchronous::Normal)
+                .busy_timeout(timeout),
+        )
+        .await
+        .map_err(|e| AyiahError::DatabaseError(e.to_string()))
+}
+
+/// Create an in-memory database with all migrations applied
+#[cfg(test)]
+pub async fn test_pool() -> Database {
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await
+        .expect("Failed to open in-memory database");
+
+    sqlx::migrate!("./migrations")
+        .run(&pool)
+        .await
+        .expect("Failed to run migrations");
+
+    pool
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_pool_uses_configured_size() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = DatabaseConfig {
+            pool_size: 3,
+            min_connections: 1,
+            timeout_seconds: 5,
+        };
+
+        let pool = connect(&dir.path().join("ayiah.db"), &config)
+            .await
+            .unwrap();
+
+        assert_eq!(pool.options().get_max_connections(), 3);
+        assert_eq!(pool.options().get_min_connections(), 1);
+        assert_eq!(pool.options().get_acquire_timeout(), Duration::from_secs(5));
+    }
+
+    #[tokio::test]
+    async fn test_zero_pool_size_is_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = DatabaseConfig {
+            pool_size: 0,
+            ..Default::default()
+        };
+
+        assert!(
+            connect(&dir.path().join("ayiah.db"), &config)
+                .await
+                .is_err()
+        );
+    }
+}
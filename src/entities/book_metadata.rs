@@ -0,0 +1,195 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+/// Book metadata entity
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct BookMetadata {
+    pub id: i64,
+    pub media_item_id: i64,
+    pub isbn: Option<String>,
+    pub authors: Option<String>, // JSON array
+    pub publisher: Option<String>,
+    pub published_date: Option<String>,
+    pub page_count: Option<i32>,
+    pub language: Option<String>,
+    pub overview: Option<String>,
+    pub cover_path: Option<String>,
+    pub genres: Option<String>, // JSON array
+    pub rating: Option<f64>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Create book metadata request
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CreateBookMetadata {
+    pub media_item_id: i64,
+    pub isbn: Option<String>,
+    pub authors: Vec<String>,
+    pub publisher: Option<String>,
+    pub published_date: Option<String>,
+    pub page_count: Option<i32>,
+    pub language: Option<String>,
+    pub overview: Option<String>,
+    pub cover_path: Option<String>,
+    pub genres: Vec<String>,
+    pub rating: Option<f64>,
+}
+
+impl BookMetadata {
+    /// Create or update book metadata
+    pub async fn upsert(
+        db: &sqlx::SqlitePool,
+        metadata: CreateBookMetadata,
+    ) -> Result<Self, sqlx::Error> {
+        let authors_json =
+            serde_json::to_string(&metadata.authors).unwrap_or_else(|_| "[]".to_string());
+        let genres_json =
+            serde_json::to_string(&metadata.genres).unwrap_or_else(|_| "[]".to_string());
+
+        let result = sqlx::query_as::<_, Self>(
+            r#"
+            INSERT INTO book_metadata (
+                media_item_id, isbn, authors, publisher, published_date,
+                page_count, language, overview, cover_path, genres, rating
+            )
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT(media_item_id) DO UPDATE SET
+                isbn = excluded.isbn,
+                authors = excluded.authors,
+                publisher = excluded.publisher,
+                published_date = excluded.published_date,
+                page_count = excluded.page_count,
+                language = excluded.language,
+                overview = excluded.overview,
+                cover_path = excluded.cover_path,
+                genres = excluded.genres,
+                rating = excluded.rating,
+                updated_at = CURRENT_TIMESTAMP
+            RETURNING *
+            "#,
+        )
+        .bind(metadata.media_item_id)
+        .bind(metadata.isbn)
+        .bind(authors_json)
+        .bind(metadata.publisher)
+        .bind(metadata.published_date)
+        .bind(metadata.page_count)
+        .bind(metadata.language)
+        .bind(metadata.overview)
+        .bind(metadata.cover_path)
+        .bind(genres_json)
+        .bind(metadata.rating)
+        .fetch_one(db)
+        .await?;
+
+        Ok(result)
+    }
+
+    /// Find metadata by media item ID
+    pub async fn find_by_media_item_id(
+        db: &sqlx::SqlitePool,
+        media_item_id: i64,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        let result = sqlx::query_as::<_, Self>(
+            r#"
+            SELECT * FROM book_metadata WHERE media_item_id = ?
+            "#,
+        )
+        .bind(media_item_id)
+        .fetch_optional(db)
+        .await?;
+
+        Ok(result)
+    }
+
+    /// Parse authors from JSON string
+    pub fn parse_authors(&self) -> Vec<String> {
+        self.authors
+            .as_ref()
+            .and_then(|a| serde_json::from_str(a).ok())
+            .unwrap_or_default()
+    }
+
+    /// Parse genres from JSON string
+    pub fn parse_genres(&self) -> Vec<String> {
+        self.genres
+            .as_ref()
+            .and_then(|g| serde_json::from_str(g).ok())
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entities::{
+        CreateLibraryFolder, CreateMediaItem, LibraryFolder, MediaItem, MediaType,
+    };
+
+    #[tokio::test]
+    async fn test_upsert_updates_existing_book() {
+        let db = crate::db::test_pool().await;
+        let folder = LibraryFolder::create(
+            &db,
+            CreateLibraryFolder {
+                name: "Books".to_string(),
+                path: "/books".to_string(),
+                media_type: MediaType::Book,
+                extensions: None,
+            },
+        )
+        .await
+        .unwrap();
+        let item = MediaItem::create(
+            &db,
+            CreateMediaItem {
+                library_folder_id: folder.id,
+                media_type: MediaType::Book,
+                title: "Dune".to_string(),
+                file_path: "/books/Dune.epub".to_string(),
+                file_size: 1,
+                content_hash: None,
+                modified_at: None,
+            },
+        )
+        .await
+        .unwrap();
+
+        let first = BookMetadata::upsert(
+            &db,
+            CreateBookMetadata {
+                media_item_id: item.id,
+                isbn: Some("9780441013593".to_string()),
+                authors: vec!["Frank Herbert".to_string()],
+                page_count: Some(412),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        let updated = BookMetadata::upsert(
+            &db,
+            CreateBookMetadata {
+                media_item_id: item.id,
+                isbn: Some("9780441013593".to_string()),
+                authors: vec!["Frank Herbert".to_string()],
+                genres: vec!["Science Fiction".to_string()],
+                page_count: Some(896),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(first.id, updated.id);
+        let found = BookMetadata::find_by_media_item_id(&db, item.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(found.page_count, Some(896));
+        assert_eq!(found.parse_authors(), ["Frank Herbert"]);
+        assert_eq!(found.parse_genres(), ["Science Fiction"]);
+    }
+}
@@ -0,0 +1,65 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+/// Remote image served from the local image cache
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct CachedImage {
+    /// Hash of `url`, also the file name in the image directory
+    pub hash: String,
+    pub url: String,
+    pub content_type: Option<String>,
+    /// When the image was stored locally; `None` until first fetched
+    pub downloaded_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl CachedImage {
+    /// Find an image by its URL hash
+    pub async fn find_by_hash(
+        db: &sqlx::SqlitePool,
+        hash: &str,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as::<_, Self>("SELECT * FROM cached_images WHERE hash = ?")
+            .bind(hash)
+            .fetch_optional(db)
+            .await
+    }
+
+    /// Remember a remote image, keeping the existing record if there is one
+    pub async fn register(
+        db: &sqlx::SqlitePool,
+        hash: &str,
+        url: &str,
+    ) -> Result<Self, sqlx::Error> {
+        sqlx::query(
+            "INSERT INTO cached_images (hash, url) VALUES (?, ?) ON CONFLICT(hash) DO NOTHING",
+        )
+        .bind(hash)
+        .bind(url)
+        .execute(db)
+        .await?;
+
+        sqlx::query_as::<_, Self>("SELECT * FROM cached_images WHERE hash = ?")
+            .bind(hash)
+            .fetch_one(db)
+            .await
+    }
+
+    /// Record that the image file has been stored
+    pub async fn mark_downloaded(
+        db: &sqlx::SqlitePool,
+        hash: &str,
+        content_type: Option<&str>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "UPDATE cached_images SET content_type = ?, downloaded_at = CURRENT_TIMESTAMP WHERE hash = ?",
+        )
+        .bind(content_type)
+        .bind(hash)
+        .execute(db)
+        .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,141 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+use super::{MediaItemWithMetadata, video_metadata::joined_columns};
+
+/// A user's named collection of media items
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Collection {
+    pub id: i64,
+    pub user_id: i64,
+    pub name: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Create collection request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateCollection {
+    pub name: String,
+}
+
+impl Collection {
+    /// Create a collection for a user
+    pub async fn create(
+        db: &sqlx::SqlitePool,
+        user_id: i64,
+        collection: CreateCollection,
+    ) -> Result<Self, sqlx::Error> {
+        sqlx::query_as::<_, Self>(
+            r#"
+            INSERT INTO collections (user_id, name)
+            VALUES (?, ?)
+            RETURNING *
+            "#,
+        )
+        .bind(user_id)
+        .bind(collection.name)
+        .fetch_one(db)
+        .await
+    }
+
+    /// Find a collection by ID, if it belongs to the user
+    pub async fn find_for_user(
+        db: &sqlx::SqlitePool,
+        user_id: i64,
+        id: i64,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as::<_, Self>("SELECT * FROM collections WHERE id = ? AND user_id = ?")
+            .bind(id)
+            .bind(user_id)
+            .fetch_optional(db)
+            .await
+    }
+
+    /// List a user's collections by name
+    pub async fn list_by_user(
+        db: &sqlx::SqlitePool,
+        user_id: i64,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as::<_, Self>(
+            "SELECT * FROM collections WHERE user_id = ? ORDER BY name COLLATE NOCASE, id",
+        )
+        .bind(user_id)
+        .fetch_all(db)
+        .await
+    }
+
+    /// Delete a collection; its media items stay in the library
+    pub async fn delete(db: &sqlx::SqlitePool, id: i64) -> Result<(), sqlx::Error> {
+        sqlx::query("DELETE FROM collections WHERE id = ?")
+            .bind(id)
+            .execute(db)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Add a media item, returning `false` if it was already in the collection
+    pub async fn add_item(
+        db: &sqlx::SqlitePool,
+        id: i64,
+        media_item_id: i64,
+    ) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO collection_items (collection_id, media_item_id)
+            VALUES (?, ?)
+            ON CONFLICT(collection_id, media_item_id) DO NOTHING
+            "#,
+        )
+        .bind(id)
+        .bind(media_item_id)
+        .execute(db)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Remove a media item, returning `false` if it wasn't in the collection
+    pub async fn remove_item(
+        db: &sqlx::SqlitePool,
+        id: i64,
+        media_item_id: i64,
+    ) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query(
+            "DELETE FROM collection_items WHERE collection_id = ? AND media_item_id = ?",
+        )
+        .bind(id)
+        .bind(media_item_id)
+        .execute(db)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// List the items of a collection with their video metadata, in the
+    /// order they were added
+    pub async fn list_items(
+        db: &sqlx::SqlitePool,
+        id: i64,
+    ) -> Result<Vec<MediaItemWithMetadata>, sqlx::Error> {
+        sqlx::query(concat!(
+            "SELECT ",
+            joined_columns!(),
+            r#"
+            FROM collection_items c
+            JOIN media_items m ON m.id = c.media_item_id
+            LEFT JOIN video_metadata v ON v.media_item_id = m.id
+            WHERE c.collection_id = ?
+            ORDER BY c.added_at, m.id
+            "#,
+        ))
+        .bind(id)
+        .fetch_all(db)
+        .await?
+        .iter()
+        .map(MediaItemWithMetadata::from_joined_row)
+        .collect()
+    }
+}
@@ -0,0 +1,210 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+/// Comic metadata entity
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct ComicMetadata {
+    pub id: i64,
+    pub media_item_id: i64,
+    pub series: Option<String>,
+    pub volume: Option<i32>,
+    pub issue_number: Option<String>,
+    pub authors: Option<String>, // JSON array
+    pub publisher: Option<String>,
+    pub published_date: Option<String>,
+    pub page_count: Option<i32>,
+    pub overview: Option<String>,
+    pub cover_path: Option<String>,
+    pub genres: Option<String>, // JSON array
+    pub rating: Option<f64>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Create comic metadata request
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CreateComicMetadata {
+    pub media_item_id: i64,
+    pub series: Option<String>,
+    pub volume: Option<i32>,
+    /// Issue numbers are not always integers ("1.5", "Annual 1")
+    pub issue_number: Option<String>,
+    pub authors: Vec<String>,
+    pub publisher: Option<String>,
+    pub published_date: Option<String>,
+    pub page_count: Option<i32>,
+    pub overview: Option<String>,
+    pub cover_path: Option<String>,
+    pub genres: Vec<String>,
+    pub rating: Option<f64>,
+}
+
+impl ComicMetadata {
+    /// Create or update comic metadata
+    pub async fn upsert(
+        db: &sqlx::SqlitePool,
+        metadata: CreateComicMetadata,
+    ) -> Result<Self, sqlx::Error> {
+        let authors_json =
+            serde_json::to_string(&metadata.authors).unwrap_or_else(|_| "[]".to_string());
+        let genres_json =
+            serde_json::to_string(&metadata.genres).unwrap_or_else(|_| "[]".to_string());
+
+        let result = sqlx::query_as::<_, Self>(
+            r#"
+            INSERT INTO comic_metadata (
+                media_item_id, series, volume, issue_number, authors, publisher,
+                published_date, page_count, overview, cover_path, genres, rating
+            )
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT(media_item_id) DO UPDATE SET
+                series = excluded.series,
+                volume = excluded.volume,
+                issue_number = excluded.issue_number,
+                authors = excluded.authors,
+                publisher = excluded.publisher,
+                published_date = excluded.published_date,
+                page_count = excluded.page_count,
+                overview = excluded.overview,
+                cover_path = excluded.cover_path,
+                genres = excluded.genres,
+                rating = excluded.rating,
+                updated_at = CURRENT_TIMESTAMP
+            RETURNING *
+            "#,
+        )
+        .bind(metadata.media_item_id)
+        .bind(metadata.series)
+        .bind(metadata.volume)
+        .bind(metadata.issue_number)
+        .bind(authors_json)
+        .bind(metadata.publisher)
+        .bind(metadata.published_date)
+        .bind(metadata.page_count)
+        .bind(metadata.overview)
+        .bind(metadata.cover_path)
+        .bind(genres_json)
+        .bind(metadata.rating)
+        .fetch_one(db)
+        .await?;
+
+        Ok(result)
+    }
+
+    /// Find metadata by media item ID
+    pub async fn find_by_media_item_id(
+        db: &sqlx::SqlitePool,
+        media_item_id: i64,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        let result = sqlx::query_as::<_, Self>(
+            r#"
+            SELECT * FROM comic_metadata WHERE media_item_id = ?
+            "#,
+        )
+        .bind(media_item_id)
+        .fetch_optional(db)
+        .await?;
+
+        Ok(result)
+    }
+
+    /// Parse authors from JSON string
+    pub fn parse_authors(&self) -> Vec<String> {
+        self.authors
+            .as_ref()
+            .and_then(|a| serde_json::from_str(a).ok())
+            .unwrap_or_default()
+    }
+
+    /// Parse genres from JSON string
+    pub fn parse_genres(&self) -> Vec<String> {
+        self.genres
+            .as_ref()
+            .and_then(|g| serde_json::from_str(g).ok())
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entities::{
+        CreateLibraryFolder, CreateMediaItem, LibraryFolder, MediaItem, MediaItemWithMetadata,
+        MediaType,
+    };
+
+    #[tokio::test]
+    async fn test_upsert_updates_existing_comic() {
+        let db = crate::db::test_pool().await;
+        let folder = LibraryFolder::create(
+            &db,
+            CreateLibraryFolder {
+                name: "Comics".to_string(),
+                path: "/comics".to_string(),
+                media_type: MediaType::Comic,
+                extensions: None,
+            },
+        )
+        .await
+        .unwrap();
+        let item = MediaItem::create(
+            &db,
+            CreateMediaItem {
+                library_folder_id: folder.id,
+                media_type: MediaType::Comic,
+                title: "Saga #1".to_string(),
+                file_path: "/comics/Saga 001.cbz".to_string(),
+                file_size: 1,
+                content_hash: None,
+                modified_at: None,
+            },
+        )
+        .await
+        .unwrap();
+
+        let first = ComicMetadata::upsert(
+            &db,
+            CreateComicMetadata {
+                media_item_id: item.id,
+                series: Some("Saga".to_string()),
+                issue_number: Some("1".to_string()),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+        let updated = ComicMetadata::upsert(
+            &db,
+            CreateComicMetadata {
+                media_item_id: item.id,
+                series: Some("Saga".to_string()),
+                volume: Some(1),
+                issue_number: Some("1".to_string()),
+                authors: vec!["Brian K. Vaughan".to_string(), "Fiona Staples".to_string()],
+                publisher: Some("Image".to_string()),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(first.id, updated.id);
+        assert_eq!(updated.volume, Some(1));
+        assert_eq!(
+            updated.parse_authors(),
+            ["Brian K. Vaughan", "Fiona Staples"]
+        );
+
+        let with_metadata = MediaItemWithMetadata::find_by_id(&db, item.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(with_metadata.metadata.is_none());
+        assert!(with_metadata.book_metadata.is_none());
+        assert_eq!(
+            with_metadata.comic_metadata.unwrap().publisher.as_deref(),
+            Some("Image")
+        );
+    }
+}
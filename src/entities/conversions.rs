@@ -0,0 +1,201 @@
+//! Conversions between scraper types and database entities
+//!
+//! Scraped details come in one variant per media type, while the
+//! `video_metadata` table stores a single flattened row. Everything that knows
+//! how each variant maps onto that row lives here.
+
+use super::{CreateEpisodeMetadata, CreateVideoMetadata, VideoMetadata};
+use crate::scraper::{self, ExternalIds, MediaDetails};
+
+/// Provider IDs are strings in scraper types but integers in the database;
+/// IDs that are not numeric are dropped
+fn numeric_id(id: Option<String>) -> Option<i64> {
+    id.and_then(|id| id.parse().ok())
+}
+
+/// Flatten scraped details into the metadata row for `media_item_id`
+impl From<(i64, MediaDetails)> for CreateVideoMetadata {
+    fn from((media_item_id, details): (i64, MediaDetails)) -> Self {
+        match details {
+            MediaDetails::Movie(movie) => Self {
+                media_item_id,
+                tmdb_id: numeric_id(movie.external_ids.tmdb_id),
+                tvdb_id: numeric_id(movie.external_ids.tvdb_id),
+                imdb_id: movie.external_ids.imdb_id,
+                overview: movie.overview,
+                poster_path: movie.poster_path,
+                backdrop_path: movie.backdrop_path,
+                release_date: movie.release_date,
+                runtime: movie.runtime,
+                vote_average: movie.vote_average,
+                vote_count: movie.vote_count,
+                genres: movie.genres,
+                anilist_id: None,
+                mal_id: None,
+                bangumi_id: None,
+                episode_count: None,
+            },
+            MediaDetails::Tv(tv) => Self {
+                media_item_id,
+                tmdb_id: numeric_id(tv.external_ids.tmdb_id),
+                tvdb_id: numeric_id(tv.external_ids.tvdb_id),
+                imdb_id: tv.external_ids.imdb_id,
+                overview: tv.overview,
+                poster_path: tv.poster_path,
+                backdrop_path: tv.backdrop_path,
+                release_date: tv.first_air_date,
+                runtime: tv.episode_run_time.first().copied(),
+                vote_average: tv.vote_average,
+                vote_count: tv.vote_count,
+                genres: tv.genres,
+                anilist_id: None,
+                mal_id: None,
+                bangumi_id: None,
+                episode_count: tv.number_of_episodes,
+            },
+            MediaDetails::Anime(anime) => Self {
+                media_item_id,
+                tmdb_id: numeric_id(anime.external_ids.tmdb_id),
+                tvdb_id: numeric_id(anime.external_ids.tvdb_id),
+                imdb_id: anime.external_ids.imdb_id,
+                overview: anime.overview,
+                poster_path: anime.poster_path,
+                backdrop_path: anime.backdrop_path,
+                release_date: anime.start_date,
+                runtime: None,
+                vote_average: anime.score,
+                vote_count: None,
+                genres: anime.genres,
+                anilist_id: numeric_id(anime.external_ids.anilist_id),
+                mal_id: numeric_id(anime.external_ids.mal_id),
+                bangumi_id: numeric_id(anime.external_ids.bangumi_id),
+                episode_count: anime.episodes,
+            },
+        }
+    }
+}
+
+/// Store a scraped episode against `media_item_id`
+impl From<(i64, scraper::EpisodeMetadata)> for CreateEpisodeMetadata {
+    fn from((media_item_id, episode): (i64, scraper::EpisodeMetadata)) -> Self {
+        Self {
+            media_item_id,
+            season_number: episode.season_number,
+            episode_number: episode.episode_number,
+            name: episode.name,
+            overview: episode.overview,
+            still_path: episode.still_path,
+            air_date: episode.air_date,
+            runtime: episode.runtime,
+            vote_average: episode.vote_average,
+        }
+    }
+}
+
+/// Provider IDs recorded for a media item, for looking it up again
+impl From<&VideoMetadata> for ExternalIds {
+    fn from(metadata: &VideoMetadata) -> Self {
+        let id = |id: Option<i64>| id.map(|id| id.to_string());
+        Self {
+            imdb_id: metadata.imdb_id.clone(),
+            tmdb_id: id(metadata.tmdb_id),
+            tvdb_id: id(metadata.tvdb_id),
+            anilist_id: id(metadata.anilist_id),
+            bangumi_id: id(metadata.bangumi_id),
+            mal_id: id(metadata.mal_id),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+
+    use super::*;
+    use crate::scraper::mock::{anime_details, movie_details};
+
+    #[test]
+    fn test_movie_details_flatten_into_video_metadata() {
+        let mut movie = movie_details("tmdb", "603", "The Matrix", 1999);
+        movie.external_ids = ExternalIds {
+            tmdb_id: Some("603".to_string()),
+            imdb_id: Some("tt0133093".to_string()),
+            tvdb_id: Some("not-a-number".to_string()),
+            ..Default::default()
+        };
+        movie.runtime = Some(136);
+
+        let metadata = CreateVideoMetadata::from((7, MediaDetails::Movie(movie)));
+        assert_eq!(metadata.media_item_id, 7);
+        assert_eq!(metadata.tmdb_id, Some(603));
+        assert_eq!(metadata.tvdb_id, None);
+        assert_eq!(metadata.imdb_id.as_deref(), Some("tt0133093"));
+        assert_eq!(metadata.release_date.as_deref(), Some("1999-01-01"));
+        assert_eq!(metadata.runtime, Some(136));
+        assert_eq!(metadata.anilist_id, None);
+    }
+
+    #[test]
+    fn test_anime_details_keep_anime_ids_and_episode_count() {
+        let anime = anime_details("anilist", "1", "Cowboy Bebop", 1998);
+
+        let metadata = CreateVideoMetadata::from((3, MediaDetails::Anime(anime)));
+        assert_eq!(metadata.anilist_id, Some(1));
+        assert_eq!(metadata.episode_count, Some(26));
+        assert_eq!(metadata.release_date.as_deref(), Some("1998-04-03"));
+        assert_eq!(metadata.runtime, None);
+    }
+
+    #[test]
+    fn test_scraped_episode_converts_to_episode_row() {
+        let episode = scraper::EpisodeMetadata {
+            id: "62085".to_string(),
+            name: "Pilot".to_string(),
+            season_number: 1,
+            episode_number: 1,
+            air_date: Some("2008-01-20".to_string()),
+            overview: None,
+            still_path: None,
+            runtime: Some(58),
+            vote_average: Some(8.2),
+            provider: "tmdb".to_string(),
+        };
+
+        let row = CreateEpisodeMetadata::from((5, episode));
+        assert_eq!(row.media_item_id, 5);
+        assert_eq!((row.season_number, row.episode_number), (1, 1));
+        assert_eq!(row.name, "Pilot");
+        assert_eq!(row.runtime, Some(58));
+    }
+
+    #[test]
+    fn test_stored_metadata_converts_back_to_external_ids() {
+        let metadata = VideoMetadata {
+            id: 1,
+            media_item_id: 1,
+            tmdb_id: Some(1396),
+            tvdb_id: Some(81189),
+            imdb_id: Some("tt0903747".to_string()),
+            overview: None,
+            poster_path: None,
+            backdrop_path: None,
+            release_date: None,
+            runtime: None,
+            vote_average: None,
+            vote_count: None,
+            genres: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            anilist_id: None,
+            mal_id: None,
+            bangumi_id: None,
+            episode_count: None,
+        };
+
+        let ids = ExternalIds::from(&metadata);
+        assert_eq!(ids.tmdb_id.as_deref(), Some("1396"));
+        assert_eq!(ids.tvdb_id.as_deref(), Some("81189"));
+        assert_eq!(ids.imdb_id.as_deref(), Some("tt0903747"));
+        assert_eq!(ids.anilist_id, None);
+    }
+}
@@ -0,0 +1,172 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+/// Episode metadata entity
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct EpisodeMetadata {
+    pub id: i64,
+    pub media_item_id: i64,
+    pub season_number: i32,
+    pub episode_number: i32,
+    pub name: String,
+    pub overview: Option<String>,
+    pub still_path: Option<String>,
+    pub air_date: Option<String>,
+    pub runtime: Option<i32>,
+    pub vote_average: Option<f64>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Create episode metadata request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateEpisodeMetadata {
+    pub media_item_id: i64,
+    pub season_number: i32,
+    pub episode_number: i32,
+    pub name: String,
+    pub overview: Option<String>,
+    pub still_path: Option<String>,
+    pub air_date: Option<String>,
+    pub runtime: Option<i32>,
+    pub vote_average: Option<f64>,
+}
+
+impl EpisodeMetadata {
+    /// Create or update metadata for one episode of a media item
+    pub async fn upsert(
+        db: &sqlx::SqlitePool,
+        metadata: CreateEpisodeMetadata,
+    ) -> Result<Self, sqlx::Error> {
+        let result = sqlx::query_as::<_, Self>(
+            r#"
+            INSERT INTO episode_metadata (
+                media_item_id, season_number, episode_number, name,
+                overview, still_path, air_date, runtime, vote_average
+            )
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT(media_item_id, season_number, episode_number) DO UPDATE SET
+                name = excluded.name,
+                overview = excluded.overview,
+                still_path = excluded.still_path,
+                air_date = excluded.air_date,
+                runtime = excluded.runtime,
+                vote_average = excluded.vote_average,
+                updated_at = CURRENT_TIMESTAMP
+            RETURNING *
+            "#,
+        )
+        .bind(metadata.media_item_id)
+        .bind(metadata.season_number)
+        .bind(metadata.episode_number)
+        .bind(metadata.name)
+        .bind(metadata.overview)
+        .bind(metadata.still_path)
+        .bind(metadata.air_date)
+        .bind(metadata.runtime)
+        .bind(metadata.vote_average)
+        .fetch_one(db)
+        .await?;
+
+        Ok(result)
+    }
+
+    /// Find metadata for a specific episode of a media item
+    pub async fn find_by_item_and_episode(
+        db: &sqlx::SqlitePool,
+        media_item_id: i64,
+        season_number: i32,
+        episode_number: i32,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        let result = sqlx::query_as::<_, Self>(
+            r#"
+            SELECT * FROM episode_metadata
+            WHERE media_item_id = ? AND season_number = ? AND episode_number = ?
+            "#,
+        )
+        .bind(media_item_id)
+        .bind(season_number)
+        .bind(episode_number)
+        .fetch_optional(db)
+        .await?;
+
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entities::{
+        CreateLibraryFolder, CreateMediaItem, LibraryFolder, MediaItem, MediaType,
+    };
+
+    fn episode(media_item_id: i64, episode_number: i32, name: &str) -> CreateEpisodeMetadata {
+        CreateEpisodeMetadata {
+            media_item_id,
+            season_number: 1,
+            episode_number,
+            name: name.to_string(),
+            overview: None,
+            still_path: None,
+            air_date: None,
+            runtime: Some(24),
+            vote_average: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_upsert_updates_existing_episode() {
+        let db = crate::db::test_pool().await;
+        let folder = LibraryFolder::create(
+            &db,
+            CreateLibraryFolder {
+                name: "Shows".to_string(),
+                path: "/shows".to_string(),
+                media_type: MediaType::Tv,
+                extensions: None,
+            },
+        )
+        .await
+        .unwrap();
+        let item = MediaItem::create(
+            &db,
+            CreateMediaItem {
+                library_folder_id: folder.id,
+                media_type: MediaType::Tv,
+                title: "Cowboy Bebop".to_string(),
+                file_path: "/shows/Cowboy Bebop".to_string(),
+                file_size: 1,
+                content_hash: None,
+                modified_at: None,
+            },
+        )
+        .await
+        .unwrap();
+
+        let first = EpisodeMetadata::upsert(&db, episode(item.id, 1, "Asteroid Blues"))
+            .await
+            .unwrap();
+        let updated =
+            EpisodeMetadata::upsert(&db, episode(item.id, 1, "Asteroid Blues (Remastered)"))
+                .await
+                .unwrap();
+        EpisodeMetadata::upsert(&db, episode(item.id, 2, "Stray Dog Strut"))
+            .await
+            .unwrap();
+
+        assert_eq!(first.id, updated.id);
+        let found = EpisodeMetadata::find_by_item_and_episode(&db, item.id, 1, 1)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(found.name, "Asteroid Blues (Remastered)");
+        assert!(
+            EpisodeMetadata::find_by_item_and_episode(&db, item.id, 1, 3)
+                .await
+                .unwrap()
+                .is_none()
+        );
+    }
+}
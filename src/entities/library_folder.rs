@@ -1,6 +1,6 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use sqlx::FromRow;
+use sqlx::{FromRow, types::Json};
 
 use super::MediaType;
 
@@ -12,6 +12,9 @@ pub struct LibraryFolder {
     pub path: String,
     pub media_type: MediaType,
     pub enabled: bool,
+    /// File extensions to scan instead of the media type's defaults
+    #[sqlx(json(nullable))]
+    pub extensions: Option<Vec<String>>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -22,6 +25,24 @@ pub struct CreateLibraryFolder {
     pub name: String,
     pub path: String,
     pub media_type: MediaType,
+    #[serde(default)]
+    pub extensions: Option<Vec<String>>,
+}
+
+/// Normalize file extensions: trimmed, lowercased and without leading dots
+///
+/// Returns `None` when no extensions are left, meaning the media type's
+/// defaults apply.
+#[must_use]
+pub fn normalize_extensions<S: AsRef<str>>(extensions: &[S]) -> Option<Vec<String>> {
+    let mut extensions: Vec<String> = extensions
+        .iter()
+        .map(|ext| ext.as_ref().trim().trim_start_matches('.').to_lowercase())
+        .filter(|ext| !ext.is_empty())
+        .collect();
+    extensions.sort();
+    extensions.dedup();
+    (!extensions.is_empty()).then_some(extensions)
 }
 
 impl LibraryFolder {
@@ -32,14 +53,15 @@ impl LibraryFolder {
     ) -> Result<Self, sqlx::Error> {
         let result = sqlx::query_as::<_, Self>(
             r#"
-            INSERT INTO library_folders (name, path, media_type)
-            VALUES (?, ?, ?)
+            INSERT INTO library_folders (name, path, media_type, extensions)
+            VALUES (?, ?, ?, ?)
             RETURNING *
             "#,
         )
         .bind(folder.name)
         .bind(folder.path)
         .bind(folder.media_type)
+        .bind(folder.extensions.map(Json))
         .fetch_one(db)
         .await?;
 
@@ -86,12 +108,28 @@ impl LibraryFolder {
         Ok(results)
     }
 
+    /// Find the library folder containing a file path
+    pub async fn find_containing(
+        db: &sqlx::SqlitePool,
+        file_path: &str,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        let folders = Self::list_all(db).await?;
+        let file_path = std::path::Path::new(file_path);
+
+        // Prefer the most specific (longest) folder when folders are nested
+        Ok(folders
+            .into_iter()
+            .filter(|f| file_path.starts_with(&f.path))
+            .max_by_key(|f| f.path.len()))
+    }
+
     /// Update library folder
     pub async fn update(&self, db: &sqlx::SqlitePool) -> Result<(), sqlx::Error> {
         sqlx::query(
             r#"
             UPDATE library_folders 
-            SET name = ?, path = ?, media_type = ?, enabled = ?, updated_at = CURRENT_TIMESTAMP
+            SET name = ?, path = ?, media_type = ?, enabled = ?, extensions = ?,
+                updated_at = CURRENT_TIMESTAMP
             WHERE id = ?
             "#,
         )
@@ -99,6 +137,7 @@ impl LibraryFolder {
         .bind(&self.path)
         .bind(self.media_type)
         .bind(self.enabled)
+        .bind(self.extensions.as_ref().map(Json))
         .bind(self.id)
         .execute(db)
         .await?;
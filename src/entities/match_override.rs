@@ -0,0 +1,109 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+use super::MediaType;
+use crate::utils::title::clean_title;
+
+/// A title pinned to a provider ID, used instead of searching
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, FromRow)]
+pub struct MatchOverride {
+    pub id: i64,
+    /// Normalized with [`normalize_override_title`]
+    pub title: String,
+    /// Library media type of the items the override applies to
+    pub media_type: MediaType,
+    pub provider: String,
+    /// Provider-specific ID of the title to match
+    pub media_id: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Create match override request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateMatchOverride {
+    pub title: String,
+    pub media_type: MediaType,
+    pub provider: String,
+    pub media_id: String,
+}
+
+/// Normalize a title the way a media item's title is looked up: release
+/// noise and the year removed, then lowercased
+///
+/// Returns `None` for titles that are empty once cleaned.
+#[must_use]
+pub fn normalize_override_title(title: &str) -> Option<String> {
+    let (title, _) = clean_title(title);
+    let title = title.split_whitespace().collect::<Vec<_>>().join(" ");
+    (!title.is_empty()).then(|| title.to_lowercase())
+}
+
+impl MatchOverride {
+    /// Create an override, replacing any existing one for the same title and
+    /// media type
+    ///
+    /// The title is normalized first; returns `None` if nothing is left of it.
+    pub async fn upsert(
+        db: &sqlx::SqlitePool,
+        request: CreateMatchOverride,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        let Some(title) = normalize_override_title(&request.title) else {
+            return Ok(None);
+        };
+
+        sqlx::query_as::<_, Self>(
+            r#"
+            INSERT INTO match_overrides (title, media_type, provider, media_id)
+            VALUES (?, ?, ?, ?)
+            ON CONFLICT(title, media_type) DO UPDATE SET
+                provider = excluded.provider,
+                media_id = excluded.media_id
+            RETURNING *
+            "#,
+        )
+        .bind(title)
+        .bind(request.media_type)
+        .bind(request.provider)
+        .bind(request.media_id)
+        .fetch_one(db)
+        .await
+        .map(Some)
+    }
+
+    /// Find the override for a raw title, e.g. a media item's file name
+    pub async fn find(
+        db: &sqlx::SqlitePool,
+        title: &str,
+        media_type: MediaType,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        let Some(title) = normalize_override_title(title) else {
+            return Ok(None);
+        };
+
+        sqlx::query_as::<_, Self>(
+            "SELECT * FROM match_overrides WHERE title = ? AND media_type = ?",
+        )
+        .bind(title)
+        .bind(media_type)
+        .fetch_optional(db)
+        .await
+    }
+
+    /// List all overrides by title
+    pub async fn list_all(db: &sqlx::SqlitePool) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as::<_, Self>("SELECT * FROM match_overrides ORDER BY title, media_type")
+            .fetch_all(db)
+            .await
+    }
+
+    /// Delete an override, returning `false` if it didn't exist
+    pub async fn delete(db: &sqlx::SqlitePool, id: i64) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query("DELETE FROM match_overrides WHERE id = ?")
+            .bind(id)
+            .execute(db)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
@@ -1,9 +1,17 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use sqlx::FromRow;
+use sqlx::{FromRow, QueryBuilder, Sqlite};
+
+use super::video_metadata::joined_columns;
 
 /// Media type enum
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+///
+/// The type of a library folder and its items. Anime has no type of its own
+/// here: it lives in `tv` (or `movie`) folders, and only the scraper's
+/// [`MediaType`](crate::scraper::MediaType) tells it apart.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, sqlx::Type,
+)]
 #[sqlx(type_name = "TEXT", rename_all = "lowercase")]
 #[serde(rename_all = "lowercase")]
 pub enum MediaType {
@@ -13,6 +21,11 @@ pub enum MediaType {
     Book,
 }
 
+impl MediaType {
+    /// Every media type, in declaration order
+    pub const ALL: [Self; 4] = [Self::Movie, Self::Tv, Self::Comic, Self::Book];
+}
+
 impl std::fmt::Display for MediaType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -33,6 +46,16 @@ pub struct MediaItem {
     pub title: String,
     pub file_path: String,
     pub file_size: i64,
+    /// Content fingerprint from [`content_hash`](crate::services::file_scanner::content_hash),
+    /// when hashing is enabled
+    pub content_hash: Option<String>,
+    /// Set when the metadata was matched by hand; refreshes skip the item
+    /// unless forced
+    pub metadata_locked: bool,
+    /// Modification time of the file as of the last scan
+    pub modified_at: Option<DateTime<Utc>>,
+    /// Set when the file changed since its metadata was fetched
+    pub metadata_stale: bool,
     pub added_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -45,6 +68,10 @@ pub struct CreateMediaItem {
     pub title: String,
     pub file_path: String,
     pub file_size: i64,
+    #[serde(default)]
+    pub content_hash: Option<String>,
+    #[serde(default)]
+    pub modified_at: Option<DateTime<Utc>>,
 }
 
 impl MediaItem {
@@ -55,8 +82,10 @@ impl MediaItem {
     ) -> Result<Self, sqlx::Error> {
         let result = sqlx::query_as::<_, Self>(
             r#"
-            INSERT INTO media_items (library_folder_id, media_type, title, file_path, file_size)
-            VALUES (?, ?, ?, ?, ?)
+            INSERT INTO media_items
+                (library_folder_id, media_type, title, file_path, file_size, content_hash,
+                 modified_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?)
             RETURNING *
             "#,
         )
@@ -65,6 +94,8 @@ impl MediaItem {
         .bind(item.title)
         .bind(item.file_path)
         .bind(item.file_size)
+        .bind(item.content_hash)
+        .bind(item.modified_at)
         .fetch_one(db)
         .await?;
 
@@ -102,23 +133,133 @@ impl MediaItem {
         Ok(result)
     }
 
-    /// List all media items by type
+    /// Find a media item by content hash, oldest first when several match
+    pub async fn find_by_hash(
+        db: &sqlx::SqlitePool,
+        content_hash: &str,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as::<_, Self>(
+            r#"
+            SELECT * FROM media_items WHERE content_hash = ?
+            ORDER BY id
+            LIMIT 1
+            "#,
+        )
+        .bind(content_hash)
+        .fetch_optional(db)
+        .await
+    }
+
+    /// List a page of media items by type, newest first
     pub async fn list_by_type(
         db: &sqlx::SqlitePool,
         media_type: MediaType,
+        limit: i64,
+        offset: i64,
     ) -> Result<Vec<Self>, sqlx::Error> {
         let results = sqlx::query_as::<_, Self>(
             r#"
-            SELECT * FROM media_items WHERE media_type = ? ORDER BY added_at DESC
+            SELECT * FROM media_items WHERE media_type = ?
+            ORDER BY added_at DESC, id DESC
+            LIMIT ? OFFSET ?
             "#,
         )
         .bind(media_type)
+        .bind(limit)
+        .bind(offset)
         .fetch_all(db)
         .await?;
 
         Ok(results)
     }
 
+    /// List the most recently added media items with their video metadata
+    ///
+    /// One joined query fetches items and metadata together; book and comic
+    /// metadata and subtitles are not included.
+    pub async fn list_recent(
+        db: &sqlx::SqlitePool,
+        media_type: Option<MediaType>,
+        limit: i64,
+    ) -> Result<Vec<super::MediaItemWithMetadata>, sqlx::Error> {
+        sqlx::query(concat!(
+            "SELECT ",
+            joined_columns!(),
+            r#"
+            FROM media_items m
+            LEFT JOIN video_metadata v ON v.media_item_id = m.id
+            WHERE ?1 IS NULL OR m.media_type = ?1
+            ORDER BY m.added_at DESC, m.id DESC
+            LIMIT ?2
+            "#,
+        ))
+        .bind(media_type)
+        .bind(limit)
+        .fetch_all(db)
+        .await?
+        .iter()
+        .map(super::MediaItemWithMetadata::from_joined_row)
+        .collect()
+    }
+
+    /// List all media items in a library folder
+    pub async fn list_by_folder(
+        db: &sqlx::SqlitePool,
+        library_folder_id: i64,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as::<_, Self>(
+            r#"
+            SELECT * FROM media_items WHERE library_folder_id = ?
+            ORDER BY id
+            "#,
+        )
+        .bind(library_folder_id)
+        .fetch_all(db)
+        .await
+    }
+
+    /// List the items in a library folder that have no metadata yet, or
+    /// whose file changed since it was fetched
+    ///
+    /// Items whose earlier lookups failed are left out until their retry is
+    /// due, and for good once they need a manual match.
+    pub async fn list_without_metadata(
+        db: &sqlx::SqlitePool,
+        library_folder_id: i64,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as::<_, Self>(
+            r#"
+            SELECT * FROM media_items
+            WHERE library_folder_id = ?
+              AND (metadata_stale OR id NOT IN (SELECT media_item_id FROM video_metadata))
+              AND id NOT IN (
+                  SELECT media_item_id FROM metadata_fetch_attempts
+                  WHERE next_retry_at IS NULL OR next_retry_at > ?
+              )
+            ORDER BY id
+            "#,
+        )
+        .bind(library_folder_id)
+        .bind(Utc::now())
+        .fetch_all(db)
+        .await
+    }
+
+    /// Count media items by type
+    pub async fn count_by_type(
+        db: &sqlx::SqlitePool,
+        media_type: MediaType,
+    ) -> Result<i64, sqlx::Error> {
+        sqlx::query_scalar(
+            r#"
+            SELECT COUNT(*) FROM media_items WHERE media_type = ?
+            "#,
+        )
+        .bind(media_type)
+        .fetch_one(db)
+        .await
+    }
+
     /// Update media item
     pub async fn update(&self, db: &sqlx::SqlitePool) -> Result<(), sqlx::Error> {
         sqlx::query(
@@ -136,6 +277,145 @@ impl MediaItem {
         Ok(())
     }
 
+    /// Update the file path of a media item after it was moved
+    pub async fn update_file_path(
+        db: &sqlx::SqlitePool,
+        id: i64,
+        file_path: &str,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            UPDATE media_items
+            SET file_path = ?, updated_at = CURRENT_TIMESTAMP
+            WHERE id = ?
+            "#,
+        )
+        .bind(file_path)
+        .bind(id)
+        .execute(db)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Lock or unlock the metadata of a media item
+    pub async fn set_metadata_locked(
+        db: &sqlx::SqlitePool,
+        id: i64,
+        locked: bool,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            UPDATE media_items
+            SET metadata_locked = ?, updated_at = CURRENT_TIMESTAMP
+            WHERE id = ?
+            "#,
+        )
+        .bind(locked)
+        .bind(id)
+        .execute(db)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Point a media item at the file it was moved to, possibly in another
+    /// library folder
+    pub async fn relocate(
+        db: &sqlx::SqlitePool,
+        id: i64,
+        library_folder_id: i64,
+        file_path: &str,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            UPDATE media_items
+            SET library_folder_id = ?, file_path = ?, updated_at = CURRENT_TIMESTAMP
+            WHERE id = ?
+            "#,
+        )
+        .bind(library_folder_id)
+        .bind(file_path)
+        .bind(id)
+        .execute(db)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Record the content hash of a media item's file
+    pub async fn update_content_hash(
+        db: &sqlx::SqlitePool,
+        id: i64,
+        content_hash: &str,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            UPDATE media_items
+            SET content_hash = ?, updated_at = CURRENT_TIMESTAMP
+            WHERE id = ?
+            "#,
+        )
+        .bind(content_hash)
+        .bind(id)
+        .execute(db)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Record the modification time of a media item's file
+    pub async fn update_modified_at(
+        db: &sqlx::SqlitePool,
+        id: i64,
+        modified_at: DateTime<Utc>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query("UPDATE media_items SET modified_at = ? WHERE id = ?")
+            .bind(modified_at)
+            .bind(id)
+            .execute(db)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Record that a media item's file was replaced, flagging its metadata
+    /// for a re-fetch
+    pub async fn mark_changed(
+        db: &sqlx::SqlitePool,
+        id: i64,
+        file_size: i64,
+        modified_at: Option<DateTime<Utc>>,
+        content_hash: Option<&str>,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            UPDATE media_items
+            SET file_size = ?, modified_at = ?, content_hash = ?, metadata_stale = 1,
+                updated_at = CURRENT_TIMESTAMP
+            WHERE id = ?
+            "#,
+        )
+        .bind(file_size)
+        .bind(modified_at)
+        .bind(content_hash)
+        .bind(id)
+        .execute(db)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Clear the stale flag once fresh metadata has been saved
+    pub async fn clear_metadata_stale(db: &sqlx::SqlitePool, id: i64) -> Result<(), sqlx::Error> {
+        sqlx::query("UPDATE media_items SET metadata_stale = 0 WHERE id = ?")
+            .bind(id)
+            .execute(db)
+            .await?;
+
+        Ok(())
+    }
+
     /// Delete media item
     pub async fn delete(db: &sqlx::SqlitePool, id: i64) -> Result<(), sqlx::Error> {
         sqlx::query(
@@ -149,4 +429,83 @@ impl MediaItem {
 
         Ok(())
     }
+
+    /// Delete several media items in one statement, returning those that
+    /// existed
+    ///
+    /// Their metadata, subtitles and other dependent rows go with them.
+    pub async fn delete_many(db: &sqlx::SqlitePool, ids: &[i64]) -> Result<Vec<Self>, sqlx::Error> {
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut query = QueryBuilder::<Sqlite>::new("DELETE FROM media_items WHERE id IN (");
+        let mut values = query.separated(", ");
+        for id in ids {
+            values.push_bind(*id);
+        }
+        query.push(") RETURNING *");
+
+        query.build_query_as().fetch_all(db).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entities::{CreateLibraryFolder, LibraryFolder};
+
+    #[tokio::test]
+    async fn test_list_by_type_pages_newest_first() {
+        let db = crate::db::test_pool().await;
+        let folder = LibraryFolder::create(
+            &db,
+            CreateLibraryFolder {
+                name: "Movies".to_string(),
+                path: "/movies".to_string(),
+                media_type: MediaType::Movie,
+                extensions: None,
+            },
+        )
+        .await
+        .unwrap();
+
+        for i in 0..5 {
+            MediaItem::create(
+                &db,
+                CreateMediaItem {
+                    library_folder_id: folder.id,
+                    media_type: MediaType::Movie,
+                    title: format!("Movie {i}"),
+                    file_path: format!("/movies/{i}.mkv"),
+                    file_size: 1,
+                    content_hash: None,
+                    modified_at: None,
+                },
+            )
+            .await
+            .unwrap();
+        }
+
+        assert_eq!(
+            MediaItem::count_by_type(&db, MediaType::Movie)
+                .await
+                .unwrap(),
+            5
+        );
+        assert_eq!(
+            MediaItem::count_by_type(&db, MediaType::Tv).await.unwrap(),
+            0
+        );
+
+        let titles = |items: Vec<MediaItem>| items.into_iter().map(|i| i.title).collect::<Vec<_>>();
+        let first = MediaItem::list_by_type(&db, MediaType::Movie, 2, 0)
+            .await
+            .unwrap();
+        let last = MediaItem::list_by_type(&db, MediaType::Movie, 2, 4)
+            .await
+            .unwrap();
+        assert_eq!(titles(first), vec!["Movie 4", "Movie 3"]);
+        assert_eq!(titles(last), vec!["Movie 0"]);
+    }
 }
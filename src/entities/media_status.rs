@@ -0,0 +1,120 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, QueryBuilder, Sqlite};
+
+/// How far a user has got with a media item
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "TEXT", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum WatchStatus {
+    #[default]
+    Unwatched,
+    InProgress,
+    /// Watched or read to the end
+    Watched,
+}
+
+/// A user's watch or read status of a media item
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, FromRow)]
+pub struct MediaStatus {
+    pub user_id: i64,
+    pub media_item_id: i64,
+    pub status: WatchStatus,
+    /// Seconds into a video, or the page reached in a book or comic
+    pub position: i64,
+    /// `None` until the user first sets a status
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+/// Set media status request
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SetMediaStatus {
+    pub status: WatchStatus,
+    /// Defaults to the start
+    #[serde(default)]
+    pub position: i64,
+}
+
+impl MediaStatus {
+    /// Status of an item the user has not touched
+    #[must_use]
+    pub fn unwatched(user_id: i64, media_item_id: i64) -> Self {
+        Self {
+            user_id,
+            media_item_id,
+            status: WatchStatus::Unwatched,
+            position: 0,
+            updated_at: None,
+        }
+    }
+
+    /// Get a user's status of a media item, or unwatched if none is saved
+    pub async fn get(
+        db: &sqlx::SqlitePool,
+        user_id: i64,
+        media_item_id: i64,
+    ) -> Result<Self, sqlx::Error> {
+        let result = sqlx::query_as::<_, Self>(
+            r#"
+            SELECT user_id, media_item_id, status, position, updated_at
+            FROM media_status WHERE user_id = ? AND media_item_id = ?
+            "#,
+        )
+        .bind(user_id)
+        .bind(media_item_id)
+        .fetch_optional(db)
+        .await?;
+
+        Ok(result.unwrap_or_else(|| Self::unwatched(user_id, media_item_id)))
+    }
+
+    /// Replace a user's status of a media item
+    pub async fn set(
+        db: &sqlx::SqlitePool,
+        user_id: i64,
+        media_item_id: i64,
+        status: SetMediaStatus,
+    ) -> Result<Self, sqlx::Error> {
+        sqlx::query_as::<_, Self>(
+            r#"
+            INSERT INTO media_status (user_id, media_item_id, status, position)
+            VALUES (?, ?, ?, ?)
+            ON CONFLICT(user_id, media_item_id) DO UPDATE SET
+                status = excluded.status,
+                position = excluded.position,
+                updated_at = CURRENT_TIMESTAMP
+            RETURNING user_id, media_item_id, status, position, updated_at
+            "#,
+        )
+        .bind(user_id)
+        .bind(media_item_id)
+        .bind(status.status)
+        .bind(status.position.max(0))
+        .fetch_one(db)
+        .await
+    }
+
+    /// List the saved statuses of a user for any of the given media items
+    pub async fn list_for_items(
+        db: &sqlx::SqlitePool,
+        user_id: i64,
+        media_item_ids: &[i64],
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        if media_item_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut query = QueryBuilder::<Sqlite>::new(
+            "SELECT user_id, media_item_id, status, position, updated_at \
+             FROM media_status WHERE user_id = ",
+        );
+        query.push_bind(user_id).push(" AND media_item_id IN (");
+        let mut ids = query.separated(", ");
+        for id in media_item_ids {
+            ids.push_bind(*id);
+        }
+        query.push(")");
+
+        query.build_query_as().fetch_all(db).await
+    }
+}
@@ -0,0 +1,134 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+use super::MediaItem;
+
+/// Failed metadata lookups for a media item, kept until one succeeds
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct MetadataFetchAttempt {
+    pub media_item_id: i64,
+    pub attempts: i64,
+    pub last_error: String,
+    /// When the item may be retried; `None` once retries are used up and it
+    /// needs a manual match
+    pub next_retry_at: Option<DateTime<Utc>>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// A media item whose metadata lookups were given up on
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct UnmatchedItem {
+    #[serde(flatten)]
+    #[sqlx(flatten)]
+    pub media_item: MediaItem,
+    pub attempts: i64,
+    pub last_error: String,
+    pub last_attempt_at: DateTime<Utc>,
+}
+
+impl MetadataFetchAttempt {
+    /// Find the failure record of a media item
+    pub async fn find_by_media_item_id(
+        db: &sqlx::SqlitePool,
+        media_item_id: i64,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as::<_, Self>("SELECT * FROM metadata_fetch_attempts WHERE media_item_id = ?")
+            .bind(media_item_id)
+            .fetch_optional(db)
+            .await
+    }
+
+    /// Record a failed lookup, replacing the previous record
+    pub async fn upsert(
+        db: &sqlx::SqlitePool,
+        media_item_id: i64,
+        attempts: i64,
+        last_error: &str,
+        next_retry_at: Option<DateTime<Utc>>,
+    ) -> Result<Self, sqlx::Error> {
+        sqlx::query_as::<_, Self>(
+            r#"
+            INSERT INTO metadata_fetch_attempts (media_item_id, attempts, last_error, next_retry_at)
+            VALUES (?, ?, ?, ?)
+            ON CONFLICT(media_item_id) DO UPDATE SET
+                attempts = excluded.attempts,
+                last_error = excluded.last_error,
+                next_retry_at = excluded.next_retry_at,
+                updated_at = CURRENT_TIMESTAMP
+            RETURNING *
+            "#,
+        )
+        .bind(media_item_id)
+        .bind(attempts)
+        .bind(last_error)
+        .bind(next_retry_at)
+        .fetch_one(db)
+        .await
+    }
+
+    /// Forget the failures of a media item, e.g. once it has metadata
+    pub async fn clear(db: &sqlx::SqlitePool, media_item_id: i64) -> Result<(), sqlx::Error> {
+        sqlx::query("DELETE FROM metadata_fetch_attempts WHERE media_item_id = ?")
+            .bind(media_item_id)
+            .execute(db)
+            .await?;
+
+        Ok(())
+    }
+
+    /// List media items still without metadata whose retry is due at `now`
+    pub async fn list_due(
+        db: &sqlx::SqlitePool,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<MediaItem>, sqlx::Error> {
+        sqlx::query_as::<_, MediaItem>(
+            r#"
+            SELECT m.* FROM media_items m
+            JOIN metadata_fetch_attempts a ON a.media_item_id = m.id
+            WHERE a.next_retry_at <= ?
+              AND m.id NOT IN (SELECT media_item_id FROM video_metadata)
+            ORDER BY a.next_retry_at
+            "#,
+        )
+        .bind(now)
+        .fetch_all(db)
+        .await
+    }
+
+    /// List media items that need a manual match, most recently failed first
+    pub async fn list_unmatched(
+        db: &sqlx::SqlitePool,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<UnmatchedItem>, sqlx::Error> {
+        sqlx::query_as::<_, UnmatchedItem>(
+            r#"
+            SELECT m.*, a.attempts, a.last_error, a.updated_at AS last_attempt_at
+            FROM media_items m
+            JOIN metadata_fetch_attempts a ON a.media_item_id = m.id
+            WHERE a.next_retry_at IS NULL
+              AND m.id NOT IN (SELECT media_item_id FROM video_metadata)
+            ORDER BY a.updated_at DESC, m.id
+            LIMIT ? OFFSET ?
+            "#,
+        )
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(db)
+        .await
+    }
+
+    /// Count media items that need a manual match
+    pub async fn count_unmatched(db: &sqlx::SqlitePool) -> Result<i64, sqlx::Error> {
+        sqlx::query_scalar(
+            r#"
+            SELECT COUNT(*) FROM metadata_fetch_attempts
+            WHERE next_retry_at IS NULL
+              AND media_item_id NOT IN (SELECT media_item_id FROM video_metadata)
+            "#,
+        )
+        .fetch_one(db)
+        .await
+    }
+}
@@ -1,7 +1,33 @@
+mod book_metadata;
+mod cached_image;
+mod collection;
+mod comic_metadata;
+mod conversions;
+mod episode_metadata;
 mod library_folder;
+mod match_override;
 mod media_item;
+mod media_status;
+mod metadata_fetch_attempt;
+mod subtitle;
+mod tag;
+mod user_preferences;
 mod video_metadata;
 
-pub use library_folder::{CreateLibraryFolder, LibraryFolder};
+pub use book_metadata::{BookMetadata, CreateBookMetadata};
+pub use cached_image::CachedImage;
+pub use collection::{Collection, CreateCollection};
+pub use comic_metadata::{ComicMetadata, CreateComicMetadata};
+pub use episode_metadata::{CreateEpisodeMetadata, EpisodeMetadata};
+pub use library_folder::{CreateLibraryFolder, LibraryFolder, normalize_extensions};
+pub use match_override::{CreateMatchOverride, MatchOverride, normalize_override_title};
 pub use media_item::{CreateMediaItem, MediaItem, MediaType};
-pub use video_metadata::{CreateVideoMetadata, MediaItemWithMetadata, VideoMetadata};
+pub use media_status::{MediaStatus, SetMediaStatus, WatchStatus};
+pub use metadata_fetch_attempt::{MetadataFetchAttempt, UnmatchedItem};
+pub use subtitle::{CreateSubtitle, Subtitle};
+pub use tag::{Tag, normalize_tag, normalize_tags};
+pub use user_preferences::{SetUserPreferences, UserPreferences};
+pub use video_metadata::{
+    CreateVideoMetadata, GenreCount, LibraryFilter, LibrarySort, MediaItemWithMetadata,
+    MetadataChange, SortOrder, TagMatch, VideoMetadata,
+};
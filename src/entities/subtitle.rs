@@ -0,0 +1,68 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+/// External subtitle file belonging to a media item
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Subtitle {
+    pub id: i64,
+    pub media_item_id: i64,
+    pub file_path: String,
+    /// Language tag taken from the file name, e.g. `en` in `Movie.en.srt`
+    pub language: Option<String>,
+    /// File extension, e.g. `srt` or `ass`
+    pub format: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Create subtitle request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateSubtitle {
+    pub media_item_id: i64,
+    pub file_path: String,
+    pub language: Option<String>,
+    pub format: String,
+}
+
+impl Subtitle {
+    /// Record a subtitle file, returning `None` if its path is already known
+    pub async fn create_if_missing(
+        db: &sqlx::SqlitePool,
+        subtitle: CreateSubtitle,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        let result = sqlx::query_as::<_, Self>(
+            r#"
+            INSERT INTO subtitles (media_item_id, file_path, language, format)
+            VALUES (?, ?, ?, ?)
+            ON CONFLICT(file_path) DO NOTHING
+            RETURNING *
+            "#,
+        )
+        .bind(subtitle.media_item_id)
+        .bind(subtitle.file_path)
+        .bind(subtitle.language)
+        .bind(subtitle.format)
+        .fetch_optional(db)
+        .await?;
+
+        Ok(result)
+    }
+
+    /// List subtitles for a media item
+    pub async fn list_by_media_item(
+        db: &sqlx::SqlitePool,
+        media_item_id: i64,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        let results = sqlx::query_as::<_, Self>(
+            r#"
+            SELECT * FROM subtitles WHERE media_item_id = ?
+            ORDER BY language, file_path
+            "#,
+        )
+        .bind(media_item_id)
+        .fetch_all(db)
+        .await?;
+
+        Ok(results)
+    }
+}
@@ -0,0 +1,218 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, QueryBuilder, Sqlite};
+
+use super::{MediaItemWithMetadata, video_metadata::joined_columns};
+
+/// A user-defined tag
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, FromRow)]
+pub struct Tag {
+    pub id: i64,
+    pub name: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Normalize a tag name: trimmed, inner whitespace collapsed and lowercased
+///
+/// Returns `None` for names that are empty once trimmed.
+#[must_use]
+pub fn normalize_tag(name: &str) -> Option<String> {
+    let name = name.split_whitespace().collect::<Vec<_>>().join(" ");
+    (!name.is_empty()).then(|| name.to_lowercase())
+}
+
+/// Normalize tag names, dropping empty ones and duplicates
+#[must_use]
+pub fn normalize_tags<S: AsRef<str>>(names: &[S]) -> Vec<String> {
+    let mut tags: Vec<String> = names
+        .iter()
+        .filter_map(|name| normalize_tag(name.as_ref()))
+        .collect();
+    tags.sort();
+    tags.dedup();
+    tags
+}
+
+impl Tag {
+    /// Attach tags to a media item, creating the ones that don't exist yet
+    ///
+    /// Names are normalized first, and tags the item already has are left
+    /// alone. Returns the item's tags afterwards.
+    pub async fn add_to_item<S: AsRef<str>>(
+        db: &sqlx::SqlitePool,
+        media_item_id: i64,
+        names: &[S],
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        let mut tx = db.begin().await?;
+        for name in normalize_tags(names) {
+            // Updating on conflict lets RETURNING report existing tags too
+            let tag_id: i64 = sqlx::query_scalar(
+                r#"
+                INSERT INTO tags (name) VALUES (?)
+                ON CONFLICT(name) DO UPDATE SET name = excluded.name
+                RETURNING id
+                "#,
+            )
+            .bind(name)
+            .fetch_one(&mut *tx)
+            .await?;
+
+            sqlx::query(
+                r#"
+                INSERT INTO media_tags (media_item_id, tag_id) VALUES (?, ?)
+                ON CONFLICT(media_item_id, tag_id) DO NOTHING
+                "#,
+            )
+            .bind(media_item_id)
+            .bind(tag_id)
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+
+        Self::list_for_item(db, media_item_id).await
+    }
+
+    /// Detach a tag from a media item, returning `false` if it wasn't attached
+    ///
+    /// Tags no longer attached to any item are deleted.
+    pub async fn remove_from_item(
+        db: &sqlx::SqlitePool,
+        media_item_id: i64,
+        name: &str,
+    ) -> Result<bool, sqlx::Error> {
+        let Some(name) = normalize_tag(name) else {
+            return Ok(false);
+        };
+
+        let result = sqlx::query(
+            r#"
+            DELETE FROM media_tags
+            WHERE media_item_id = ? AND tag_id = (SELECT id FROM tags WHERE name = ?)
+            "#,
+        )
+        .bind(media_item_id)
+        .bind(name)
+        .execute(db)
+        .await?;
+        sqlx::query("DELETE FROM tags WHERE id NOT IN (SELECT tag_id FROM media_tags)")
+            .execute(db)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// List the tags of a media item by name
+    pub async fn list_for_item(
+        db: &sqlx::SqlitePool,
+        media_item_id: i64,
+    ) -> Result<Vec<Self>, sqlx::Error> {
+        sqlx::query_as::<_, Self>(
+            r#"
+            SELECT t.* FROM tags t
+            JOIN media_tags mt ON mt.tag_id = t.id
+            WHERE mt.media_item_id = ?
+            ORDER BY t.name
+            "#,
+        )
+        .bind(media_item_id)
+        .fetch_all(db)
+        .await
+    }
+
+    /// Tag names of each of the given media items, sorted
+    pub async fn names_for_items(
+        db: &sqlx::SqlitePool,
+        media_item_ids: &[i64],
+    ) -> Result<HashMap<i64, Vec<String>>, sqlx::Error> {
+        let mut names: HashMap<i64, Vec<String>> = HashMap::new();
+        if media_item_ids.is_empty() {
+            return Ok(names);
+        }
+
+        let mut query = QueryBuilder::<Sqlite>::new(
+            "SELECT mt.media_item_id, t.name FROM media_tags mt \
+             JOIN tags t ON t.id = mt.tag_id WHERE mt.media_item_id IN (",
+        );
+        let mut ids = query.separated(", ");
+        for id in media_item_ids {
+            ids.push_bind(*id);
+        }
+        query.push(") ORDER BY t.name");
+
+        let rows: Vec<(i64, String)> = query.build_query_as().fetch_all(db).await?;
+        for (media_item_id, name) in rows {
+            names.entry(media_item_id).or_default().push(name);
+        }
+        Ok(names)
+    }
+
+    /// List a page of the media items carrying a tag, newest first
+    ///
+    /// Items come with their video metadata and tags.
+    pub async fn list_items(
+        db: &sqlx::SqlitePool,
+        name: &str,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<MediaItemWithMetadata>, sqlx::Error> {
+        let mut items = sqlx::query(concat!(
+            "SELECT ",
+            joined_columns!(),
+            r#"
+            FROM media_tags mt
+            JOIN tags t ON t.id = mt.tag_id
+            JOIN media_items m ON m.id = mt.media_item_id
+            LEFT JOIN video_metadata v ON v.media_item_id = m.id
+            WHERE t.name = ?
+            ORDER BY m.added_at DESC, m.id DESC
+            LIMIT ? OFFSET ?
+            "#,
+        ))
+        .bind(normalize_tag(name))
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(db)
+        .await?
+        .iter()
+        .map(MediaItemWithMetadata::from_joined_row)
+        .collect::<Result<Vec<_>, _>>()?;
+
+        MediaItemWithMetadata::attach_tags(db, &mut items).await?;
+        Ok(items)
+    }
+
+    /// Count the media items carrying a tag
+    pub async fn count_items(db: &sqlx::SqlitePool, name: &str) -> Result<i64, sqlx::Error> {
+        sqlx::query_scalar(
+            r#"
+            SELECT COUNT(*) FROM media_tags mt
+            JOIN tags t ON t.id = mt.tag_id
+            WHERE t.name = ?
+            "#,
+        )
+        .bind(normalize_tag(name))
+        .fetch_one(db)
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tags_are_normalized_and_deduplicated() {
+        assert_eq!(
+            normalize_tag("  Date   Night "),
+            Some("date night".to_string())
+        );
+        assert_eq!(normalize_tag(" \t "), None);
+        assert_eq!(
+            normalize_tags(&["Cozy", "cozy", " COZY ", "", "Rainy Day"]),
+            ["cozy", "rainy day"]
+        );
+    }
+}
@@ -0,0 +1,154 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+use crate::{DEFAULT_PER_PAGE, MAX_PER_PAGE};
+
+/// Per-user preferences
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, FromRow)]
+pub struct UserPreferences {
+    pub user_id: i64,
+    /// Preferred metadata language as a BCP 47 tag; the server default when unset
+    pub language: Option<String>,
+    /// Provider to search first; the server default when unset
+    pub default_provider: Option<String>,
+    pub items_per_page: i64,
+}
+
+/// Set user preferences request; omitted fields fall back to their defaults
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SetUserPreferences {
+    pub language: Option<String>,
+    pub default_provider: Option<String>,
+    pub items_per_page: Option<u32>,
+}
+
+impl UserPreferences {
+    /// Preferences for a user who has not saved any
+    #[must_use]
+    pub fn defaults(user_id: i64) -> Self {
+        Self {
+            user_id,
+            language: None,
+            default_provider: None,
+            items_per_page: i64::from(DEFAULT_PER_PAGE),
+        }
+    }
+
+    /// Get a user's preferences, or the defaults if none are saved
+    ///
+    /// Returns `None` if the user does not exist.
+    pub async fn get(db: &sqlx::SqlitePool, user_id: i64) -> Result<Option<Self>, sqlx::Error> {
+        sqlx::query_as::<_, Self>(
+            r#"
+            SELECT users.id AS user_id, p.language, p.default_provider,
+                COALESCE(p.items_per_page, ?) AS items_per_page
+            FROM users
+            LEFT JOIN user_preferences p ON p.user_id = users.id
+            WHERE users.id = ?
+            "#,
+        )
+        .bind(i64::from(DEFAULT_PER_PAGE))
+        .bind(user_id)
+        .fetch_optional(db)
+        .await
+    }
+
+    /// Replace a user's preferences
+    ///
+    /// Returns `None` if the user does not exist.
+    pub async fn set(
+        db: &sqlx::SqlitePool,
+        user_id: i64,
+        preferences: SetUserPreferences,
+    ) -> Result<Option<Self>, sqlx::Error> {
+        let items_per_page = preferences
+            .items_per_page
+            .unwrap_or(DEFAULT_PER_PAGE)
+            .clamp(1, MAX_PER_PAGE);
+
+        let result = sqlx::query_as::<_, Self>(
+            r#"
+            INSERT INTO user_preferences (user_id, language, default_provider, items_per_page)
+            SELECT id, ?, ?, ? FROM users WHERE id = ?
+            ON CONFLICT(user_id) DO UPDATE SET
+                language = excluded.language,
+                default_provider = excluded.default_provider,
+                items_per_page = excluded.items_per_page,
+                updated_at = CURRENT_TIMESTAMP
+            RETURNING user_id, language, default_provider, items_per_page
+            "#,
+        )
+        .bind(preferences.language)
+        .bind(preferences.default_provider)
+        .bind(i64::from(items_per_page))
+        .bind(user_id)
+        .fetch_optional(db)
+        .await?;
+
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn seed_user(db: &sqlx::SqlitePool) -> i64 {
+        sqlx::query_scalar(
+            "INSERT INTO users (username, email, password_hash) VALUES ('admin', 'a@b.c', 'x') RETURNING id",
+        )
+        .fetch_one(db)
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_set_then_get_round_trips() {
+        let db = crate::db::test_pool().await;
+        let user_id = seed_user(&db).await;
+        assert_eq!(
+            UserPreferences::get(&db, user_id).await.unwrap(),
+            Some(UserPreferences::defaults(user_id))
+        );
+
+        let saved = UserPreferences::set(
+            &db,
+            user_id,
+            SetUserPreferences {
+                language: Some("ja-JP".to_string()),
+                default_provider: Some("anilist".to_string()),
+                items_per_page: Some(1000),
+            },
+        )
+        .await
+        .unwrap()
+        .unwrap();
+        assert_eq!(saved.items_per_page, i64::from(MAX_PER_PAGE));
+        assert_eq!(
+            UserPreferences::get(&db, user_id).await.unwrap(),
+            Some(saved)
+        );
+
+        // Omitted fields go back to their defaults
+        let reset = UserPreferences::set(
+            &db,
+            user_id,
+            SetUserPreferences {
+                language: Some("en-US".to_string()),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap()
+        .unwrap();
+        assert_eq!(reset.language.as_deref(), Some("en-US"));
+        assert_eq!(reset.default_provider, None);
+        assert_eq!(reset.items_per_page, i64::from(DEFAULT_PER_PAGE));
+
+        // Nothing is saved for a user that does not exist
+        let missing = user_id + 1;
+        assert_eq!(UserPreferences::get(&db, missing).await.unwrap(), None);
+        let saved = UserPreferences::set(&db, missing, SetUserPreferences::default()).await;
+        assert_eq!(saved.unwrap(), None);
+    }
+}
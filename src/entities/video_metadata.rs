@@ -1,6 +1,30 @@
+use std::collections::HashMap;
+
 use chrono::{DateTime, Utc};
+use futures::{Stream, StreamExt};
 use serde::{Deserialize, Serialize};
-use sqlx::FromRow;
+use sqlx::{FromRow, QueryBuilder, Row, Sqlite, sqlite::SqliteRow};
+
+/// Columns of a media item `m` joined with its video metadata `v`, the latter
+/// prefixed with `metadata_`; see `MediaItemWithMetadata::from_joined_row`
+macro_rules! joined_columns {
+    () => {
+        r#"
+            m.*,
+            v.id AS metadata_id, v.tmdb_id AS metadata_tmdb_id,
+            v.tvdb_id AS metadata_tvdb_id, v.imdb_id AS metadata_imdb_id,
+            v.overview AS metadata_overview, v.poster_path AS metadata_poster_path,
+            v.backdrop_path AS metadata_backdrop_path,
+            v.release_date AS metadata_release_date, v.runtime AS metadata_runtime,
+            v.vote_average AS metadata_vote_average, v.vote_count AS metadata_vote_count,
+            v.genres AS metadata_genres, v.created_at AS metadata_created_at,
+            v.updated_at AS metadata_updated_at, v.anilist_id AS metadata_anilist_id,
+            v.mal_id AS metadata_mal_id, v.bangumi_id AS metadata_bangumi_id,
+            v.episode_count AS metadata_episode_count
+        "#
+    };
+}
+pub(super) use joined_columns;
 
 /// Video metadata entity
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
@@ -20,6 +44,10 @@ pub struct VideoMetadata {
     pub genres: Option<String>, // JSON array
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    pub anilist_id: Option<i64>,
+    pub mal_id: Option<i64>,
+    pub bangumi_id: Option<i64>,
+    pub episode_count: Option<i32>,
 }
 
 /// Create video metadata request
@@ -37,14 +65,219 @@ pub struct CreateVideoMetadata {
     pub vote_average: Option<f64>,
     pub vote_count: Option<i32>,
     pub genres: Vec<String>,
+    pub anilist_id: Option<i64>,
+    pub mal_id: Option<i64>,
+    pub bangumi_id: Option<i64>,
+    pub episode_count: Option<i32>,
+}
+
+/// A field a metadata refresh would change
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MetadataChange {
+    pub field: String,
+    /// `null` when the field is unset
+    pub old: serde_json::Value,
+    pub new: serde_json::Value,
+}
+
+impl CreateVideoMetadata {
+    /// Fields saving this would change on `existing`
+    ///
+    /// Without existing metadata every field with a value is an addition.
+    /// Unset and empty values, like no genres, count as the same.
+    #[must_use]
+    pub fn changes_from(&self, existing: Option<&VideoMetadata>) -> Vec<MetadataChange> {
+        let Ok(serde_json::Value::Object(new)) = serde_json::to_value(self) else {
+            return Vec::new();
+        };
+        let old = existing.map_or(serde_json::Value::Null, |existing| {
+            let mut old = serde_json::to_value(existing).unwrap_or_default();
+            old["genres"] = serde_json::json!(existing.parse_genres());
+            old
+        });
+        let is_blank = |value: &serde_json::Value| {
+            value.is_null() || value.as_array().is_some_and(Vec::is_empty)
+        };
+
+        new.into_iter()
+            .filter(|(field, _)| field != "media_item_id")
+            .filter_map(|(field, new)| {
+                let old = old.get(&field).cloned().unwrap_or_default();
+                let unchanged = old == new || (is_blank(&old) && is_blank(&new));
+                (!unchanged).then_some(MetadataChange { field, old, new })
+            })
+            .collect()
+    }
+}
+
+/// A genre along with how many media items carry it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, FromRow)]
+pub struct GenreCount {
+    pub genre: String,
+    pub count: i64,
 }
 
-/// Media item with video metadata
+/// Field a library listing is sorted by
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LibrarySort {
+    Title,
+    /// When the item was added to the library
+    #[default]
+    Added,
+    /// Provider vote average
+    Rating,
+    /// Release date
+    Release,
+}
+
+impl LibrarySort {
+    /// Expression sorted on, over `media_items m` joined with `video_metadata v`
+    const fn column(self) -> &'static str {
+        match self {
+            Self::Title => "m.title COLLATE NOCASE",
+            Self::Added => "m.added_at",
+            Self::Rating => "v.vote_average",
+            Self::Release => "v.release_date",
+        }
+    }
+}
+
+/// Sort direction
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    Asc,
+    Desc,
+}
+
+impl SortOrder {
+    const fn keyword(self) -> &'static str {
+        match self {
+            Self::Asc => "ASC",
+            Self::Desc => "DESC",
+        }
+    }
+}
+
+/// How a listing filtered by several tags matches them
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TagMatch {
+    /// Items carrying every tag
+    #[default]
+    All,
+    /// Items carrying at least one of the tags
+    Any,
+}
+
+/// Sorting and filtering of a library listing
+///
+/// Filters other than the media type look at video metadata, so they only
+/// make sense for movies and TV shows.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LibraryFilter {
+    #[serde(default)]
+    pub sort: LibrarySort,
+    /// Defaults to ascending for titles and descending otherwise
+    pub order: Option<SortOrder>,
+    /// Only items tagged with this genre, ignoring case
+    pub genre: Option<String>,
+    /// Only items released in this year
+    pub year: Option<i32>,
+    /// Only items with, or without, video metadata
+    pub has_metadata: Option<bool>,
+    /// Comma-separated tags the items must carry
+    pub tags: Option<String>,
+    /// Whether items need all of `tags` or any one of them
+    #[serde(default)]
+    pub tag_match: TagMatch,
+}
+
+impl LibraryFilter {
+    /// Requested sort direction, or the natural one for the sort field
+    #[must_use]
+    pub fn order(&self) -> SortOrder {
+        self.order.unwrap_or(match self.sort {
+            LibrarySort::Title => SortOrder::Asc,
+            _ => SortOrder::Desc,
+        })
+    }
+
+    /// Append the `WHERE` clause to a query over `media_items m` joined with
+    /// `video_metadata v`
+    fn push_conditions<'a>(
+        &'a self,
+        query: &mut QueryBuilder<'a, Sqlite>,
+        media_type: super::MediaType,
+    ) {
+        query.push(" WHERE m.media_type = ").push_bind(media_type);
+        if let Some(genre) = &self.genre {
+            query
+                .push(" AND EXISTS (SELECT 1 FROM json_each(v.genres) WHERE value = ")
+                .push_bind(genre)
+                .push(" COLLATE NOCASE)");
+        }
+        if let Some(year) = self.year {
+            query
+                .push(" AND CAST(substr(v.release_date, 1, 4) AS INTEGER) = ")
+                .push_bind(year);
+        }
+        let tags = self
+            .tags
+            .as_deref()
+            .map(|tags| super::tag::normalize_tags(&tags.split(',').collect::<Vec<_>>()))
+            .unwrap_or_default();
+        if !tags.is_empty() {
+            query.push(
+                " AND m.id IN (SELECT mt.media_item_id FROM media_tags mt \
+                 JOIN tags t ON t.id = mt.tag_id WHERE t.name IN (",
+            );
+            let mut names = query.separated(", ");
+            for tag in &tags {
+                names.push_bind(tag.clone());
+            }
+            query.push(")");
+            if self.tag_match == TagMatch::All {
+                query
+                    .push(" GROUP BY mt.media_item_id HAVING COUNT(*) = ")
+                    .push_bind(i64::try_from(tags.len()).unwrap_or(i64::MAX));
+            }
+            query.push(")");
+        }
+        match self.has_metadata {
+            Some(true) => {
+                query.push(" AND v.id IS NOT NULL");
+            }
+            Some(false) => {
+                query.push(" AND v.id IS NULL");
+            }
+            None => {}
+        }
+    }
+}
+
+/// Media item with the metadata stored for its type
+///
+/// Movies and TV shows carry `metadata`; books and comics carry
+/// `book_metadata` or `comic_metadata` instead.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MediaItemWithMetadata {
     #[serde(flatten)]
     pub media_item: super::MediaItem,
     pub metadata: Option<VideoMetadata>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub book_metadata: Option<super::BookMetadata>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub comic_metadata: Option<super::ComicMetadata>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub subtitles: Vec<super::Subtitle>,
+    /// User-defined tags, normalized and sorted
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// The requesting user's watch status, when the request was authenticated
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<super::MediaStatus>,
 }
 
 impl VideoMetadata {
@@ -60,9 +293,10 @@ impl VideoMetadata {
             INSERT INTO video_metadata (
                 media_item_id, tmdb_id, tvdb_id, imdb_id, overview, 
                 poster_path, backdrop_path, release_date, runtime, 
-                vote_average, vote_count, genres
+                vote_average, vote_count, genres,
+                anilist_id, mal_id, bangumi_id, episode_count
             )
-            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
             ON CONFLICT(media_item_id) DO UPDATE SET
                 tmdb_id = excluded.tmdb_id,
                 tvdb_id = excluded.tvdb_id,
@@ -75,6 +309,10 @@ impl VideoMetadata {
                 vote_average = excluded.vote_average,
                 vote_count = excluded.vote_count,
                 genres = excluded.genres,
+                anilist_id = excluded.anilist_id,
+                mal_id = excluded.mal_id,
+                bangumi_id = excluded.bangumi_id,
+                episode_count = excluded.episode_count,
                 updated_at = CURRENT_TIMESTAMP
             RETURNING *
             "#,
@@ -91,6 +329,10 @@ impl VideoMetadata {
         .bind(metadata.vote_average)
         .bind(metadata.vote_count)
         .bind(genres_json)
+        .bind(metadata.anilist_id)
+        .bind(metadata.mal_id)
+        .bind(metadata.bangumi_id)
+        .bind(metadata.episode_count)
         .fetch_one(db)
         .await?;
 
@@ -114,6 +356,31 @@ impl VideoMetadata {
         Ok(result)
     }
 
+    /// Count the media items carrying each genre, most common first
+    ///
+    /// Items with no genres, or genres that aren't a valid JSON array, are
+    /// left out.
+    pub async fn count_genres(
+        db: &sqlx::SqlitePool,
+        media_type: Option<super::MediaType>,
+    ) -> Result<Vec<GenreCount>, sqlx::Error> {
+        sqlx::query_as::<_, GenreCount>(
+            r#"
+            SELECT g.value AS genre, COUNT(DISTINCT v.media_item_id) AS count
+            FROM video_metadata v
+            JOIN media_items m ON m.id = v.media_item_id
+            JOIN json_each(CASE WHEN json_valid(v.genres) THEN v.genres END) g
+            WHERE (?1 IS NULL OR m.media_type = ?1)
+              AND g.type = 'text' AND trim(g.value) <> ''
+            GROUP BY g.value
+            ORDER BY count DESC, genre
+            "#,
+        )
+        .bind(media_type)
+        .fetch_all(db)
+        .await
+    }
+
     /// Parse genres from JSON string
     pub fn parse_genres(&self) -> Vec<String> {
         self.genres
@@ -124,25 +391,210 @@ impl VideoMetadata {
 }
 
 impl MediaItemWithMetadata {
-    /// Get media items with metadata by type
+    /// Load the metadata table matching the item's media type
+    async fn load(
+        db: &sqlx::SqlitePool,
+        media_item: super::MediaItem,
+    ) -> Result<Self, sqlx::Error> {
+        let mut result = Self {
+            metadata: None,
+            book_metadata: None,
+            comic_metadata: None,
+            subtitles: Vec::new(),
+            tags: Vec::new(),
+            status: None,
+            media_item,
+        };
+        let id = result.media_item.id;
+
+        match result.media_item.media_type {
+            super::MediaType::Movie | super::MediaType::Tv => {
+                result.metadata = VideoMetadata::find_by_media_item_id(db, id).await?;
+                result.subtitles = super::Subtitle::list_by_media_item(db, id).await?;
+            }
+            super::MediaType::Book => {
+                result.book_metadata = super::BookMetadata::find_by_media_item_id(db, id).await?;
+            }
+            super::MediaType::Comic => {
+                result.comic_metadata = super::ComicMetadata::find_by_media_item_id(db, id).await?;
+            }
+        }
+        result.tags = super::Tag::list_for_item(db, id)
+            .await?
+            .into_iter()
+            .map(|tag| tag.name)
+            .collect();
+
+        Ok(result)
+    }
+
+    /// Stream every media item with its video metadata, oldest first
+    ///
+    /// Rows come from a single joined query and are decoded as they arrive,
+    /// so the library is never held in memory at once. Book and comic
+    /// metadata are not included.
+    pub fn stream_all(
+        db: &sqlx::SqlitePool,
+        media_type: Option<super::MediaType>,
+    ) -> impl Stream<Item = Result<Self, sqlx::Error>> + '_ {
+        sqlx::query(concat!(
+            "SELECT ",
+            joined_columns!(),
+            r#"
+            FROM media_items m
+            LEFT JOIN video_metadata v ON v.media_item_id = m.id
+            WHERE ?1 IS NULL OR m.media_type = ?1
+            ORDER BY m.id
+            "#,
+        ))
+        .bind(media_type)
+        .fetch(db)
+        .map(|row| row.and_then(|row| Self::from_joined_row(&row)))
+    }
+
+    /// Decode a row selecting `joined_columns!()`
+    pub(super) fn from_joined_row(row: &SqliteRow) -> Result<Self, sqlx::Error> {
+        let media_item = super::MediaItem::from_row(row)?;
+        let metadata = match row.try_get::<Option<i64>, _>("metadata_id")? {
+            Some(id) => Some(VideoMetadata {
+                id,
+                media_item_id: media_item.id,
+                tmdb_id: row.try_get("metadata_tmdb_id")?,
+                tvdb_id: row.try_get("metadata_tvdb_id")?,
+                imdb_id: row.try_get("metadata_imdb_id")?,
+                overview: row.try_get("metadata_overview")?,
+                poster_path: row.try_get("metadata_poster_path")?,
+                backdrop_path: row.try_get("metadata_backdrop_path")?,
+                release_date: row.try_get("metadata_release_date")?,
+                runtime: row.try_get("metadata_runtime")?,
+                vote_average: row.try_get("metadata_vote_average")?,
+                vote_count: row.try_get("metadata_vote_count")?,
+                genres: row.try_get("metadata_genres")?,
+                created_at: row.try_get("metadata_created_at")?,
+                updated_at: row.try_get("metadata_updated_at")?,
+                anilist_id: row.try_get("metadata_anilist_id")?,
+                mal_id: row.try_get("metadata_mal_id")?,
+                bangumi_id: row.try_get("metadata_bangumi_id")?,
+                episode_count: row.try_get("metadata_episode_count")?,
+            }),
+            None => None,
+        };
+
+        Ok(Self {
+            media_item,
+            metadata,
+            book_metadata: None,
+            comic_metadata: None,
+            subtitles: Vec::new(),
+            tags: Vec::new(),
+            status: None,
+        })
+    }
+
+    /// Get a page of media items with metadata by type, sorted and filtered
+    ///
+    /// Items and their video metadata come from one joined query; subtitles,
+    /// or book and comic metadata, are fetched for the whole page in bulk.
     pub async fn list_by_type(
         db: &sqlx::SqlitePool,
         media_type: super::MediaType,
+        filter: &LibraryFilter,
+        limit: i64,
+        offset: i64,
     ) -> Result<Vec<Self>, sqlx::Error> {
-        let media_items = super::MediaItem::list_by_type(db, media_type).await?;
-
-        let mut results = Vec::new();
-        for item in media_items {
-            let metadata = VideoMetadata::find_by_media_item_id(db, item.id).await?;
-            results.push(Self {
-                media_item: item,
-                metadata,
-            });
+        let mut query = QueryBuilder::<Sqlite>::new(concat!(
+            "SELECT ",
+            joined_columns!(),
+            " FROM media_items m LEFT JOIN video_metadata v ON v.media_item_id = m.id",
+        ));
+        filter.push_conditions(&mut query, media_type);
+        let order = filter.order().keyword();
+        query.push(format!(
+            " ORDER BY {} {order} NULLS LAST, m.id {order}",
+            filter.sort.column()
+        ));
+        query.push(" LIMIT ").push_bind(limit);
+        query.push(" OFFSET ").push_bind(offset);
+
+        let mut results = query
+            .build()
+            .fetch_all(db)
+            .await?
+            .iter()
+            .map(Self::from_joined_row)
+            .collect::<Result<Vec<_>, _>>()?;
+        let ids: Vec<i64> = results.iter().map(|result| result.media_item.id).collect();
+
+        match media_type {
+            super::MediaType::Movie | super::MediaType::Tv => {
+                let mut subtitles: HashMap<i64, Vec<super::Subtitle>> = HashMap::new();
+                for subtitle in
+                    fetch_for_items::<super::Subtitle>(db, "subtitles", &ids, "language, file_path")
+                        .await?
+                {
+                    subtitles
+                        .entry(subtitle.media_item_id)
+                        .or_default()
+                        .push(subtitle);
+                }
+
+                for result in &mut results {
+                    result.subtitles = subtitles.remove(&result.media_item.id).unwrap_or_default();
+                }
+            }
+            super::MediaType::Book => {
+                let mut metadata: HashMap<_, _> =
+                    fetch_for_items::<super::BookMetadata>(db, "book_metadata", &ids, "id")
+                        .await?
+                        .into_iter()
+                        .map(|m| (m.media_item_id, m))
+                        .collect();
+                for result in &mut results {
+                    result.book_metadata = metadata.remove(&result.media_item.id);
+                }
+            }
+            super::MediaType::Comic => {
+                let mut metadata: HashMap<_, _> =
+                    fetch_for_items::<super::ComicMetadata>(db, "comic_metadata", &ids, "id")
+                        .await?
+                        .into_iter()
+                        .map(|m| (m.media_item_id, m))
+                        .collect();
+                for result in &mut results {
+                    result.comic_metadata = metadata.remove(&result.media_item.id);
+                }
+            }
         }
+        Self::attach_tags(db, &mut results).await?;
 
         Ok(results)
     }
 
+    /// Fill in the tags of each item with a single query
+    pub async fn attach_tags(db: &sqlx::SqlitePool, items: &mut [Self]) -> Result<(), sqlx::Error> {
+        let ids: Vec<i64> = items.iter().map(|item| item.media_item.id).collect();
+        let mut tags = super::Tag::names_for_items(db, &ids).await?;
+        for item in items {
+            item.tags = tags.remove(&item.media_item.id).unwrap_or_default();
+        }
+
+        Ok(())
+    }
+
+    /// Count the media items of a type matching a filter
+    pub async fn count_by_type(
+        db: &sqlx::SqlitePool,
+        media_type: super::MediaType,
+        filter: &LibraryFilter,
+    ) -> Result<i64, sqlx::Error> {
+        let mut query = QueryBuilder::<Sqlite>::new(
+            "SELECT COUNT(*) FROM media_items m LEFT JOIN video_metadata v ON v.media_item_id = m.id",
+        );
+        filter.push_conditions(&mut query, media_type);
+
+        query.build_query_scalar().fetch_one(db).await
+    }
+
     /// Get media item with metadata by ID
     pub async fn find_by_id(
         db: &sqlx::SqlitePool,
@@ -153,11 +605,155 @@ impl MediaItemWithMetadata {
             None => return Ok(None),
         };
 
-        let metadata = VideoMetadata::find_by_media_item_id(db, media_item.id).await?;
+        Self::load(db, media_item).await.map(Some)
+    }
+}
 
-        Ok(Some(Self {
-            media_item,
-            metadata,
-        }))
+/// Fetch the rows of `table` belonging to any of the given media items
+async fn fetch_for_items<T>(
+    db: &sqlx::SqlitePool,
+    table: &str,
+    media_item_ids: &[i64],
+    order_by: &str,
+) -> Result<Vec<T>, sqlx::Error>
+where
+    T: for<'r> FromRow<'r, SqliteRow> + Send + Unpin,
+{
+    if media_item_ids.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut query =
+        QueryBuilder::<Sqlite>::new(format!("SELECT * FROM {table} WHERE media_item_id IN ("));
+    let mut ids = query.separated(", ");
+    for id in media_item_ids {
+        ids.push_bind(*id);
+    }
+    query.push(format!(") ORDER BY {order_by}"));
+
+    query.build_query_as().fetch_all(db).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entities::{
+        CreateLibraryFolder, CreateMediaItem, CreateSubtitle, LibraryFolder, MediaItem, MediaType,
+        Subtitle,
+    };
+
+    fn metadata_for(media_item_id: i64, tmdb_id: i64) -> CreateVideoMetadata {
+        CreateVideoMetadata {
+            media_item_id,
+            tmdb_id: Some(tmdb_id),
+            tvdb_id: None,
+            imdb_id: None,
+            overview: None,
+            poster_path: None,
+            backdrop_path: None,
+            release_date: None,
+            runtime: None,
+            vote_average: None,
+            vote_count: None,
+            genres: Vec::new(),
+            anilist_id: None,
+            mal_id: None,
+            bangumi_id: None,
+            episode_count: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_list_by_type_attaches_metadata_to_each_item() {
+        let db = crate::db::test_pool().await;
+        let folder = LibraryFolder::create(
+            &db,
+            CreateLibraryFolder {
+                name: "Movies".to_string(),
+                path: "/movies".to_string(),
+                media_type: MediaType::Movie,
+                extensions: None,
+            },
+        )
+        .await
+        .unwrap();
+
+        let mut items = Vec::new();
+        for title in ["Alien", "Heat", "Ronin", "Solaris"] {
+            items.push(
+                MediaItem::create(
+                    &db,
+                    CreateMediaItem {
+                        library_folder_id: folder.id,
+                        media_type: MediaType::Movie,
+                        title: title.to_string(),
+                        file_path: format!("/movies/{title}.mkv"),
+                        file_size: 1,
+                        content_hash: None,
+                        modified_at: None,
+                    },
+                )
+                .await
+                .unwrap(),
+            );
+        }
+        // Heat and Solaris have no metadata; only Ronin has subtitles
+        VideoMetadata::upsert(&db, metadata_for(items[0].id, 348))
+            .await
+            .unwrap();
+        VideoMetadata::upsert(&db, metadata_for(items[2].id, 8195))
+            .await
+            .unwrap();
+        for language in ["fr", "en"] {
+            Subtitle::create_if_missing(
+                &db,
+                CreateSubtitle {
+                    media_item_id: items[2].id,
+                    file_path: format!("/movies/Ronin.{language}.srt"),
+                    language: Some(language.to_string()),
+                    format: "srt".to_string(),
+                },
+            )
+            .await
+            .unwrap();
+        }
+
+        let page = MediaItemWithMetadata::list_by_type(
+            &db,
+            MediaType::Movie,
+            &LibraryFilter::default(),
+            10,
+            0,
+        )
+        .await
+        .unwrap();
+
+        let summary: Vec<_> = page
+            .iter()
+            .map(|item| {
+                (
+                    item.media_item.title.as_str(),
+                    item.metadata.as_ref().and_then(|m| m.tmdb_id),
+                    item.subtitles
+                        .iter()
+                        .filter_map(|s| s.language.as_deref())
+                        .collect::<Vec<_>>(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("Solaris", None, vec![]),
+                ("Ronin", Some(8195), vec!["en", "fr"]),
+                ("Heat", None, vec![]),
+                ("Alien", Some(348), vec![]),
+            ]
+        );
+        for item in &page {
+            if let Some(metadata) = &item.metadata {
+                assert_eq!(metadata.media_item_id, item.media_item.id);
+            }
+        }
     }
 }
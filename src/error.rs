@@ -1,10 +1,15 @@
+use std::time::Duration;
+
 use axum::{
     Json,
+    http::{HeaderValue, header::RETRY_AFTER},
     response::{IntoResponse, Response},
 };
 use hyper::StatusCode;
 use serde_json::json;
 
+use crate::scraper::ScraperError;
+
 #[derive(thiserror::Error, Debug)]
 pub enum AyiahError {
     #[error("{0}")]
@@ -30,6 +35,9 @@ pub enum AyiahError {
 
     #[error("{0}")]
     ScrapeError(#[from] ScrapeError),
+
+    #[error("{0}")]
+    ScraperError(#[from] ScraperError),
 }
 
 impl AyiahError {
@@ -67,20 +75,92 @@ impl AyiahError {
                     format!("Scrape operation failed: {err}"),
                 )
             }
+            Self::ScraperError(err) => scraper_error_code(err),
+        }
+    }
+
+    /// Machine-readable error kind, sent as `error_code` next to the message
+    #[must_use]
+    pub fn error_code(&self) -> &'static str {
+        match self {
+            Self::ApiError(err) => err.error_code(),
+            Self::AuthError(err) => err.error_code(),
+            Self::ConfigError(_) => "CONFIG_ERROR",
+            Self::DatabaseError(_) | Self::SqlxError(_) => "DATABASE_ERROR",
+            Self::SerdeJsonError(_) => "INVALID_JSON",
+            Self::ValidationError(_) => "VALIDATION_FAILED",
+            Self::ScrapeError(_) => "SCRAPE_FAILED",
+            Self::ScraperError(err) => match err {
+                ScraperError::NotFound(_) => "NOT_FOUND",
+                ScraperError::RateLimit(_) => "RATE_LIMITED",
+                ScraperError::Api { .. } | ScraperError::Network(_) => "PROVIDER_UNAVAILABLE",
+                ScraperError::Config(_) => "PROVIDER_MISCONFIGURED",
+                ScraperError::Parse(_) | ScraperError::Cache(_) => "SCRAPER_ERROR",
+            },
+        }
+    }
+
+    /// Seconds a client should wait before retrying, for rate-limited requests
+    fn retry_after(&self) -> Option<u64> {
+        match self {
+            Self::ScraperError(ScraperError::RateLimit(wait)) => Some(retry_after_secs(*wait)),
+            _ => None,
         }
     }
 }
 
+/// Map a provider failure to a status: upstream trouble is a bad gateway,
+/// while a missing match or an exhausted rate limit is passed through
+fn scraper_error_code(err: &ScraperError) -> (StatusCode, String) {
+    match err {
+        ScraperError::NotFound(_) => (StatusCode::NOT_FOUND, err.to_string()),
+        ScraperError::RateLimit(wait) => (
+            StatusCode::TOO_MANY_REQUESTS,
+            format!(
+                "Provider rate limit exceeded, retry after {}s",
+                retry_after_secs(*wait)
+            ),
+        ),
+        ScraperError::Api { .. } | ScraperError::Network(_) => {
+            tracing::warn!("Provider error: {}", err);
+            (
+                StatusCode::BAD_GATEWAY,
+                format!("Provider request failed: {err}"),
+            )
+        }
+        ScraperError::Config(_) => (StatusCode::BAD_REQUEST, err.to_string()),
+        ScraperError::Parse(_) | ScraperError::Cache(_) => {
+            tracing::error!("Scraper error: {}", err);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Scraper failed: {err}"),
+            )
+        }
+    }
+}
+
+/// Whole seconds to wait, rounded up and at least one
+fn retry_after_secs(wait: Duration) -> u64 {
+    (wait.as_secs() + u64::from(wait.subsec_nanos() > 0)).max(1)
+}
+
 // Implement Axum's IntoResponse for our error type
 impl IntoResponse for AyiahError {
     fn into_response(self) -> Response {
         let (status_code, message) = self.code();
         let body = Json(json!({
             "code": status_code.as_u16(),
+            "error_code": self.error_code(),
             "message": message,
         }));
 
-        (status_code, body).into_response()
+        let mut response = (status_code, body).into_response();
+        if let Some(secs) = self.retry_after() {
+            response
+                .headers_mut()
+                .insert(RETRY_AFTER, HeaderValue::from(secs));
+        }
+        response
     }
 }
 
@@ -103,6 +183,12 @@ pub enum ApiError {
 
     #[error("{0}")]
     InternalServerError(String),
+
+    #[error("{0}")]
+    ServiceUnavailable(String),
+
+    #[error("Provider not registered: {0}")]
+    ProviderNotFound(String),
 }
 
 impl ApiError {
@@ -114,6 +200,21 @@ impl ApiError {
             Self::NotFound(msg) => (StatusCode::NOT_FOUND, msg.clone()),
             Self::Conflict(msg) => (StatusCode::CONFLICT, msg.clone()),
             Self::InternalServerError(msg) => (StatusCode::INTERNAL_SERVER_ERROR, msg.clone()),
+            Self::ServiceUnavailable(msg) => (StatusCode::SERVICE_UNAVAILABLE, msg.clone()),
+            Self::ProviderNotFound(_) => (StatusCode::BAD_REQUEST, self.to_string()),
+        }
+    }
+
+    fn error_code(&self) -> &'static str {
+        match self {
+            Self::BadRequest(_) => "BAD_REQUEST",
+            Self::Unauthorized(_) => "UNAUTHORIZED",
+            Self::Forbidden(_) => "FORBIDDEN",
+            Self::NotFound(_) => "NOT_FOUND",
+            Self::Conflict(_) => "CONFLICT",
+            Self::InternalServerError(_) => "INTERNAL_ERROR",
+            Self::ServiceUnavailable(_) => "SERVICE_UNAVAILABLE",
+            Self::ProviderNotFound(_) => "PROVIDER_NOT_FOUND",
         }
     }
 }
@@ -147,6 +248,14 @@ impl AuthError {
             ),
         }
     }
+
+    fn error_code(&self) -> &'static str {
+        match self {
+            Self::InvalidToken => "INVALID_TOKEN",
+            Self::TokenCreation => "TOKEN_CREATION_FAILED",
+            Self::MissingAuth => "AUTH_REQUIRED",
+        }
+    }
 }
 
 #[derive(thiserror::Error, Debug)]
@@ -162,6 +271,9 @@ pub enum ConfigError {
 
     #[error("Configuration not initialized")]
     NotInitialized,
+
+    #[error("Failed to watch configuration: {0}")]
+    WatchError(String),
 }
 
 impl ConfigError {
@@ -183,6 +295,10 @@ impl ConfigError {
                 StatusCode::INTERNAL_SERVER_ERROR,
                 "Configuration not initialized".to_string(),
             ),
+            Self::WatchError(msg) => (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to watch configuration: {msg}"),
+            ),
         }
     }
 }
@@ -228,6 +344,9 @@ pub enum ScrapeError {
     #[error("Directory creation failed: {0}")]
     DirectoryCreationError(String),
 
+    #[error("NFO error: {0}")]
+    NfoError(String),
+
     #[error("File scan failed: {0}")]
     ScanError(String),
 
@@ -240,3 +359,33 @@ pub enum ScrapeError {
     #[error("Channel receive error")]
     ChannelReceiveError,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn body_of(response: Response) -> serde_json::Value {
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_scraper_not_found_is_404() {
+        let error = AyiahError::from(ScraperError::NotFound("tmdb:603".to_string()));
+        let response = error.into_response();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert!(response.headers().get(RETRY_AFTER).is_none());
+        assert_eq!(body_of(response).await["error_code"], "NOT_FOUND");
+    }
+
+    #[tokio::test]
+    async fn test_scraper_rate_limit_is_429_with_retry_after() {
+        let error = AyiahError::from(ScraperError::RateLimit(Duration::from_millis(2500)));
+        let response = error.into_response();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(response.headers()[RETRY_AFTER], "3");
+        assert_eq!(body_of(response).await["error_code"], "RATE_LIMITED");
+    }
+}
@@ -39,6 +39,68 @@ where
     }
 }
 
+/// Default number of items per page
+pub const DEFAULT_PER_PAGE: u32 = 50;
+
+/// Upper bound on items per page
+pub const MAX_PER_PAGE: u32 = 200;
+
+/// Pagination query parameters
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct Pagination {
+    pub page: Option<u32>,
+    pub per_page: Option<u32>,
+}
+
+impl Pagination {
+    /// Requested page, starting at 1
+    #[must_use]
+    pub fn page(&self) -> u32 {
+        self.page.unwrap_or(1).max(1)
+    }
+
+    /// Requested page size, clamped to `1..=MAX_PER_PAGE`
+    #[must_use]
+    pub fn per_page(&self) -> u32 {
+        self.per_page
+            .unwrap_or(DEFAULT_PER_PAGE)
+            .clamp(1, MAX_PER_PAGE)
+    }
+
+    /// SQL `LIMIT`
+    #[must_use]
+    pub fn limit(&self) -> i64 {
+        i64::from(self.per_page())
+    }
+
+    /// SQL `OFFSET`
+    #[must_use]
+    pub fn offset(&self) -> i64 {
+        i64::from(self.page() - 1) * self.limit()
+    }
+}
+
+/// A page of results along with the total item count
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PaginatedResponse<T> {
+    pub items: Vec<T>,
+    pub total: i64,
+    pub page: u32,
+    pub per_page: u32,
+}
+
+impl<T> PaginatedResponse<T> {
+    #[must_use]
+    pub fn new(items: Vec<T>, total: i64, pagination: Pagination) -> Self {
+        Self {
+            items,
+            total,
+            page: pagination.page(),
+            per_page: pagination.per_page(),
+        }
+    }
+}
+
 /// Context holds all shared application resources
 #[derive(Clone)]
 pub struct Context {
@@ -48,9 +110,50 @@ pub struct Context {
     /// Database connection
     pub db: db::Database,
 
+    /// Scraper cache shared by the scraper manager and its providers
+    pub scraper_cache: Arc<scraper::ScraperCache>,
+
     /// Scraper manager for metadata fetching
     pub scraper_manager: Option<Arc<scraper::ScraperManager>>,
 
+    /// Providers left out at startup because they are misconfigured
+    pub disabled_providers: Vec<scraper::DisabledProvider>,
+
     /// Metadata agent for fetching and saving metadata
     pub metadata_agent: Option<Arc<services::MetadataAgent>>,
+
+    /// Background jobs such as scans and metadata refreshes
+    pub jobs: Arc<services::JobQueue>,
+
+    /// Webhooks notified of library events
+    pub webhooks: Arc<services::WebhookNotifier>,
+
+    /// Periodic rescans, when scheduled in the config
+    pub scan_scheduler: Option<Arc<services::ScanScheduler>>,
+
+    /// Local copies of metadata images, unless caching is off
+    pub image_cache: Option<Arc<services::ImageCache>>,
+}
+
+#[cfg(test)]
+impl Context {
+    /// Build a context backed by the given database and a throwaway config
+    pub(crate) fn for_tests(db: db::Database) -> Self {
+        let dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let config = ConfigManager::new(Some(dir.path().join("config.toml")))
+            .expect("Failed to create test config");
+
+        Self {
+            config,
+            db,
+            scraper_cache: Arc::new(scraper::ScraperCache::new()),
+            scraper_manager: None,
+            disabled_providers: Vec::new(),
+            metadata_agent: None,
+            jobs: Arc::new(services::JobQueue::default()),
+            webhooks: Arc::new(services::WebhookNotifier::new(Default::default())),
+            scan_scheduler: None,
+            image_cache: None,
+        }
+    }
 }
@@ -1,29 +1,30 @@
-use std::{env, path::PathBuf, sync::Arc};
+use std::{env, path::PathBuf, sync::Arc, time::Duration};
 
 use axum::{Router, http::HeaderName, middleware};
 use tokio::net::TcpListener;
 use tower_http::{
     compression::CompressionLayer,
-    cors::CorsLayer,
     propagate_header::PropagateHeaderLayer,
     request_id::{MakeRequestUuid, SetRequestIdLayer},
     services::{ServeDir, ServeFile},
 };
-use tracing::info;
+use tracing::{info, warn};
 
 use ayiah::{
     Context,
-    app::config::ConfigManager,
+    app::config::{ConfigManager, DEFAULT_JWT_SECRET, RunMode},
     db,
-    middleware::logger as middleware_logger,
+    middleware::{cors_layer, logger as middleware_logger},
     routes,
-    scraper::{ScraperCache, ScraperManager, provider::tmdb::TmdbProvider},
-    services::MetadataAgent,
+    scraper::{ScraperCache, ScraperManager},
+    services::{
+        ImageCache, ImageCacheMode, JobQueue, MetadataAgent, ScanScheduler, WebhookNotifier,
+        image_cache, job_queue::DEFAULT_DRAIN_TIMEOUT, scan_scheduler,
+    },
     utils::{graceful_shutdown::shutdown_signal, logger},
 };
 
-#[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
+fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Load configuration
     let config_path = env::var("AYIAH_CONFIG_PATH").map(PathBuf::from).ok();
 
@@ -35,49 +36,132 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     logger::init(&config_manager.read().logging)
         .map_err(|e| format!("Logging initialization error: {e}"))?;
 
-    let conn = db::init().await?;
-
-    // Initialize scraper manager and metadata agent
-    let (scraper_manager, metadata_agent) = {
-        let config = config_manager.read();
-        
-        if let Some(tmdb_api_key) = &config.scraper.tmdb_api_key {
-            let cache = Arc::new(ScraperCache::new());
-            let mut scraper_manager = ScraperManager::new();
-            
-            // Add TMDB provider
-            let tmdb_provider = TmdbProvider::new(tmdb_api_key.clone(), cache.clone());
-            scraper_manager.add_provider(Box::new(tmdb_provider));
-            
-            let scraper_manager = Arc::new(scraper_manager);
-            let metadata_agent = Arc::new(MetadataAgent::new(
-                scraper_manager.clone(),
-                conn.clone(),
-            ));
-            
-            info!("Initialized scraper manager with TMDB provider");
-            (Some(scraper_manager), Some(metadata_agent))
+    // Production mode already refuses the default secret during validation
+    if config_manager.read().auth.jwt_secret == DEFAULT_JWT_SECRET {
+        warn!(
+            "auth.jwt_secret is the built-in default; anyone can forge tokens. \
+             Set a unique secret before exposing this server (AYIAH_ENV=production refuses to start)"
+        );
+    }
+
+    let mut runtime = tokio::runtime::Builder::new_multi_thread();
+    runtime.enable_all();
+    if let Some(workers) = config_manager.read().server.workers {
+        runtime.worker_threads(workers.max(1));
+    }
+
+    runtime.build()?.block_on(serve(config_manager))
+}
+
+async fn serve(config_manager: &'static ConfigManager) -> Result<(), Box<dyn std::error::Error>> {
+    // Pick up config file edits without a restart
+    if let Err(e) = config_manager.watch() {
+        warn!("Configuration hot reload disabled: {e}");
+    }
+
+    let db_config = config_manager.read().database.clone();
+    let conn = db::init(&db_config).await?;
+
+    let cache = {
+        let (strategy, ttl_seconds) = {
+            let config = config_manager.read();
+            (config.scraper.cache, config.scraper.cache_ttl_seconds)
+        };
+        Arc::new(ScraperCache::from_strategy(strategy, ttl_seconds).await)
+    };
+
+    let image_cache = match config_manager.read().images.mode {
+        ImageCacheMode::Off => None,
+        mode => Some(Arc::new(ImageCache::new(
+            conn.clone(),
+            image_cache::default_image_dir(),
+            mode,
+        ))),
+    };
+
+    // Initialize scraper manager and metadata agent. Providers missing the
+    // API key they need are left out and reported by the health check.
+    let (scraper_manager, metadata_agent, disabled_providers) = {
+        let (scraper_manager, disabled_providers) =
+            ScraperManager::from_config(&config_manager.read(), &cache);
+        for disabled in &disabled_providers {
+            warn!("Disabling provider {}: {}", disabled.name, disabled.reason);
+        }
+
+        if scraper_manager.providers().is_empty() {
+            info!("No metadata providers enabled, metadata fetching disabled");
+            (None, None, disabled_providers)
         } else {
-            info!("No TMDB API key configured, metadata fetching disabled");
-            (None, None)
+            let scraper_manager = Arc::new(scraper_manager);
+            let mut metadata_agent = MetadataAgent::new(scraper_manager.clone(), conn.clone());
+            if let Some(image_cache) = &image_cache {
+                metadata_agent = metadata_agent.with_image_cache(image_cache.clone());
+            }
+            let metadata_agent = Arc::new(metadata_agent);
+
+            info!(
+                "Initialized scraper manager with providers: {}",
+                scraper_manager
+                    .providers()
+                    .iter()
+                    .map(|provider| provider.name())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            );
+            (
+                Some(scraper_manager),
+                Some(metadata_agent),
+                disabled_providers,
+            )
         }
     };
 
+    let webhooks = Arc::new(WebhookNotifier::new(config_manager.read().webhooks.clone()));
+
     // Create shared application state
     let ctx = Arc::new(Context {
         db: conn,
         config: config_manager.clone(),
+        scraper_cache: cache,
         scraper_manager,
+        disabled_providers,
         metadata_agent,
+        jobs: Arc::new(JobQueue::default()),
+        webhooks,
+        scan_scheduler: ScanScheduler::from_config(&config_manager.read().scanner.schedule)
+            .map(Arc::new),
+        image_cache,
     });
 
+    if let Some(scheduler) = ctx.scan_scheduler.clone() {
+        let ctx = ctx.clone();
+        scheduler.spawn(move |run| {
+            let db = ctx.db.clone();
+            let scanner_config = ctx.config.read().scanner.clone();
+            let metadata_agent = ctx.metadata_agent.clone();
+            let webhooks = ctx.webhooks.clone();
+            ctx.jobs.enqueue("scheduled_scan", move |job| async move {
+                let _run = run;
+                scan_scheduler::scan_and_fetch_metadata(
+                    db,
+                    scanner_config,
+                    metadata_agent,
+                    webhooks,
+                    job,
+                )
+                .await
+            });
+        });
+        info!("Scheduled library rescans enabled");
+    }
+
     // Create application router
     let app = Router::new()
-        .merge(routes::mount())
+        .merge(routes::mount(&config_manager.read().server))
         .fallback_service(
             ServeDir::new("/dist").not_found_service(ServeFile::new("/dist/index.html")),
         )
-        .with_state(ctx)
+        .with_state(ctx.clone())
         .layer(middleware::from_fn(middleware_logger))
         .layer(CompressionLayer::new())
         .layer(PropagateHeaderLayer::new(HeaderName::from_static(
@@ -87,7 +171,10 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             HeaderName::from_static("x-request-id"),
             MakeRequestUuid,
         ))
-        .layer(CorsLayer::permissive());
+        .layer(cors_layer(
+            &config_manager.read().server,
+            RunMode::from_env(),
+        ));
 
     // Parse host:port string into SocketAddr
     let address = config_manager.socket_addr()?;
@@ -100,5 +187,21 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .with_graceful_shutdown(shutdown_signal())
         .await?;
 
+    // Let scans and metadata fetches finish their database writes
+    let timeout = config_manager
+        .read()
+        .server
+        .shutdown_timeout_seconds
+        .map_or(DEFAULT_DRAIN_TIMEOUT, Duration::from_secs);
+    let outcome = ctx.jobs.drain(timeout).await;
+    info!("Drained {} background jobs", outcome.drained);
+    if outcome.abandoned > 0 {
+        warn!(
+            "Gave up on {} background jobs still running after {}s",
+            outcome.abandoned,
+            timeout.as_secs()
+        );
+    }
+
     Ok(())
 }
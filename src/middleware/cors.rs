@@ -0,0 +1,131 @@
+use axum::http::{HeaderName, HeaderValue, Method, header};
+use tower_http::cors::{AllowHeaders, AllowMethods, AllowOrigin, Any, CorsLayer};
+
+use crate::app::config::{RunMode, ServerConfig};
+
+/// Methods allowed when none are configured
+const DEFAULT_METHODS: [Method; 5] = [
+    Method::GET,
+    Method::POST,
+    Method::PUT,
+    Method::PATCH,
+    Method::DELETE,
+];
+
+/// CORS layer for the configured origins, methods and headers
+///
+/// With no origins configured, development allows any origin so a local
+/// frontend dev server works out of the box, while production allows none.
+/// Entries are checked when the config is validated; any that still don't
+/// parse are skipped.
+pub fn cors_layer(config: &ServerConfig, mode: RunMode) -> CorsLayer {
+    if config.cors_origins.is_empty() && mode == RunMode::Development {
+        return CorsLayer::permissive();
+    }
+
+    let origins = if config.cors_origins.iter().any(|origin| origin == "*") {
+        AllowOrigin::any()
+    } else {
+        AllowOrigin::list(
+            config
+                .cors_origins
+                .iter()
+                .filter_map(|origin| HeaderValue::from_str(origin).ok()),
+        )
+    };
+
+    let methods = if config.cors_methods.iter().any(|method| method == "*") {
+        AllowMethods::any()
+    } else if config.cors_methods.is_empty() {
+        AllowMethods::list(DEFAULT_METHODS)
+    } else {
+        AllowMethods::list(
+            config
+                .cors_methods
+                .iter()
+                .filter_map(|method| Method::from_bytes(method.as_bytes()).ok()),
+        )
+    };
+
+    let headers = if config.cors_headers.iter().any(|header| header == "*") {
+        AllowHeaders::any()
+    } else if config.cors_headers.is_empty() {
+        AllowHeaders::list([header::AUTHORIZATION, header::CONTENT_TYPE])
+    } else {
+        AllowHeaders::list(
+            config
+                .cors_headers
+                .iter()
+                .filter_map(|header| HeaderName::from_bytes(header.as_bytes()).ok()),
+        )
+    };
+
+    CorsLayer::new()
+        .allow_origin(origins)
+        .allow_methods(methods)
+        .allow_headers(headers)
+        .expose_headers(Any)
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{
+        Router,
+        body::Body,
+        http::{
+            Request,
+            header::{ACCESS_CONTROL_ALLOW_ORIGIN, ACCESS_CONTROL_REQUEST_METHOD, ORIGIN},
+        },
+        routing::get,
+    };
+    use tower::ServiceExt;
+
+    use super::*;
+
+    async fn preflight(config: &ServerConfig, mode: RunMode, origin: &str) -> Option<String> {
+        let app = Router::new()
+            .route("/ping", get(|| async { "pong" }))
+            .layer(cors_layer(config, mode));
+        let request = Request::builder()
+            .method(Method::OPTIONS)
+            .uri("/ping")
+            .header(ORIGIN, origin)
+            .header(ACCESS_CONTROL_REQUEST_METHOD, "GET")
+            .body(Body::empty())
+            .unwrap();
+
+        let response = app.oneshot(request).await.unwrap();
+        response
+            .headers()
+            .get(ACCESS_CONTROL_ALLOW_ORIGIN)
+            .map(|value| value.to_str().unwrap().to_string())
+    }
+
+    #[tokio::test]
+    async fn test_only_configured_origins_are_allowed() {
+        let mut config = ServerConfig {
+            cors_origins: vec!["https://ayiah.example".to_string()],
+            ..ServerConfig::default()
+        };
+        assert_eq!(
+            preflight(&config, RunMode::Production, "https://ayiah.example").await,
+            Some("https://ayiah.example".to_string())
+        );
+        assert_eq!(
+            preflight(&config, RunMode::Production, "https://evil.example").await,
+            None
+        );
+
+        // Nothing configured: open in development, closed in production
+        config.cors_origins.clear();
+        assert!(
+            preflight(&config, RunMode::Development, "http://localhost:5173")
+                .await
+                .is_some()
+        );
+        assert_eq!(
+            preflight(&config, RunMode::Production, "http://localhost:5173").await,
+            None
+        );
+    }
+}
@@ -0,0 +1,84 @@
+use axum::{
+    body::{Body, to_bytes},
+    http::{
+        HeaderValue, Method, Request, Response, StatusCode,
+        header::{ETAG, IF_NONE_MATCH},
+    },
+    middleware::Next,
+};
+use xxhash_rust::xxh3::xxh3_64;
+
+/// Conditional GET middleware
+///
+/// Tags successful GET responses with a weak ETag derived from the body and
+/// answers `304 Not Modified` when the client's `If-None-Match` already has
+/// it. The body is buffered to hash it, so this belongs on JSON read routes
+/// rather than streaming ones.
+pub async fn etag(request: Request<Body>, next: Next) -> Response<Body> {
+    let if_none_match = request.headers().get(IF_NONE_MATCH).cloned();
+    let is_get = request.method() == Method::GET;
+
+    let response = next.run(request).await;
+    if !is_get || response.status() != StatusCode::OK {
+        return response;
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let bytes = match to_bytes(body, usize::MAX).await {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            tracing::warn!("Failed to buffer response for ETag: {e}");
+            return Response::from_parts(parts, Body::empty());
+        }
+    };
+
+    let tag = format!("W/\"{:016x}\"", xxh3_64(&bytes));
+    let Ok(value) = HeaderValue::from_str(&tag) else {
+        return Response::from_parts(parts, Body::from(bytes));
+    };
+
+    if if_none_match.is_some_and(|header| matches(&header, &tag)) {
+        let mut not_modified = Response::new(Body::empty());
+        *not_modified.status_mut() = StatusCode::NOT_MODIFIED;
+        not_modified.headers_mut().insert(ETAG, value);
+        return not_modified;
+    }
+
+    parts.headers.insert(ETAG, value);
+    Response::from_parts(parts, Body::from(bytes))
+}
+
+/// Whether an `If-None-Match` header lists `tag`, comparing weakly
+fn matches(header: &HeaderValue, tag: &str) -> bool {
+    let Ok(header) = header.to_str() else {
+        return false;
+    };
+    let opaque = |t: &str| t.trim().trim_start_matches("W/").to_string();
+
+    header
+        .split(',')
+        .any(|candidate| candidate.trim() == "*" || opaque(candidate) == opaque(tag))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_if_none_match_compares_weakly() {
+        let tag = "W/\"00ff\"";
+        for (header, expected) in [
+            ("W/\"00ff\"", true),
+            ("\"00ff\"", true),
+            ("\"abcd\", W/\"00ff\"", true),
+            ("*", true),
+            ("W/\"abcd\"", false),
+        ] {
+            assert_eq!(
+                matches(&HeaderValue::from_static(header), tag),
+                expected,
+                "{header}"
+            );
+        }
+    }
+}
@@ -5,9 +5,13 @@ use axum::{
     http::{Request, Response},
     middleware::Next,
 };
-use tracing::info;
+use tracing::{Instrument, info, info_span};
 
 /// Custom request logger middleware
+///
+/// Runs the rest of the stack inside a `request` span carrying the
+/// `x-request-id`, so every event emitted while handling the request
+/// (scrapers, providers, the metadata agent) is tagged with it.
 pub async fn logger(request: Request<Body>, next: Next) -> Response<Body> {
     // Extract request information and create owned copies of everything
     let method = request.method().clone();
@@ -26,11 +30,13 @@ pub async fn logger(request: Request<Body>, next: Next) -> Response<Body> {
         .map_or("-", |v| v.to_str().unwrap_or("-"))
         .to_string();
 
+    let span = info_span!("request", request_id = %request_id);
+
     // Record start time for latency calculation
     let start = Instant::now();
 
     // Process the request
-    let response = next.run(request).await;
+    let response = next.run(request).instrument(span.clone()).await;
 
     // Calculate latency
     let latency = start.elapsed();
@@ -39,6 +45,7 @@ pub async fn logger(request: Request<Body>, next: Next) -> Response<Body> {
     let status = response.status().as_u16();
 
     // Log using structured format
+    let _entered = span.enter();
     info!(
         method = %method,
         uri = %uri_path,
@@ -46,8 +53,45 @@ pub async fn logger(request: Request<Body>, next: Next) -> Response<Body> {
         status = %status,
         ?latency,
         user_agent = %user_agent,
-        request_id = ?request_id,
     );
 
     response
 }
+
+#[cfg(test)]
+mod tests {
+    use axum::{Router, middleware, routing::get};
+    use tower::ServiceExt;
+    use tracing_test::traced_test;
+
+    use super::*;
+
+    async fn handler() -> &'static str {
+        info!("handling request");
+        "ok"
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn test_handler_events_carry_request_id() {
+        let app = Router::new()
+            .route("/", get(handler))
+            .layer(middleware::from_fn(logger));
+
+        let request = Request::builder()
+            .uri("/")
+            .header("x-request-id", "req-1234")
+            .body(Body::empty())
+            .unwrap();
+        app.oneshot(request).await.unwrap();
+
+        logs_assert(|lines: &[&str]| {
+            lines
+                .iter()
+                .find(|line| line.contains("handling request"))
+                .filter(|line| line.contains("request_id=req-1234"))
+                .map(|_| ())
+                .ok_or_else(|| "handler event is missing the request id".to_string())
+        });
+    }
+}
@@ -1,3 +1,7 @@
+pub mod cors;
+pub mod etag;
 pub mod logger;
 
+pub use cors::cors_layer;
+pub use etag::etag;
 pub use logger::logger;
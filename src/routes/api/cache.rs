@@ -0,0 +1,70 @@
+use axum::{
+    Router,
+    extract::State,
+    routing::{get, post},
+};
+
+use crate::{ApiResponse, ApiResult, Ctx, scraper::CacheStats};
+
+/// Get scraper cache statistics
+async fn cache_stats(State(ctx): State<Ctx>) -> ApiResult<CacheStats> {
+    ctx.scraper_cache.run_pending_tasks().await;
+
+    Ok(ApiResponse {
+        code: 200,
+        message: "Cache statistics retrieved successfully".to_string(),
+        data: Some(ctx.scraper_cache.stats()),
+    })
+}
+
+/// Drop every scraper cache entry
+async fn clear_cache(State(ctx): State<Ctx>) -> ApiResult<CacheStats> {
+    ctx.scraper_cache.clear().await;
+
+    Ok(ApiResponse {
+        code: 200,
+        message: "Cache cleared successfully".to_string(),
+        data: Some(ctx.scraper_cache.stats()),
+    })
+}
+
+/// Mount cache routes
+pub fn mount() -> Router<Ctx> {
+    Router::new()
+        .route("/cache/stats", get(cache_stats))
+        .route("/cache/clear", post(clear_cache))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use axum::{body::Body, http::Request};
+    use tower::ServiceExt;
+
+    use crate::{Context, scraper::CacheKey};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_clear_empties_cache() {
+        let ctx = Arc::new(Context::for_tests(crate::db::test_pool().await));
+        ctx.scraper_cache
+            .set(CacheKey::new("tmdb", "movie", "alien"), &"Alien")
+            .await
+            .unwrap();
+
+        let response = mount()
+            .with_state(ctx.clone())
+            .oneshot(Request::post("/cache/clear").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+        assert_eq!(body["data"]["entry_count"], 0);
+        assert!(ctx.scraper_cache.is_empty());
+    }
+}
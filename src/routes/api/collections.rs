@@ -0,0 +1,352 @@
+use axum::{
+    Json, Router,
+    extract::{Path, State},
+    routing::{delete, get},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    ApiResponse, ApiResult, Ctx,
+    app::auth::AuthUser,
+    entities::{Collection, CreateCollection, MediaItem, MediaItemWithMetadata},
+    error::{ApiError, AyiahError},
+};
+
+/// Add collection item request
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AddCollectionItemRequest {
+    pub media_item_id: i64,
+}
+
+/// Find a collection of the current user, or fail with 404
+async fn find_collection(ctx: &Ctx, user: AuthUser, id: i64) -> Result<Collection, AyiahError> {
+    Collection::find_for_user(&ctx.db, user.id, id)
+        .await
+        .map_err(|e| AyiahError::DatabaseError(format!("Failed to fetch collection: {e}")))?
+        .ok_or_else(|| ApiError::NotFound(format!("Collection with ID {id} not found")).into())
+}
+
+/// List the current user's collections
+async fn list_collections(State(ctx): State<Ctx>, user: AuthUser) -> ApiResult<Vec<Collection>> {
+    let collections = Collection::list_by_user(&ctx.db, user.id)
+        .await
+        .map_err(|e| AyiahError::DatabaseError(format!("Failed to fetch collections: {e}")))?;
+
+    Ok(ApiResponse {
+        code: 200,
+        message: "Collections retrieved successfully".to_string(),
+        data: Some(collections),
+    })
+}
+
+/// Create a collection for the current user
+async fn create_collection(
+    State(ctx): State<Ctx>,
+    user: AuthUser,
+    Json(request): Json<CreateCollection>,
+) -> ApiResult<Collection> {
+    let name = request.name.trim().to_string();
+    if name.is_empty() {
+        return Err(ApiError::BadRequest("Collection name must not be empty".to_string()).into());
+    }
+
+    let collection = Collection::create(&ctx.db, user.id, CreateCollection { name })
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::Database(e) if e.is_unique_violation() => {
+                ApiError::Conflict("A collection with this name already exists".to_string()).into()
+            }
+            e => AyiahError::DatabaseError(format!("Failed to create collection: {e}")),
+        })?;
+
+    Ok(ApiResponse {
+        code: 201,
+        message: "Collection created successfully".to_string(),
+        data: Some(collection),
+    })
+}
+
+/// Delete a collection, leaving its media items in the library
+async fn delete_collection(
+    State(ctx): State<Ctx>,
+    user: AuthUser,
+    Path(id): Path<i64>,
+) -> ApiResult<String> {
+    let collection = find_collection(&ctx, user, id).await?;
+    Collection::delete(&ctx.db, collection.id)
+        .await
+        .map_err(|e| AyiahError::DatabaseError(format!("Failed to delete collection: {e}")))?;
+
+    Ok(ApiResponse {
+        code: 200,
+        message: "Collection deleted successfully".to_string(),
+        data: Some("Deleted".to_string()),
+    })
+}
+
+/// List the items of a collection with their metadata
+async fn list_collection_items(
+    State(ctx): State<Ctx>,
+    user: AuthUser,
+    Path(id): Path<i64>,
+) -> ApiResult<Vec<MediaItemWithMetadata>> {
+    let collection = find_collection(&ctx, user, id).await?;
+    let items = Collection::list_items(&ctx.db, collection.id)
+        .await
+        .map_err(|e| AyiahError::DatabaseError(format!("Failed to fetch collection items: {e}")))?;
+
+    Ok(ApiResponse {
+        code: 200,
+        message: "Collection items retrieved successfully".to_string(),
+        data: Some(items),
+    })
+}
+
+/// Add a media item to a collection; adding it again changes nothing
+async fn add_collection_item(
+    State(ctx): State<Ctx>,
+    user: AuthUser,
+    Path(id): Path<i64>,
+    Json(request): Json<AddCollectionItemRequest>,
+) -> ApiResult<String> {
+    let collection = find_collection(&ctx, user, id).await?;
+    let media_item_id = request.media_item_id;
+    MediaItem::find_by_id(&ctx.db, media_item_id)
+        .await
+        .map_err(|e| AyiahError::DatabaseError(format!("Failed to fetch media item: {e}")))?
+        .ok_or_else(|| {
+            ApiError::NotFound(format!("Media item with ID {media_item_id} not found"))
+        })?;
+
+    Collection::add_item(&ctx.db, collection.id, media_item_id)
+        .await
+        .map_err(|e| AyiahError::DatabaseError(format!("Failed to add collection item: {e}")))?;
+
+    Ok(ApiResponse {
+        code: 200,
+        message: "Item added to collection".to_string(),
+        data: Some("Added".to_string()),
+    })
+}
+
+/// Remove a media item from a collection
+async fn remove_collection_item(
+    State(ctx): State<Ctx>,
+    user: AuthUser,
+    Path((id, media_item_id)): Path<(i64, i64)>,
+) -> ApiResult<String> {
+    let collection = find_collection(&ctx, user, id).await?;
+    let removed = Collection::remove_item(&ctx.db, collection.id, media_item_id)
+        .await
+        .map_err(|e| AyiahError::DatabaseError(format!("Failed to remove collection item: {e}")))?;
+    if !removed {
+        return Err(ApiError::NotFound(format!(
+            "Media item with ID {media_item_id} is not in the collection"
+        ))
+        .into());
+    }
+
+    Ok(ApiResponse {
+        code: 200,
+        message: "Item removed from collection".to_string(),
+        data: Some("Removed".to_string()),
+    })
+}
+
+pub fn mount() -> Router<Ctx> {
+    Router::new()
+        .route(
+            "/collections",
+            get(list_collections).post(create_collection),
+        )
+        .route("/collections/{id}", delete(delete_collection))
+        .route(
+            "/collections/{id}/items",
+            get(list_collection_items).post(add_collection_item),
+        )
+        .route(
+            "/collections/{id}/items/{media_item_id}",
+            delete(remove_collection_item),
+        )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use axum::{
+        body::Body,
+        http::{
+            Request, StatusCode,
+            header::{AUTHORIZATION, CONTENT_TYPE},
+        },
+    };
+    use tower::ServiceExt;
+
+    use super::*;
+    use crate::{
+        Context,
+        app::auth,
+        entities::{CreateLibraryFolder, CreateMediaItem, LibraryFolder, MediaType},
+    };
+
+    #[tokio::test]
+    async fn test_items_can_be_in_several_collections() {
+        let db = crate::db::test_pool().await;
+        let folder = LibraryFolder::create(
+            &db,
+            CreateLibraryFolder {
+                name: "Movies".to_string(),
+                path: "/movies".to_string(),
+                media_type: MediaType::Movie,
+                extensions: None,
+            },
+        )
+        .await
+        .unwrap();
+        let mut items = Vec::new();
+        for title in ["Die Hard", "Home Alone", "Heat"] {
+            items.push(
+                MediaItem::create(
+                    &db,
+                    CreateMediaItem {
+                        library_folder_id: folder.id,
+                        media_type: MediaType::Movie,
+                        title: title.to_string(),
+                        file_path: format!("/movies/{title}.mkv"),
+                        file_size: 1,
+                        content_hash: None,
+                        modified_at: None,
+                    },
+                )
+                .await
+                .unwrap(),
+            );
+        }
+        let ctx = Arc::new(Context::for_tests(db.clone()));
+        let mut tokens = Vec::new();
+        for name in ["alice", "bob"] {
+            let user_id: i64 = sqlx::query_scalar(
+                "INSERT INTO users (username, email, password_hash) VALUES (?, ?, 'x') RETURNING id",
+            )
+            .bind(name)
+            .bind(format!("{name}@example.com"))
+            .fetch_one(&db)
+            .await
+            .unwrap();
+            tokens.push(
+                auth::issue_token(
+                    &ctx.config.read().auth.jwt_secret,
+                    &user_id.to_string(),
+                    chrono::Duration::hours(1),
+                )
+                .unwrap(),
+            );
+        }
+        let app = mount().with_state(ctx);
+
+        let send = |method: &str, uri: String, token: &str, body: Option<serde_json::Value>| {
+            let request = Request::builder()
+                .method(method)
+                .uri(uri)
+                .header(AUTHORIZATION, format!("Bearer {token}"))
+                .header(CONTENT_TYPE, "application/json")
+                .body(body.map_or_else(Body::empty, |body| Body::from(body.to_string())))
+                .unwrap();
+            let app = app.clone();
+            async move {
+                let response = app.oneshot(request).await.unwrap();
+                let status = response.status();
+                let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+                    .await
+                    .unwrap();
+                let body: serde_json::Value = serde_json::from_slice(&body).unwrap_or_default();
+                (status, body["data"].clone())
+            }
+        };
+        let (alice, bob) = (&tokens[0], &tokens[1]);
+
+        let mut collections = Vec::new();
+        for name in ["Christmas Movies", "Action"] {
+            let (status, data) = send(
+                "POST",
+                "/collections".to_string(),
+                alice,
+                Some(serde_json::json!({ "name": name })),
+            )
+            .await;
+            assert_eq!(status, StatusCode::OK);
+            collections.push(data["id"].as_i64().unwrap());
+        }
+        let (status, _) = send(
+            "POST",
+            "/collections".to_string(),
+            alice,
+            Some(serde_json::json!({ "name": "Action" })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CONFLICT);
+
+        // Die Hard is both a Christmas movie and an action movie
+        let (christmas, action) = (collections[0], collections[1]);
+        for (collection, item) in [(christmas, 0), (christmas, 1), (action, 0), (action, 2)] {
+            let (status, _) = send(
+                "POST",
+                format!("/collections/{collection}/items"),
+                alice,
+                Some(serde_json::json!({ "media_item_id": items[item].id })),
+            )
+            .await;
+            assert_eq!(status, StatusCode::OK);
+        }
+
+        let titles = |collection: i64| {
+            let send = &send;
+            async move {
+                let (status, data) = send(
+                    "GET",
+                    format!("/collections/{collection}/items"),
+                    alice,
+                    None,
+                )
+                .await;
+                assert_eq!(status, StatusCode::OK);
+                data.as_array()
+                    .unwrap()
+                    .iter()
+                    .map(|item| item["title"].as_str().unwrap().to_string())
+                    .collect::<Vec<_>>()
+            }
+        };
+        assert_eq!(titles(christmas).await, ["Die Hard", "Home Alone"]);
+        assert_eq!(titles(action).await, ["Die Hard", "Heat"]);
+
+        let (status, _) = send(
+            "DELETE",
+            format!("/collections/{action}/items/{}", items[0].id),
+            alice,
+            None,
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(titles(action).await, ["Heat"]);
+        assert_eq!(titles(christmas).await, ["Die Hard", "Home Alone"]);
+
+        // Other users can't see or change the collection
+        let (_, data) = send("GET", "/collections".to_string(), bob, None).await;
+        assert_eq!(data, serde_json::json!([]));
+        let (status, _) = send("DELETE", format!("/collections/{christmas}"), bob, None).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+
+        let (status, _) = send("DELETE", format!("/collections/{christmas}"), alice, None).await;
+        assert_eq!(status, StatusCode::OK);
+        let (_, data) = send("GET", "/collections".to_string(), alice, None).await;
+        assert_eq!(data.as_array().unwrap().len(), 1);
+        assert_eq!(
+            MediaItem::count_by_type(&db, MediaType::Movie)
+                .await
+                .unwrap(),
+            3
+        );
+    }
+}
@@ -1,33 +1,200 @@
-use axum::{Json, Router, routing::get};
+use std::time::{Duration, Instant};
+
+use axum::{Json, Router, extract::State, http::StatusCode, routing::get};
 use serde::{Deserialize, Serialize};
 
-use crate::{ApiResponse, Ctx};
+use crate::{ApiResponse, Ctx, scraper::DisabledProvider};
+
+/// How long a single provider may take to answer a health ping
+const PROVIDER_PING_TIMEOUT: Duration = Duration::from_secs(3);
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct HealthResponse {
+    /// `healthy`, `degraded` (an optional component is down) or `unhealthy`
     pub status: String,
+    /// `connected` or `disconnected`
     pub database: String,
+    /// Per-subsystem status
+    pub components: Vec<ComponentHealth>,
+    /// Approximate number of cached scraper entries, if scraping is enabled
+    pub cache_entries: Option<u64>,
+    /// Whether the configuration file is present on disk
+    pub config_loaded: bool,
+    /// Providers left out at startup because they are misconfigured
+    #[serde(default)]
+    pub disabled_providers: Vec<DisabledProvider>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ComponentHealth {
+    pub name: String,
+    /// Whether the app can serve requests without this component
+    pub critical: bool,
+    pub healthy: bool,
+    pub latency_ms: u64,
+    pub error: Option<String>,
+}
+
+impl ComponentHealth {
+    fn new(name: impl Into<String>, critical: bool, start: Instant, error: Option<String>) -> Self {
+        Self {
+            name: name.into(),
+            critical,
+            healthy: error.is_none(),
+            latency_ms: start.elapsed().as_millis() as u64,
+            error,
+        }
+    }
 }
 
 /// Health check endpoint
-pub async fn health_check(ctx: axum::extract::State<Ctx>) -> Json<ApiResponse<HealthResponse>> {
-    // Test database connection
-    let db_status = match sqlx::query("SELECT 1").fetch_one(&ctx.db).await {
-        Ok(_) => "connected",
-        Err(_) => "disconnected",
+///
+/// Responds with 503 when a critical component (the database) is down.
+pub async fn health_check(
+    State(ctx): State<Ctx>,
+) -> (StatusCode, Json<ApiResponse<HealthResponse>>) {
+    let start = Instant::now();
+    let db_error = sqlx::query("SELECT 1")
+        .fetch_one(&ctx.db)
+        .await
+        .err()
+        .map(|e| e.to_string());
+    let mut components = vec![ComponentHealth::new("database", true, start, db_error)];
+
+    if let Some(scraper_manager) = &ctx.scraper_manager {
+        let pings = scraper_manager
+            .providers()
+            .iter()
+            .map(|provider| async move {
+                let start = Instant::now();
+                let error = match tokio::time::timeout(PROVIDER_PING_TIMEOUT, provider.ping()).await
+                {
+                    Ok(Ok(())) => None,
+                    Ok(Err(e)) => Some(e.to_string()),
+                    Err(_) => Some("timed out".to_string()),
+                };
+                ComponentHealth::new(format!("provider:{}", provider.name()), false, start, error)
+            });
+        components.extend(futures::future::join_all(pings).await);
+    }
+
+    let critical_down = components.iter().any(|c| c.critical && !c.healthy);
+    let any_down = components.iter().any(|c| !c.healthy);
+    let (status_code, status) = if critical_down {
+        (StatusCode::SERVICE_UNAVAILABLE, "unhealthy")
+    } else if any_down {
+        (StatusCode::OK, "degraded")
+    } else {
+        (StatusCode::OK, "healthy")
+    };
+
+    let response = HealthResponse {
+        status: status.to_string(),
+        database: if components[0].healthy {
+            "connected"
+        } else {
+            "disconnected"
+        }
+        .to_string(),
+        components,
+        cache_entries: ctx.scraper_manager.as_ref().map(|m| m.cache().len()),
+        config_loaded: ctx.config.config_path().is_file(),
+        disabled_providers: ctx.disabled_providers.clone(),
     };
 
-    Json(ApiResponse {
-        code: 200,
-        message: "OK".to_string(),
-        data: Some(HealthResponse {
-            status: "healthy".to_string(),
-            database: db_status.to_string(),
+    (
+        status_code,
+        Json(ApiResponse {
+            code: status_code.as_u16(),
+            message: if critical_down { "Unavailable" } else { "OK" }.to_string(),
+            data: Some(response),
         }),
-    })
+    )
 }
 
 /// Mount health routes
 pub fn mount() -> Router<Ctx> {
     Router::new().route("/health", get(health_check))
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use axum::{body::Body, http::Request};
+    use tower::ServiceExt;
+
+    use crate::{
+        Context,
+        scraper::{ScraperCache, ScraperManager, mock::FakeProvider, provider::tmdb::TmdbProvider},
+    };
+
+    use super::*;
+
+    async fn get_health(ctx: Context) -> (StatusCode, serde_json::Value) {
+        let response = mount()
+            .with_state(Arc::new(ctx))
+            .oneshot(Request::get("/health").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let status = response.status();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_healthy_reports_components() {
+        let mut manager = ScraperManager::new();
+        manager.add_provider(Box::new(FakeProvider::new("fake")));
+        let ctx = Context {
+            scraper_manager: Some(Arc::new(manager)),
+            ..Context::for_tests(crate::db::test_pool().await)
+        };
+
+        let (status, body) = get_health(ctx).await;
+
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"]["status"], "healthy");
+        assert_eq!(body["data"]["components"][1]["name"], "provider:fake");
+    }
+
+    #[tokio::test]
+    async fn test_provider_without_key_is_reported_disabled() {
+        let mut manager = ScraperManager::new();
+        manager
+            .add_provider_checked(Box::new(FakeProvider::new("fake")), None)
+            .unwrap();
+        let tmdb = TmdbProvider::new("", Arc::new(ScraperCache::new()), None);
+        let disabled = manager
+            .add_provider_checked(Box::new(tmdb), Some(" "))
+            .unwrap_err();
+        assert_eq!(manager.providers().len(), 1);
+
+        let ctx = Context {
+            scraper_manager: Some(Arc::new(manager)),
+            disabled_providers: vec![disabled],
+            ..Context::for_tests(crate::db::test_pool().await)
+        };
+        let (status, body) = get_health(ctx).await;
+
+        // A provider left out is off, not down
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"]["status"], "healthy");
+        assert_eq!(body["data"]["disabled_providers"][0]["name"], "tmdb");
+    }
+
+    #[tokio::test]
+    async fn test_database_down_is_unavailable() {
+        let db = crate::db::test_pool().await;
+        db.close().await;
+
+        let (status, body) = get_health(Context::for_tests(db)).await;
+
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(body["data"]["status"], "unhealthy");
+        assert_eq!(body["data"]["database"], "disconnected");
+        assert_eq!(body["data"]["components"][0]["healthy"], false);
+    }
+}
@@ -0,0 +1,143 @@
+use axum::{
+    Router,
+    extract::{Path, State},
+    http::header::{CACHE_CONTROL, CONTENT_TYPE},
+    response::{IntoResponse, Redirect, Response},
+    routing::get,
+};
+
+use crate::{
+    Ctx,
+    error::{ApiError, AyiahError},
+    services::image_cache::ImageCacheError,
+};
+
+/// Serve a cached image, downloading it on first request
+///
+/// Falls back to redirecting to the remote image when it can't be downloaded.
+async fn get_image(
+    State(ctx): State<Ctx>,
+    Path(hash): Path<String>,
+) -> Result<Response, AyiahError> {
+    let image_cache = ctx
+        .image_cache
+        .as_ref()
+        .ok_or_else(|| ApiError::ServiceUnavailable("Image cache is disabled".to_string()))?;
+
+    let image = match image_cache.fetch(&hash).await {
+        Ok(image) => image,
+        Err(ImageCacheError::NotFound) => {
+            return Err(ApiError::NotFound(format!("Image {hash} not found")).into());
+        }
+        Err(ImageCacheError::Download { url, .. }) => {
+            return Ok(Redirect::temporary(&url).into_response());
+        }
+        Err(e) => return Err(ApiError::InternalServerError(e.to_string()).into()),
+    };
+
+    let bytes = tokio::fs::read(&image.path)
+        .await
+        .map_err(|e| ApiError::InternalServerError(format!("Failed to read image: {e}")))?;
+    let content_type = image
+        .content_type
+        .unwrap_or_else(|| "application/octet-stream".to_string());
+
+    // Images never change under the same URL hash
+    Ok((
+        [
+            (CONTENT_TYPE, content_type),
+            (
+                CACHE_CONTROL,
+                "public, max-age=31536000, immutable".to_string(),
+            ),
+        ],
+        bytes,
+    )
+        .into_response())
+}
+
+/// Mount image routes
+pub fn mount() -> Router<Ctx> {
+    Router::new().route("/images/{hash}", get(get_image))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use axum::{
+        body::Body,
+        http::{Request, StatusCode},
+    };
+    use tower::ServiceExt;
+    use wiremock::{
+        Mock, MockServer, ResponseTemplate,
+        matchers::{method, path},
+    };
+
+    use super::*;
+    use crate::{
+        Context,
+        services::{ImageCache, ImageCacheMode, image_cache::url_hash},
+    };
+
+    async fn get(ctx: &Ctx, uri: &str) -> Response {
+        mount()
+            .with_state(ctx.clone())
+            .oneshot(Request::get(uri).body(Body::empty()).unwrap())
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_fetched_image_is_stored_and_served_locally() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/poster.jpg"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_raw(b"fake jpeg".to_vec(), "image/jpeg"),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let db = crate::db::test_pool().await;
+        let dir = tempfile::tempdir().unwrap();
+        let mut ctx = Context::for_tests(db.clone());
+        ctx.image_cache = Some(Arc::new(ImageCache::new(
+            db,
+            dir.path(),
+            ImageCacheMode::Lazy,
+        )));
+        let ctx = Arc::new(ctx);
+
+        let url = format!("{}/poster.jpg", server.uri());
+        let hash = url_hash(&url);
+        let local = ctx
+            .image_cache
+            .as_ref()
+            .unwrap()
+            .localize(Some(url))
+            .await
+            .unwrap();
+        assert_eq!(local, format!("/api/images/{hash}"));
+        assert!(!dir.path().join(&hash).exists());
+
+        // Downloaded on the first request, then served from disk
+        for _ in 0..2 {
+            let response = get(&ctx, &format!("/images/{hash}")).await;
+            assert_eq!(response.status(), StatusCode::OK);
+            assert_eq!(response.headers()[CONTENT_TYPE], "image/jpeg");
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+                .await
+                .unwrap();
+            assert_eq!(&body[..], b"fake jpeg");
+        }
+        assert_eq!(std::fs::read(dir.path().join(&hash)).unwrap(), b"fake jpeg");
+
+        let unknown = get(&ctx, &format!("/images/{}", "0".repeat(32))).await;
+        assert_eq!(unknown.status(), StatusCode::NOT_FOUND);
+        let invalid = get(&ctx, "/images/..%2Fconfig.toml").await;
+        assert_eq!(invalid.status(), StatusCode::NOT_FOUND);
+    }
+}
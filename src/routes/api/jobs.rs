@@ -0,0 +1,182 @@
+use axum::{
+    Router,
+    extract::{Path, State},
+    routing::{get, post},
+};
+
+use crate::{
+    ApiResponse, ApiResult, Ctx,
+    error::{ApiError, AyiahError},
+    services::{
+        job_queue::{JobId, JobInfo},
+        scan_scheduler::ScheduleInfo,
+    },
+};
+
+/// List background jobs, newest first
+async fn list_jobs(State(ctx): State<Ctx>) -> ApiResult<Vec<JobInfo>> {
+    Ok(ApiResponse {
+        code: 200,
+        message: "Jobs retrieved successfully".to_string(),
+        data: Some(ctx.jobs.list()),
+    })
+}
+
+/// Get a background job by ID
+async fn get_job(State(ctx): State<Ctx>, Path(id): Path<JobId>) -> ApiResult<JobInfo> {
+    let job = ctx.jobs.get(id).ok_or_else(|| {
+        AyiahError::ApiError(ApiError::NotFound(format!("Job with ID {id} not found")))
+    })?;
+
+    Ok(ApiResponse {
+        code: 200,
+        message: "Job retrieved successfully".to_string(),
+        data: Some(job),
+    })
+}
+
+/// Ask a queued or running job to stop
+///
+/// The job reports `cancelled` once it has stopped; work it finished is kept.
+async fn cancel_job(State(ctx): State<Ctx>, Path(id): Path<JobId>) -> ApiResult<JobInfo> {
+    let job = ctx.jobs.get(id).ok_or_else(|| {
+        AyiahError::ApiError(ApiError::NotFound(format!("Job with ID {id} not found")))
+    })?;
+    if !ctx.jobs.cancel(id) {
+        return Err(AyiahError::ApiError(ApiError::Conflict(format!(
+            "Job with ID {id} has already finished"
+        ))));
+    }
+
+    Ok(ApiResponse {
+        code: 200,
+        message: "Job cancellation requested".to_string(),
+        data: Some(job),
+    })
+}
+
+/// Get the scan schedule and when it next runs; no data when it's disabled
+async fn get_schedule(State(ctx): State<Ctx>) -> ApiResult<ScheduleInfo> {
+    let schedule = ctx
+        .scan_scheduler
+        .as_ref()
+        .map(|scheduler| scheduler.info());
+
+    Ok(ApiResponse {
+        code: 200,
+        message: if schedule.is_some() {
+            "Scan schedule retrieved successfully".to_string()
+        } else {
+            "Scheduled scans are disabled".to_string()
+        },
+        data: schedule,
+    })
+}
+
+/// Mount job routes
+pub fn mount() -> Router<Ctx> {
+    Router::new()
+        .route("/jobs", get(list_jobs))
+        .route("/jobs/schedule", get(get_schedule))
+        .route("/jobs/{id}", get(get_job))
+        .route("/jobs/{id}/cancel", post(cancel_job))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{sync::Arc, time::Duration};
+
+    use axum::{
+        body::{Body, to_bytes},
+        http::{Request, StatusCode},
+    };
+    use tower::ServiceExt;
+
+    use crate::{Context, services::job_queue::JobStatus};
+
+    use super::*;
+
+    async fn fetch_job(ctx: &Ctx, id: JobId) -> (StatusCode, Option<JobInfo>) {
+        let response = mount()
+            .with_state(ctx.clone())
+            .oneshot(
+                Request::get(format!("/jobs/{id}"))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let status = response.status();
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body: ApiResponse<JobInfo> = serde_json::from_slice(&body).unwrap();
+        (status, body.data)
+    }
+
+    #[tokio::test]
+    async fn test_poll_enqueued_job_until_done() {
+        let ctx = Arc::new(Context::for_tests(crate::db::test_pool().await));
+        let id = ctx.jobs.enqueue("scan", |handle| async move {
+            handle.set_progress(2, 2);
+            Ok(())
+        });
+
+        let job = tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                let (status, job) = fetch_job(&ctx, id).await;
+                assert_eq!(status, StatusCode::OK);
+                let job = job.unwrap();
+                if job.status == JobStatus::Done {
+                    return job;
+                }
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("job did not finish");
+
+        assert_eq!((job.processed, job.total), (2, Some(2)));
+        assert_eq!(fetch_job(&ctx, id + 1).await.0, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_cancel_running_job() {
+        let ctx = Arc::new(Context::for_tests(crate::db::test_pool().await));
+        let (started, wait_started) = tokio::sync::oneshot::channel::<()>();
+        let id = ctx.jobs.enqueue("refresh_metadata", |handle| async move {
+            let _ = started.send(());
+            handle.cancellation_token().cancelled().await;
+            Ok(())
+        });
+        wait_started.await.unwrap();
+
+        let cancel = async |id: JobId| {
+            mount()
+                .with_state(ctx.clone())
+                .oneshot(
+                    Request::post(format!("/jobs/{id}/cancel"))
+                        .body(Body::empty())
+                        .unwrap(),
+                )
+                .await
+                .unwrap()
+                .status()
+        };
+        assert_eq!(cancel(id).await, StatusCode::OK);
+
+        let job = tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                let job = fetch_job(&ctx, id).await.1.unwrap();
+                if job.status.is_finished() {
+                    return job;
+                }
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("job did not stop");
+        assert_eq!(job.status, JobStatus::Cancelled);
+
+        assert_eq!(cancel(id).await, StatusCode::CONFLICT);
+        assert_eq!(cancel(id + 1).await, StatusCode::NOT_FOUND);
+    }
+}
@@ -1,54 +1,187 @@
+use std::{
+    collections::{HashMap, HashSet},
+    convert::Infallible,
+};
+
 use axum::{
     Json, Router,
-    extract::{Path, State},
-    http::StatusCode,
-    routing::get,
+    body::{Body, Bytes},
+    extract::{Path, Query, State},
+    http::{StatusCode, header::CONTENT_TYPE},
+    middleware,
+    response::{IntoResponse, Response},
+    routing::{delete, get, post, put},
 };
+use futures::{StreamExt, stream};
 use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc;
 
 use crate::{
-    ApiResponse, ApiResult, Ctx,
-    entities::{MediaItemWithMetadata, MediaType},
+    ApiResponse, ApiResult, Ctx, PaginatedResponse, Pagination,
+    app::auth::AuthUser,
+    entities::{
+        GenreCount, LibraryFilter, MediaItem, MediaItemWithMetadata, MediaStatus, MediaType,
+        MetadataChange, MetadataFetchAttempt, SetMediaStatus, Tag, UnmatchedItem, VideoMetadata,
+        normalize_tags,
+    },
+    error::{ApiError, AyiahError},
+    middleware::etag,
+    services::{
+        MetadataAgentError,
+        deduplicator::{self, DedupReport},
+        job_queue::JobId,
+        library_import::{self, ImportMode, ImportReport},
+    },
 };
 
 /// Library API response
-#[derive(Debug, Serialize, Deserialize)]
-pub struct LibraryResponse {
-    pub items: Vec<MediaItemWithMetadata>,
-    pub total: usize,
+pub type LibraryResponse = PaginatedResponse<MediaItemWithMetadata>;
+
+/// Export lines buffered ahead of a slow client
+const EXPORT_BUFFER: usize = 64;
+
+/// Recently added items returned when no limit is given
+const DEFAULT_RECENT_LIMIT: u32 = 20;
+
+/// Most recently added items returned at once
+const MAX_RECENT_LIMIT: u32 = 100;
+
+/// Most items deleted by one bulk delete request
+const MAX_BULK_DELETE: usize = 500;
+
+/// Fetch one page of a media type
+///
+/// Sort fields are a closed set, so an unknown `sort` or `order` is rejected
+/// with 400 while deserializing the query.
+async fn list_page(
+    ctx: &Ctx,
+    user: Option<AuthUser>,
+    media_type: MediaType,
+    filter: &LibraryFilter,
+    pagination: Pagination,
+) -> Result<LibraryResponse, sqlx::Error> {
+    let mut items = MediaItemWithMetadata::list_by_type(
+        &ctx.db,
+        media_type,
+        filter,
+        pagination.limit(),
+        pagination.offset(),
+    )
+    .await?;
+    let total = MediaItemWithMetadata::count_by_type(&ctx.db, media_type, filter).await?;
+
+    if let Some(user) = user {
+        let ids: Vec<i64> = items.iter().map(|item| item.media_item.id).collect();
+        let mut statuses: HashMap<_, _> = MediaStatus::list_for_items(&ctx.db, user.id, &ids)
+            .await?
+            .into_iter()
+            .map(|status| (status.media_item_id, status))
+            .collect();
+        for item in &mut items {
+            let id = item.media_item.id;
+            item.status = Some(
+                statuses
+                    .remove(&id)
+                    .unwrap_or_else(|| MediaStatus::unwatched(user.id, id)),
+            );
+        }
+    }
+
+    Ok(PaginatedResponse::new(items, total, pagination))
 }
 
 /// Get movies
-async fn get_movies(State(ctx): State<Ctx>) -> ApiResult<LibraryResponse> {
-    let items = MediaItemWithMetadata::list_by_type(&ctx.db, MediaType::Movie)
+async fn get_movies(
+    State(ctx): State<Ctx>,
+    user: Option<AuthUser>,
+    Query(pagination): Query<Pagination>,
+    Query(filter): Query<LibraryFilter>,
+) -> ApiResult<LibraryResponse> {
+    let page = list_page(&ctx, user, MediaType::Movie, &filter, pagination)
         .await
         .map_err(|e| {
             crate::error::AyiahError::DatabaseError(format!("Failed to fetch movies: {e}"))
         })?;
 
-    let total = items.len();
-
     Ok(ApiResponse {
         code: 200,
         message: "Movies retrieved successfully".to_string(),
-        data: Some(LibraryResponse { items, total }),
+        data: Some(page),
     })
 }
 
 /// Get TV shows
-async fn get_tv_shows(State(ctx): State<Ctx>) -> ApiResult<LibraryResponse> {
-    let items = MediaItemWithMetadata::list_by_type(&ctx.db, MediaType::Tv)
+async fn get_tv_shows(
+    State(ctx): State<Ctx>,
+    user: Option<AuthUser>,
+    Query(pagination): Query<Pagination>,
+    Query(filter): Query<LibraryFilter>,
+) -> ApiResult<LibraryResponse> {
+    let page = list_page(&ctx, user, MediaType::Tv, &filter, pagination)
         .await
         .map_err(|e| {
             crate::error::AyiahError::DatabaseError(format!("Failed to fetch TV shows: {e}"))
         })?;
 
-    let total = items.len();
-
     Ok(ApiResponse {
         code: 200,
         message: "TV shows retrieved successfully".to_string(),
-        data: Some(LibraryResponse { items, total }),
+        data: Some(page),
+    })
+}
+
+/// Recently added query parameters
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct RecentQuery {
+    /// Number of items, clamped to `1..=MAX_RECENT_LIMIT`
+    pub limit: Option<u32>,
+    /// Only list items of this type
+    pub media_type: Option<MediaType>,
+}
+
+/// Get the most recently added media items, newest first
+async fn get_recent(
+    State(ctx): State<Ctx>,
+    Query(query): Query<RecentQuery>,
+) -> ApiResult<Vec<MediaItemWithMetadata>> {
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_RECENT_LIMIT)
+        .clamp(1, MAX_RECENT_LIMIT);
+    let items = MediaItem::list_recent(&ctx.db, query.media_type, i64::from(limit))
+        .await
+        .map_err(|e| {
+            crate::error::AyiahError::DatabaseError(format!("Failed to fetch recent items: {e}"))
+        })?;
+
+    Ok(ApiResponse {
+        code: 200,
+        message: "Recent items retrieved successfully".to_string(),
+        data: Some(items),
+    })
+}
+
+/// Genres query parameters
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct GenresQuery {
+    /// Only count items of this type
+    pub media_type: Option<MediaType>,
+}
+
+/// List the genres present in the library with their item counts, most
+/// common first
+async fn get_genres(
+    State(ctx): State<Ctx>,
+    Query(query): Query<GenresQuery>,
+) -> ApiResult<Vec<GenreCount>> {
+    let genres = VideoMetadata::count_genres(&ctx.db, query.media_type)
+        .await
+        .map_err(|e| AyiahError::DatabaseError(format!("Failed to count genres: {e}")))?;
+
+    Ok(ApiResponse {
+        code: 200,
+        message: "Genres retrieved successfully".to_string(),
+        data: Some(genres),
     })
 }
 
@@ -75,10 +208,187 @@ async fn get_media_item(
     })
 }
 
+/// Fail with 404 unless the media item exists
+async fn ensure_media_item(ctx: &Ctx, id: i64) -> Result<(), AyiahError> {
+    MediaItem::find_by_id(&ctx.db, id)
+        .await
+        .map_err(|e| AyiahError::DatabaseError(format!("Failed to fetch media item: {e}")))?
+        .ok_or_else(|| ApiError::NotFound(format!("Media item with ID {id} not found")))?;
+
+    Ok(())
+}
+
+/// Get the current user's watch status of a media item
+async fn get_media_status(
+    State(ctx): State<Ctx>,
+    user: AuthUser,
+    Path(id): Path<i64>,
+) -> ApiResult<MediaStatus> {
+    ensure_media_item(&ctx, id).await?;
+    let status = MediaStatus::get(&ctx.db, user.id, id)
+        .await
+        .map_err(|e| AyiahError::DatabaseError(format!("Failed to fetch media status: {e}")))?;
+
+    Ok(ApiResponse {
+        code: 200,
+        message: "Media status retrieved successfully".to_string(),
+        data: Some(status),
+    })
+}
+
+/// Set the current user's watch status of a media item
+async fn set_media_status(
+    State(ctx): State<Ctx>,
+    user: AuthUser,
+    Path(id): Path<i64>,
+    Json(request): Json<SetMediaStatus>,
+) -> ApiResult<MediaStatus> {
+    ensure_media_item(&ctx, id).await?;
+    let status = MediaStatus::set(&ctx.db, user.id, id, request)
+        .await
+        .map_err(|e| AyiahError::DatabaseError(format!("Failed to save media status: {e}")))?;
+
+    Ok(ApiResponse {
+        code: 200,
+        message: "Media status saved successfully".to_string(),
+        data: Some(status),
+    })
+}
+
+/// Add tags request
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AddTagsRequest {
+    pub tags: Vec<String>,
+}
+
+/// Tag a media item, returning all of its tags
+async fn add_tags(
+    State(ctx): State<Ctx>,
+    Path(id): Path<i64>,
+    Json(request): Json<AddTagsRequest>,
+) -> ApiResult<Vec<Tag>> {
+    ensure_media_item(&ctx, id).await?;
+    if normalize_tags(&request.tags).is_empty() {
+        return Err(
+            ApiError::BadRequest("At least one non-empty tag is required".to_string()).into(),
+        );
+    }
+
+    let tags = Tag::add_to_item(&ctx.db, id, &request.tags)
+        .await
+        .map_err(|e| AyiahError::DatabaseError(format!("Failed to add tags: {e}")))?;
+
+    Ok(ApiResponse {
+        code: 200,
+        message: "Tags added successfully".to_string(),
+        data: Some(tags),
+    })
+}
+
+/// Remove a tag from a media item
+async fn remove_tag(
+    State(ctx): State<Ctx>,
+    Path((id, tag)): Path<(i64, String)>,
+) -> ApiResult<String> {
+    let removed = Tag::remove_from_item(&ctx.db, id, &tag)
+        .await
+        .map_err(|e| AyiahError::DatabaseError(format!("Failed to remove tag: {e}")))?;
+    if !removed {
+        return Err(ApiError::NotFound(format!("Media item {id} is not tagged {tag}")).into());
+    }
+
+    Ok(ApiResponse {
+        code: 200,
+        message: "Tag removed successfully".to_string(),
+        data: Some("Removed".to_string()),
+    })
+}
+
+/// Metadata lock request
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MetadataLockRequest {
+    pub locked: bool,
+}
+
+/// Lock or unlock the metadata of a media item, returning the item
+///
+/// Refreshes skip items with locked metadata unless forced.
+async fn set_metadata_lock(
+    State(ctx): State<Ctx>,
+    Path(id): Path<i64>,
+    Json(request): Json<MetadataLockRequest>,
+) -> ApiResult<MediaItem> {
+    ensure_media_item(&ctx, id).await?;
+    MediaItem::set_metadata_locked(&ctx.db, id, request.locked)
+        .await
+        .map_err(|e| AyiahError::DatabaseError(format!("Failed to update media item: {e}")))?;
+    let item = MediaItem::find_by_id(&ctx.db, id)
+        .await
+        .map_err(|e| AyiahError::DatabaseError(format!("Failed to fetch media item: {e}")))?
+        .ok_or_else(|| ApiError::NotFound(format!("Media item with ID {id} not found")))?;
+
+    Ok(ApiResponse {
+        code: 200,
+        message: "Metadata lock updated successfully".to_string(),
+        data: Some(item),
+    })
+}
+
+/// List the media items carrying a tag, newest first
+async fn get_tagged_items(
+    State(ctx): State<Ctx>,
+    Path(tag): Path<String>,
+    Query(pagination): Query<Pagination>,
+) -> ApiResult<LibraryResponse> {
+    let items = Tag::list_items(&ctx.db, &tag, pagination.limit(), pagination.offset())
+        .await
+        .map_err(|e| AyiahError::DatabaseError(format!("Failed to fetch tagged items: {e}")))?;
+    let total = Tag::count_items(&ctx.db, &tag)
+        .await
+        .map_err(|e| AyiahError::DatabaseError(format!("Failed to count tagged items: {e}")))?;
+
+    Ok(ApiResponse {
+        code: 200,
+        message: "Tagged items retrieved successfully".to_string(),
+        data: Some(PaginatedResponse::new(items, total, pagination)),
+    })
+}
+
+/// List items whose metadata lookups keep failing and need a manual match
+async fn get_unmatched(
+    State(ctx): State<Ctx>,
+    Query(pagination): Query<Pagination>,
+) -> ApiResult<PaginatedResponse<UnmatchedItem>> {
+    let items =
+        MetadataFetchAttempt::list_unmatched(&ctx.db, pagination.limit(), pagination.offset())
+            .await
+            .map_err(|e| {
+                AyiahError::DatabaseError(format!("Failed to fetch unmatched items: {e}"))
+            })?;
+    let total = MetadataFetchAttempt::count_unmatched(&ctx.db)
+        .await
+        .map_err(|e| AyiahError::DatabaseError(format!("Failed to count unmatched items: {e}")))?;
+
+    Ok(ApiResponse {
+        code: 200,
+        message: "Unmatched items retrieved successfully".to_string(),
+        data: Some(PaginatedResponse::new(items, total, pagination)),
+    })
+}
+
+/// Refresh metadata query parameters
+#[derive(Debug, Default, Deserialize)]
+pub struct RefreshQuery {
+    /// Refresh even if the item's metadata is locked
+    #[serde(default)]
+    pub force: bool,
+}
+
 /// Refresh metadata for a media item
 async fn refresh_metadata(
     State(ctx): State<Ctx>,
     Path(id): Path<i64>,
+    Query(query): Query<RefreshQuery>,
 ) -> Result<Json<ApiResponse<String>>, (StatusCode, Json<ApiResponse<String>>)> {
     let metadata_agent = ctx.metadata_agent.as_ref().ok_or_else(|| {
         (
@@ -91,12 +401,20 @@ async fn refresh_metadata(
         )
     })?;
 
-    match metadata_agent.refresh_metadata(id).await {
+    match metadata_agent.refresh_metadata(id, query.force).await {
         Ok(_) => Ok(Json(ApiResponse {
             code: 200,
             message: "Metadata refreshed successfully".to_string(),
             data: Some("Metadata updated".to_string()),
         })),
+        Err(MetadataAgentError::MetadataLocked) => Err((
+            StatusCode::CONFLICT,
+            Json(ApiResponse {
+                code: 409,
+                message: "Metadata is locked; refresh with force=true to override".to_string(),
+                data: None,
+            }),
+        )),
         Err(e) => Err((
             StatusCode::INTERNAL_SERVER_ERROR,
             Json(ApiResponse {
@@ -108,11 +426,1287 @@ async fn refresh_metadata(
     }
 }
 
+/// Fetch fresh metadata for a media item and list what a refresh would
+/// change, old and new value per field, without saving it
+async fn preview_refresh(
+    State(ctx): State<Ctx>,
+    Path(id): Path<i64>,
+) -> ApiResult<Vec<MetadataChange>> {
+    let metadata_agent = ctx
+        .metadata_agent
+        .as_ref()
+        .ok_or_else(|| ApiError::ServiceUnavailable("Metadata agent not available".to_string()))?;
+
+    let changes = metadata_agent
+        .preview_refresh(id)
+        .await
+        .map_err(|e| match e {
+            MetadataAgentError::MediaItemNotFound => {
+                ApiError::NotFound(format!("Media item with ID {id} not found")).into()
+            }
+            MetadataAgentError::NoMatchingResults => ApiError::NotFound(e.to_string()).into(),
+            MetadataAgentError::DatabaseError(e) => AyiahError::DatabaseError(e),
+            e => ApiError::InternalServerError(format!("Failed to fetch metadata: {e}")).into(),
+        })?;
+
+    Ok(ApiResponse {
+        code: 200,
+        message: "Metadata refresh previewed".to_string(),
+        data: Some(changes),
+    })
+}
+
+/// Export query parameters
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ExportQuery {
+    /// Only export items of this type
+    pub media_type: Option<MediaType>,
+}
+
+/// Stream the library as newline-delimited JSON, one item per line
+///
+/// Rows are serialized as they are read, so memory stays flat however large
+/// the library is. An error mid-export ends the stream early, since the
+/// status has already been sent.
+async fn export_library(State(ctx): State<Ctx>, Query(query): Query<ExportQuery>) -> Response {
+    let (tx, rx) = mpsc::channel::<Bytes>(EXPORT_BUFFER);
+
+    let db = ctx.db.clone();
+    tokio::spawn(async move {
+        let mut records = MediaItemWithMetadata::stream_all(&db, query.media_type);
+        while let Some(record) = records.next().await {
+            let line = record
+                .map_err(|e| e.to_string())
+                .and_then(|record| serde_json::to_vec(&record).map_err(|e| e.to_string()));
+            let mut line = match line {
+                Ok(line) => line,
+                Err(e) => {
+                    tracing::warn!("Library export stopped early: {e}");
+                    break;
+                }
+            };
+            line.push(b'\n');
+
+            // The client went away
+            if tx.send(Bytes::from(line)).await.is_err() {
+                break;
+            }
+        }
+    });
+
+    let lines = stream::unfold(rx, |mut rx| async move {
+        rx.recv().await.map(|line| (Ok::<_, Infallible>(line), rx))
+    });
+
+    (
+        [(CONTENT_TYPE, "application/x-ndjson")],
+        Body::from_stream(lines),
+    )
+        .into_response()
+}
+
+/// Import query parameters
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ImportQuery {
+    /// What to do with items that already exist (defaults to skip)
+    #[serde(default)]
+    pub mode: ImportMode,
+}
+
+/// Import newline-delimited JSON produced by the export endpoint
+///
+/// The body is read incrementally, so large imports aren't buffered whole.
+async fn import_library(
+    State(ctx): State<Ctx>,
+    Query(query): Query<ImportQuery>,
+    body: Body,
+) -> ApiResult<ImportReport> {
+    let report = library_import::import_ndjson(&ctx.db, body.into_data_stream(), query.mode)
+        .await
+        .map_err(|e| AyiahError::DatabaseError(format!("Failed to import library: {e}")))?;
+
+    Ok(ApiResponse {
+        code: 200,
+        message: "Library imported".to_string(),
+        data: Some(report),
+    })
+}
+
+/// Bulk delete request
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BulkDeleteRequest {
+    /// Up to `MAX_BULK_DELETE` media item IDs; repeats are ignored
+    pub ids: Vec<i64>,
+    /// Also remove the media files from disk
+    #[serde(default)]
+    pub delete_files: bool,
+}
+
+/// Outcome for one ID of a bulk delete
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BulkDeleteResult {
+    pub id: i64,
+    pub deleted: bool,
+    /// Why the item wasn't deleted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Why the file of a deleted item couldn't be removed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file_error: Option<String>,
+}
+
+/// Bulk delete response
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BulkDeleteReport {
+    pub deleted: usize,
+    pub failed: usize,
+    /// One result per requested ID, in request order
+    pub results: Vec<BulkDeleteResult>,
+}
+
+/// Delete many media items at once, with their metadata
+///
+/// Deletion is best effort per ID: IDs that don't exist are reported and
+/// skipped, and the rest are deleted together in a single statement, so a
+/// database error deletes none of them. Files are removed once the rows are
+/// gone; a file that can't be removed is reported on its item without
+/// restoring it.
+async fn bulk_delete(
+    State(ctx): State<Ctx>,
+    Json(request): Json<BulkDeleteRequest>,
+) -> ApiResult<BulkDeleteReport> {
+    let mut ids = request.ids;
+    let mut seen = HashSet::new();
+    ids.retain(|id| seen.insert(*id));
+    if ids.is_empty() {
+        return Err(ApiError::BadRequest("No media item IDs given".to_string()).into());
+    }
+    if ids.len() > MAX_BULK_DELETE {
+        return Err(ApiError::BadRequest(format!(
+            "At most {MAX_BULK_DELETE} media items can be deleted at once"
+        ))
+        .into());
+    }
+
+    let mut deleted: HashMap<i64, MediaItem> = MediaItem::delete_many(&ctx.db, &ids)
+        .await
+        .map_err(|e| AyiahError::DatabaseError(format!("Failed to delete media items: {e}")))?
+        .into_iter()
+        .map(|item| (item.id, item))
+        .collect();
+
+    let mut results = Vec::with_capacity(ids.len());
+    for id in ids {
+        let Some(item) = deleted.remove(&id) else {
+            results.push(BulkDeleteResult {
+                id,
+                deleted: false,
+                error: Some(format!("Media item with ID {id} not found")),
+                file_error: None,
+            });
+            continue;
+        };
+
+        let file_error = if request.delete_files {
+            match tokio::fs::remove_file(&item.file_path).await {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                    tracing::warn!("Failed to remove {}: {e}", item.file_path);
+                    Some(format!("Failed to remove {}: {e}", item.file_path))
+                }
+                _ => None,
+            }
+        } else {
+            None
+        };
+        results.push(BulkDeleteResult {
+            id,
+            deleted: true,
+            error: None,
+            file_error,
+        });
+    }
+
+    let deleted = results.iter().filter(|result| result.deleted).count();
+    Ok(ApiResponse {
+        code: 200,
+        message: format!("Deleted {deleted} media items"),
+        data: Some(BulkDeleteReport {
+            deleted,
+            failed: results.len() - deleted,
+            results,
+        }),
+    })
+}
+
+/// Deduplicate request
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DeduplicateRequest {
+    /// Only report what would be linked
+    #[serde(default)]
+    pub dry_run: bool,
+    /// Must be set to run a destructive (non dry-run) deduplication
+    #[serde(default)]
+    pub confirm: bool,
+    /// Wait for deduplication to finish instead of running it in the background
+    #[serde(default)]
+    pub sync: bool,
+}
+
+/// Deduplicate response
+///
+/// `report` is only known when deduplication ran synchronously; otherwise
+/// `job_id` identifies the background job doing the work.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DeduplicateResponse {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub report: Option<DedupReport>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub job_id: Option<JobId>,
+}
+
+/// Replace identical files with hard links to reclaim space
+async fn deduplicate(
+    State(ctx): State<Ctx>,
+    Json(request): Json<DeduplicateRequest>,
+) -> ApiResult<DeduplicateResponse> {
+    if !request.dry_run && !request.confirm {
+        return Err(ApiError::BadRequest(
+            "Deduplication replaces files; set confirm to true or use dry_run".to_string(),
+        )
+        .into());
+    }
+
+    if !request.sync {
+        let db = ctx.db.clone();
+        let dry_run = request.dry_run;
+        let job_id = ctx.jobs.enqueue("deduplicate", move |_| async move {
+            deduplicator::deduplicate(&db, dry_run)
+                .await
+                .map(|_| ())
+                .map_err(|e| format!("Deduplication failed: {e}"))
+        });
+
+        return Ok(ApiResponse {
+            code: 202,
+            message: "Deduplication started".to_string(),
+            data: Some(DeduplicateResponse {
+                report: None,
+                job_id: Some(job_id),
+            }),
+        });
+    }
+
+    let report = deduplicator::deduplicate(&ctx.db, request.dry_run)
+        .await
+        .map_err(AyiahError::from)?;
+
+    Ok(ApiResponse {
+        code: 200,
+        message: if report.dry_run {
+            "Deduplication planned".to_string()
+        } else {
+            "Deduplication completed".to_string()
+        },
+        data: Some(DeduplicateResponse {
+            report: Some(report),
+            job_id: None,
+        }),
+    })
+}
+
 /// Mount library routes
 pub fn mount() -> Router<Ctx> {
-    Router::new()
+    // Read endpoints answer conditional GETs so polling clients can skip
+    // unchanged payloads
+    let reads = Router::new()
         .route("/library/movies", get(get_movies))
         .route("/library/tv", get(get_tv_shows))
+        .route("/library/recent", get(get_recent))
+        .route("/library/genres", get(get_genres))
+        .route("/library/tags/{tag}/items", get(get_tagged_items))
         .route("/library/items/{id}", get(get_media_item))
+        .route_layer(middleware::from_fn(etag));
+
+    Router::new()
+        .merge(reads)
+        .route("/library/unmatched", get(get_unmatched))
+        .route(
+            "/library/items/{id}/status",
+            get(get_media_status).put(set_media_status),
+        )
+        .route("/library/items/delete", post(bulk_delete))
+        .route("/library/items/{id}/tags", post(add_tags))
+        .route("/library/items/{id}/tags/{tag}", delete(remove_tag))
+        .route("/library/items/{id}/metadata-lock", put(set_metadata_lock))
         .route("/library/items/{id}/refresh", get(refresh_metadata))
+        .route("/library/items/{id}/preview-refresh", post(preview_refresh))
+        .route("/library/export", get(export_library))
+}
+
+/// Routes that may relink the whole library within the request when asked to
+/// wait, mounted outside the request timeout
+pub fn mount_long_running() -> Router<Ctx> {
+    Router::new().route("/library/deduplicate", post(deduplicate))
+}
+
+/// Routes streaming request bodies of any size, mounted outside the request
+/// limits
+pub fn mount_imports() -> Router<Ctx> {
+    Router::new().route("/library/import", post(import_library))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{sync::Arc, time::Duration};
+
+    use axum::{
+        body::Body,
+        http::{
+            HeaderValue, Request,
+            header::{AUTHORIZATION, ETAG, IF_NONE_MATCH},
+        },
+    };
+    use tower::ServiceExt;
+
+    use super::*;
+    use crate::{
+        Context,
+        entities::{CreateLibraryFolder, CreateMediaItem, CreateVideoMetadata, LibraryFolder},
+        scraper::{
+            ExternalIds, MediaDetails, ScraperManager,
+            mock::{FakeProvider, movie_details},
+        },
+        services::{MetadataAgent, metadata_agent::FetchRetryPolicy},
+    };
+
+    async fn seed_item(db: &crate::db::Database, folder: &LibraryFolder, title: &str) -> MediaItem {
+        MediaItem::create(
+            db,
+            CreateMediaItem {
+                library_folder_id: folder.id,
+                media_type: folder.media_type,
+                title: title.to_string(),
+                file_path: format!("{}/{title}.mkv", folder.path),
+                file_size: 1,
+                content_hash: None,
+                modified_at: None,
+            },
+        )
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_unchanged_movies_answer_not_modified() {
+        let db = crate::db::test_pool().await;
+        let folder = LibraryFolder::create(
+            &db,
+            CreateLibraryFolder {
+                name: "Movies".to_string(),
+                path: "/movies".to_string(),
+                media_type: MediaType::Movie,
+                extensions: None,
+            },
+        )
+        .await
+        .unwrap();
+        let mut item = seed_item(&db, &folder, "Heat").await;
+        let app = mount().with_state(Arc::new(Context::for_tests(db.clone())));
+
+        let get = |etag: Option<&HeaderValue>| {
+            let mut request = Request::get("/library/movies");
+            if let Some(etag) = etag {
+                request = request.header(IF_NONE_MATCH, etag);
+            }
+            app.clone().oneshot(request.body(Body::empty()).unwrap())
+        };
+
+        let response = get(None).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let etag = response.headers().get(ETAG).unwrap().clone();
+
+        let response = get(Some(&etag)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert!(body.is_empty());
+
+        item.title = "Heat (1995)".to_string();
+        item.update(&db).await.unwrap();
+        let response = get(Some(&etag)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_ne!(response.headers().get(ETAG), Some(&etag));
+    }
+
+    async fn seed_folders(db: &crate::db::Database) -> Vec<LibraryFolder> {
+        let mut folders = Vec::new();
+        for (name, media_type) in [("Movies", MediaType::Movie), ("Shows", MediaType::Tv)] {
+            folders.push(
+                LibraryFolder::create(
+                    db,
+                    CreateLibraryFolder {
+                        name: name.to_string(),
+                        path: format!("/{name}"),
+                        media_type,
+                        extensions: None,
+                    },
+                )
+                .await
+                .unwrap(),
+            );
+        }
+        folders
+    }
+
+    /// Two folders with three items, one of them with metadata
+    async fn seed_library(db: &crate::db::Database) {
+        let folders = seed_folders(db).await;
+        let heat = seed_item(db, &folders[0], "Heat").await;
+        seed_item(db, &folders[0], "Ronin").await;
+        seed_item(db, &folders[1], "Cowboy Bebop").await;
+        VideoMetadata::upsert(
+            db,
+            CreateVideoMetadata {
+                media_item_id: heat.id,
+                tmdb_id: Some(949),
+                tvdb_id: None,
+                imdb_id: None,
+                overview: None,
+                poster_path: None,
+                backdrop_path: None,
+                release_date: Some("1995-12-15".to_string()),
+                runtime: None,
+                vote_average: None,
+                vote_count: None,
+                genres: vec!["Crime".to_string()],
+                anilist_id: None,
+                mal_id: None,
+                bangumi_id: None,
+                episode_count: None,
+            },
+        )
+        .await
+        .unwrap();
+    }
+
+    async fn body_text(response: Response) -> String {
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        String::from_utf8(body.to_vec()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_export_streams_one_item_per_line() {
+        let db = crate::db::test_pool().await;
+        seed_library(&db).await;
+        let app = mount().with_state(Arc::new(Context::for_tests(db)));
+
+        let export = |uri: &'static str| {
+            let app = app.clone();
+            async move {
+                let response = app
+                    .oneshot(Request::get(uri).body(Body::empty()).unwrap())
+                    .await
+                    .unwrap();
+                assert_eq!(response.status(), StatusCode::OK);
+                assert_eq!(response.headers()[CONTENT_TYPE], "application/x-ndjson");
+                body_text(response)
+                    .await
+                    .lines()
+                    .map(|line| serde_json::from_str::<MediaItemWithMetadata>(line).unwrap())
+                    .collect::<Vec<_>>()
+            }
+        };
+
+        let records = export("/library/export").await;
+        let titles: Vec<_> = records
+            .iter()
+            .map(|r| r.media_item.title.as_str())
+            .collect();
+        assert_eq!(titles, ["Heat", "Ronin", "Cowboy Bebop"]);
+        let metadata = records[0].metadata.as_ref().unwrap();
+        assert_eq!(metadata.tmdb_id, Some(949));
+        assert_eq!(metadata.parse_genres(), ["Crime"]);
+        assert!(records[1].metadata.is_none());
+
+        let records = export("/library/export?media_type=tv").await;
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].media_item.title, "Cowboy Bebop");
+    }
+
+    #[tokio::test]
+    async fn test_recent_lists_newest_items_first() {
+        let db = crate::db::test_pool().await;
+        seed_library(&db).await;
+        for (title, days_ago) in [("Heat", 1), ("Ronin", 3), ("Cowboy Bebop", 2)] {
+            sqlx::query("UPDATE media_items SET added_at = ? WHERE title = ?")
+                .bind(chrono::Utc::now() - chrono::Duration::days(days_ago))
+                .bind(title)
+                .execute(&db)
+                .await
+                .unwrap();
+        }
+        let app = mount().with_state(Arc::new(Context::for_tests(db)));
+
+        let recent = |uri: &'static str| {
+            let app = app.clone();
+            async move {
+                let response = app
+                    .oneshot(Request::get(uri).body(Body::empty()).unwrap())
+                    .await
+                    .unwrap();
+                assert_eq!(response.status(), StatusCode::OK);
+                let body: ApiResponse<Vec<MediaItemWithMetadata>> =
+                    serde_json::from_str(&body_text(response).await).unwrap();
+                body.data.unwrap()
+            }
+        };
+        let titles = |items: &[MediaItemWithMetadata]| {
+            items
+                .iter()
+                .map(|item| item.media_item.title.clone())
+                .collect::<Vec<_>>()
+        };
+
+        let items = recent("/library/recent").await;
+        assert_eq!(titles(&items), ["Heat", "Cowboy Bebop", "Ronin"]);
+        assert_eq!(items[0].metadata.as_ref().unwrap().tmdb_id, Some(949));
+        assert!(items[2].metadata.is_none());
+
+        let items = recent("/library/recent?limit=2").await;
+        assert_eq!(titles(&items), ["Heat", "Cowboy Bebop"]);
+        let items = recent("/library/recent?limit=1&media_type=movie").await;
+        assert_eq!(titles(&items), ["Heat"]);
+    }
+
+    #[tokio::test]
+    async fn test_movies_sort_by_rating_and_filter_by_genre() {
+        let db = crate::db::test_pool().await;
+        let folders = seed_folders(&db).await;
+        seed_item(&db, &folders[0], "Solaris").await;
+        for (title, rating, release_date, genres) in [
+            ("Heat", 7.9, "1995-12-15", ["Crime", "Drama"]),
+            ("Alien", 8.1, "1979-05-25", ["Horror", "Science Fiction"]),
+            ("Ronin", 7.2, "1998-09-25", ["Action", "Crime"]),
+        ] {
+            let item = seed_item(&db, &folders[0], title).await;
+            VideoMetadata::upsert(
+                &db,
+                CreateVideoMetadata {
+                    media_item_id: item.id,
+                    tmdb_id: None,
+                    tvdb_id: None,
+                    imdb_id: None,
+                    overview: None,
+                    poster_path: None,
+                    backdrop_path: None,
+                    release_date: Some(release_date.to_string()),
+                    runtime: None,
+                    vote_average: Some(rating),
+                    vote_count: None,
+                    genres: genres.map(String::from).to_vec(),
+                    anilist_id: None,
+                    mal_id: None,
+                    bangumi_id: None,
+                    episode_count: None,
+                },
+            )
+            .await
+            .unwrap();
+        }
+        let app = mount().with_state(Arc::new(Context::for_tests(db)));
+
+        let movies = |uri: &'static str| {
+            let app = app.clone();
+            async move {
+                let response = app
+                    .oneshot(Request::get(uri).body(Body::empty()).unwrap())
+                    .await
+                    .unwrap();
+                assert_eq!(response.status(), StatusCode::OK);
+                let body: ApiResponse<LibraryResponse> =
+                    serde_json::from_str(&body_text(response).await).unwrap();
+                let page = body.data.unwrap();
+                let titles: Vec<_> = page
+                    .items
+                    .into_iter()
+                    .map(|item| item.media_item.title)
+                    .collect();
+                (titles, page.total)
+            }
+        };
+
+        // Items without a rating sort last either way
+        assert_eq!(
+            movies("/library/movies?sort=rating&order=desc").await,
+            (
+                vec![
+                    "Alien".into(),
+                    "Heat".into(),
+                    "Ronin".into(),
+                    "Solaris".into()
+                ],
+                4
+            )
+        );
+        assert_eq!(
+            movies("/library/movies?sort=rating&order=asc&per_page=2").await,
+            (vec!["Ronin".into(), "Heat".into()], 4)
+        );
+        assert_eq!(
+            movies("/library/movies?genre=crime&sort=title").await,
+            (vec!["Heat".into(), "Ronin".into()], 2)
+        );
+        assert_eq!(
+            movies("/library/movies?year=1979").await,
+            (vec!["Alien".into()], 1)
+        );
+        assert_eq!(
+            movies("/library/movies?has_metadata=false").await,
+            (vec!["Solaris".into()], 1)
+        );
+
+        let response = app
+            .oneshot(
+                Request::get("/library/movies?sort=popularity")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_genres_are_counted_across_items() {
+        let db = crate::db::test_pool().await;
+        let folders = seed_folders(&db).await;
+        let seeded = [
+            (&folders[0], "Heat", Some(r#"["Crime","Drama"]"#)),
+            (&folders[0], "Ronin", Some(r#"["Action","Crime"]"#)),
+            (&folders[0], "Alien", Some("[]")),
+            (&folders[0], "Solaris", None),
+            (
+                &folders[1],
+                "Cowboy Bebop",
+                Some(r#"["Action","Animation"]"#),
+            ),
+        ];
+        for (folder, title, genres) in seeded {
+            let item = seed_item(&db, folder, title).await;
+            sqlx::query("INSERT INTO video_metadata (media_item_id, genres) VALUES (?, ?)")
+                .bind(item.id)
+                .bind(genres)
+                .execute(&db)
+                .await
+                .unwrap();
+        }
+        let app = mount().with_state(Arc::new(Context::for_tests(db)));
+
+        let genres = |uri: &'static str| {
+            let app = app.clone();
+            async move {
+                let response = app
+                    .oneshot(Request::get(uri).body(Body::empty()).unwrap())
+                    .await
+                    .unwrap();
+                assert_eq!(response.status(), StatusCode::OK);
+                let body: ApiResponse<Vec<GenreCount>> =
+                    serde_json::from_str(&body_text(response).await).unwrap();
+                body.data
+                    .unwrap()
+                    .into_iter()
+                    .map(|g| (g.genre, g.count))
+                    .collect::<Vec<_>>()
+            }
+        };
+        let counts = |expected: &[(&str, i64)]| {
+            expected
+                .iter()
+                .map(|&(genre, count)| (genre.to_string(), count))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            genres("/library/genres").await,
+            counts(&[("Action", 2), ("Crime", 2), ("Animation", 1), ("Drama", 1)])
+        );
+        assert_eq!(
+            genres("/library/genres?media_type=movie").await,
+            counts(&[("Crime", 2), ("Action", 1), ("Drama", 1)])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_media_status_is_tracked_per_user() {
+        let db = crate::db::test_pool().await;
+        let folders = seed_folders(&db).await;
+        let heat = seed_item(&db, &folders[0], "Heat").await;
+        let ronin = seed_item(&db, &folders[0], "Ronin").await;
+        let mut tokens = Vec::new();
+        let ctx = Arc::new(Context::for_tests(db.clone()));
+        for name in ["alice", "bob"] {
+            let user_id: i64 = sqlx::query_scalar(
+                "INSERT INTO users (username, email, password_hash) VALUES (?, ?, 'x') RETURNING id",
+            )
+            .bind(name)
+            .bind(format!("{name}@example.com"))
+            .fetch_one(&db)
+            .await
+            .unwrap();
+            tokens.push(
+                crate::app::auth::issue_token(
+                    &ctx.config.read().auth.jwt_secret,
+                    &user_id.to_string(),
+                    chrono::Duration::hours(1),
+                )
+                .unwrap(),
+            );
+        }
+        let app = mount().with_state(ctx);
+
+        let send = |request: axum::http::request::Builder, token: Option<&str>, body: Body| {
+            let request = match token {
+                Some(token) => request.header(AUTHORIZATION, format!("Bearer {token}")),
+                None => request,
+            };
+            let app = app.clone();
+            let request = request
+                .header(CONTENT_TYPE, "application/json")
+                .body(body)
+                .unwrap();
+            async move {
+                let response = app.oneshot(request).await.unwrap();
+                let status = response.status();
+                let body: serde_json::Value =
+                    serde_json::from_str(&body_text(response).await).unwrap_or_default();
+                (status, body["data"].clone())
+            }
+        };
+        let status_uri = format!("/library/items/{}/status", heat.id);
+
+        let (status, _) = send(Request::get(&status_uri), None, Body::empty()).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        let (status, data) = send(Request::get(&status_uri), Some(&tokens[0]), Body::empty()).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(data["status"], "unwatched");
+
+        let (status, data) = send(
+            Request::put(&status_uri),
+            Some(&tokens[0]),
+            Body::from(r#"{"status":"in_progress","position":1200}"#),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(data["status"], "in_progress");
+        let (_, data) = send(Request::get(&status_uri), Some(&tokens[0]), Body::empty()).await;
+        assert_eq!(data["position"], 1200);
+        let (_, data) = send(Request::get(&status_uri), Some(&tokens[1]), Body::empty()).await;
+        assert_eq!(data["status"], "unwatched");
+
+        let (status, _) = send(
+            Request::put("/library/items/9999/status"),
+            Some(&tokens[0]),
+            Body::from(r#"{"status":"watched"}"#),
+        )
+        .await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+
+        // Listings carry the status only for authenticated requests
+        let (_, page) = send(
+            Request::get("/library/movies"),
+            Some(&tokens[0]),
+            Body::empty(),
+        )
+        .await;
+        let statuses: Vec<_> = page["items"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|item| (item["id"].clone(), item["status"]["status"].clone()))
+            .collect();
+        assert_eq!(
+            statuses,
+            [
+                (ronin.id.into(), "unwatched".into()),
+                (heat.id.into(), "in_progress".into()),
+            ]
+        );
+        let (_, page) = send(Request::get("/library/movies"), None, Body::empty()).await;
+        assert!(page["items"][0].get("status").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_tagging_and_filtering_by_tags() {
+        let db = crate::db::test_pool().await;
+        let folders = seed_folders(&db).await;
+        let mut items = Vec::new();
+        for title in ["Heat", "Ronin", "Alien"] {
+            items.push(seed_item(&db, &folders[0], title).await);
+        }
+        let app = mount().with_state(Arc::new(Context::for_tests(db)));
+
+        let send = |request: axum::http::request::Builder, body: Body| {
+            let app = app.clone();
+            let request = request
+                .header(CONTENT_TYPE, "application/json")
+                .body(body)
+                .unwrap();
+            async move {
+                let response = app.oneshot(request).await.unwrap();
+                let status = response.status();
+                let body: serde_json::Value =
+                    serde_json::from_str(&body_text(response).await).unwrap_or_default();
+                (status, body["data"].clone())
+            }
+        };
+        let tag = |id: i64, tags: &'static str| {
+            send(
+                Request::post(format!("/library/items/{id}/tags")),
+                Body::from(format!(r#"{{"tags":{tags}}}"#)),
+            )
+        };
+        let titles = |uri: &'static str| {
+            let send = &send;
+            async move {
+                let (status, page) = send(Request::get(uri), Body::empty()).await;
+                assert_eq!(status, StatusCode::OK);
+                page["items"]
+                    .as_array()
+                    .unwrap()
+                    .iter()
+                    .map(|item| item["title"].as_str().unwrap().to_string())
+                    .collect::<Vec<_>>()
+            }
+        };
+
+        let (status, data) = tag(items[0].id, r#"["Favorites", " favorites ", "Rewatch"]"#).await;
+        assert_eq!(status, StatusCode::OK);
+        let names: Vec<_> = data
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|tag| tag["name"].as_str().unwrap())
+            .collect();
+        assert_eq!(names, ["favorites", "rewatch"]);
+        tag(items[1].id, r#"["FAVORITES"]"#).await;
+        tag(items[2].id, r#"["rewatch", "Sci-Fi"]"#).await;
+        let (status, _) = tag(items[2].id, r#"["  "]"#).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        let (_, item) = send(
+            Request::get(format!("/library/items/{}", items[0].id)),
+            Body::empty(),
+        )
+        .await;
+        assert_eq!(item["tags"], serde_json::json!(["favorites", "rewatch"]));
+
+        assert_eq!(
+            titles("/library/movies?tags=favorites,rewatch").await,
+            ["Heat"]
+        );
+        assert_eq!(
+            titles("/library/movies?tags=Favorites,Sci-Fi&tag_match=any&sort=title").await,
+            ["Alien", "Heat", "Ronin"]
+        );
+        assert_eq!(
+            titles("/library/tags/Rewatch/items").await,
+            ["Alien", "Heat"]
+        );
+
+        let (status, _) = send(
+            Request::delete(format!("/library/items/{}/tags/Rewatch", items[0].id)),
+            Body::empty(),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        let (status, _) = send(
+            Request::delete(format!("/library/items/{}/tags/rewatch", items[0].id)),
+            Body::empty(),
+        )
+        .await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(titles("/library/tags/rewatch/items").await, ["Alien"]);
+        assert!(
+            titles("/library/movies?tags=favorites,rewatch")
+                .await
+                .is_empty()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_bulk_delete_reports_missing_ids() {
+        let db = crate::db::test_pool().await;
+        let dir = tempfile::tempdir().unwrap();
+        let folder = LibraryFolder::create(
+            &db,
+            CreateLibraryFolder {
+                name: "Movies".to_string(),
+                path: dir.path().to_string_lossy().into_owned(),
+                media_type: MediaType::Movie,
+                extensions: None,
+            },
+        )
+        .await
+        .unwrap();
+        let mut items = Vec::new();
+        for title in ["Heat", "Ronin", "Alien"] {
+            let item = seed_item(&db, &folder, title).await;
+            std::fs::write(&item.file_path, b"video").unwrap();
+            items.push(item);
+        }
+        VideoMetadata::upsert(
+            &db,
+            CreateVideoMetadata {
+                media_item_id: items[0].id,
+                tmdb_id: Some(949),
+                tvdb_id: None,
+                imdb_id: None,
+                overview: None,
+                poster_path: None,
+                backdrop_path: None,
+                release_date: None,
+                runtime: None,
+                vote_average: None,
+                vote_count: None,
+                genres: Vec::new(),
+                anilist_id: None,
+                mal_id: None,
+                bangumi_id: None,
+                episode_count: None,
+            },
+        )
+        .await
+        .unwrap();
+        let app = mount().with_state(Arc::new(Context::for_tests(db.clone())));
+
+        let delete = |body: serde_json::Value| {
+            let app = app.clone();
+            async move {
+                let response = app
+                    .oneshot(
+                        Request::post("/library/items/delete")
+                            .header(CONTENT_TYPE, "application/json")
+                            .body(Body::from(body.to_string()))
+                            .unwrap(),
+                    )
+                    .await
+                    .unwrap();
+                let status = response.status();
+                let body: serde_json::Value =
+                    serde_json::from_str(&body_text(response).await).unwrap();
+                (status, body["data"].clone())
+            }
+        };
+
+        let (status, report) = delete(serde_json::json!({
+            "ids": [items[0].id, 9999, items[1].id, items[0].id],
+            "delete_files": true,
+        }))
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(report["deleted"], 2);
+        assert_eq!(report["failed"], 1);
+        let outcomes: Vec<_> = report["results"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|result| {
+                (
+                    result["id"].as_i64().unwrap(),
+                    result["deleted"].as_bool().unwrap(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            outcomes,
+            [(items[0].id, true), (9999, false), (items[1].id, true)]
+        );
+
+        for item in &items[..2] {
+            assert!(MediaItem::find_by_id(&db, item.id).await.unwrap().is_none());
+            assert!(!std::path::Path::new(&item.file_path).exists());
+        }
+        assert!(
+            VideoMetadata::find_by_media_item_id(&db, items[0].id)
+                .await
+                .unwrap()
+                .is_none()
+        );
+        assert!(
+            MediaItem::find_by_id(&db, items[2].id)
+                .await
+                .unwrap()
+                .is_some()
+        );
+        assert!(std::path::Path::new(&items[2].file_path).exists());
+
+        let too_many: Vec<i64> = (1..=501).collect();
+        let (status, _) = delete(serde_json::json!({ "ids": too_many })).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(
+            MediaItem::find_by_id(&db, items[2].id)
+                .await
+                .unwrap()
+                .is_some()
+        );
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_deduplicate_runs_as_a_job_unless_sync() {
+        use std::os::unix::fs::MetadataExt;
+
+        let db = crate::db::test_pool().await;
+        let dir = tempfile::tempdir().unwrap();
+        let folder = LibraryFolder::create(
+            &db,
+            CreateLibraryFolder {
+                name: "Movies".to_string(),
+                path: dir.path().to_string_lossy().into_owned(),
+                media_type: MediaType::Movie,
+                extensions: None,
+            },
+        )
+        .await
+        .unwrap();
+        let mut paths = Vec::new();
+        for title in ["Heat", "Heat (copy)"] {
+            let item = seed_item(&db, &folder, title).await;
+            std::fs::write(&item.file_path, b"video").unwrap();
+            paths.push(item.file_path);
+        }
+        let ctx = Arc::new(Context::for_tests(db));
+
+        let deduplicate = |body: serde_json::Value| {
+            let app = mount_long_running().with_state(ctx.clone());
+            async move {
+                let response = app
+                    .oneshot(
+                        Request::post("/library/deduplicate")
+                            .header(CONTENT_TYPE, "application/json")
+                            .body(Body::from(body.to_string()))
+                            .unwrap(),
+                    )
+                    .await
+                    .unwrap();
+                assert_eq!(response.status(), StatusCode::OK);
+                let body: ApiResponse<DeduplicateResponse> =
+                    serde_json::from_str(&body_text(response).await).unwrap();
+                (body.code, body.data.unwrap())
+            }
+        };
+
+        let (code, data) = deduplicate(serde_json::json!({ "confirm": true })).await;
+        assert_eq!(code, 202);
+        assert!(data.report.is_none());
+        let job_id = data.job_id.unwrap();
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while !ctx.jobs.get(job_id).unwrap().status.is_finished() {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+        let ino = |path: &str| std::fs::metadata(path).unwrap().ino();
+        assert_eq!(ino(&paths[0]), ino(&paths[1]));
+
+        let (code, data) = deduplicate(serde_json::json!({ "dry_run": true, "sync": true })).await;
+        assert_eq!(code, 200);
+        assert!(data.job_id.is_none());
+        assert_eq!(data.report.unwrap().files_linked, 0);
+    }
+
+    #[tokio::test]
+    async fn test_import_round_trips_an_export() {
+        let source = crate::db::test_pool().await;
+        seed_library(&source).await;
+        let response = mount()
+            .with_state(Arc::new(Context::for_tests(source)))
+            .oneshot(Request::get("/library/export").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let export = body_text(response).await;
+
+        let db = crate::db::test_pool().await;
+        seed_folders(&db).await;
+        let app = mount_imports().with_state(Arc::new(Context::for_tests(db.clone())));
+        let import = |uri: &'static str, body: String| {
+            let app = app.clone();
+            async move {
+                let response = app
+                    .oneshot(Request::post(uri).body(Body::from(body)).unwrap())
+                    .await
+                    .unwrap();
+                assert_eq!(response.status(), StatusCode::OK);
+                let body: serde_json::Value =
+                    serde_json::from_str(&body_text(response).await).unwrap();
+                let count = |key: &str| body["data"][key].as_u64().unwrap();
+                (
+                    count("inserted"),
+                    count("updated"),
+                    count("skipped"),
+                    count("errored"),
+                )
+            }
+        };
+
+        // A bad line is reported without losing the rest
+        let with_bad_line = format!("{export}not json\n");
+        assert_eq!(import("/library/import", with_bad_line).await, (3, 0, 0, 1));
+
+        let items = MediaItem::list_by_type(&db, MediaType::Movie, 10, 0)
+            .await
+            .unwrap();
+        let heat = items.iter().find(|item| item.title == "Heat").unwrap();
+        let metadata = VideoMetadata::find_by_media_item_id(&db, heat.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(metadata.tmdb_id, Some(949));
+        assert_eq!(metadata.parse_genres(), ["Crime"]);
+        assert_eq!(
+            MediaItem::count_by_type(&db, MediaType::Tv).await.unwrap(),
+            1
+        );
+
+        assert_eq!(
+            import("/library/import", export.clone()).await,
+            (0, 0, 3, 0)
+        );
+        assert_eq!(
+            import("/library/import?mode=upsert", export).await,
+            (0, 3, 0, 0)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_failing_item_is_retried_then_listed_as_unmatched() {
+        let db = crate::db::test_pool().await;
+        let folders = seed_folders(&db).await;
+        let item = seed_item(&db, &folders[0], "Nonexistent").await;
+        // No providers, so every lookup fails
+        let agent = MetadataAgent::new(Arc::new(ScraperManager::new()), db.clone())
+            .with_retry_policy(FetchRetryPolicy {
+                max_attempts: 2,
+                base_delay: Duration::ZERO,
+                max_delay: Duration::ZERO,
+            });
+        let app = mount().with_state(Arc::new(Context::for_tests(db.clone())));
+        let unmatched = || async {
+            let response = app
+                .clone()
+                .oneshot(
+                    Request::get("/library/unmatched")
+                        .body(Body::empty())
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+            let body: serde_json::Value = serde_json::from_str(&body_text(response).await).unwrap();
+            body["data"].clone()
+        };
+
+        assert!(agent.fetch_and_save_metadata(&item, false).await.is_err());
+        let attempt = MetadataFetchAttempt::find_by_media_item_id(&db, item.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(attempt.attempts, 1);
+        assert!(attempt.next_retry_at.is_some());
+        assert_eq!(unmatched().await["total"], 0);
+
+        let retried = agent.retry_due().await.unwrap();
+        assert_eq!(retried.len(), 1);
+        assert!(retried[0].is_err());
+
+        let page = unmatched().await;
+        assert_eq!(page["total"], 1);
+        assert_eq!(page["items"][0]["title"], "Nonexistent");
+        assert_eq!(page["items"][0]["attempts"], 2);
+        assert!(agent.retry_due().await.unwrap().is_empty());
+        assert!(
+            MediaItem::list_without_metadata(&db, folders[0].id)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_preview_refresh_lists_changed_fields() {
+        let db = crate::db::test_pool().await;
+        seed_library(&db).await;
+        let mut heat = movie_details("tmdb", "949", "Heat", 1995);
+        heat.release_date = Some("1995-12-15".to_string());
+        heat.overview = Some("A heist goes wrong.".to_string());
+        heat.genres = vec!["Crime".to_string(), "Thriller".to_string()];
+        heat.external_ids = ExternalIds {
+            tmdb_id: Some("949".to_string()),
+            ..ExternalIds::default()
+        };
+        let mut manager = ScraperManager::new();
+        manager.add_provider(Box::new(
+            FakeProvider::new("tmdb")
+                .with_details(MediaDetails::Movie(heat))
+                .with_movie("1", "Ronin", 1998),
+        ));
+        let ctx = Context {
+            metadata_agent: Some(Arc::new(MetadataAgent::new(Arc::new(manager), db.clone()))),
+            ..Context::for_tests(db.clone())
+        };
+        let app = mount().with_state(Arc::new(ctx));
+        let preview = |title: &str| {
+            let app = app.clone();
+            let title = title.to_string();
+            let db = db.clone();
+            async move {
+                let id: i64 = sqlx::query_scalar("SELECT id FROM media_items WHERE title = ?")
+                    .bind(title)
+                    .fetch_one(&db)
+                    .await
+                    .unwrap();
+                let response = app
+                    .oneshot(
+                        Request::post(format!("/library/items/{id}/preview-refresh"))
+                            .body(Body::empty())
+                            .unwrap(),
+                    )
+                    .await
+                    .unwrap();
+                assert_eq!(response.status(), StatusCode::OK);
+                let body: serde_json::Value =
+                    serde_json::from_str(&body_text(response).await).unwrap();
+                body["data"].as_array().unwrap().clone()
+            }
+        };
+
+        // The TMDB ID and release date are unchanged and left out
+        let changes = preview("Heat").await;
+        let fields: Vec<_> = changes
+            .iter()
+            .map(|c| c["field"].as_str().unwrap())
+            .collect();
+        assert_eq!(fields, ["genres", "overview"]);
+        assert_eq!(changes[0]["old"], serde_json::json!(["Crime"]));
+        assert_eq!(changes[0]["new"], serde_json::json!(["Crime", "Thriller"]));
+        assert_eq!(changes[1]["old"], serde_json::Value::Null);
+
+        // Nothing stored yet, so whatever was found is an addition
+        let changes = preview("Ronin").await;
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0]["field"], "release_date");
+        assert_eq!(changes[0]["old"], serde_json::Value::Null);
+        assert_eq!(changes[0]["new"], "1998-01-01");
+
+        // Previewing saves nothing
+        let stored = VideoMetadata::find_by_media_item_id(&db, 1)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.parse_genres(), ["Crime"]);
+    }
 }
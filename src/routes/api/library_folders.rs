@@ -1,15 +1,23 @@
+use std::convert::Infallible;
+
 use axum::{
     Json, Router,
-    extract::{Path, State},
+    extract::{Path, Query, State},
     http::StatusCode,
+    response::sse::{Event, KeepAlive, Sse},
     routing::{get, post},
 };
+use futures::{Stream, stream};
 use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc;
 
 use crate::{
     ApiResponse, ApiResult, Ctx,
-    entities::{CreateLibraryFolder, LibraryFolder},
-    services::{FileScanner, ScanResult},
+    entities::{CreateLibraryFolder, LibraryFolder, MediaItem, normalize_extensions},
+    error::{ApiError, AyiahError},
+    services::{
+        FileScanner, MetadataAgentError, ScanEvent, ScanResult, WebhookEvent, job_queue::JobId,
+    },
 };
 
 /// Create library folder request
@@ -18,6 +26,20 @@ pub struct CreateLibraryFolderRequest {
     pub name: String,
     pub path: String,
     pub media_type: crate::entities::MediaType,
+    /// File extensions to scan instead of the media type's defaults
+    #[serde(default)]
+    pub extensions: Option<Vec<String>>,
+}
+
+/// Update library folder request; omitted fields are left unchanged
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct UpdateLibraryFolderRequest {
+    pub name: Option<String>,
+    pub path: Option<String>,
+    pub media_type: Option<crate::entities::MediaType>,
+    pub enabled: Option<bool>,
+    /// An empty list goes back to the media type's defaults
+    pub extensions: Option<Vec<String>>,
 }
 
 /// Scan response
@@ -25,6 +47,36 @@ pub struct CreateLibraryFolderRequest {
 pub struct ScanResponse {
     pub folder: LibraryFolder,
     pub result: ScanResult,
+    /// Background job fetching metadata for the newly found items
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata_job_id: Option<JobId>,
+}
+
+/// Refresh metadata query parameters
+#[derive(Debug, Default, Deserialize)]
+pub struct RefreshMetadataQuery {
+    /// Wait for the refresh to finish instead of running it in the background
+    #[serde(default)]
+    pub sync: bool,
+    /// Also refresh items whose metadata is locked
+    #[serde(default)]
+    pub force: bool,
+}
+
+/// Refresh metadata response
+///
+/// `succeeded`, `failed` and `skipped` are only known when the refresh ran
+/// synchronously; otherwise `job_id` identifies the background job doing the work.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RefreshMetadataResponse {
+    pub total: usize,
+    pub succeeded: Option<usize>,
+    pub failed: Option<usize>,
+    /// Items left alone because their metadata is locked
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub skipped: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub job_id: Option<JobId>,
 }
 
 /// List all library folders
@@ -65,27 +117,13 @@ async fn create_folder(
     State(ctx): State<Ctx>,
     Json(request): Json<CreateLibraryFolderRequest>,
 ) -> ApiResult<LibraryFolder> {
-    // Validate path exists
-    let path = std::path::Path::new(&request.path);
-    if !path.exists() {
-        return Err(crate::error::AyiahError::ApiError(
-            crate::error::ApiError::BadRequest(format!("Path does not exist: {}", request.path)),
-        ));
-    }
-
-    if !path.is_dir() {
-        return Err(crate::error::AyiahError::ApiError(
-            crate::error::ApiError::BadRequest(format!(
-                "Path is not a directory: {}",
-                request.path
-            )),
-        ));
-    }
+    validate_folder_path(&request.path)?;
 
     let create_folder = CreateLibraryFolder {
         name: request.name,
         path: request.path,
         media_type: request.media_type,
+        extensions: request.extensions.as_deref().and_then(normalize_extensions),
     };
 
     let folder = LibraryFolder::create(&ctx.db, create_folder)
@@ -101,6 +139,73 @@ async fn create_folder(
     })
 }
 
+/// Update a library folder
+async fn update_folder(
+    State(ctx): State<Ctx>,
+    Path(id): Path<i64>,
+    Json(request): Json<UpdateLibraryFolderRequest>,
+) -> ApiResult<LibraryFolder> {
+    let mut folder = LibraryFolder::find_by_id(&ctx.db, id)
+        .await
+        .map_err(|e| AyiahError::DatabaseError(format!("Failed to fetch library folder: {e}")))?
+        .ok_or_else(|| {
+            AyiahError::ApiError(ApiError::NotFound(format!(
+                "Library folder with ID {id} not found"
+            )))
+        })?;
+
+    if let Some(path) = request.path {
+        validate_folder_path(&path)?;
+        folder.path = path;
+    }
+    if let Some(name) = request.name {
+        folder.name = name;
+    }
+    if let Some(media_type) = request.media_type {
+        folder.media_type = media_type;
+    }
+    if let Some(enabled) = request.enabled {
+        folder.enabled = enabled;
+    }
+    if let Some(extensions) = request.extensions {
+        folder.extensions = normalize_extensions(&extensions);
+    }
+
+    folder
+        .update(&ctx.db)
+        .await
+        .map_err(|e| AyiahError::DatabaseError(format!("Failed to update library folder: {e}")))?;
+
+    let folder = LibraryFolder::find_by_id(&ctx.db, id)
+        .await
+        .map_err(|e| AyiahError::DatabaseError(format!("Failed to fetch library folder: {e}")))?
+        .unwrap_or(folder);
+
+    Ok(ApiResponse {
+        code: 200,
+        message: "Library folder updated successfully".to_string(),
+        data: Some(folder),
+    })
+}
+
+/// Ensure a library folder path exists and is a directory
+fn validate_folder_path(path: &str) -> Result<(), AyiahError> {
+    let dir = std::path::Path::new(path);
+    if !dir.exists() {
+        return Err(AyiahError::ApiError(ApiError::BadRequest(format!(
+            "Path does not exist: {path}"
+        ))));
+    }
+
+    if !dir.is_dir() {
+        return Err(AyiahError::ApiError(ApiError::BadRequest(format!(
+            "Path is not a directory: {path}"
+        ))));
+    }
+
+    Ok(())
+}
+
 /// Delete a library folder
 async fn delete_folder(
     State(ctx): State<Ctx>,
@@ -152,7 +257,12 @@ async fn scan_folder(
             )
         })?;
 
-    let scanner = FileScanner::new(ctx.db.clone());
+    let scanner = {
+        let config = ctx.config.read();
+        FileScanner::new(ctx.db.clone())
+            .with_ignore_patterns(config.scanner.ignore_patterns.clone())
+            .with_content_hashing(config.scanner.hash_files)
+    };
     let result = scanner.scan_library_folder(&folder).await.map_err(|e| {
         (
             StatusCode::INTERNAL_SERVER_ERROR,
@@ -163,53 +273,258 @@ async fn scan_folder(
             }),
         )
     })?;
+    ctx.webhooks
+        .notify(WebhookEvent::ScanComplete, folder.id, result.new_items);
 
     // If metadata agent is available, fetch metadata for new items
-    if let Some(metadata_agent) = &ctx.metadata_agent {
-        tokio::spawn({
-            let metadata_agent = metadata_agent.clone();
-            let db = ctx.db.clone();
-            let folder_id = folder.id;
-            async move {
-                // Get all media items without metadata from this folder
-                let items = match sqlx::query_as::<_, crate::entities::MediaItem>(
-                    "SELECT * FROM media_items WHERE library_folder_id = ? AND id NOT IN (SELECT media_item_id FROM video_metadata)"
+    let metadata_job_id = ctx.metadata_agent.clone().map(|metadata_agent| {
+        let db = ctx.db.clone();
+        let webhooks = ctx.webhooks.clone();
+        let folder_id = folder.id;
+        ctx.jobs.enqueue("fetch_metadata", move |job| async move {
+            // Get all media items without metadata from this folder
+            let items = MediaItem::list_without_metadata(&db, folder_id)
+                .await
+                .map_err(|e| format!("Failed to fetch items without metadata: {e}"))?;
+
+            let total = items.len();
+            tracing::info!("Fetching metadata for {} items", total);
+            job.set_progress(0, total);
+            let results = metadata_agent
+                .batch_fetch_metadata_with_progress(
+                    items,
+                    false,
+                    job.cancellation_token(),
+                    |done| job.set_progress(done, total),
                 )
-                .bind(folder_id)
-                .fetch_all(&db)
-                .await {
-                    Ok(items) => items,
-                    Err(e) => {
-                        tracing::error!("Failed to fetch items without metadata: {}", e);
-                        return;
-                    }
-                };
-
-                tracing::info!("Fetching metadata for {} items", items.len());
-                let results = metadata_agent.batch_fetch_metadata(items).await;
-
-                let success_count = results.iter().filter(|r| r.is_ok()).count();
-                tracing::info!(
-                    "Metadata fetch complete: {}/{} successful",
-                    success_count,
-                    results.len()
-                );
-            }
-        });
-    }
+                .await;
+
+            let success_count = results.iter().filter(|r| r.is_ok()).count();
+            tracing::info!(
+                "Metadata fetch complete: {}/{} successful",
+                success_count,
+                results.len()
+            );
+            webhooks.notify(WebhookEvent::MetadataComplete, folder_id, success_count);
+            Ok(())
+        })
+    });
 
     Ok(Json(ApiResponse {
         code: 200,
         message: "Library folder scanned successfully".to_string(),
-        data: Some(ScanResponse { folder, result }),
+        data: Some(ScanResponse {
+            folder,
+            result,
+            metadata_job_id,
+        }),
     }))
 }
 
+/// Scan a library folder, streaming progress as Server-Sent Events
+async fn scan_folder_stream(
+    State(ctx): State<Ctx>,
+    Path(id): Path<i64>,
+) -> Result<
+    Sse<impl Stream<Item = Result<Event, Infallible>>>,
+    (StatusCode, Json<ApiResponse<String>>),
+> {
+    let folder = LibraryFolder::find_by_id(&ctx.db, id)
+        .await
+        .map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResponse {
+                    code: 500,
+                    message: format!("Failed to fetch library folder: {e}"),
+                    data: None,
+                }),
+            )
+        })?
+        .ok_or_else(|| {
+            (
+                StatusCode::NOT_FOUND,
+                Json(ApiResponse {
+                    code: 404,
+                    message: format!("Library folder with ID {id} not found"),
+                    data: None,
+                }),
+            )
+        })?;
+
+    let (tx, rx) = mpsc::channel::<ScanEvent>(64);
+
+    // The scan job owns the sender; once it finishes and the final event is
+    // sent, dropping the sender ends the stream. If the client disconnects
+    // first, forwarding stops and the scanner, finding nobody listening,
+    // stops scanning.
+    let db = ctx.db.clone();
+    let webhooks = ctx.webhooks.clone();
+    let scanner_config = ctx.config.read().scanner.clone();
+    ctx.jobs.enqueue("scan", move |job| async move {
+        let scanner = FileScanner::new(db)
+            .with_ignore_patterns(scanner_config.ignore_patterns)
+            .with_content_hashing(scanner_config.hash_files);
+        let (progress_tx, progress_rx) = mpsc::channel::<ScanEvent>(64);
+
+        // Mirror progress into the job before passing it on to the client.
+        // Owning the receiver here means it is dropped when the client leaves.
+        let forward = async {
+            let mut progress_rx = progress_rx;
+            // Cancelling the job drops the receiver too, which stops the scan
+            while let Some(event) = tokio::select! {
+                event = progress_rx.recv() => event,
+                () = job.cancellation_token().cancelled() => None,
+            } {
+                if let ScanEvent::Progress(progress) = &event {
+                    job.set_progress(progress.processed, progress.total);
+                }
+                job.send_scan_event(event.clone());
+                if tx.send(event).await.is_err() {
+                    break;
+                }
+            }
+        };
+        let (result, ()) = tokio::join!(
+            scanner.scan_library_folder_with_progress(&folder, Some(progress_tx)),
+            forward
+        );
+
+        let (event, outcome) = match result {
+            Ok(result) => {
+                webhooks.notify(WebhookEvent::ScanComplete, folder.id, result.new_items);
+                (ScanEvent::Complete(result), Ok(()))
+            }
+            Err(e) => {
+                let message = format!("Failed to scan library folder: {e}");
+                (
+                    ScanEvent::Failed {
+                        message: message.clone(),
+                    },
+                    Err(message),
+                )
+            }
+        };
+        job.send_scan_event(event.clone());
+        let _ = tx.send(event).await;
+        outcome
+    });
+
+    let events = stream::unfold(rx, |mut rx| async move {
+        let event = rx.recv().await?;
+        let name = match &event {
+            ScanEvent::Progress(_) => "progress",
+            ScanEvent::Complete(_) => "complete",
+            ScanEvent::Failed { .. } => "error",
+        };
+        let sse_event = match &event {
+            ScanEvent::Progress(progress) => Event::default().event(name).json_data(progress),
+            ScanEvent::Complete(result) => Event::default().event(name).json_data(result),
+            ScanEvent::Failed { message } => Ok(Event::default().event(name).data(message)),
+        }
+        .unwrap_or_else(|_| Event::default().event(name));
+
+        Some((Ok(sse_event), rx))
+    });
+
+    Ok(Sse::new(events).keep_alive(KeepAlive::default()))
+}
+
+/// Re-fetch metadata for every item in a library folder, including matched ones
+async fn refresh_folder_metadata(
+    State(ctx): State<Ctx>,
+    Path(id): Path<i64>,
+    Query(query): Query<RefreshMetadataQuery>,
+) -> ApiResult<RefreshMetadataResponse> {
+    let metadata_agent = ctx.metadata_agent.clone().ok_or_else(|| {
+        AyiahError::ApiError(ApiError::ServiceUnavailable(
+            "Metadata agent not available".to_string(),
+        ))
+    })?;
+
+    let folder = LibraryFolder::find_by_id(&ctx.db, id)
+        .await
+        .map_err(|e| AyiahError::DatabaseError(format!("Failed to fetch library folder: {e}")))?
+        .ok_or_else(|| {
+            AyiahError::ApiError(ApiError::NotFound(format!(
+                "Library folder with ID {id} not found"
+            )))
+        })?;
+
+    let items = MediaItem::list_by_folder(&ctx.db, folder.id)
+        .await
+        .map_err(|e| AyiahError::DatabaseError(format!("Failed to fetch media items: {e}")))?;
+    let total = items.len();
+
+    let force = query.force;
+    if !query.sync {
+        let webhooks = ctx.webhooks.clone();
+        let job_id = ctx.jobs.enqueue("refresh_metadata", move |job| async move {
+            job.set_progress(0, total);
+            let results = metadata_agent
+                .batch_fetch_metadata_with_progress(
+                    items,
+                    force,
+                    job.cancellation_token(),
+                    |done| job.set_progress(done, total),
+                )
+                .await;
+            let succeeded = results.iter().filter(|r| r.is_ok()).count();
+            tracing::info!(
+                "Metadata refresh for folder {} complete: {}/{} successful",
+                folder.name,
+                succeeded,
+                total
+            );
+            webhooks.notify(WebhookEvent::MetadataComplete, folder.id, succeeded);
+            Ok(())
+        });
+
+        return Ok(ApiResponse {
+            code: 202,
+            message: "Metadata refresh started".to_string(),
+            data: Some(RefreshMetadataResponse {
+                total,
+                succeeded: None,
+                failed: None,
+                skipped: None,
+                job_id: Some(job_id),
+            }),
+        });
+    }
+
+    let results = metadata_agent.batch_fetch_metadata(items, force).await;
+    let succeeded = results.iter().filter(|r| r.is_ok()).count();
+    let skipped = results
+        .iter()
+        .filter(|r| matches!(r, Err(MetadataAgentError::MetadataLocked)))
+        .count();
+    ctx.webhooks
+        .notify(WebhookEvent::MetadataComplete, folder.id, succeeded);
+
+    Ok(ApiResponse {
+        code: 200,
+        message: "Metadata refreshed successfully".to_string(),
+        data: Some(RefreshMetadataResponse {
+            total,
+            succeeded: Some(succeeded),
+            failed: Some(total - succeeded - skipped),
+            skipped: Some(skipped),
+            job_id: None,
+        }),
+    })
+}
+
 /// Scan all library folders
 async fn scan_all_folders(
     State(ctx): State<Ctx>,
 ) -> Result<Json<ApiResponse<Vec<ScanResponse>>>, (StatusCode, Json<ApiResponse<String>>)> {
-    let scanner = FileScanner::new(ctx.db.clone());
+    let scanner = {
+        let config = ctx.config.read();
+        FileScanner::new(ctx.db.clone())
+            .with_ignore_patterns(config.scanner.ignore_patterns.clone())
+            .with_content_hashing(config.scanner.hash_files)
+    };
     let results = scanner.scan_all_libraries().await.map_err(|e| {
         (
             StatusCode::INTERNAL_SERVER_ERROR,
@@ -223,7 +538,15 @@ async fn scan_all_folders(
 
     let response: Vec<ScanResponse> = results
         .into_iter()
-        .map(|(folder, result)| ScanResponse { folder, result })
+        .map(|(folder, result)| {
+            ctx.webhooks
+                .notify(WebhookEvent::ScanComplete, folder.id, result.new_items);
+            ScanResponse {
+                folder,
+                result,
+                metadata_job_id: None,
+            }
+        })
         .collect();
 
     Ok(Json(ApiResponse {
@@ -239,8 +562,257 @@ pub fn mount() -> Router<Ctx> {
         .route("/library-folders", get(list_folders).post(create_folder))
         .route(
             "/library-folders/{id}",
-            get(get_folder).delete(delete_folder),
+            get(get_folder).put(update_folder).delete(delete_folder),
         )
+        .route("/library-folders/{id}/scan/stream", get(scan_folder_stream))
+}
+
+/// Routes that scan or refresh whole folders within the request, mounted
+/// outside the request timeout
+pub fn mount_long_running() -> Router<Ctx> {
+    Router::new()
         .route("/library-folders/{id}/scan", post(scan_folder))
+        .route(
+            "/library-folders/{id}/refresh-metadata",
+            post(refresh_folder_metadata),
+        )
         .route("/library-folders/scan-all", post(scan_all_folders))
 }
+
+#[cfg(test)]
+mod tests {
+    use axum::{body::Body, http::Request};
+    use tower::ServiceExt;
+
+    use crate::{
+        Context,
+        entities::{CreateMediaItem, MediaType},
+        scraper::{ScraperManager, mock::FakeProvider},
+        services::MetadataAgent,
+    };
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_scan_stream_emits_progress_and_result() {
+        let db = crate::db::test_pool().await;
+        let dir = tempfile::tempdir().unwrap();
+        for name in ["a.mkv", "b.mp4", "notes.txt"] {
+            std::fs::write(dir.path().join(name), b"data").unwrap();
+        }
+
+        let folder = LibraryFolder::create(
+            &db,
+            CreateLibraryFolder {
+                name: "Movies".to_string(),
+                path: dir.path().to_string_lossy().to_string(),
+                media_type: MediaType::Movie,
+                extensions: None,
+            },
+        )
+        .await
+        .unwrap();
+
+        let app = mount().with_state(std::sync::Arc::new(Context::for_tests(db)));
+        let response = app
+            .oneshot(
+                Request::get(format!("/library-folders/{}/scan/stream", folder.id))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body = String::from_utf8(body.to_vec()).unwrap();
+        let events: Vec<&str> = body
+            .lines()
+            .filter_map(|line| line.strip_prefix("event: "))
+            .collect();
+
+        assert_eq!(events, vec!["progress", "progress", "complete"]);
+        assert!(body.contains(r#""processed":2,"total":2"#));
+        assert!(body.contains(r#""new_items":2"#));
+    }
+
+    #[tokio::test]
+    async fn test_refresh_metadata_covers_every_item_in_folder() {
+        let db = crate::db::test_pool().await;
+        let mut folders = Vec::new();
+        for name in ["Movies", "Other"] {
+            let folder = LibraryFolder::create(
+                &db,
+                CreateLibraryFolder {
+                    name: name.to_string(),
+                    path: format!("/library/{name}"),
+                    media_type: MediaType::Movie,
+                    extensions: None,
+                },
+            )
+            .await
+            .unwrap();
+            folders.push(folder);
+        }
+        for (folder, title) in [
+            (&folders[0], "Alien"),
+            (&folders[0], "Heat"),
+            (&folders[0], "Nonexistent"),
+            (&folders[1], "Ronin"),
+        ] {
+            MediaItem::create(
+                &db,
+                CreateMediaItem {
+                    library_folder_id: folder.id,
+                    media_type: MediaType::Movie,
+                    title: title.to_string(),
+                    file_path: format!("{}/{title}.mkv", folder.path),
+                    file_size: 1,
+                    content_hash: None,
+                    modified_at: None,
+                },
+            )
+            .await
+            .unwrap();
+        }
+
+        let mut manager = ScraperManager::new();
+        manager.add_provider(Box::new(
+            FakeProvider::new("tmdb")
+                .with_movie("1", "Alien", 1979)
+                .with_movie("2", "Heat", 1995)
+                .with_movie("3", "Ronin", 1998),
+        ));
+        let manager = std::sync::Arc::new(manager);
+        let agent = MetadataAgent::new(manager.clone(), db.clone());
+        // An item that already has metadata is refreshed too
+        let alien = MediaItem::list_by_folder(&db, folders[0].id).await.unwrap();
+        agent
+            .fetch_and_save_metadata(&alien[0], false)
+            .await
+            .unwrap();
+
+        let ctx = Context {
+            scraper_manager: Some(manager),
+            metadata_agent: Some(std::sync::Arc::new(agent)),
+            ..Context::for_tests(db)
+        };
+        let app = mount_long_running().with_state(std::sync::Arc::new(ctx));
+        let response = app
+            .oneshot(
+                Request::post(format!(
+                    "/library-folders/{}/refresh-metadata?sync=true",
+                    folders[0].id
+                ))
+                .body(Body::empty())
+                .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["data"]["total"], 3);
+        assert_eq!(body["data"]["succeeded"], 2);
+        assert_eq!(body["data"]["failed"], 1);
+    }
+
+    #[tokio::test]
+    async fn test_refresh_metadata_without_agent_is_unavailable() {
+        let app = mount_long_running().with_state(std::sync::Arc::new(Context::for_tests(
+            crate::db::test_pool().await,
+        )));
+        let response = app
+            .oneshot(
+                Request::post("/library-folders/1/refresh-metadata")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+    }
+
+    async fn put_folder(
+        db: crate::db::Database,
+        id: i64,
+        body: serde_json::Value,
+    ) -> axum::response::Response {
+        mount()
+            .with_state(std::sync::Arc::new(Context::for_tests(db)))
+            .oneshot(
+                Request::put(format!("/library-folders/{id}"))
+                    .header("content-type", "application/json")
+                    .body(Body::from(body.to_string()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_update_folder_toggles_enabled() {
+        let db = crate::db::test_pool().await;
+        let dir = tempfile::tempdir().unwrap();
+        let folder = LibraryFolder::create(
+            &db,
+            CreateLibraryFolder {
+                name: "Movies".to_string(),
+                path: dir.path().to_string_lossy().to_string(),
+                media_type: MediaType::Movie,
+                extensions: None,
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(LibraryFolder::list_enabled(&db).await.unwrap().len(), 1);
+
+        let response = put_folder(
+            db.clone(),
+            folder.id,
+            serde_json::json!({ "name": "Films", "enabled": false }),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        assert!(LibraryFolder::list_enabled(&db).await.unwrap().is_empty());
+        let updated = LibraryFolder::find_by_id(&db, folder.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(updated.name, "Films");
+        assert_eq!(updated.path, folder.path);
+    }
+
+    #[tokio::test]
+    async fn test_update_folder_rejects_missing_folder_and_bad_path() {
+        let db = crate::db::test_pool().await;
+        let response = put_folder(db.clone(), 42, serde_json::json!({ "enabled": false })).await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let dir = tempfile::tempdir().unwrap();
+        let folder = LibraryFolder::create(
+            &db,
+            CreateLibraryFolder {
+                name: "Movies".to_string(),
+                path: dir.path().to_string_lossy().to_string(),
+                media_type: MediaType::Movie,
+                extensions: None,
+            },
+        )
+        .await
+        .unwrap();
+        let response = put_folder(
+            db,
+            folder.id,
+            serde_json::json!({ "path": dir.path().join("missing") }),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+}
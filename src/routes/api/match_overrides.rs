@@ -0,0 +1,82 @@
+use axum::{
+    Json, Router,
+    extract::{Path, State},
+    routing::{delete, get},
+};
+
+use crate::{
+    ApiResponse, ApiResult, Ctx,
+    entities::{CreateMatchOverride, MatchOverride},
+    error::{ApiError, AyiahError},
+};
+
+/// List all match overrides
+async fn list_overrides(State(ctx): State<Ctx>) -> ApiResult<Vec<MatchOverride>> {
+    let overrides = MatchOverride::list_all(&ctx.db)
+        .await
+        .map_err(|e| AyiahError::DatabaseError(format!("Failed to fetch match overrides: {e}")))?;
+
+    Ok(ApiResponse {
+        code: 200,
+        message: "Match overrides retrieved successfully".to_string(),
+        data: Some(overrides),
+    })
+}
+
+/// Pin a title to a provider ID, replacing any override it already had
+///
+/// Items with this title are matched to the ID from then on, on their next
+/// metadata fetch or refresh.
+async fn create_override(
+    State(ctx): State<Ctx>,
+    Json(request): Json<CreateMatchOverride>,
+) -> ApiResult<MatchOverride> {
+    if let Some(scraper_manager) = &ctx.scraper_manager
+        && !scraper_manager
+            .providers()
+            .iter()
+            .any(|provider| provider.name() == request.provider)
+    {
+        return Err(ApiError::ProviderNotFound(request.provider).into());
+    }
+    if request.media_id.trim().is_empty() {
+        return Err(ApiError::BadRequest("Media ID must not be empty".to_string()).into());
+    }
+
+    let created = MatchOverride::upsert(&ctx.db, request)
+        .await
+        .map_err(|e| AyiahError::DatabaseError(format!("Failed to save match override: {e}")))?
+        .ok_or_else(|| ApiError::BadRequest("Title must not be empty".to_string()))?;
+
+    Ok(ApiResponse {
+        code: 201,
+        message: "Match override saved successfully".to_string(),
+        data: Some(created),
+    })
+}
+
+/// Delete a match override; items already matched through it keep their
+/// metadata
+async fn delete_override(State(ctx): State<Ctx>, Path(id): Path<i64>) -> ApiResult<String> {
+    let deleted = MatchOverride::delete(&ctx.db, id)
+        .await
+        .map_err(|e| AyiahError::DatabaseError(format!("Failed to delete match override: {e}")))?;
+    if !deleted {
+        return Err(ApiError::NotFound(format!("Match override with ID {id} not found")).into());
+    }
+
+    Ok(ApiResponse {
+        code: 200,
+        message: "Match override deleted successfully".to_string(),
+        data: Some("Deleted".to_string()),
+    })
+}
+
+pub fn mount() -> Router<Ctx> {
+    Router::new()
+        .route(
+            "/match-overrides",
+            get(list_overrides).post(create_override),
+        )
+        .route("/match-overrides/{id}", delete(delete_override))
+}
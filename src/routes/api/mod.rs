@@ -1,15 +1,142 @@
 use axum::Router;
+use tower_http::{limit::RequestBodyLimitLayer, timeout::TimeoutLayer};
 
-use crate::Ctx;
+use crate::{Ctx, app::config::ServerConfig};
 
+pub mod cache;
+pub mod collections;
 pub mod health;
+pub mod images;
+pub mod jobs;
 pub mod library;
 pub mod library_folders;
+pub mod match_overrides;
+pub mod scan;
+pub mod scrape;
+pub mod users;
+pub mod ws;
 
 /// Mount all API routes
-pub fn mount() -> Router<Ctx> {
-    Router::new()
+///
+/// Requests with a body over `max_body_bytes` are refused with 413, and those
+/// taking longer than the request timeout are answered with 408. Scans,
+/// refreshes, scrapes and deduplication can run to completion within the request
+/// and are exempt from the timeout; library imports stream arbitrarily large
+/// bodies and are exempt from both. The limits are read once, so changing them
+/// requires a restart.
+pub fn mount(config: &ServerConfig) -> Router<Ctx> {
+    let routes = Router::new()
+        .merge(cache::mount())
+        .merge(collections::mount())
         .merge(health::mount())
+        .merge(images::mount())
+        .merge(jobs::mount())
         .merge(library::mount())
         .merge(library_folders::mount())
+        .merge(match_overrides::mount())
+        .merge(scan::mount())
+        .merge(scrape::mount())
+        .merge(users::mount())
+        .merge(ws::mount());
+    let long_running = Router::new()
+        .merge(library::mount_long_running())
+        .merge(library_folders::mount_long_running())
+        .merge(scrape::mount_long_running());
+
+    with_limits(config, routes, long_running, library::mount_imports())
+}
+
+/// Apply the body limit and request timeout to `routes`, only the body limit
+/// to `long_running`, and neither to `unlimited`
+fn with_limits<S: Clone + Send + Sync + 'static>(
+    config: &ServerConfig,
+    routes: Router<S>,
+    long_running: Router<S>,
+    unlimited: Router<S>,
+) -> Router<S> {
+    routes
+        .layer(TimeoutLayer::new(config.request_timeout()))
+        .merge(long_running)
+        .layer(RequestBodyLimitLayer::new(config.max_body_bytes()))
+        .merge(unlimited)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use axum::{
+        body::Body,
+        http::{Request, StatusCode, header::CONTENT_TYPE},
+    };
+    use tower::ServiceExt;
+
+    use super::*;
+    use crate::Context;
+
+    #[tokio::test(start_paused = true)]
+    async fn test_long_running_routes_are_not_timed_out() {
+        use axum::routing::post;
+
+        let config = ServerConfig {
+            request_timeout_seconds: Some(1),
+            ..ServerConfig::default()
+        };
+        let slow = || async {
+            tokio::time::sleep(std::time::Duration::from_secs(5)).await;
+            "done"
+        };
+        let app = with_limits(
+            &config,
+            Router::new().route("/quick", post(slow)),
+            Router::new().route("/scan", post(slow)),
+            Router::new(),
+        );
+
+        let status = |uri: &'static str| {
+            let app = app.clone();
+            async move {
+                app.oneshot(Request::post(uri).body(Body::empty()).unwrap())
+                    .await
+                    .unwrap()
+                    .status()
+            }
+        };
+        assert_eq!(status("/quick").await, StatusCode::REQUEST_TIMEOUT);
+        assert_eq!(status("/scan").await, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_oversized_bodies_are_rejected() {
+        let db = crate::db::test_pool().await;
+        let config = ServerConfig {
+            max_body_bytes: Some(1024),
+            ..ServerConfig::default()
+        };
+        let app = mount(&config).with_state(Arc::new(Context::for_tests(db)));
+        let body = format!(r#"{{"queries":["{}"]}}"#, "a".repeat(2048));
+
+        let response = app
+            .clone()
+            .oneshot(
+                Request::post("/scrape")
+                    .header(CONTENT_TYPE, "application/json")
+                    .body(Body::from(body.clone()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+
+        // Imports may be as large as the library
+        let response = app
+            .oneshot(
+                Request::post("/library/import")
+                    .body(Body::from(body))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
 }
@@ -0,0 +1,79 @@
+use std::collections::BTreeMap;
+
+use axum::{Router, routing::get};
+
+use crate::{
+    ApiResponse, ApiResult, Ctx, entities::MediaType,
+    services::file_scanner::get_supported_extensions,
+};
+
+/// List the file extensions scanned for each library folder media type
+///
+/// Keys are folder types, so anime shows up under `tv` and `movie` rather
+/// than as a type of its own.
+async fn get_extensions() -> ApiResult<BTreeMap<MediaType, Vec<&'static str>>> {
+    let extensions = MediaType::ALL
+        .into_iter()
+        .map(|media_type| (media_type, get_supported_extensions(media_type)))
+        .collect();
+
+    Ok(ApiResponse {
+        code: 200,
+        message: "Supported extensions retrieved successfully".to_string(),
+        data: Some(extensions),
+    })
+}
+
+pub fn mount() -> Router<Ctx> {
+    Router::new().route("/scan/extensions", get(get_extensions))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use axum::{
+        body::Body,
+        http::{Request, StatusCode},
+    };
+    use tower::ServiceExt;
+
+    use super::*;
+    use crate::Context;
+
+    #[tokio::test]
+    async fn test_extensions_are_listed_per_media_type() {
+        let db = crate::db::test_pool().await;
+        let app = mount().with_state(Arc::new(Context::for_tests(db)));
+
+        let response = app
+            .oneshot(
+                Request::get("/scan/extensions")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+        let movie = body["data"]["movie"].as_array().unwrap();
+        assert!(movie.contains(&"mkv".into()));
+        assert!(movie.contains(&"mp4".into()));
+        assert!(
+            body["data"]["comic"]
+                .as_array()
+                .unwrap()
+                .contains(&"cbz".into())
+        );
+        assert!(
+            body["data"]["book"]
+                .as_array()
+                .unwrap()
+                .contains(&"epub".into())
+        );
+    }
+}
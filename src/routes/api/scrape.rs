@@ -0,0 +1,1381 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Instant,
+};
+
+use axum::{
+    Json, Router,
+    extract::{Query, State},
+    routing::{get, post},
+};
+use serde::{Deserialize, Serialize};
+use tokio::sync::Semaphore;
+use walkdir::WalkDir;
+
+use crate::{
+    ApiResponse, ApiResult, Ctx,
+    entities::{CreateMediaItem, LibraryFolder, MediaItem, MediaType},
+    error::{ApiError, AyiahError},
+    scraper::{
+        self, MediaDetails, MediaSearchResult, NamingContext, NamingTemplate, ProviderCapabilities,
+        ScraperError, ScraperManager, SearchOptions, naming,
+    },
+    services::{
+        ConflictPolicy, OrganizeMethod,
+        file_scanner::{detect_media_type, get_supported_extensions},
+        organizer,
+    },
+    utils::title::clean_title,
+};
+
+const DEFAULT_CONCURRENT_LIMIT: usize = 4;
+
+/// What a scrape request operates on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ScrapeTargetType {
+    File,
+    Batch,
+    Directory,
+}
+
+/// Scrape request payload
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScrapePayload {
+    pub target_type: ScrapeTargetType,
+    /// Single file to scrape (`file`)
+    pub file_path: Option<String>,
+    /// Files to scrape (`batch`)
+    pub file_paths: Option<Vec<String>>,
+    /// Directory to walk (`directory`)
+    pub directory: Option<String>,
+    /// Whether to descend into subdirectories (`directory`)
+    pub recursive: Option<bool>,
+    /// Extensions to include when walking a directory
+    pub file_extensions: Option<Vec<String>>,
+    /// Media type, auto-detected when absent
+    pub media_type: Option<MediaType>,
+    /// Restrict matching to a single provider
+    pub provider: Option<String>,
+    /// Organize files into `target_dir` after matching
+    pub auto_organize: Option<bool>,
+    /// How organized files are placed (defaults to symlink)
+    pub organize_method: Option<OrganizeMethod>,
+    /// Library root organized files are placed under
+    pub target_dir: Option<String>,
+    /// Naming template for organized files, e.g. `{title} ({year})/{title}`
+    pub naming_template: Option<String>,
+    /// What to do when an organize target already exists (defaults to skip)
+    pub conflict_policy: Option<ConflictPolicy>,
+    /// Report where files would be organized without touching them
+    pub dry_run: Option<bool>,
+    /// Maximum number of files scraped at once
+    pub concurrent_limit: Option<usize>,
+}
+
+/// Outcome for a single file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScrapeResult {
+    pub file_path: String,
+    pub success: bool,
+    pub title: Option<String>,
+    pub year: Option<i32>,
+    pub metadata: Option<MediaDetails>,
+    pub organized_path: Option<String>,
+    pub error: Option<String>,
+}
+
+impl ScrapeResult {
+    fn failed(file_path: &str, error: impl Into<String>) -> Self {
+        Self {
+            file_path: file_path.to_string(),
+            success: false,
+            title: None,
+            year: None,
+            metadata: None,
+            organized_path: None,
+            error: Some(error.into()),
+        }
+    }
+}
+
+/// Scrape response
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScrapeResponse {
+    pub total: usize,
+    pub succeeded: usize,
+    pub failed: usize,
+    pub results: Vec<ScrapeResult>,
+    pub duration_ms: u64,
+}
+
+/// Manual match payload
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ManualMatchPayload {
+    pub file_path: String,
+    /// Provider-specific ID of the chosen title
+    pub media_id: String,
+    pub provider: String,
+    /// Kind of title `media_id` refers to, inferred from the provider when absent
+    pub media_type: Option<scraper::MediaType>,
+    pub auto_organize: Option<bool>,
+    pub organize_method: Option<OrganizeMethod>,
+    pub target_dir: Option<String>,
+    pub naming_template: Option<String>,
+    pub conflict_policy: Option<ConflictPolicy>,
+    pub dry_run: Option<bool>,
+}
+
+/// Query parameters for previewing provider matches
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchQuery {
+    pub query: String,
+    pub year: Option<i32>,
+    /// Only return titles of this kind
+    pub media_type: Option<scraper::MediaType>,
+    /// Only query this provider
+    pub provider: Option<String>,
+    /// Return adult titles if the server allows them; defaults to true
+    pub include_adult: Option<bool>,
+}
+
+/// Query parameters for inspecting one provider title
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DetailsQuery {
+    pub provider: String,
+    /// Provider-specific ID of the title
+    pub id: String,
+    /// Kind of title `id` refers to, inferred from the provider when absent
+    pub media_type: Option<scraper::MediaType>,
+}
+
+/// A registered provider and what it supports
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProviderInfo {
+    pub name: String,
+    #[serde(flatten)]
+    pub capabilities: ProviderCapabilities,
+}
+
+/// Outcome of a provider self-test
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProviderTestResult {
+    pub provider: String,
+    pub ok: bool,
+    pub latency_ms: u64,
+    pub error: Option<String>,
+}
+
+/// Organize settings shared by every file in a request
+#[derive(Debug, Clone)]
+struct OrganizeOptions {
+    target_dir: PathBuf,
+    method: OrganizeMethod,
+    template: Option<NamingTemplate>,
+    conflict: ConflictPolicy,
+    dry_run: bool,
+}
+
+/// Scrape metadata for a file, a list of files, or a directory
+async fn scrape(
+    State(ctx): State<Ctx>,
+    Json(payload): Json<ScrapePayload>,
+) -> ApiResult<ScrapeResponse> {
+    let scraper_manager = ctx
+        .scraper_manager
+        .clone()
+        .ok_or_else(|| ApiError::ServiceUnavailable("Scraper manager not available".to_string()))?;
+
+    if let Some(provider) = &payload.provider
+        && !scraper_manager
+            .providers()
+            .iter()
+            .any(|p| p.name() == provider)
+    {
+        return Err(ApiError::ProviderNotFound(provider.clone()).into());
+    }
+
+    let organize = organize_options(
+        payload.auto_organize,
+        payload.target_dir.as_ref(),
+        payload.organize_method,
+        payload.naming_template.as_ref(),
+        payload.conflict_policy,
+        payload.dry_run,
+    )?;
+
+    let files = collect_files(&payload)?;
+    let start = Instant::now();
+
+    let semaphore = Arc::new(Semaphore::new(
+        payload
+            .concurrent_limit
+            .unwrap_or(DEFAULT_CONCURRENT_LIMIT)
+            .max(1),
+    ));
+
+    let tasks = files.iter().map(|file| {
+        let semaphore = semaphore.clone();
+        let scraper_manager = scraper_manager.clone();
+        let payload = &payload;
+        let organize = organize.as_ref();
+        let db = &ctx.db;
+        async move {
+            let _permit = semaphore.acquire().await.ok();
+            let media_type = match payload.media_type.or_else(|| detect_media_type(file)) {
+                Some(media_type) => Some(media_type),
+                // Ambiguous extensions take the type of the library folder they live in
+                None => LibraryFolder::find_containing(db, &file.to_string_lossy())
+                    .await
+                    .ok()
+                    .flatten()
+                    .map(|folder| folder.media_type),
+            };
+            scrape_file(&scraper_manager, file, payload, media_type, organize).await
+        }
+    });
+
+    let results = futures::future::join_all(tasks).await;
+    let succeeded = results.iter().filter(|r| r.success).count();
+
+    Ok(ApiResponse {
+        code: 200,
+        message: "Scrape completed".to_string(),
+        data: Some(ScrapeResponse {
+            total: results.len(),
+            succeeded,
+            failed: results.len() - succeeded,
+            results,
+            duration_ms: start.elapsed().as_millis() as u64,
+        }),
+    })
+}
+
+/// List the registered providers and the media types each one supports
+async fn providers(State(ctx): State<Ctx>) -> ApiResult<Vec<ProviderInfo>> {
+    let scraper_manager = ctx
+        .scraper_manager
+        .as_ref()
+        .ok_or_else(|| ApiError::ServiceUnavailable("Scraper manager not available".to_string()))?;
+
+    let providers = scraper_manager
+        .providers()
+        .iter()
+        .map(|p| ProviderInfo {
+            name: p.name().to_string(),
+            capabilities: p.capabilities(),
+        })
+        .collect();
+
+    Ok(ApiResponse {
+        code: 200,
+        message: "Providers retrieved successfully".to_string(),
+        data: Some(providers),
+    })
+}
+
+/// Check that a provider answers and accepts its credentials
+///
+/// A failed check is reported in the result rather than as an error status.
+async fn test_provider(
+    State(ctx): State<Ctx>,
+    axum::extract::Path(name): axum::extract::Path<String>,
+) -> ApiResult<ProviderTestResult> {
+    let scraper_manager = ctx
+        .scraper_manager
+        .as_ref()
+        .ok_or_else(|| ApiError::ServiceUnavailable("Scraper manager not available".to_string()))?;
+    let provider = scraper_manager
+        .providers()
+        .iter()
+        .find(|p| p.name() == name)
+        .ok_or_else(|| ApiError::ProviderNotFound(name.clone()))?;
+
+    let start = Instant::now();
+    let error = provider.ping().await.err().map(|e| e.to_string());
+    let result = ProviderTestResult {
+        provider: name,
+        ok: error.is_none(),
+        latency_ms: start.elapsed().as_millis() as u64,
+        error,
+    };
+
+    Ok(ApiResponse {
+        code: 200,
+        message: if result.ok {
+            "Provider test passed".to_string()
+        } else {
+            "Provider test failed".to_string()
+        },
+        data: Some(result),
+    })
+}
+
+/// Search the providers directly, without saving anything
+///
+/// Lets a client preview candidates before picking one for a manual match.
+async fn search(
+    State(ctx): State<Ctx>,
+    Query(query): Query<SearchQuery>,
+) -> ApiResult<Vec<MediaSearchResult>> {
+    let scraper_manager = ctx
+        .scraper_manager
+        .as_ref()
+        .ok_or_else(|| ApiError::ServiceUnavailable("Scraper manager not available".to_string()))?;
+
+    if query.query.trim().is_empty() {
+        return Err(ApiError::BadRequest("query must not be empty".to_string()).into());
+    }
+
+    let mut options = SearchOptions::new(query.query.trim())
+        .with_year(query.year)
+        .with_media_types(query.media_type)
+        .with_include_adult(query.include_adult.unwrap_or(true));
+    if let Some(provider) = query.provider {
+        if !scraper_manager
+            .providers()
+            .iter()
+            .any(|p| p.name() == provider)
+        {
+            return Err(ApiError::ProviderNotFound(provider).into());
+        }
+        options = options.with_provider(provider);
+    }
+
+    let results = scraper_manager
+        .search(&options)
+        .await
+        .map_err(|e| match e {
+            ScraperError::NotFound(_) => AyiahError::from(ApiError::NotFound(format!(
+                "No matches found for: {}",
+                options.query
+            ))),
+            e => e.into(),
+        })?;
+
+    Ok(ApiResponse {
+        code: 200,
+        message: "Search completed".to_string(),
+        data: Some(results),
+    })
+}
+
+/// Fetch full details for a search candidate, without saving anything
+async fn details(
+    State(ctx): State<Ctx>,
+    Query(query): Query<DetailsQuery>,
+) -> ApiResult<MediaDetails> {
+    let scraper_manager = ctx
+        .scraper_manager
+        .as_ref()
+        .ok_or_else(|| ApiError::ServiceUnavailable("Scraper manager not available".to_string()))?;
+
+    if !scraper_manager
+        .providers()
+        .iter()
+        .any(|p| p.name() == query.provider)
+    {
+        return Err(ApiError::ProviderNotFound(query.provider).into());
+    }
+
+    let media_type = query
+        .media_type
+        .unwrap_or_else(|| default_media_type(scraper_manager, &query.provider));
+    let candidate = MediaSearchResult::from_id(media_type, &query.provider, &query.id);
+    let details = scraper_manager
+        .get_details(&candidate)
+        .await
+        .map_err(|e| details_error(&query.provider, &query.id, e))?;
+
+    Ok(ApiResponse {
+        code: 200,
+        message: "Details fetched".to_string(),
+        data: Some(details),
+    })
+}
+
+/// Persist a user-chosen provider match for a file
+async fn manual_match(
+    State(ctx): State<Ctx>,
+    Json(payload): Json<ManualMatchPayload>,
+) -> ApiResult<ScrapeResult> {
+    let scraper_manager = ctx
+        .scraper_manager
+        .as_ref()
+        .ok_or_else(|| ApiError::ServiceUnavailable("Scraper manager not available".to_string()))?;
+    let metadata_agent = ctx
+        .metadata_agent
+        .as_ref()
+        .ok_or_else(|| ApiError::ServiceUnavailable("Metadata agent not available".to_string()))?;
+
+    if !scraper_manager
+        .providers()
+        .iter()
+        .any(|p| p.name() == payload.provider)
+    {
+        return Err(ApiError::ProviderNotFound(payload.provider).into());
+    }
+
+    let source = PathBuf::from(&payload.file_path);
+    if !source.is_file() {
+        return Err(
+            ApiError::BadRequest(format!("File does not exist: {}", payload.file_path)).into(),
+        );
+    }
+
+    let organize = organize_options(
+        payload.auto_organize,
+        payload.target_dir.as_ref(),
+        payload.organize_method,
+        payload.naming_template.as_ref(),
+        payload.conflict_policy,
+        payload.dry_run,
+    )?;
+
+    let media_type = payload
+        .media_type
+        .unwrap_or_else(|| default_media_type(scraper_manager, &payload.provider));
+    let candidate = MediaSearchResult::from_id(media_type, &payload.provider, &payload.media_id);
+
+    let details = scraper_manager
+        .get_details(&candidate)
+        .await
+        .map_err(|e| details_error(&payload.provider, &payload.media_id, e))?;
+
+    let media_item = match MediaItem::find_by_path(&ctx.db, &payload.file_path).await? {
+        Some(item) => item,
+        None => {
+            let folder = LibraryFolder::find_containing(&ctx.db, &payload.file_path)
+                .await?
+                .ok_or_else(|| {
+                    ApiError::BadRequest(format!(
+                        "File is not inside any library folder: {}",
+                        payload.file_path
+                    ))
+                })?;
+            let file_size = std::fs::metadata(&source).map_or(0, |m| m.len() as i64);
+
+            MediaItem::create(
+                &ctx.db,
+                CreateMediaItem {
+                    library_folder_id: folder.id,
+                    media_type: entity_media_type(details.media_type()),
+                    title: details.title().to_string(),
+                    file_path: payload.file_path.clone(),
+                    file_size,
+                    content_hash: None,
+                    modified_at: None,
+                },
+            )
+            .await?
+        }
+    };
+
+    metadata_agent
+        .save_metadata(media_item.id, details.clone())
+        .await
+        .map_err(|e| ApiError::InternalServerError(format!("Failed to save metadata: {e}")))?;
+    // Keep automatic refreshes from overwriting the hand-picked match
+    MediaItem::set_metadata_locked(&ctx.db, media_item.id, true)
+        .await
+        .map_err(|e| AyiahError::DatabaseError(format!("Failed to lock metadata: {e}")))?;
+
+    let organized_path = match &organize {
+        Some(options) => {
+            let context = naming_context(scraper_manager, &details, &source).await;
+            let target = organizer::target_path(
+                &options.target_dir,
+                options.template.as_ref(),
+                &context,
+                &source,
+            );
+            let organized = organizer::organize_file(
+                &source,
+                &target,
+                options.method,
+                options.conflict,
+                options.dry_run,
+            )
+            .await
+            .map_err(AyiahError::from)?;
+            let organized = organized.to_string_lossy().to_string();
+
+            // A moved file is only reachable at its new location; a skipped one stays put
+            if options.method == OrganizeMethod::Move && !options.dry_run && !source.exists() {
+                MediaItem::update_file_path(&ctx.db, media_item.id, &organized).await?;
+            }
+            Some(organized)
+        }
+        None => None,
+    };
+
+    Ok(ApiResponse {
+        code: 200,
+        message: "Manual match saved".to_string(),
+        data: Some(ScrapeResult {
+            file_path: payload.file_path,
+            success: true,
+            title: Some(details.title().to_string()),
+            year: details.year(),
+            metadata: Some(details),
+            organized_path,
+            error: None,
+        }),
+    })
+}
+
+/// Map a failed details lookup to a response, naming the ID when it didn't resolve
+fn details_error(provider: &str, media_id: &str, error: ScraperError) -> AyiahError {
+    match error {
+        ScraperError::NotFound(_) | ScraperError::Api { status: 404, .. } => {
+            ApiError::NotFound(format!("{provider} could not resolve media ID {media_id}")).into()
+        }
+        e => e.into(),
+    }
+}
+
+/// Build organize options from request fields
+fn organize_options(
+    auto_organize: Option<bool>,
+    target_dir: Option<&String>,
+    method: Option<OrganizeMethod>,
+    template: Option<&String>,
+    conflict: Option<ConflictPolicy>,
+    dry_run: Option<bool>,
+) -> Result<Option<OrganizeOptions>, AyiahError> {
+    if !auto_organize.unwrap_or(false) {
+        return Ok(None);
+    }
+
+    let target_dir = target_dir.ok_or_else(|| {
+        ApiError::BadRequest("target_dir is required when auto_organize is set".to_string())
+    })?;
+
+    Ok(Some(OrganizeOptions {
+        target_dir: PathBuf::from(target_dir),
+        method: method.unwrap_or_default(),
+        template: template.map(NamingTemplate::new),
+        conflict: conflict.unwrap_or_default(),
+        dry_run: dry_run.unwrap_or(false),
+    }))
+}
+
+/// Gather naming values for a matched file
+///
+/// Episode numbers come from an `S01E02` marker in the file name, or from an
+/// absolute number mapped onto the series' seasons; the episode title is
+/// looked up on a best-effort basis.
+async fn naming_context(
+    scraper_manager: &ScraperManager,
+    details: &MediaDetails,
+    source: &Path,
+) -> NamingContext {
+    let stem = source
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let context =
+        NamingContext::from_details(details).with_resolution(naming::parse_resolution(&stem));
+
+    if details.media_type() == scraper::MediaType::Movie {
+        return context;
+    }
+    let seasons = match details {
+        MediaDetails::Tv(tv) => tv.seasons.as_slice(),
+        _ => &[],
+    };
+    let Some((season, episode)) = naming::parse_episode_marker(&stem).or_else(|| {
+        naming::parse_absolute_episode(&stem)
+            .and_then(|absolute| naming::absolute_to_season_episode(seasons, absolute))
+    }) else {
+        return context;
+    };
+
+    match scraper_manager
+        .get_episode_details(details.provider(), details.id(), season, episode)
+        .await
+    {
+        Ok(episode) => context.with_episode(&episode),
+        Err(_) => context.with_episode_numbers(season, episode),
+    }
+}
+
+/// Guess what kind of title an ID refers to from the provider's specialty
+fn default_media_type(scraper_manager: &ScraperManager, provider: &str) -> scraper::MediaType {
+    scraper_manager
+        .providers()
+        .iter()
+        .find(|p| p.name() == provider)
+        .and_then(|p| p.capabilities().media_types.first().copied())
+        .unwrap_or(scraper::MediaType::Movie)
+}
+
+/// Library media type a scraped title is stored under
+const fn entity_media_type(media_type: scraper::MediaType) -> MediaType {
+    match media_type {
+        scraper::MediaType::Movie => MediaType::Movie,
+        scraper::MediaType::Tv | scraper::MediaType::Anime => MediaType::Tv,
+    }
+}
+
+/// Resolve the files a payload refers to
+fn collect_files(payload: &ScrapePayload) -> Result<Vec<PathBuf>, AyiahError> {
+    match payload.target_type {
+        ScrapeTargetType::File => {
+            let file_path = payload.file_path.as_ref().ok_or_else(|| {
+                ApiError::BadRequest("file_path is required for target_type 'file'".to_string())
+            })?;
+            let path = PathBuf::from(file_path);
+            if !path.is_file() {
+                return Err(
+                    ApiError::BadRequest(format!("File does not exist: {file_path}")).into(),
+                );
+            }
+            Ok(vec![path])
+        }
+        ScrapeTargetType::Batch => {
+            let file_paths = payload
+                .file_paths
+                .as_ref()
+                .filter(|paths| !paths.is_empty())
+                .ok_or_else(|| {
+                    ApiError::BadRequest(
+                        "file_paths is required for target_type 'batch'".to_string(),
+                    )
+                })?;
+            Ok(file_paths.iter().map(PathBuf::from).collect())
+        }
+        ScrapeTargetType::Directory => {
+            let directory = payload.directory.as_ref().ok_or_else(|| {
+                ApiError::BadRequest(
+                    "directory is required for target_type 'directory'".to_string(),
+                )
+            })?;
+            let path = Path::new(directory);
+            if !path.is_dir() {
+                return Err(
+                    ApiError::BadRequest(format!("Path is not a directory: {directory}")).into(),
+                );
+            }
+
+            let extensions: Vec<String> = match &payload.file_extensions {
+                Some(exts) => exts
+                    .iter()
+                    .map(|e| e.trim_start_matches('.').to_lowercase())
+                    .collect(),
+                None => get_supported_extensions(payload.media_type.unwrap_or(MediaType::Movie))
+                    .into_iter()
+                    .map(String::from)
+                    .collect(),
+            };
+
+            let max_depth = if payload.recursive.unwrap_or(false) {
+                usize::MAX
+            } else {
+                1
+            };
+
+            Ok(WalkDir::new(path)
+                .max_depth(max_depth)
+                .into_iter()
+                .filter_map(|e| e.ok())
+                .filter(|e| e.file_type().is_file())
+                .filter(|e| {
+                    e.path().extension().is_some_and(|ext| {
+                        extensions.contains(&ext.to_string_lossy().to_lowercase())
+                    })
+                })
+                .map(|e| e.into_path())
+                .collect())
+        }
+    }
+}
+
+/// Match a single file against the providers and optionally organize it
+async fn scrape_file(
+    scraper_manager: &ScraperManager,
+    path: &Path,
+    payload: &ScrapePayload,
+    media_type: Option<MediaType>,
+    organize: Option<&OrganizeOptions>,
+) -> ScrapeResult {
+    let file_path = path.to_string_lossy().to_string();
+
+    if !path.is_file() {
+        return ScrapeResult::failed(&file_path, "File does not exist");
+    }
+
+    let stem = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let (title, year) = clean_title(naming::strip_episode_marker(&stem));
+
+    let mut options = SearchOptions::new(title)
+        .with_year(year)
+        .with_include_adult(true);
+    options.provider.clone_from(&payload.provider);
+    let results = match scraper_manager.search(&options).await {
+        Ok(results) => results,
+        Err(e) => return ScrapeResult::failed(&file_path, e.to_string()),
+    };
+
+    let Some(best) = pick_best_match(results, payload, media_type) else {
+        return ScrapeResult::failed(&file_path, "No matching results found");
+    };
+
+    let details = match scraper_manager.get_details(&best).await {
+        Ok(details) => details,
+        Err(e) => return ScrapeResult::failed(&file_path, e.to_string()),
+    };
+
+    let organized_path = match organize {
+        Some(options) => {
+            let context = naming_context(scraper_manager, &details, path).await;
+            let target = organizer::target_path(
+                &options.target_dir,
+                options.template.as_ref(),
+                &context,
+                path,
+            );
+            match organizer::organize_file(
+                path,
+                &target,
+                options.method,
+                options.conflict,
+                options.dry_run,
+            )
+            .await
+            {
+                Ok(organized) => Some(organized.to_string_lossy().to_string()),
+                Err(e) => return ScrapeResult::failed(&file_path, e.to_string()),
+            }
+        }
+        None => None,
+    };
+
+    ScrapeResult {
+        file_path,
+        success: true,
+        title: Some(details.title().to_string()),
+        year: details.year(),
+        metadata: Some(details),
+        organized_path,
+        error: None,
+    }
+}
+
+/// Pick the first result honoring the requested provider and media type
+fn pick_best_match(
+    results: Vec<MediaSearchResult>,
+    payload: &ScrapePayload,
+    media_type: Option<MediaType>,
+) -> Option<MediaSearchResult> {
+    results.into_iter().find(|result| {
+        let provider_matches = payload
+            .provider
+            .as_ref()
+            .is_none_or(|p| p == result.provider());
+
+        let type_matches = media_type.is_none_or(|media_type| {
+            matches!(
+                (media_type, result.media_type()),
+                (MediaType::Movie, crate::scraper::MediaType::Movie)
+                    | (MediaType::Tv, crate::scraper::MediaType::Tv)
+                    | (MediaType::Tv, crate::scraper::MediaType::Anime)
+            )
+        });
+
+        provider_matches && type_matches
+    })
+}
+
+/// Mount scrape routes
+pub fn mount() -> Router<Ctx> {
+    Router::new()
+        .route("/scrape/providers", get(providers))
+        .route("/scrape/providers/{name}/test", post(test_provider))
+        .route("/scrape/search", get(search))
+        .route("/scrape/details", get(details))
+        .route("/scrape/manual-match", post(manual_match))
+}
+
+/// Routes that may organize whole directories within the request, mounted
+/// outside the request timeout
+pub fn mount_long_running() -> Router<Ctx> {
+    Router::new().route("/scrape", post(scrape))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use axum::{
+        body::Body,
+        http::{Request, StatusCode, header::RETRY_AFTER},
+    };
+    use tower::ServiceExt;
+
+    use crate::{
+        Context,
+        entities::{CreateLibraryFolder, VideoMetadata},
+        scraper::mock::{FakeProvider, anime_details},
+        services::MetadataAgent,
+    };
+
+    use super::*;
+
+    async fn app(scraper_manager: Option<ScraperManager>) -> Router {
+        app_with_db(scraper_manager, crate::db::test_pool().await)
+    }
+
+    fn app_with_db(scraper_manager: Option<ScraperManager>, db: crate::db::Database) -> Router {
+        let scraper_manager = scraper_manager.map(Arc::new);
+        let metadata_agent = scraper_manager
+            .clone()
+            .map(|m| Arc::new(MetadataAgent::new(m, db.clone())));
+        let ctx = Context {
+            scraper_manager,
+            metadata_agent,
+            ..Context::for_tests(db)
+        };
+        mount()
+            .merge(mount_long_running())
+            .with_state(Arc::new(ctx))
+    }
+
+    async fn post_json(app: Router, body: serde_json::Value) -> (StatusCode, serde_json::Value) {
+        post_json_to(app, "/scrape", body).await
+    }
+
+    async fn post_json_to(
+        app: Router,
+        uri: &str,
+        body: serde_json::Value,
+    ) -> (StatusCode, serde_json::Value) {
+        let response = app
+            .oneshot(
+                Request::post(uri)
+                    .header("content-type", "application/json")
+                    .body(Body::from(body.to_string()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let status = response.status();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    async fn get_json(app: Router, uri: &str) -> (StatusCode, serde_json::Value) {
+        let response = app
+            .oneshot(Request::get(uri).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let status = response.status();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_scrape_without_manager_is_unavailable() {
+        let (status, _) = post_json(
+            app(None).await,
+            serde_json::json!({ "target_type": "batch", "file_paths": ["/nope.mkv"] }),
+        )
+        .await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    }
+
+    #[tokio::test]
+    async fn test_scrape_directory_matches_and_organizes() {
+        let source = tempfile::tempdir().unwrap();
+        let target = tempfile::tempdir().unwrap();
+        std::fs::write(source.path().join("The Matrix (1999).mkv"), b"data").unwrap();
+        std::fs::write(source.path().join("Unknown Film.mkv"), b"data").unwrap();
+        std::fs::write(source.path().join("readme.txt"), b"data").unwrap();
+
+        let mut manager = ScraperManager::new();
+        manager.add_provider(Box::new(FakeProvider::new("fake").with_movie(
+            "603",
+            "The Matrix",
+            1999,
+        )));
+
+        let (status, body) = post_json(
+            app(Some(manager)).await,
+            serde_json::json!({
+                "target_type": "directory",
+                "directory": source.path(),
+                "auto_organize": true,
+                "organize_method": "copy",
+                "target_dir": target.path(),
+            }),
+        )
+        .await;
+
+        assert_eq!(status, StatusCode::OK);
+        let data = &body["data"];
+        assert_eq!(data["total"], 2);
+        assert_eq!(data["succeeded"], 1);
+        assert_eq!(data["failed"], 1);
+        assert!(
+            target
+                .path()
+                .join("The Matrix (1999)/The Matrix (1999).mkv")
+                .is_file()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_scrape_episode_uses_naming_template() {
+        let source = tempfile::tempdir().unwrap();
+        let target = tempfile::tempdir().unwrap();
+        let file = source.path().join("Cowboy.Bebop.S01E05.1080p.mkv");
+        std::fs::write(&file, b"data").unwrap();
+
+        let mut manager = ScraperManager::new();
+        manager.add_provider(Box::new(FakeProvider::new("fake").with_details(
+            MediaDetails::Anime(anime_details("fake", "1", "Cowboy Bebop", 1998)),
+        )));
+
+        let (status, body) = post_json(
+            app(Some(manager)).await,
+            serde_json::json!({
+                "target_type": "file",
+                "file_path": file,
+                "auto_organize": true,
+                "organize_method": "copy",
+                "target_dir": target.path(),
+            }),
+        )
+        .await;
+
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"]["succeeded"], 1);
+        assert!(
+            target
+                .path()
+                .join("Cowboy Bebop (1998)/Season 01/Cowboy Bebop - S01E05 - Episode 5.mkv")
+                .is_file()
+        );
+
+        let manager = || {
+            let mut manager = ScraperManager::new();
+            manager.add_provider(Box::new(FakeProvider::new("fake").with_details(
+                MediaDetails::Anime(anime_details("fake", "1", "Cowboy Bebop", 1998)),
+            )));
+            manager
+        };
+        let (_, body) = post_json(
+            app(Some(manager())).await,
+            serde_json::json!({
+                "target_type": "file",
+                "file_path": file,
+                "auto_organize": true,
+                "organize_method": "copy",
+                "target_dir": target.path(),
+                "naming_template": "Anime/{title}/{episode:03} [{resolution}]",
+            }),
+        )
+        .await;
+
+        assert_eq!(body["data"]["succeeded"], 1);
+        assert!(
+            target
+                .path()
+                .join("Anime/Cowboy Bebop/005 [1080p].mkv")
+                .is_file()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_scrape_maps_absolute_episode_numbers() {
+        let source = tempfile::tempdir().unwrap();
+        let target = tempfile::tempdir().unwrap();
+        let file = source.path().join("Cowboy Bebop - 24 [1080p].mkv");
+        std::fs::write(&file, b"data").unwrap();
+
+        // Anime details carry no season counts, so the number stays in season 1
+        let mut manager = ScraperManager::new();
+        manager.add_provider(Box::new(FakeProvider::new("fake").with_details(
+            MediaDetails::Anime(anime_details("fake", "1", "Cowboy Bebop", 1998)),
+        )));
+
+        let (status, body) = post_json(
+            app(Some(manager)).await,
+            serde_json::json!({
+                "target_type": "file",
+                "file_path": file,
+                "auto_organize": true,
+                "organize_method": "copy",
+                "target_dir": target.path(),
+            }),
+        )
+        .await;
+
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"]["results"][0]["title"], "Cowboy Bebop");
+        assert!(
+            target
+                .path()
+                .join("Cowboy Bebop (1998)/Season 01/Cowboy Bebop - S01E24 - Episode 24.mkv")
+                .is_file()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_scrape_detects_episodes_as_tv() {
+        let source = tempfile::tempdir().unwrap();
+        let file = source.path().join("Cowboy Bebop S01E01.mkv");
+        std::fs::write(&file, b"data").unwrap();
+
+        // The movie comes first, so only the detected type skips it
+        let mut manager = ScraperManager::new();
+        manager.add_provider(Box::new(
+            FakeProvider::new("fake")
+                .with_movie("11", "Cowboy Bebop: Knockin' on Heaven's Door", 2001)
+                .with_details(MediaDetails::Anime(anime_details(
+                    "fake",
+                    "1",
+                    "Cowboy Bebop",
+                    1998,
+                ))),
+        ));
+
+        let (status, body) = post_json(
+            app(Some(manager)).await,
+            serde_json::json!({ "target_type": "file", "file_path": file }),
+        )
+        .await;
+
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"]["results"][0]["title"], "Cowboy Bebop");
+    }
+
+    #[tokio::test]
+    async fn test_scrape_dry_run_leaves_files_untouched() {
+        let source = tempfile::tempdir().unwrap();
+        let target = tempfile::tempdir().unwrap();
+        let file = source.path().join("The Matrix (1999).mkv");
+        std::fs::write(&file, b"data").unwrap();
+
+        let mut manager = ScraperManager::new();
+        manager.add_provider(Box::new(FakeProvider::new("fake").with_movie(
+            "603",
+            "The Matrix",
+            1999,
+        )));
+
+        let (status, body) = post_json(
+            app(Some(manager)).await,
+            serde_json::json!({
+                "target_type": "file",
+                "file_path": file,
+                "auto_organize": true,
+                "organize_method": "move",
+                "target_dir": target.path(),
+                "dry_run": true,
+            }),
+        )
+        .await;
+
+        assert_eq!(status, StatusCode::OK);
+        let planned = target
+            .path()
+            .join("The Matrix (1999)/The Matrix (1999).mkv");
+        assert_eq!(
+            body["data"]["results"][0]["organized_path"],
+            planned.to_string_lossy().as_ref()
+        );
+        assert!(file.is_file());
+        assert_eq!(std::fs::read_dir(target.path()).unwrap().count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_manual_match_persists_metadata() {
+        let db = crate::db::test_pool().await;
+        let library = tempfile::tempdir().unwrap();
+        let file = library.path().join("matrix.mkv");
+        std::fs::write(&file, b"data").unwrap();
+        LibraryFolder::create(
+            &db,
+            CreateLibraryFolder {
+                name: "Movies".to_string(),
+                path: library.path().to_string_lossy().to_string(),
+                media_type: MediaType::Movie,
+                extensions: None,
+            },
+        )
+        .await
+        .unwrap();
+
+        let manager = || {
+            let mut manager = ScraperManager::new();
+            manager.add_provider(Box::new(FakeProvider::new("fake").with_movie(
+                "603",
+                "The Matrix",
+                1999,
+            )));
+            manager
+        };
+        let request = |provider: &str, media_id: &str| {
+            serde_json::json!({
+                "file_path": file,
+                "media_id": media_id,
+                "provider": provider,
+                "media_type": "movie",
+            })
+        };
+
+        let (status, _) = post_json_to(
+            app_with_db(Some(manager()), db.clone()),
+            "/scrape/manual-match",
+            request("other", "603"),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        let (status, _) = post_json_to(
+            app_with_db(Some(manager()), db.clone()),
+            "/scrape/manual-match",
+            request("fake", "999"),
+        )
+        .await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+
+        let (status, body) = post_json_to(
+            app_with_db(Some(manager()), db.clone()),
+            "/scrape/manual-match",
+            request("fake", "603"),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"]["title"], "The Matrix");
+
+        let item = MediaItem::find_by_path(&db, &file.to_string_lossy())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(item.title, "The Matrix");
+        assert!(
+            VideoMetadata::find_by_media_item_id(&db, item.id)
+                .await
+                .unwrap()
+                .is_some()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_search_passes_results_through() {
+        let manager = || {
+            let mut manager = ScraperManager::new();
+            manager.add_provider(Box::new(FakeProvider::new("first").with_movie(
+                "603",
+                "The Matrix",
+                1999,
+            )));
+            manager.add_provider(Box::new(FakeProvider::new("second").with_movie(
+                "m1",
+                "The Matrix Reloaded",
+                2003,
+            )));
+            manager
+        };
+
+        let (status, _) = get_json(app(None).await, "/scrape/search?query=matrix").await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+
+        let (status, body) =
+            get_json(app(Some(manager())).await, "/scrape/search?query=matrix").await;
+        assert_eq!(status, StatusCode::OK);
+        let providers: Vec<_> = body["data"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|r| r["provider"].as_str().unwrap())
+            .collect();
+        assert_eq!(providers, ["first", "second"]);
+
+        let (status, body) = get_json(
+            app(Some(manager())).await,
+            "/scrape/search?query=matrix&provider=second",
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["data"].as_array().unwrap().len(), 1);
+        assert_eq!(body["data"][0]["title"], "The Matrix Reloaded");
+
+        let (status, _) = get_json(
+            app(Some(manager())).await,
+            "/scrape/search?query=matrix&provider=other",
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        let (status, _) = get_json(app(Some(manager())).await, "/scrape/search?query=heat").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_details_returns_cached_candidate() {
+        let provider = FakeProvider::new("fake").with_movie("603", "The Matrix", 1999);
+        let details_calls = provider.details_calls();
+        let mut manager = ScraperManager::new();
+        manager.add_provider(Box::new(provider));
+        let app = app(Some(manager)).await;
+
+        for _ in 0..2 {
+            let (status, body) = get_json(
+                app.clone(),
+                "/scrape/details?provider=fake&media_type=movie&id=603",
+            )
+            .await;
+            assert_eq!(status, StatusCode::OK);
+            assert_eq!(body["data"]["title"], "The Matrix");
+            assert_eq!(body["data"]["id"], "603");
+        }
+        assert_eq!(details_calls.load(std::sync::atomic::Ordering::SeqCst), 1);
+
+        let (status, _) = get_json(app.clone(), "/scrape/details?provider=other&id=603").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        let (status, _) =
+            get_json(app, "/scrape/details?provider=fake&media_type=movie&id=999").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_rate_limits_reach_client_with_retry_after() {
+        let provider = FakeProvider::new("fake")
+            .with_movie("603", "The Matrix", 1999)
+            .with_rate_limit(Duration::from_secs(5));
+        let mut manager = ScraperManager::new();
+        manager.add_provider(Box::new(provider));
+        let app = app(Some(manager)).await;
+
+        for uri in [
+            "/scrape/details?provider=fake&media_type=movie&id=603",
+            "/scrape/search?query=matrix",
+        ] {
+            let response = app
+                .clone()
+                .oneshot(Request::get(uri).body(Body::empty()).unwrap())
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS, "{uri}");
+            assert_eq!(response.headers()[RETRY_AFTER], "5", "{uri}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_search_rate_limit_reaches_client() {
+        let provider = FakeProvider::new("fake")
+            .with_movie("603", "The Matrix", 1999)
+            .with_rate_limit(Duration::from_secs(5));
+        let mut manager = ScraperManager::new();
+        manager.add_provider(Box::new(provider));
+
+        let (status, body) =
+            get_json(app(Some(manager)).await, "/scrape/search?query=matrix").await;
+        assert_eq!(status, StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(body["error_code"], "RATE_LIMITED");
+    }
+
+    #[tokio::test]
+    async fn test_tmdb_outage_reaches_client_as_bad_gateway() {
+        use wiremock::{Mock, MockServer, ResponseTemplate, matchers::method};
+
+        use crate::scraper::provider::{ProviderConfig, RetryConfig, tmdb::TmdbProvider};
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(500))
+            .mount(&server)
+            .await;
+        let config = ProviderConfig::new(server.uri()).with_retry(RetryConfig {
+            max_attempts: 1,
+            ..RetryConfig::default()
+        });
+        let mut manager = ScraperManager::new();
+        manager.add_provider(Box::new(TmdbProvider::with_config(
+            "key",
+            config,
+            Arc::new(crate::scraper::ScraperCache::new()),
+        )));
+
+        let (status, body) =
+            get_json(app(Some(manager)).await, "/scrape/search?query=matrix").await;
+        assert_eq!(status, StatusCode::BAD_GATEWAY);
+        assert_eq!(body["error_code"], "PROVIDER_UNAVAILABLE");
+    }
+
+    #[tokio::test]
+    async fn test_upstream_rate_limit_reaches_client_with_retry_after() {
+        use wiremock::{Mock, MockServer, ResponseTemplate, matchers::method};
+
+        use crate::scraper::provider::{ProviderConfig, RetryConfig, tmdb::TmdbProvider};
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(429).insert_header("Retry-After", "7"))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let config = ProviderConfig::new(server.uri()).with_retry(RetryConfig {
+            max_attempts: 1,
+            ..RetryConfig::default()
+        });
+        let mut manager = ScraperManager::new();
+        manager.add_provider(Box::new(TmdbProvider::with_config(
+            "key",
+            config,
+            Arc::new(crate::scraper::ScraperCache::new()),
+        )));
+
+        let response = app(Some(manager))
+            .await
+            .oneshot(
+                Request::get("/scrape/search?query=matrix&media_type=movie")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(response.headers()[RETRY_AFTER], "7");
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["error_code"], "RATE_LIMITED");
+    }
+
+    #[tokio::test]
+    async fn test_providers_lists_capabilities() {
+        let mut manager = ScraperManager::new();
+        manager
+            .add_provider(Box::new(FakeProvider::new("fake").with_capabilities(
+                ProviderCapabilities::new([scraper::MediaType::Anime]),
+            )));
+
+        let (status, body) = get_json(app(Some(manager)).await, "/scrape/providers").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(
+            body["data"],
+            serde_json::json!([
+                {
+                    "name": "fake",
+                    "media_types": ["anime"],
+                    "episode_details": false,
+                    "cjk_titles": false,
+                }
+            ])
+        );
+    }
+}
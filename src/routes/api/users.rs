@@ -0,0 +1,155 @@
+use axum::{Json, Router, extract::State, routing::get};
+
+use crate::{
+    ApiResponse, ApiResult, Ctx,
+    app::auth::AuthUser,
+    entities::{SetUserPreferences, UserPreferences},
+    error::{ApiError, AyiahError},
+};
+
+/// Get the current user's preferences, or the defaults if none are saved
+async fn get_preferences(State(ctx): State<Ctx>, user: AuthUser) -> ApiResult<UserPreferences> {
+    let preferences = UserPreferences::get(&ctx.db, user.id)
+        .await
+        .map_err(|e| AyiahError::DatabaseError(format!("Failed to fetch preferences: {e}")))?
+        // A valid token can still name a user that has since been deleted
+        .ok_or_else(|| {
+            AyiahError::ApiError(ApiError::NotFound(format!(
+                "User with ID {} not found",
+                user.id
+            )))
+        })?;
+
+    Ok(ApiResponse {
+        code: 200,
+        message: "Preferences retrieved successfully".to_string(),
+        data: Some(preferences),
+    })
+}
+
+/// Replace the current user's preferences
+///
+/// Omitted fields go back to their defaults.
+async fn set_preferences(
+    State(ctx): State<Ctx>,
+    user: AuthUser,
+    Json(request): Json<SetUserPreferences>,
+) -> ApiResult<UserPreferences> {
+    let preferences = UserPreferences::set(&ctx.db, user.id, request)
+        .await
+        .map_err(|e| AyiahError::DatabaseError(format!("Failed to save preferences: {e}")))?
+        .ok_or_else(|| {
+            AyiahError::ApiError(ApiError::NotFound(format!(
+                "User with ID {} not found",
+                user.id
+            )))
+        })?;
+
+    Ok(ApiResponse {
+        code: 200,
+        message: "Preferences saved successfully".to_string(),
+        data: Some(preferences),
+    })
+}
+
+/// Mount user routes
+pub fn mount() -> Router<Ctx> {
+    Router::new().route(
+        "/users/me/preferences",
+        get(get_preferences).put(set_preferences),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use axum::{
+        body::{Body, to_bytes},
+        http::{
+            Request, StatusCode,
+            header::{AUTHORIZATION, CONTENT_TYPE},
+        },
+    };
+    use tower::ServiceExt;
+
+    use super::*;
+    use crate::Context;
+
+    #[tokio::test]
+    async fn test_preferences_are_scoped_to_the_caller() {
+        let db = crate::db::test_pool().await;
+        let ctx = Arc::new(Context::for_tests(db.clone()));
+        let mut tokens = Vec::new();
+        for name in ["alice", "bob"] {
+            let user_id: i64 = sqlx::query_scalar(
+                "INSERT INTO users (username, email, password_hash) VALUES (?, ?, 'x') RETURNING id",
+            )
+            .bind(name)
+            .bind(format!("{name}@example.com"))
+            .fetch_one(&db)
+            .await
+            .unwrap();
+            tokens.push(
+                crate::app::auth::issue_token(
+                    &ctx.config.read().auth.jwt_secret,
+                    &user_id.to_string(),
+                    chrono::Duration::hours(1),
+                )
+                .unwrap(),
+            );
+        }
+        let secret = ctx.config.read().auth.jwt_secret.clone();
+        let app = mount().with_state(ctx);
+
+        let send = |request: axum::http::request::Builder, token: Option<&str>, body: &str| {
+            let request = match token {
+                Some(token) => request.header(AUTHORIZATION, format!("Bearer {token}")),
+                None => request,
+            };
+            let request = request
+                .header(CONTENT_TYPE, "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap();
+            let app = app.clone();
+            async move {
+                let response = app.oneshot(request).await.unwrap();
+                let status = response.status();
+                let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+                let body: serde_json::Value = serde_json::from_slice(&body).unwrap_or_default();
+                (status, body["data"].clone())
+            }
+        };
+        let uri = "/users/me/preferences";
+
+        let (status, _) = send(Request::get(uri), None, "").await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+        let (status, data) = send(
+            Request::put(uri),
+            Some(&tokens[0]),
+            r#"{"language":"ja-JP","items_per_page":50}"#,
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(data["language"], "ja-JP");
+
+        let (status, data) = send(Request::get(uri), Some(&tokens[0]), "").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(data["language"], "ja-JP");
+        assert_eq!(data["items_per_page"], 50);
+
+        // Another user still sees the defaults
+        let (status, data) = send(Request::get(uri), Some(&tokens[1]), "").await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(data["language"].is_null());
+
+        // A valid token for a user that does not exist
+        let token =
+            crate::app::auth::issue_token(&secret, "999", chrono::Duration::hours(1)).unwrap();
+        let (status, _) = send(Request::get(uri), Some(&token), "").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        let (status, _) = send(Request::put(uri), Some(&token), "{}").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+}
@@ -0,0 +1,202 @@
+use std::time::Duration;
+
+use axum::{
+    Router,
+    extract::{
+        Query, State, WebSocketUpgrade,
+        ws::{CloseFrame, Message, WebSocket, close_code},
+    },
+    http::{HeaderMap, header::SEC_WEBSOCKET_PROTOCOL},
+    response::Response,
+    routing::get,
+};
+use futures::{SinkExt, StreamExt};
+use serde::Deserialize;
+use tokio::sync::broadcast::{self, error::RecvError};
+use tracing::warn;
+
+use crate::{
+    Ctx,
+    app::auth,
+    error::{AuthError, AyiahError},
+    services::job_queue::JobEvent,
+};
+
+/// Subprotocol prefix carrying the access token, as in `bearer.<token>`;
+/// browsers can't set headers on WebSocket requests
+const TOKEN_PROTOCOL_PREFIX: &str = "bearer.";
+
+/// How long a single frame may take to reach the client before it's dropped
+const SEND_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Deserialize)]
+struct EventsQuery {
+    token: Option<String>,
+}
+
+/// Stream job and scan events as JSON text frames
+///
+/// The access token goes in the `token` query parameter or a `bearer.<token>`
+/// subprotocol. Clients that fall behind are disconnected.
+async fn events_socket(
+    State(ctx): State<Ctx>,
+    Query(query): Query<EventsQuery>,
+    headers: HeaderMap,
+    ws: WebSocketUpgrade,
+) -> Result<Response, AyiahError> {
+    let protocol = headers
+        .get_all(SEC_WEBSOCKET_PROTOCOL)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(str::trim)
+        .find(|protocol| protocol.starts_with(TOKEN_PROTOCOL_PREFIX))
+        .map(str::to_string);
+    let token = query
+        .token
+        .as_deref()
+        .or_else(|| protocol.as_deref()?.strip_prefix(TOKEN_PROTOCOL_PREFIX))
+        .ok_or(AuthError::MissingAuth)?;
+    auth::verify_token(&ctx.config.read().auth.jwt_secret, token)?;
+
+    // Subscribe before upgrading so nothing is missed once the client is in
+    let events = ctx.jobs.subscribe();
+    let ws = match protocol {
+        Some(protocol) => ws.protocols([protocol]),
+        None => ws,
+    };
+
+    Ok(ws.on_upgrade(move |socket| forward_events(socket, events)))
+}
+
+async fn forward_events(socket: WebSocket, mut events: broadcast::Receiver<JobEvent>) {
+    let (mut sender, mut receiver) = socket.split();
+
+    loop {
+        tokio::select! {
+            event = events.recv() => {
+                let event = match event {
+                    Ok(event) => event,
+                    Err(RecvError::Lagged(missed)) => {
+                        warn!("Disconnecting WebSocket client {missed} events behind");
+                        let close = Message::Close(Some(CloseFrame {
+                            code: close_code::AGAIN,
+                            reason: "Too slow to keep up with events".into(),
+                        }));
+                        let _ = tokio::time::timeout(SEND_TIMEOUT, sender.send(close)).await;
+                        break;
+                    }
+                    Err(RecvError::Closed) => break,
+                };
+                let Ok(text) = serde_json::to_string(&event) else {
+                    continue;
+                };
+                match tokio::time::timeout(SEND_TIMEOUT, sender.send(Message::text(text))).await {
+                    Ok(Ok(())) => {}
+                    _ => break,
+                }
+            }
+            // Pings are answered by axum; anything else from the client is ignored
+            message = receiver.next() => match message {
+                Some(Ok(Message::Close(_)) | Err(_)) | None => break,
+                Some(Ok(_)) => {}
+            }
+        }
+    }
+}
+
+/// Mount WebSocket routes
+pub fn mount() -> Router<Ctx> {
+    Router::new().route("/ws", get(events_socket))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use chrono::Duration as TokenTtl;
+    use tokio_tungstenite::{
+        connect_async,
+        tungstenite::{self, client::IntoClientRequest},
+    };
+
+    use super::*;
+    use crate::{
+        Context,
+        services::{
+            ScanEvent, ScanProgress,
+            job_queue::{JobInfo, JobStatus},
+        },
+    };
+
+    #[tokio::test]
+    async fn test_client_receives_job_events() {
+        let ctx = Arc::new(Context::for_tests(crate::db::test_pool().await));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(axum::serve(listener, mount().with_state(ctx.clone())).into_future());
+
+        let url = format!("ws://{addr}/ws");
+        let unauthenticated = connect_async(url.as_str()).await;
+        assert!(matches!(
+            unauthenticated,
+            Err(tungstenite::Error::Http(response)) if response.status() == 401
+        ));
+
+        let token =
+            auth::issue_token(&ctx.config.read().auth.jwt_secret, "1", TokenTtl::hours(1)).unwrap();
+        let mut request = url.as_str().into_client_request().unwrap();
+        request.headers_mut().insert(
+            SEC_WEBSOCKET_PROTOCOL,
+            format!("{TOKEN_PROTOCOL_PREFIX}{token}").parse().unwrap(),
+        );
+        let (mut client, _) = connect_async(request).await.unwrap();
+
+        let id = ctx.jobs.enqueue("scan", |job| async move {
+            job.send_scan_event(ScanEvent::Progress(ScanProgress {
+                processed: 1,
+                total: 2,
+                current: "/movies/Alien.mkv".to_string(),
+            }));
+            job.set_progress(2, 2);
+            Ok(())
+        });
+
+        let mut statuses = Vec::new();
+        let mut scanned = Vec::new();
+        while statuses.last() != Some(&JobStatus::Done) {
+            let message = tokio::time::timeout(Duration::from_secs(5), client.next())
+                .await
+                .expect("no event received")
+                .unwrap()
+                .unwrap();
+            match serde_json::from_str(message.to_text().unwrap()).unwrap() {
+                JobEvent::Job(JobInfo {
+                    id: job_id, status, ..
+                }) => {
+                    assert_eq!(job_id, id);
+                    statuses.push(status);
+                }
+                JobEvent::Scan {
+                    job_id,
+                    event: ScanEvent::Progress(progress),
+                } => {
+                    assert_eq!(job_id, id);
+                    scanned.push(progress.current);
+                }
+                other => panic!("unexpected event {other:?}"),
+            }
+        }
+
+        assert_eq!(
+            statuses,
+            [
+                JobStatus::Queued,
+                JobStatus::Running,
+                JobStatus::Running,
+                JobStatus::Done
+            ]
+        );
+        assert_eq!(scanned, ["/movies/Alien.mkv"]);
+    }
+}
@@ -1,9 +1,9 @@
 use axum::Router;
 
-use crate::Ctx;
+use crate::{Ctx, app::config::ServerConfig};
 
 pub mod api;
 
-pub fn mount() -> Router<Ctx> {
-    Router::new().nest("/api", api::mount())
+pub fn mount(config: &ServerConfig) -> Router<Ctx> {
+    Router::new().nest("/api", api::mount(config))
 }
@@ -1,13 +1,50 @@
 use moka::future::Cache;
 use serde::{Deserialize, Serialize};
-use std::time::Duration;
+use sqlx::SqlitePool;
+use std::{
+    path::{Path, PathBuf},
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::Duration,
+};
+
+/// Where scraper cache entries are kept
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CacheStrategy {
+    /// In memory only; lost on restart
+    #[default]
+    Memory,
+    /// In memory, backed by a SQLite file that survives restarts
+    Persistent,
+}
+
+/// Get the persistent cache file path, next to the main database
+#[must_use]
+pub fn default_cache_path() -> PathBuf {
+    std::env::var("AYIAH_DATA_DIR").map_or_else(
+        |_| {
+            dirs::data_dir()
+                .unwrap_or_else(|| PathBuf::from("."))
+                .join("ayiah")
+                .join("scraper_cache.db")
+        },
+        |data_dir| PathBuf::from(data_dir).join("scraper_cache.db"),
+    )
+}
 
 /// Scraper cache key
+///
+/// `year` and `language` keep lookups that only differ by them apart.
 #[derive(Debug, Clone, Hash, PartialEq, Eq)]
 pub struct CacheKey {
     pub provider: String,
     pub media_type: String,
     pub query: String,
+    pub year: Option<i32>,
+    pub language: Option<String>,
 }
 
 impl CacheKey {
@@ -20,14 +57,172 @@ impl CacheKey {
             provider: provider.into(),
             media_type: media_type.into(),
             query: query.into(),
+            year: None,
+            language: None,
         }
     }
+
+    /// Narrow the key to a release year
+    #[must_use]
+    pub const fn with_year(mut self, year: Option<i32>) -> Self {
+        self.year = year;
+        self
+    }
+
+    /// Narrow the key to a result language
+    #[must_use]
+    pub fn with_language(mut self, language: Option<impl Into<String>>) -> Self {
+        self.language = language.map(Into::into);
+        self
+    }
+}
+
+/// Snapshot of cache effectiveness
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    /// Entries held in memory (approximate)
+    pub entry_count: u64,
+    /// Share of lookups served from the cache, `0.0` before any lookup
+    pub hit_rate: f64,
+}
+
+/// Disk tier of the scraper cache
+#[derive(Clone)]
+struct DiskCache {
+    pool: SqlitePool,
+    ttl: Duration,
+}
+
+impl DiskCache {
+    async fn open(path: &Path, ttl: Duration) -> Result<Self, sqlx::Error> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let pool = SqlitePool::connect_with(
+            sqlx::sqlite::SqliteConnectOptions::new()
+                .filename(path)
+                .create_if_missing(true)
+                .journal_mode(sqlx::sqlite::SqliteJournalMode::Wal)
+                .busy_timeout(Duration::from_secs(30)),
+        )
+        .await?;
+
+        sqlx::migrate!("./migrations/scraper_cache")
+            .run(&pool)
+            .await?;
+
+        sqlx::query("DELETE FROM scraper_cache WHERE expires_at <= ?")
+            .bind(chrono::Utc::now().timestamp())
+            .execute(&pool)
+            .await?;
+
+        Ok(Self { pool, ttl })
+    }
+
+    async fn get(&self, key: &CacheKey) -> Result<Option<Vec<u8>>, sqlx::Error> {
+        sqlx::query_scalar(
+            r#"
+            SELECT value FROM scraper_cache
+            WHERE provider = ? AND media_type = ? AND query = ? AND year IS ? AND language IS ?
+                AND expires_at > ?
+            "#,
+        )
+        .bind(&key.provider)
+        .bind(&key.media_type)
+        .bind(&key.query)
+        .bind(key.year)
+        .bind(key.language.as_deref())
+        .bind(chrono::Utc::now().timestamp())
+        .fetch_optional(&self.pool)
+        .await
+    }
+
+    async fn set(&self, key: &CacheKey, value: &[u8]) -> Result<(), sqlx::Error> {
+        let ttl = i64::try_from(self.ttl.as_secs()).unwrap_or(i64::MAX);
+        // Unset years and languages are NULL, which a unique constraint can't
+        // match, so replace the old entry by hand
+        let mut tx = self.pool.begin().await?;
+        Self::remove_in(&mut *tx, key).await?;
+        sqlx::query(
+            r#"
+            INSERT INTO scraper_cache
+                (provider, media_type, query, year, language, value, expires_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(&key.provider)
+        .bind(&key.media_type)
+        .bind(&key.query)
+        .bind(key.year)
+        .bind(key.language.as_deref())
+        .bind(value)
+        .bind(chrono::Utc::now().timestamp().saturating_add(ttl))
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await
+    }
+
+    async fn remove(&self, key: &CacheKey) -> Result<(), sqlx::Error> {
+        Self::remove_in(&self.pool, key).await
+    }
+
+    async fn remove_in(
+        executor: impl sqlx::SqliteExecutor<'_>,
+        key: &CacheKey,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            DELETE FROM scraper_cache
+            WHERE provider = ? AND media_type = ? AND query = ? AND year IS ? AND language IS ?
+            "#,
+        )
+        .bind(&key.provider)
+        .bind(&key.media_type)
+        .bind(&key.query)
+        .bind(key.year)
+        .bind(key.language.as_deref())
+        .execute(executor)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn remove_variants(&self, key: &CacheKey) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "DELETE FROM scraper_cache WHERE provider = ? AND media_type = ? AND query = ?",
+        )
+        .bind(&key.provider)
+        .bind(&key.media_type)
+        .bind(&key.query)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn clear(&self) -> Result<(), sqlx::Error> {
+        sqlx::query("DELETE FROM scraper_cache")
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
 }
 
 /// Scraper cache
+///
+/// Memory-only by default. A persistent cache also writes every entry to disk
+/// and loads entries back into memory on a miss.
 #[derive(Clone)]
 pub struct ScraperCache {
     cache: Cache<CacheKey, Vec<u8>>,
+    disk: Option<DiskCache>,
+    hits: Arc<AtomicU64>,
+    misses: Arc<AtomicU64>,
 }
 
 impl ScraperCache {
@@ -43,9 +238,52 @@ impl ScraperCache {
         let cache = Cache::builder()
             .time_to_live(Duration::from_secs(ttl_seconds))
             .max_capacity(max_capacity)
+            .support_invalidation_closures()
             .build();
 
-        Self { cache }
+        Self {
+            cache,
+            disk: None,
+            hits: Arc::new(AtomicU64::new(0)),
+            misses: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// Create a two-tier cache backed by the SQLite file at `path`
+    pub async fn persistent(
+        path: &Path,
+        ttl_seconds: u64,
+        max_capacity: u64,
+    ) -> Result<Self, sqlx::Error> {
+        let disk = DiskCache::open(path, Duration::from_secs(ttl_seconds)).await?;
+
+        Ok(Self {
+            disk: Some(disk),
+            ..Self::with_config(ttl_seconds, max_capacity)
+        })
+    }
+
+    /// Create a cache using the given strategy
+    ///
+    /// Falls back to memory-only if the disk tier cannot be opened.
+    pub async fn from_strategy(strategy: CacheStrategy, ttl_seconds: u64) -> Self {
+        const MAX_CAPACITY: u64 = 10000;
+
+        match strategy {
+            CacheStrategy::Memory => Self::with_config(ttl_seconds, MAX_CAPACITY),
+            CacheStrategy::Persistent => {
+                let path = default_cache_path();
+                Self::persistent(&path, ttl_seconds, MAX_CAPACITY)
+                    .await
+                    .unwrap_or_else(|e| {
+                        tracing::warn!(
+                            "Failed to open scraper cache at {}, using memory only: {e}",
+                            path.display()
+                        );
+                        Self::with_config(ttl_seconds, MAX_CAPACITY)
+                    })
+            }
+        }
     }
 
     /// Store data to cache
@@ -57,29 +295,105 @@ impl ScraperCache {
         let serialized = serde_json::to_vec(value)
             .map_err(|e| format!("Failed to serialize cache entry: {e}"))?;
 
+        if let Some(disk) = &self.disk
+            && let Err(e) = disk.set(&key, &serialized).await
+        {
+            tracing::debug!("Failed to persist cache entry: {e}");
+        }
+
         self.cache.insert(key, serialized).await;
         Ok(())
     }
 
     /// Get data from cache
     pub async fn get<T: for<'de> Deserialize<'de>>(&self, key: &CacheKey) -> Option<T> {
-        let data = self.cache.get(key).await?;
-        serde_json::from_slice(&data).ok()
+        let data = self.lookup(key).await;
+        let counter = if data.is_some() {
+            &self.hits
+        } else {
+            &self.misses
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+
+        serde_json::from_slice(&data?).ok()
+    }
+
+    /// Find raw entry data, loading it from disk into memory on a memory miss
+    async fn lookup(&self, key: &CacheKey) -> Option<Vec<u8>> {
+        if let Some(data) = self.cache.get(key).await {
+            return Some(data);
+        }
+
+        let data = self
+            .disk
+            .as_ref()?
+            .get(key)
+            .await
+            .inspect_err(|e| tracing::debug!("Failed to read cache entry: {e}"))
+            .ok()??;
+        self.cache.insert(key.clone(), data.clone()).await;
+        Some(data)
+    }
+
+    /// Get hit/miss counters and the current entry count
+    #[must_use]
+    pub fn stats(&self) -> CacheStats {
+        let hits = self.hits.load(Ordering::Relaxed);
+        let misses = self.misses.load(Ordering::Relaxed);
+        let lookups = hits + misses;
+
+        CacheStats {
+            hits,
+            misses,
+            entry_count: self.cache.entry_count(),
+            hit_rate: if lookups == 0 {
+                0.0
+            } else {
+                hits as f64 / lookups as f64
+            },
+        }
     }
 
     /// Invalidate a cache entry
     pub async fn invalidate(&self, key: &CacheKey) {
+        if let Some(disk) = &self.disk
+            && let Err(e) = disk.remove(key).await
+        {
+            tracing::debug!("Failed to remove cache entry: {e}");
+        }
         self.cache.invalidate(key).await;
     }
 
+    /// Invalidate every entry for `key`'s provider, media type and query,
+    /// whatever its year and language
+    pub async fn invalidate_variants(&self, key: &CacheKey) {
+        if let Some(disk) = &self.disk
+            && let Err(e) = disk.remove_variants(key).await
+        {
+            tracing::debug!("Failed to remove cache entries: {e}");
+        }
+
+        let key = key.clone();
+        if let Err(e) = self.cache.invalidate_entries_if(move |k, _| {
+            k.provider == key.provider && k.media_type == key.media_type && k.query == key.query
+        }) {
+            tracing::debug!("Failed to invalidate cache entries: {e}");
+        }
+    }
+
     /// Clear all cache entries
     pub async fn clear(&self) {
+        if let Some(disk) = &self.disk
+            && let Err(e) = disk.clear().await
+        {
+            tracing::debug!("Failed to clear persistent cache: {e}");
+        }
         self.cache.invalidate_all();
         // Wait for all invalidation operations to complete
         self.cache.run_pending_tasks().await;
     }
 
-    /// Get cache size (approximate)
+    /// Get the number of entries held in memory (approximate)
     #[must_use]
     pub fn len(&self) -> u64 {
         self.cache.entry_count()
@@ -150,6 +464,31 @@ mod tests {
         assert!(cache.get::<Vec<String>>(&key).await.is_none());
     }
 
+    #[tokio::test]
+    async fn test_invalidate_variants_ignores_year_and_language() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cache.db");
+        let key = CacheKey::new("tmdb", "details:movie", "603");
+        let localized = key.clone().with_language(Some("zh-CN"));
+        let other = CacheKey::new("tmdb", "details:movie", "604");
+
+        let cache = ScraperCache::persistent(&path, 3600, 1000).await.unwrap();
+        for k in [&key, &localized, &other] {
+            cache.set(k.clone(), &"cached").await.unwrap();
+        }
+        cache.invalidate_variants(&key).await;
+
+        // Gone from memory and from disk after a restart
+        for cache in [
+            cache,
+            ScraperCache::persistent(&path, 3600, 1000).await.unwrap(),
+        ] {
+            assert!(cache.get::<String>(&key).await.is_none());
+            assert!(cache.get::<String>(&localized).await.is_none());
+            assert!(cache.get::<String>(&other).await.is_some());
+        }
+    }
+
     #[tokio::test]
     async fn test_cache_clear() {
         let cache = ScraperCache::new();
@@ -180,4 +519,171 @@ mod tests {
         assert!(cache.get::<Vec<String>>(&key2).await.is_none());
         assert_eq!(cache.len(), 0);
     }
+
+    #[tokio::test]
+    async fn test_persistent_cache_survives_restart() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cache.db");
+        let key = CacheKey::new("tmdb", "movie", "test");
+        let value = vec!["movie1".to_string()];
+
+        let cache = ScraperCache::persistent(&path, 3600, 1000).await.unwrap();
+        cache.set(key.clone(), &value).await.unwrap();
+        drop(cache);
+
+        let cache = ScraperCache::persistent(&path, 3600, 1000).await.unwrap();
+        assert!(cache.is_empty());
+        assert_eq!(cache.get::<Vec<String>>(&key).await, Some(value));
+        cache.run_pending_tasks().await;
+        assert_eq!(cache.len(), 1);
+
+        cache.clear().await;
+        drop(cache);
+        let cache = ScraperCache::persistent(&path, 3600, 1000).await.unwrap();
+        assert!(cache.get::<Vec<String>>(&key).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_year_and_language_keep_entries_apart() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cache.db");
+        let dune = |year| CacheKey::new("tmdb", "search:movie", "dune").with_year(Some(year));
+        let mut cache = ScraperCache::persistent(&path, 3600, 1000).await.unwrap();
+        cache.set(dune(1984), &"Dune (1984)").await.unwrap();
+        cache.set(dune(2021), &"Dune (2021)").await.unwrap();
+        let localized = dune(2021).with_language(Some("zh-CN"));
+        cache.set(localized.clone(), &"沙丘").await.unwrap();
+
+        // From memory first, then from disk after a restart
+        for _ in 0..2 {
+            assert_eq!(
+                cache.get::<String>(&dune(1984)).await.as_deref(),
+                Some("Dune (1984)")
+            );
+            assert_eq!(
+                cache.get::<String>(&dune(2021)).await.as_deref(),
+                Some("Dune (2021)")
+            );
+            assert_eq!(
+                cache.get::<String>(&localized).await.as_deref(),
+                Some("沙丘")
+            );
+            assert!(
+                cache
+                    .get::<String>(&CacheKey::new("tmdb", "search:movie", "dune"))
+                    .await
+                    .is_none()
+            );
+
+            drop(cache);
+            cache = ScraperCache::persistent(&path, 3600, 1000).await.unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_unset_year_and_language_are_not_zero_or_empty() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cache.db");
+        let key = CacheKey::new("tmdb", "search:movie", "dune");
+        let mut cache = ScraperCache::persistent(&path, 3600, 1000).await.unwrap();
+        cache.set(key.clone(), &"unset").await.unwrap();
+        cache.set(key.clone(), &"unset again").await.unwrap();
+        cache
+            .set(key.clone().with_year(Some(0)), &"year 0")
+            .await
+            .unwrap();
+        cache
+            .set(key.clone().with_language(Some("")), &"empty language")
+            .await
+            .unwrap();
+
+        drop(cache);
+        cache = ScraperCache::persistent(&path, 3600, 1000).await.unwrap();
+        assert_eq!(
+            cache.get::<String>(&key).await.as_deref(),
+            Some("unset again")
+        );
+        assert_eq!(
+            cache
+                .get::<String>(&key.clone().with_year(Some(0)))
+                .await
+                .as_deref(),
+            Some("year 0")
+        );
+        assert_eq!(
+            cache
+                .get::<String>(&key.clone().with_language(Some("")))
+                .await
+                .as_deref(),
+            Some("empty language")
+        );
+
+        // Rewriting an entry replaces it rather than adding another row
+        let disk = DiskCache::open(&path, Duration::from_secs(3600))
+            .await
+            .unwrap();
+        let rows: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM scraper_cache")
+            .fetch_one(&disk.pool)
+            .await
+            .unwrap();
+        assert_eq!(rows, 3);
+    }
+
+    #[tokio::test]
+    async fn test_entries_without_year_or_language_survive_migration() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cache.db");
+        let pool = SqlitePool::connect_with(
+            sqlx::sqlite::SqliteConnectOptions::new()
+                .filename(&path)
+                .create_if_missing(true),
+        )
+        .await
+        .unwrap();
+        sqlx::query(include_str!(
+            "../../migrations/scraper_cache/20251101000000_create_scraper_cache_table.sql"
+        ))
+        .execute(&pool)
+        .await
+        .unwrap();
+        sqlx::query("INSERT INTO scraper_cache VALUES ('tmdb', 'movie', 'dune', ?, ?)")
+            .bind(serde_json::to_vec("Dune").unwrap())
+            .bind(chrono::Utc::now().timestamp() + 3600)
+            .execute(&pool)
+            .await
+            .unwrap();
+        pool.close().await;
+
+        let cache = ScraperCache::persistent(&path, 3600, 1000).await.unwrap();
+        let key = CacheKey::new("tmdb", "movie", "dune");
+        assert_eq!(cache.get::<String>(&key).await.as_deref(), Some("Dune"));
+        assert!(
+            cache
+                .get::<String>(&key.with_year(Some(2021)))
+                .await
+                .is_none()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_stats_count_hits_and_misses() {
+        let cache = ScraperCache::new();
+        let key = CacheKey::new("tmdb", "movie", "test");
+        cache.set(key.clone(), &"movie1").await.unwrap();
+
+        assert!(cache.get::<String>(&key).await.is_some());
+        assert!(
+            cache
+                .get::<String>(&CacheKey::new("tmdb", "movie", "other"))
+                .await
+                .is_none()
+        );
+        cache.run_pending_tasks().await;
+
+        let stats = cache.stats();
+        assert_eq!(stats.hits, 1);
+        assert_eq!(stats.misses, 1);
+        assert_eq!(stats.entry_count, 1);
+        assert!((stats.hit_rate - 0.5).abs() < f64::EPSILON);
+    }
 }
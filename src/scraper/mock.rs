@@ -0,0 +1,250 @@
+//! In-memory provider used by tests
+
+use async_trait::async_trait;
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+    time::Duration,
+};
+
+use super::{
+    AnimeMetadata, EpisodeMetadata, ExternalIds, MediaDetails, MediaSearchResult, MetadataProvider,
+    MovieMetadata, MovieSearchResult, ProviderCapabilities, Result, ScraperError, SearchOptions,
+};
+
+/// Fake provider serving a fixed set of titles
+pub struct FakeProvider {
+    name: String,
+    entries: Vec<(MediaSearchResult, MediaDetails)>,
+    capabilities: ProviderCapabilities,
+    details_calls: Arc<AtomicUsize>,
+    search_calls: Arc<AtomicUsize>,
+    rate_limit: Option<Duration>,
+}
+
+impl FakeProvider {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            entries: Vec::new(),
+            capabilities: ProviderCapabilities::default(),
+            details_calls: Arc::new(AtomicUsize::new(0)),
+            search_calls: Arc::new(AtomicUsize::new(0)),
+            rate_limit: None,
+        }
+    }
+
+    /// Shared counter of `get_details` calls, usable after the provider is boxed
+    pub fn details_calls(&self) -> Arc<AtomicUsize> {
+        self.details_calls.clone()
+    }
+
+    /// Shared counter of `search` calls, usable after the provider is boxed
+    pub fn search_calls(&self) -> Arc<AtomicUsize> {
+        self.search_calls.clone()
+    }
+
+    /// Restrict what the provider claims to support
+    pub fn with_capabilities(mut self, capabilities: ProviderCapabilities) -> Self {
+        self.capabilities = capabilities;
+        self
+    }
+
+    /// Fail every search and details lookup as rate limited for `wait`
+    pub fn with_rate_limit(mut self, wait: Duration) -> Self {
+        self.rate_limit = Some(wait);
+        self
+    }
+
+    /// Add a movie served by both search and details
+    pub fn with_movie(self, id: &str, title: &str, year: i32) -> Self {
+        let details = movie_details(&self.name, id, title, year);
+        self.with_details(MediaDetails::Movie(details))
+    }
+
+    /// Add a movie whose search result is flagged as adult content
+    pub fn with_adult_movie(mut self, id: &str, title: &str, year: i32) -> Self {
+        let details = MediaDetails::Movie(movie_details(&self.name, id, title, year));
+        let mut result = search_result_for(&details);
+        if let MediaSearchResult::Movie(movie) = &mut result {
+            movie.adult = true;
+        }
+        self.entries.push((result, details));
+        self
+    }
+
+    /// Add an arbitrary details entry; the search result is derived from it
+    pub fn with_details(mut self, details: MediaDetails) -> Self {
+        let result = search_result_for(&details);
+        self.entries.push((result, details));
+        self
+    }
+}
+
+#[async_trait]
+impl MetadataProvider for FakeProvider {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn capabilities(&self) -> ProviderCapabilities {
+        self.capabilities.clone()
+    }
+
+    async fn search(&self, options: &SearchOptions) -> Result<Vec<MediaSearchResult>> {
+        self.search_calls.fetch_add(1, Ordering::SeqCst);
+        if let Some(wait) = self.rate_limit {
+            return Err(ScraperError::RateLimit(wait));
+        }
+        let query = options.query.to_lowercase();
+        let results: Vec<_> = self
+            .entries
+            .iter()
+            .filter(|(result, _)| result.title().to_lowercase().contains(&query))
+            .map(|(result, _)| result.clone())
+            .collect();
+
+        if results.is_empty() {
+            Err(ScraperError::NotFound(format!(
+                "No results found for: {query}"
+            )))
+        } else {
+            Ok(results)
+        }
+    }
+
+    async fn get_details(&self, result: &MediaSearchResult) -> Result<MediaDetails> {
+        self.details_calls.fetch_add(1, Ordering::SeqCst);
+        if let Some(wait) = self.rate_limit {
+            return Err(ScraperError::RateLimit(wait));
+        }
+        self.entries
+            .iter()
+            .find(|(_, details)| {
+                details.id() == result.id() && details.media_type() == result.media_type()
+            })
+            .map(|(_, details)| details.clone())
+            .ok_or_else(|| ScraperError::NotFound(format!("Unknown id: {}", result.id())))
+    }
+
+    async fn get_episode_details(
+        &self,
+        series_id: &str,
+        season: i32,
+        episode: i32,
+    ) -> Result<EpisodeMetadata> {
+        Ok(EpisodeMetadata {
+            id: format!("{series_id}-{season}-{episode}"),
+            name: format!("Episode {episode}"),
+            season_number: season,
+            episode_number: episode,
+            air_date: None,
+            overview: None,
+            still_path: None,
+            runtime: None,
+            vote_average: None,
+            provider: self.name.clone(),
+        })
+    }
+}
+
+/// Build movie details with only the identifying fields filled in
+pub fn movie_details(provider: &str, id: &str, title: &str, year: i32) -> MovieMetadata {
+    MovieMetadata {
+        id: id.to_string(),
+        title: title.to_string(),
+        original_title: None,
+        release_date: Some(format!("{year}-01-01")),
+        runtime: None,
+        overview: None,
+        poster_path: None,
+        backdrop_path: None,
+        vote_average: None,
+        vote_count: None,
+        genres: Vec::new(),
+        production_companies: Vec::new(),
+        production_countries: Vec::new(),
+        original_language: None,
+        cast: Vec::new(),
+        director: None,
+        writers: Vec::new(),
+        collection: None,
+        provider: provider.to_string(),
+        external_ids: ExternalIds::default(),
+    }
+}
+
+/// Build anime details with only the identifying fields filled in
+pub fn anime_details(provider: &str, id: &str, title: &str, year: i32) -> AnimeMetadata {
+    AnimeMetadata {
+        id: id.to_string(),
+        title: title.to_string(),
+        title_english: None,
+        title_japanese: None,
+        start_date: Some(format!("{year}-04-03")),
+        end_date: None,
+        overview: None,
+        poster_path: None,
+        backdrop_path: None,
+        score: None,
+        genres: Vec::new(),
+        episodes: Some(26),
+        status: None,
+        format: Some("TV".to_string()),
+        studios: Vec::new(),
+        staff: Vec::new(),
+        provider: provider.to_string(),
+        external_ids: ExternalIds {
+            anilist_id: Some(id.to_string()),
+            ..Default::default()
+        },
+    }
+}
+
+/// Derive the search result a provider would have returned for some details
+pub fn search_result_for(details: &MediaDetails) -> MediaSearchResult {
+    match details {
+        MediaDetails::Movie(m) => MediaSearchResult::Movie(MovieSearchResult {
+            id: m.id.clone(),
+            title: m.title.clone(),
+            original_title: m.original_title.clone(),
+            year: details.year(),
+            poster_path: m.poster_path.clone(),
+            overview: m.overview.clone(),
+            vote_average: m.vote_average,
+            adult: false,
+            provider: m.provider.clone(),
+            external_ids: m.external_ids.clone(),
+            also_matched: Vec::new(),
+        }),
+        MediaDetails::Tv(t) => MediaSearchResult::Tv(super::TvSearchResult {
+            id: t.id.clone(),
+            name: t.name.clone(),
+            original_name: t.original_name.clone(),
+            first_air_date: t.first_air_date.clone(),
+            poster_path: t.poster_path.clone(),
+            overview: t.overview.clone(),
+            vote_average: t.vote_average,
+            adult: false,
+            provider: t.provider.clone(),
+            external_ids: t.external_ids.clone(),
+            also_matched: Vec::new(),
+        }),
+        MediaDetails::Anime(a) => MediaSearchResult::Anime(super::AnimeSearchResult {
+            id: a.id.clone(),
+            title: a.title.clone(),
+            title_english: a.title_english.clone(),
+            title_japanese: a.title_japanese.clone(),
+            year: details.year(),
+            poster_path: a.poster_path.clone(),
+            overview: a.overview.clone(),
+            score: a.score,
+            adult: false,
+            provider: a.provider.clone(),
+            external_ids: a.external_ids.clone(),
+            also_matched: Vec::new(),
+        }),
+    }
+}
@@ -1,15 +1,28 @@
 pub mod provider;
 
 mod cache;
+#[cfg(test)]
+pub(crate) mod mock;
+pub mod naming;
 mod rate_limiter;
 mod types;
 
-pub use cache::ScraperCache;
-pub use rate_limiter::{RateLimitConfig, RateLimiter};
+pub use cache::{CacheStats, CacheStrategy, ScraperCache, default_cache_path};
+pub use naming::{NamingContext, NamingTemplate};
+
+pub use cache::CacheKey;
+pub use rate_limiter::{RateLimitConfig, RateLimitStrategy, RateLimiter};
 pub use types::*;
 
 use async_trait::async_trait;
-use std::time::Duration;
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+use crate::app::config::AppConfig;
+use provider::{
+    ProviderConfig, anilist::AniListProvider, bangumi::BangumiProvider, kitsu::KitsuProvider,
+    tmdb::TmdbProvider, tvdb::TvdbProvider,
+};
 
 /// Scraper result type
 pub type Result<T> = std::result::Result<T, ScraperError>;
@@ -50,17 +63,53 @@ pub trait MetadataProvider: Send + Sync {
         false
     }
 
+    /// Media types and lookups the provider supports
+    ///
+    /// The manager only routes requests the provider can serve. Defaults to
+    /// everything.
+    fn capabilities(&self) -> ProviderCapabilities {
+        ProviderCapabilities::default()
+    }
+
+    /// Time until the provider's daily request quota resets, if it is used up
+    fn quota_exhausted(&self) -> Option<Duration> {
+        None
+    }
+
+    /// Check that the provider's API is reachable and, where the provider
+    /// needs them, that its credentials are accepted
+    async fn ping(&self) -> Result<()> {
+        Ok(())
+    }
+
     /// Generic search
     ///
-    /// Search for media based on query string and year, returning all matching results.
-    /// Each provider decides which media types to search based on its capabilities.
-    async fn search(&self, query: &str, year: Option<i32>) -> Result<Vec<MediaSearchResult>>;
+    /// Search for media matching `options`, returning all matching results.
+    /// Each provider decides which media types to search based on its capabilities,
+    /// skipping any that `options.media_types` excludes.
+    async fn search(&self, options: &SearchOptions) -> Result<Vec<MediaSearchResult>>;
+
+    /// Fetch the page of search results named by `options.page`
+    ///
+    /// Providers that do not paginate return everything as a single page.
+    async fn search_page(&self, options: &SearchOptions) -> Result<SearchPage> {
+        self.search(options).await.map(SearchPage::single)
+    }
 
     /// Get media details
     ///
     /// Retrieve complete metadata based on search results.
     async fn get_details(&self, result: &MediaSearchResult) -> Result<MediaDetails>;
 
+    /// Get media details with per-request options such as the result language
+    async fn get_details_with(
+        &self,
+        result: &MediaSearchResult,
+        _options: &SearchOptions,
+    ) -> Result<MediaDetails> {
+        self.get_details(result).await
+    }
+
     /// Get episode details
     ///
     /// Retrieve specific episode information for TV shows or anime.
@@ -72,10 +121,23 @@ pub trait MetadataProvider: Send + Sync {
     ) -> Result<EpisodeMetadata>;
 }
 
+/// A provider left out at startup, and why
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DisabledProvider {
+    pub name: String,
+    pub reason: String,
+}
+
 /// Scraper manager for managing multiple providers
 pub struct ScraperManager {
     providers: Vec<Box<dyn MetadataProvider>>,
+    /// Index into `providers` of each registered built-in provider
+    registry: HashMap<Provider, usize>,
     cache: ScraperCache,
+    priority: Vec<String>,
+    options: SearchOptions,
+    max_pages: u32,
+    deduplicate: bool,
 }
 
 impl ScraperManager {
@@ -84,21 +146,239 @@ impl ScraperManager {
     pub fn new() -> Self {
         Self {
             providers: Vec::new(),
+            registry: HashMap::new(),
             cache: ScraperCache::new(),
+            priority: Vec::new(),
+            options: SearchOptions::default(),
+            max_pages: 1,
+            deduplicate: true,
+        }
+    }
+
+    /// Create a scraper manager using an existing cache
+    #[must_use]
+    pub fn with_cache(cache: ScraperCache) -> Self {
+        Self {
+            cache,
+            ..Self::new()
         }
     }
 
     /// Add a provider
+    ///
+    /// Built-in providers are also registered under their [`Provider`]; adding
+    /// a second one of the same kind replaces the first in the registry.
     pub fn add_provider(&mut self, provider: Box<dyn MetadataProvider>) {
+        if let Ok(kind) = provider.name().parse::<Provider>() {
+            self.registry.insert(kind, self.providers.len());
+        }
         self.providers.push(provider);
     }
 
+    /// Build a manager with every provider enabled in `config`, sharing
+    /// `cache`
+    ///
+    /// Providers missing a required API key are left out and returned; see
+    /// [`add_provider_checked`](Self::add_provider_checked).
+    #[must_use]
+    pub fn from_config(
+        config: &AppConfig,
+        cache: &Arc<ScraperCache>,
+    ) -> (Self, Vec<DisabledProvider>) {
+        let mut manager = Self::with_cache((**cache).clone());
+        manager.set_language(config.scraper.default_language.clone());
+        manager.set_include_adult(config.scraper.include_adult);
+        manager.set_deduplicate(!config.scraper.keep_duplicates);
+        manager.set_priority(config.scraper.provider_order());
+
+        let providers = &config.providers;
+        let localized = |provider_config: ProviderConfig| match &config.scraper.default_language {
+            Some(language) => provider_config.with_language(language),
+            None => provider_config,
+        };
+        let tmdb_api_key = config.scraper.tmdb_api_key.as_deref();
+        let tvdb_api_key = config.scraper.tvdb_api_key.as_deref();
+        let mut candidates: Vec<(Box<dyn MetadataProvider>, Option<&str>)> = Vec::new();
+        if providers.tmdb.enabled {
+            let settings = &providers.tmdb;
+            candidates.push((
+                Box::new(TmdbProvider::with_config(
+                    tmdb_api_key.unwrap_or_default(),
+                    localized(
+                        TmdbProvider::default_config(settings.rate_limit.clone())
+                            .with_http(settings.http.clone()),
+                    ),
+                    cache.clone(),
+                )),
+                tmdb_api_key,
+            ));
+        }
+        if providers.tvdb.enabled {
+            let settings = &providers.tvdb;
+            let api_key = tvdb_api_key.unwrap_or_default();
+            candidates.push((
+                Box::new(TvdbProvider::with_config(
+                    api_key,
+                    localized(
+                        TvdbProvider::default_config(settings.rate_limit.clone())
+                            .with_api_key(api_key)
+                            .with_http(settings.http.clone()),
+                    ),
+                    cache.clone(),
+                )),
+                tvdb_api_key,
+            ));
+        }
+        if providers.anilist.enabled {
+            let settings = &providers.anilist;
+            candidates.push((
+                Box::new(AniListProvider::with_config(
+                    localized(
+                        AniListProvider::default_config(settings.rate_limit.clone())
+                            .with_http(settings.http.clone()),
+                    ),
+                    cache.clone(),
+                )),
+                None,
+            ));
+        }
+        if providers.bangumi.enabled {
+            let settings = &providers.bangumi;
+            candidates.push((
+                Box::new(BangumiProvider::with_config(
+                    localized(
+                        BangumiProvider::default_config(settings.rate_limit.clone())
+                            .with_http(settings.http.clone()),
+                    ),
+                    cache.clone(),
+                )),
+                None,
+            ));
+        }
+        if providers.kitsu.enabled {
+            let settings = &providers.kitsu;
+            candidates.push((
+                Box::new(KitsuProvider::with_config(
+                    localized(
+                        KitsuProvider::default_config(settings.rate_limit.clone())
+                            .with_http(settings.http.clone()),
+                    ),
+                    cache.clone(),
+                )),
+                None,
+            ));
+        }
+
+        let disabled = candidates
+            .into_iter()
+            .filter_map(|(provider, api_key)| manager.add_provider_checked(provider, api_key).err())
+            .collect();
+        (manager, disabled)
+    }
+
+    /// Add a provider, unless it requires an API key and `api_key` is
+    /// missing or blank
+    ///
+    /// A provider without its key would only fail at request time, so it is
+    /// left out instead and reported back.
+    pub fn add_provider_checked(
+        &mut self,
+        provider: Box<dyn MetadataProvider>,
+        api_key: Option<&str>,
+    ) -> std::result::Result<(), DisabledProvider> {
+        if provider.requires_api_key() && api_key.is_none_or(|key| key.trim().is_empty()) {
+            return Err(DisabledProvider {
+                name: provider.name().to_string(),
+                reason: "No API key configured".to_string(),
+            });
+        }
+
+        self.add_provider(provider);
+        Ok(())
+    }
+
+    /// Set the server-wide language for search and details lookups
+    pub fn set_language(&mut self, language: Option<String>) {
+        self.options.language = language;
+    }
+
+    /// Set whether searches may return adult titles
+    ///
+    /// This is a server-wide ceiling: a [`search`](Self::search) only returns
+    /// adult titles when both this and its own `include_adult` allow them.
+    pub const fn set_include_adult(&mut self, include_adult: bool) {
+        self.options.include_adult = include_adult;
+    }
+
+    /// Set how many result pages each provider is asked for per search
+    ///
+    /// Defaults to 1; values below 1 are treated as 1.
+    pub fn set_max_pages(&mut self, max_pages: u32) {
+        self.max_pages = max_pages.max(1);
+    }
+
+    /// Set whether searches collapse results for the same title
+    ///
+    /// On by default. See [`search`](Self::search) for how duplicates are found.
+    pub const fn set_deduplicate(&mut self, deduplicate: bool) {
+        self.deduplicate = deduplicate;
+    }
+
+    /// Set the provider order used when merging details and picking matches
+    ///
+    /// Providers not listed rank after the listed ones, in registration order.
+    pub fn set_priority(&mut self, priority: Vec<String>) {
+        self.priority = priority;
+    }
+
+    /// Position of a provider in the priority list, if it is listed
+    #[must_use]
+    pub fn preference(&self, provider_name: &str) -> Option<usize> {
+        self.priority.iter().position(|p| p == provider_name)
+    }
+
+    /// Rank of a provider when merging; lower wins
+    fn rank(&self, provider_name: &str) -> usize {
+        self.preference(provider_name)
+            .or_else(|| {
+                self.providers
+                    .iter()
+                    .position(|p| p.name() == provider_name)
+                    .map(|i| self.priority.len() + i)
+            })
+            .unwrap_or(usize::MAX)
+    }
+
     /// Get all providers
     #[must_use]
     pub fn providers(&self) -> &[Box<dyn MetadataProvider>] {
         &self.providers
     }
 
+    /// Get the registered instance of a built-in provider
+    #[must_use]
+    pub fn get_provider(&self, provider: Provider) -> Option<&dyn MetadataProvider> {
+        self.registry
+            .get(&provider)
+            .map(|&index| self.providers[index].as_ref())
+    }
+
+    /// Look up a registered provider by name
+    ///
+    /// Built-in names go through the registry; other names, such as test
+    /// providers, are matched against each provider's `name()`.
+    fn provider(&self, name: &str) -> Result<&dyn MetadataProvider> {
+        let provider = match name.parse::<Provider>() {
+            Ok(kind) => self.get_provider(kind),
+            Err(_) => self
+                .providers
+                .iter()
+                .find(|p| p.name() == name)
+                .map(AsRef::as_ref),
+        };
+        provider.ok_or_else(|| ScraperError::Config(format!("Provider not found: {name}")))
+    }
+
     /// Get cache
     #[must_use]
     pub const fn cache(&self) -> &ScraperCache {
@@ -107,43 +387,264 @@ impl ScraperManager {
 
     /// Search media
     ///
-    /// Query all registered providers and aggregate results.
-    pub async fn search(&self, query: &str, year: Option<i32>) -> Result<Vec<MediaSearchResult>> {
+    /// Query all registered providers, or only the one `options` names, and
+    /// aggregate results. Providers supporting none of the requested media
+    /// types are skipped. The server-wide
+    /// language applies when `options` does not name one. Adult titles are
+    /// dropped after the providers return unless both `options` and the
+    /// server-wide setting allow them. If no provider answers, the last error
+    /// other than `NotFound`, such as a rate limit, is returned. Each
+    /// provider's pages are cached per query, year, language and page.
+    ///
+    /// Unless deduplication is turned off, results from different providers
+    /// sharing an external ID, or with the same media type, year and
+    /// normalized title, collapse into the highest-scored one. The others are
+    /// listed in its `also_matched`.
+    pub async fn search(&self, options: &SearchOptions) -> Result<Vec<MediaSearchResult>> {
+        let mut options = options.clone();
+        if options.language.is_none() {
+            options.language.clone_from(&self.options.language);
+        }
+        options.include_adult &= self.options.include_adult;
+
         let mut all_results = Vec::new();
 
-        for provider in &self.providers {
-            match provider.search(query, year).await {
+        let providers = self
+            .providers
+            .iter()
+            .filter(|p| {
+                options
+                    .provider
+                    .as_ref()
+                    .is_none_or(|name| name == p.name())
+            })
+            .filter(|p| {
+                p.capabilities()
+                    .media_types
+                    .iter()
+                    .any(|&media_type| options.allows(media_type))
+            });
+        // Reported when no provider answers, so clients can tell a rate limit
+        // or outage from a title that doesn't exist
+        let mut failure = None;
+        let mut answered = false;
+        for provider in providers {
+            if let Some(reset) = provider.quota_exhausted() {
+                tracing::debug!("Skipping {}: quota resets in {:?}", provider.name(), reset);
+                failure = Some(ScraperError::RateLimit(reset));
+                continue;
+            }
+
+            match self.search_pages(provider.as_ref(), &options).await {
                 Ok(results) => {
-                    all_results.extend(results);
+                    answered = true;
+                    all_results.extend(
+                        results
+                            .into_iter()
+                            .filter(|result| options.allows(result.media_type()))
+                            .filter(|result| options.include_adult || !result.is_adult()),
+                    );
                 }
                 Err(e) => {
                     tracing::debug!("Provider {} search failed: {}", provider.name(), e);
+                    if !matches!(e, ScraperError::NotFound(_)) {
+                        failure = Some(e);
+                    }
                 }
             }
         }
 
         if all_results.is_empty() {
-            Err(ScraperError::NotFound(format!(
-                "No provider could find: {query}"
-            )))
+            Err(match failure {
+                Some(e) if !answered => e,
+                _ => ScraperError::NotFound(format!("No provider could find: {}", options.query)),
+            })
+        } else if self.deduplicate {
+            Ok(dedupe(all_results))
         } else {
             Ok(all_results)
         }
     }
 
+    /// Collect up to `max_pages` pages from one provider
+    ///
+    /// A failure after the first page keeps the results gathered so far.
+    async fn search_pages(
+        &self,
+        provider: &dyn MetadataProvider,
+        options: &SearchOptions,
+    ) -> Result<Vec<MediaSearchResult>> {
+        let mut page = self.search_page(provider, options).await?;
+        let mut results = std::mem::take(&mut page.results);
+        let mut options = options.clone();
+
+        for _ in 1..self.max_pages {
+            if !page.has_next() {
+                break;
+            }
+            options.page = Some(page.page + 1);
+            match self.search_page(provider, &options).await {
+                Ok(next) => page = next,
+                Err(e) => {
+                    tracing::debug!(
+                        "Provider {} page {:?} failed: {}",
+                        provider.name(),
+                        options.page,
+                        e
+                    );
+                    break;
+                }
+            }
+            results.append(&mut page.results);
+        }
+
+        Ok(results)
+    }
+
+    /// Fetch one page from a provider, cached per query, year, language and page
+    async fn search_page(
+        &self,
+        provider: &dyn MetadataProvider,
+        options: &SearchOptions,
+    ) -> Result<SearchPage> {
+        let key = search_key(provider.name(), options);
+        if let Some(page) = self.cache.get::<SearchPage>(&key).await {
+            tracing::debug!("Search cache hit for {}:{}", provider.name(), options.query);
+            return Ok(page);
+        }
+
+        let page = provider.search_page(options).await?;
+        if let Err(e) = self.cache.set(key, &page).await {
+            tracing::debug!("Failed to cache search for {}: {e}", provider.name());
+        }
+
+        Ok(page)
+    }
+
     /// Get media details
     ///
     /// Automatically select the correct provider based on search results.
+    /// Successful lookups are cached per provider, media type and ID.
     pub async fn get_details(&self, result: &MediaSearchResult) -> Result<MediaDetails> {
+        self.get_details_with(result, &self.options).await
+    }
+
+    /// Get media details with explicit options instead of the server-wide defaults
+    ///
+    /// The server-wide language still applies when `options` name none.
+    pub async fn get_details_with(
+        &self,
+        result: &MediaSearchResult,
+        options: &SearchOptions,
+    ) -> Result<MediaDetails> {
         let provider_name = result.provider();
+        let provider = self.provider(provider_name)?;
+        if !provider.capabilities().supports(result.media_type()) {
+            return Err(ScraperError::Config(format!(
+                "{provider_name} does not support {}",
+                result.media_type().as_str()
+            )));
+        }
 
-        let provider = self
-            .providers
-            .iter()
-            .find(|p| p.name() == provider_name)
-            .ok_or_else(|| ScraperError::Config(format!("Provider not found: {provider_name}")))?;
+        let mut options = options.clone();
+        if options.language.is_none() {
+            options.language.clone_from(&self.options.language);
+        }
 
-        provider.get_details(result).await
+        let key = details_key(provider_name, result.media_type(), result.id(), &options);
+        if let Some(details) = self.cache.get::<MediaDetails>(&key).await {
+            tracing::debug!("Details cache hit for {provider_name}:{}", result.id());
+            return Ok(details);
+        }
+
+        let details = provider.get_details_with(result, &options).await?;
+        if let Err(e) = self.cache.set(key, &details).await {
+            tracing::debug!("Failed to cache details for {provider_name}: {e}");
+        }
+
+        Ok(details)
+    }
+
+    /// Get details for one title from several providers, merged into one result
+    ///
+    /// The highest-ranked provider's details form the base. Details from other
+    /// providers are merged in only if they share an external ID with what has
+    /// been merged so far, filling fields that are still empty. Conflicting
+    /// values are resolved by provider rank.
+    pub async fn get_merged_details(&self, results: &[MediaSearchResult]) -> Result<MediaDetails> {
+        let fetched = futures::future::join_all(results.iter().map(|r| self.get_details(r))).await;
+
+        let mut details = Vec::new();
+        let mut last_error = None;
+        for result in fetched {
+            match result {
+                Ok(d) => details.push(d),
+                Err(e) => {
+                    tracing::debug!("Skipping details for merge: {e}");
+                    last_error = Some(e);
+                }
+            }
+        }
+        details.sort_by_key(|d| self.rank(d.provider()));
+
+        let mut details = details.into_iter();
+        let Some(mut merged) = details.next() else {
+            return Err(last_error
+                .unwrap_or_else(|| ScraperError::NotFound("No details to merge".to_string())));
+        };
+
+        let mut ids = merged.all_ids();
+        for other in details {
+            let other_ids = other.all_ids();
+            if !ids.overlaps(&other_ids) {
+                tracing::debug!(
+                    "Not merging {}:{} into {}: no shared IDs",
+                    other.provider(),
+                    other.id(),
+                    merged.title()
+                );
+                continue;
+            }
+            merged.merge_from(&other);
+            ids.merge(&other_ids);
+        }
+        *merged.external_ids_mut() = ids;
+
+        Ok(merged)
+    }
+
+    /// Resolve the IDs other providers use for a title known on `provider`
+    ///
+    /// The media type is not known up front, so each type the provider supports
+    /// is tried in turn. The result includes the source ID itself, plus any
+    /// AniList/MAL/Bangumi cross-references the provider reports for anime.
+    pub async fn resolve_external(&self, provider: &str, id: &str) -> Result<ExternalIds> {
+        let mut last_error = None;
+        for media_type in self.provider(provider)?.capabilities().media_types {
+            let result = MediaSearchResult::from_id(media_type, provider, id);
+            match self.get_details(&result).await {
+                Ok(details) => return Ok(details.all_ids()),
+                Err(e @ ScraperError::Config(_)) => return Err(e),
+                Err(e) => last_error = Some(e),
+            }
+        }
+
+        Err(last_error.unwrap_or_else(|| ScraperError::NotFound(format!("{provider}:{id}"))))
+    }
+
+    /// Drop cached details for an ID in every language so the next
+    /// `get_details` refetches it
+    pub async fn invalidate_details(&self, provider: &str, id: &str) {
+        for media_type in [MediaType::Movie, MediaType::Tv, MediaType::Anime] {
+            self.cache
+                .invalidate_variants(&details_key(
+                    provider,
+                    media_type,
+                    id,
+                    &SearchOptions::default(),
+                ))
+                .await;
+        }
     }
 
     /// Get episode details
@@ -156,11 +657,12 @@ impl ScraperManager {
         season: i32,
         episode: i32,
     ) -> Result<EpisodeMetadata> {
-        let provider = self
-            .providers
-            .iter()
-            .find(|p| p.name() == provider_name)
-            .ok_or_else(|| ScraperError::Config(format!("Provider not found: {provider_name}")))?;
+        let provider = self.provider(provider_name)?;
+        if !provider.capabilities().episode_details {
+            return Err(ScraperError::Config(format!(
+                "{provider_name} does not provide episode details"
+            )));
+        }
 
         provider
             .get_episode_details(series_id, season, episode)
@@ -173,3 +675,367 @@ impl Default for ScraperManager {
         Self::new()
     }
 }
+
+/// Collapse results naming the same title, keeping the first appearance order
+fn dedupe(results: Vec<MediaSearchResult>) -> Vec<MediaSearchResult> {
+    let mut groups: Vec<(ExternalIds, Vec<MediaSearchResult>)> = Vec::new();
+    for result in results {
+        let ids = result.all_ids();
+        let group = groups.iter_mut().find(|(group_ids, members)| {
+            group_ids.overlaps(&ids) || members.iter().any(|m| same_title(m, &result))
+        });
+        match group {
+            Some((group_ids, members)) => {
+                group_ids.merge(&ids);
+                members.push(result);
+            }
+            None => groups.push((ids, vec![result])),
+        }
+    }
+
+    groups
+        .into_iter()
+        .map(|(_, mut members)| {
+            // The first of equally scored results wins
+            let best = members.iter().enumerate().fold(0, |best, (i, m)| {
+                if m.score() > members[best].score() {
+                    i
+                } else {
+                    best
+                }
+            });
+            let mut representative = members.remove(best);
+            for other in members {
+                representative.absorb(other);
+            }
+            representative
+        })
+        .collect()
+}
+
+/// Whether two providers' results look like the same title
+///
+/// Results from one provider are never the same title by name alone, and
+/// both years must be known.
+fn same_title(a: &MediaSearchResult, b: &MediaSearchResult) -> bool {
+    let normalize = |title: &str| -> String {
+        title
+            .chars()
+            .filter(|c| c.is_alphanumeric())
+            .flat_map(char::to_lowercase)
+            .collect()
+    };
+
+    a.provider() != b.provider()
+        && a.media_type() == b.media_type()
+        && a.year().is_some()
+        && a.year() == b.year()
+        && normalize(a.title()) == normalize(b.title())
+}
+
+fn details_key(
+    provider: &str,
+    media_type: MediaType,
+    id: &str,
+    options: &SearchOptions,
+) -> CacheKey {
+    CacheKey::new(provider, format!("details:{}", media_type.as_str()), id)
+        .with_language(options.language.as_deref())
+}
+
+/// Searches differing in anything the provider sees get separate entries
+fn search_key(provider: &str, options: &SearchOptions) -> CacheKey {
+    let mut media_types: Vec<_> = options.media_types.iter().map(|t| t.as_str()).collect();
+    media_types.sort_unstable();
+    let mut kind = format!(
+        "search:{}:page{}",
+        media_types.join(","),
+        options.page.unwrap_or(1)
+    );
+    if options.include_adult {
+        kind.push_str(":adult");
+    }
+    CacheKey::new(provider, kind, options.query.as_str())
+        .with_year(options.year)
+        .with_language(options.language.as_deref())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mock::FakeProvider;
+    use std::sync::{Arc, atomic::Ordering};
+
+    #[tokio::test]
+    async fn test_server_adult_setting_caps_per_call_flag() {
+        let mut manager = ScraperManager::new();
+        manager.add_provider(Box::new(
+            FakeProvider::new("tmdb")
+                .with_movie("1", "Perfect Blue", 1997)
+                .with_adult_movie("2", "Perfect Blue Uncut", 1997),
+        ));
+        async fn found(manager: &ScraperManager, include_adult: bool) -> usize {
+            let options = SearchOptions::new("perfect blue").with_include_adult(include_adult);
+            manager.search(&options).await.unwrap().len()
+        }
+
+        manager.set_include_adult(true);
+        assert_eq!(found(&manager, false).await, 1);
+        assert_eq!(found(&manager, true).await, 2);
+
+        manager.set_include_adult(false);
+        assert_eq!(found(&manager, true).await, 1);
+    }
+
+    #[tokio::test]
+    async fn test_get_details_is_cached() {
+        let provider = FakeProvider::new("fake").with_movie("1", "Alien", 1979);
+        let calls = provider.details_calls();
+        let mut manager = ScraperManager::new();
+        manager.add_provider(Box::new(provider));
+
+        let result = MediaSearchResult::from_id(MediaType::Movie, "fake", "1");
+        manager.get_details(&result).await.unwrap();
+        let details = manager.get_details(&result).await.unwrap();
+
+        assert_eq!(details.id(), "1");
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        let japanese = SearchOptions::default().with_language("ja-JP");
+        manager.get_details_with(&result, &japanese).await.unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+        manager.invalidate_details("fake", "1").await;
+        manager.get_details(&result).await.unwrap();
+        manager.get_details_with(&result, &japanese).await.unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 4);
+    }
+
+    #[tokio::test]
+    async fn test_search_is_cached_per_year_and_language() {
+        let provider = FakeProvider::new("fake").with_movie("1", "Alien", 1979);
+        let calls = provider.search_calls();
+        let mut manager = ScraperManager::new();
+        manager.add_provider(Box::new(provider));
+
+        let options = SearchOptions::new("Alien")
+            .with_year(Some(1979))
+            .with_language("en-US");
+        manager.search(&options).await.unwrap();
+        manager.search(&options).await.unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        manager
+            .search(&options.clone().with_year(Some(1986)))
+            .await
+            .unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+        manager
+            .search(&options.clone().with_language("ja-JP"))
+            .await
+            .unwrap();
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_merged_details_fill_fields_by_priority() {
+        let mut tmdb = mock::movie_details("tmdb", "603", "The Matrix", 1999);
+        tmdb.poster_path = Some("/tmdb-poster.jpg".to_string());
+        tmdb.external_ids.imdb_id = Some("tt0133093".to_string());
+
+        let mut tvdb = mock::movie_details("tvdb", "169", "Matrix", 1999);
+        tvdb.poster_path = Some("/tvdb-poster.jpg".to_string());
+        tvdb.overview = Some("A hacker learns the truth.".to_string());
+        tvdb.runtime = Some(136);
+        tvdb.external_ids.imdb_id = Some("tt0133093".to_string());
+
+        // Same type, different title with no shared IDs
+        let mut unrelated = mock::movie_details("other", "1", "The Matrix", 2021);
+        unrelated.original_language = Some("de".to_string());
+
+        let mut manager = ScraperManager::new();
+        manager.add_provider(Box::new(
+            FakeProvider::new("tvdb").with_details(MediaDetails::Movie(tvdb)),
+        ));
+        manager.add_provider(Box::new(
+            FakeProvider::new("tmdb").with_details(MediaDetails::Movie(tmdb)),
+        ));
+        manager.add_provider(Box::new(
+            FakeProvider::new("other").with_details(MediaDetails::Movie(unrelated)),
+        ));
+        manager.set_priority(vec!["tmdb".to_string()]);
+
+        let results = [
+            MediaSearchResult::from_id(MediaType::Movie, "tvdb", "169"),
+            MediaSearchResult::from_id(MediaType::Movie, "tmdb", "603"),
+            MediaSearchResult::from_id(MediaType::Movie, "other", "1"),
+        ];
+        let MediaDetails::Movie(merged) = manager.get_merged_details(&results).await.unwrap()
+        else {
+            panic!("expected movie details");
+        };
+
+        assert_eq!(merged.provider, "tmdb");
+        assert_eq!(merged.title, "The Matrix");
+        assert_eq!(merged.poster_path.as_deref(), Some("/tmdb-poster.jpg"));
+        assert_eq!(
+            merged.overview.as_deref(),
+            Some("A hacker learns the truth.")
+        );
+        assert_eq!(merged.runtime, Some(136));
+        assert_eq!(merged.original_language, None);
+        assert_eq!(merged.external_ids.tvdb_id.as_deref(), Some("169"));
+        assert_eq!(merged.external_ids.imdb_id.as_deref(), Some("tt0133093"));
+    }
+
+    #[tokio::test]
+    async fn test_search_collapses_duplicates_across_providers() {
+        let mut tmdb = mock::movie_details("tmdb", "603", "The Matrix", 1999);
+        tmdb.vote_average = Some(8.2);
+        tmdb.external_ids.imdb_id = Some("tt0133093".to_string());
+        let mut tvdb = mock::movie_details("tvdb", "169", "Matrix, The", 1998);
+        tvdb.vote_average = Some(8.7);
+        tvdb.external_ids.imdb_id = Some("tt0133093".to_string());
+
+        let mut manager = ScraperManager::new();
+        manager.add_provider(Box::new(
+            FakeProvider::new("tmdb")
+                .with_details(MediaDetails::Movie(tmdb))
+                .with_movie("604", "The Matrix Reloaded", 2003),
+        ));
+        manager.add_provider(Box::new(
+            FakeProvider::new("tvdb").with_details(MediaDetails::Movie(tvdb)),
+        ));
+        let options = SearchOptions::new("matrix");
+
+        let results = manager.search(&options).await.unwrap();
+        assert_eq!(results.len(), 2);
+        let matrix = &results[0];
+        assert_eq!(matrix.provider(), "tvdb");
+        assert_eq!(
+            matrix.also_matched(),
+            [ProviderMatch {
+                provider: "tmdb".to_string(),
+                id: "603".to_string(),
+                media_type: MediaType::Movie,
+            }]
+        );
+        assert_eq!(matrix.all_ids().tmdb_id.as_deref(), Some("603"));
+        assert_eq!(results[1].id(), "604");
+
+        let merged = manager
+            .get_merged_details(&matrix.matched_results())
+            .await
+            .unwrap();
+        assert_eq!(merged.external_ids().tmdb_id.as_deref(), Some("603"));
+        assert_eq!(merged.external_ids().tvdb_id.as_deref(), Some("169"));
+
+        manager.set_deduplicate(false);
+        assert_eq!(manager.search(&options).await.unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_provider_registry_selects_by_kind() {
+        let cache = Arc::new(ScraperCache::new());
+        let mut manager = ScraperManager::new();
+        manager.add_provider(Box::new(provider::tmdb::TmdbProvider::new(
+            "key",
+            cache.clone(),
+            None,
+        )));
+        manager.add_provider(Box::new(provider::tvdb::TvdbProvider::new(
+            "key", cache, None,
+        )));
+        manager.add_provider(Box::new(FakeProvider::new("fake")));
+
+        let tvdb = manager.get_provider(Provider::Tvdb).unwrap();
+        assert_eq!(tvdb.name(), Provider::Tvdb.as_str());
+        assert!(manager.get_provider(Provider::Kitsu).is_none());
+        assert_eq!(manager.provider("fake").unwrap().name(), "fake");
+
+        for kind in Provider::ALL {
+            assert_eq!(kind.as_str().parse::<Provider>().unwrap(), kind);
+        }
+        assert!("imdb".parse::<Provider>().is_err());
+    }
+
+    #[test]
+    fn test_manager_registers_enabled_providers_from_config() {
+        let mut config = AppConfig::default();
+        config.scraper.tvdb_api_key = Some("key".to_string());
+        config.providers.bangumi.enabled = false;
+        config.providers.kitsu.enabled = false;
+
+        let (manager, disabled) =
+            ScraperManager::from_config(&config, &Arc::new(ScraperCache::new()));
+
+        assert!(manager.get_provider(Provider::Tvdb).is_some());
+        assert!(manager.get_provider(Provider::AniList).is_some());
+        assert!(manager.get_provider(Provider::Bangumi).is_none());
+        assert!(manager.get_provider(Provider::Kitsu).is_none());
+        // TMDB is enabled but has no key
+        assert!(manager.get_provider(Provider::Tmdb).is_none());
+        assert_eq!(disabled.len(), 1);
+        assert_eq!(disabled[0].name, "tmdb");
+    }
+
+    #[tokio::test]
+    async fn test_requests_only_reach_capable_providers() {
+        let anilist = provider::anilist::AniListProvider::new(Arc::new(ScraperCache::new()), None);
+        assert_eq!(
+            anilist.capabilities(),
+            ProviderCapabilities::new([MediaType::Anime]).with_cjk_titles(true)
+        );
+
+        let provider = FakeProvider::new("anime")
+            .with_movie("1", "Alien", 1979)
+            .with_capabilities(anilist.capabilities());
+        let calls = provider.details_calls();
+        let mut manager = ScraperManager::new();
+        manager.add_provider(Box::new(provider));
+
+        let result = MediaSearchResult::from_id(MediaType::Movie, "anime", "1");
+        assert!(matches!(
+            manager.get_details(&result).await,
+            Err(ScraperError::Config(_))
+        ));
+        assert!(matches!(
+            manager.get_episode_details("anime", "1", 1, 1).await,
+            Err(ScraperError::Config(_))
+        ));
+        assert!(
+            manager
+                .search(&SearchOptions::new("Alien").with_media_types(Some(MediaType::Movie)))
+                .await
+                .is_err()
+        );
+        assert_eq!(calls.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn test_resolve_external_surfaces_provider_ids() {
+        let mut movie = mock::movie_details("tmdb", "603", "The Matrix", 1999);
+        movie.external_ids.imdb_id = Some("tt0133093".to_string());
+        movie.external_ids.tvdb_id = Some("169".to_string());
+
+        let mut manager = ScraperManager::new();
+        manager.add_provider(Box::new(
+            FakeProvider::new("tmdb").with_details(MediaDetails::Movie(movie)),
+        ));
+
+        let ids = manager.resolve_external("tmdb", "603").await.unwrap();
+        assert_eq!(ids.imdb_id.as_deref(), Some("tt0133093"));
+        assert_eq!(ids.tvdb_id.as_deref(), Some("169"));
+        assert_eq!(ids.tmdb_id.as_deref(), Some("603"));
+
+        assert!(matches!(
+            manager.resolve_external("tmdb", "1").await,
+            Err(ScraperError::NotFound(_))
+        ));
+        assert!(matches!(
+            manager.resolve_external("tvdb", "169").await,
+            Err(ScraperError::Config(_))
+        ));
+    }
+}
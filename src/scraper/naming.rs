@@ -0,0 +1,335 @@
+//! Template-based naming for organized library files
+//!
+//! Templates are `/`-separated path segments containing tokens such as
+//! `{title}`, `{year}`, `{season:02}`, `{episode:02}`, `{episode_title}` and
+//! `{resolution}`. Tokens without a value render empty, and the punctuation
+//! left around them is cleaned up, so `{title} ({year})` becomes just the title
+//! when the year is unknown.
+
+use std::path::PathBuf;
+
+use once_cell::sync::Lazy;
+use regex::{Captures, Regex};
+
+use super::{EpisodeMetadata, MediaDetails, SeasonInfo};
+
+/// Default template for movies and series without episode numbers
+pub const MOVIE_TEMPLATE: &str = "{title} ({year})/{title} ({year})";
+
+/// Default template for TV and anime episodes
+pub const EPISODE_TEMPLATE: &str =
+    "{title} ({year})/Season {season:02}/{title} - S{season:02}E{episode:02} - {episode_title}";
+
+static TOKEN: Lazy<Regex> = Lazy::new(|| Regex::new(r"\{(\w+)(?::0?(\d+))?\}").unwrap());
+static EMPTY_BRACKETS: Lazy<Regex> = Lazy::new(|| Regex::new(r"\(\s*\)|\[\s*\]").unwrap());
+static EPISODE_MARKER: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?i)\bS(\d{1,2})E(\d{1,3})\b").unwrap());
+static ABSOLUTE_EPISODE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)(?:\bE(?:p|pisode)?\.?\s?|\s-\s)(\d{1,4})(?:v\d)?(?:\s*[\[(.]|$)").unwrap()
+});
+static RESOLUTION: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?i)\b(2160p|1080p|720p|576p|480p|4k)\b").unwrap());
+
+/// Values available to a naming template
+#[derive(Debug, Clone, Default)]
+pub struct NamingContext {
+    pub title: String,
+    pub year: Option<i32>,
+    pub season: Option<i32>,
+    pub episode: Option<i32>,
+    pub episode_title: Option<String>,
+    pub resolution: Option<String>,
+}
+
+impl NamingContext {
+    /// Start a context from scraped title details
+    #[must_use]
+    pub fn from_details(details: &MediaDetails) -> Self {
+        Self {
+            title: details.title().to_string(),
+            year: details.year(),
+            ..Default::default()
+        }
+    }
+
+    /// Fill season, episode and episode title from episode details
+    #[must_use]
+    pub fn with_episode(mut self, episode: &EpisodeMetadata) -> Self {
+        self.season = Some(episode.season_number);
+        self.episode = Some(episode.episode_number);
+        self.episode_title = Some(episode.name.clone());
+        self
+    }
+
+    /// Fill season and episode numbers when no episode details are available
+    #[must_use]
+    pub const fn with_episode_numbers(mut self, season: i32, episode: i32) -> Self {
+        self.season = Some(season);
+        self.episode = Some(episode);
+        self
+    }
+
+    /// Set the resolution tag, e.g. `1080p`
+    #[must_use]
+    pub fn with_resolution(mut self, resolution: Option<String>) -> Self {
+        self.resolution = resolution;
+        self
+    }
+
+    fn value(&self, token: &str, width: usize) -> Option<String> {
+        let number = |n: Option<i32>| n.map(|n| format!("{n:0width$}"));
+        match token {
+            "title" => Some(self.title.clone()),
+            "year" => self.year.map(|y| y.to_string()),
+            "season" => number(self.season),
+            "episode" => number(self.episode),
+            "episode_title" => self.episode_title.clone(),
+            "resolution" => self.resolution.clone(),
+            _ => None,
+        }
+    }
+}
+
+/// A file naming template
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NamingTemplate(String);
+
+impl NamingTemplate {
+    #[must_use]
+    pub fn new(template: impl Into<String>) -> Self {
+        Self(template.into())
+    }
+
+    /// Built-in template suited to the context: episodic when an episode number is known
+    #[must_use]
+    pub fn default_for(context: &NamingContext) -> Self {
+        if context.episode.is_some() {
+            Self::new(EPISODE_TEMPLATE)
+        } else {
+            Self::new(MOVIE_TEMPLATE)
+        }
+    }
+
+    /// Render the template into a relative path, without file extension
+    #[must_use]
+    pub fn render(&self, context: &NamingContext) -> PathBuf {
+        self.0
+            .split('/')
+            .map(|segment| {
+                let rendered = TOKEN.replace_all(segment, |caps: &Captures| {
+                    let width = caps.get(2).map_or(0, |w| w.as_str().parse().unwrap_or(0));
+                    context.value(&caps[1], width).unwrap_or_default()
+                });
+                clean_segment(&rendered)
+            })
+            .filter(|segment| !segment.is_empty())
+            .collect()
+    }
+}
+
+/// Find an `S01E02`-style marker, returning season and episode numbers
+#[must_use]
+pub fn parse_episode_marker(name: &str) -> Option<(i32, i32)> {
+    let caps = EPISODE_MARKER.captures(name)?;
+    Some((caps[1].parse().ok()?, caps[2].parse().ok()?))
+}
+
+/// Text before an episode marker, i.e. the series name in `Show S01E02` or
+/// `Show - 38`
+#[must_use]
+pub fn strip_episode_marker(name: &str) -> &str {
+    let marker = EPISODE_MARKER
+        .find(name)
+        .or_else(|| ABSOLUTE_EPISODE.find(name));
+    marker.map_or(name, |m| {
+        name[..m.start()].trim_end_matches([' ', '.', '-', '_'])
+    })
+}
+
+/// Find an absolute episode number, as in `Show - 38` or `Show E038`
+///
+/// `S01E02`-style markers are not absolute and yield `None`.
+#[must_use]
+pub fn parse_absolute_episode(name: &str) -> Option<u32> {
+    if EPISODE_MARKER.is_match(name) {
+        return None;
+    }
+    ABSOLUTE_EPISODE.captures(name)?[1].parse().ok()
+}
+
+/// Map an absolute episode number onto a season and episode
+///
+/// Seasons are counted in order, skipping specials (season 0). Without any
+/// counts the series is treated as a single season. Returns `None` for 0 and
+/// for numbers past the last known episode.
+#[must_use]
+pub fn absolute_to_season_episode(seasons: &[SeasonInfo], absolute: u32) -> Option<(i32, i32)> {
+    let absolute = i32::try_from(absolute).ok().filter(|&n| n > 0)?;
+
+    let mut regular: Vec<_> = seasons.iter().filter(|s| s.season_number > 0).collect();
+    if regular.is_empty() {
+        return Some((1, absolute));
+    }
+    regular.sort_by_key(|s| s.season_number);
+
+    let mut remaining = absolute;
+    for season in regular {
+        if remaining <= season.episode_count {
+            return Some((season.season_number, remaining));
+        }
+        remaining -= season.episode_count.max(0);
+    }
+    None
+}
+
+/// Find a resolution tag such as `1080p` in a file name
+#[must_use]
+pub fn parse_resolution(name: &str) -> Option<String> {
+    let tag = RESOLUTION.find(name)?.as_str().to_lowercase();
+    Some(if tag == "4k" {
+        "2160p".to_string()
+    } else {
+        tag
+    })
+}
+
+/// Make a rendered segment safe on Windows and Linux filesystems
+fn clean_segment(segment: &str) -> String {
+    let sanitized: String = segment
+        .chars()
+        .filter(|c| !c.is_control())
+        .filter(|c| !matches!(c, '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|'))
+        .collect();
+    let without_empty = EMPTY_BRACKETS.replace_all(&sanitized, "");
+
+    // Drop separators left dangling by missing tokens, e.g. "Show - S01E02 - "
+    without_empty
+        .split(" - ")
+        .map(|part| part.split_whitespace().collect::<Vec<_>>().join(" "))
+        .map(|part| part.trim_matches('-').trim().to_string())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join(" - ")
+        .trim_end_matches(['.', ' '])
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scraper::mock::movie_details;
+
+    fn episode() -> EpisodeMetadata {
+        EpisodeMetadata {
+            id: "1".to_string(),
+            name: "Cat's in the Bag...".to_string(),
+            season_number: 1,
+            episode_number: 2,
+            air_date: None,
+            overview: None,
+            still_path: None,
+            runtime: None,
+            vote_average: None,
+            provider: "tmdb".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_movie_template() {
+        let details = MediaDetails::Movie(movie_details("tmdb", "1", "Mission: Impossible", 1996));
+        let context =
+            NamingContext::from_details(&details).with_resolution(parse_resolution("mi.1080P.mkv"));
+
+        let path = NamingTemplate::new("{title} ({year})/{title} ({year}) [{resolution}]")
+            .render(&context);
+
+        assert_eq!(
+            path,
+            PathBuf::from("Mission Impossible (1996)/Mission Impossible (1996) [1080p]")
+        );
+    }
+
+    #[test]
+    fn test_episode_template() {
+        let context = NamingContext {
+            title: "Breaking Bad".to_string(),
+            year: Some(2008),
+            ..Default::default()
+        }
+        .with_episode(&episode());
+
+        let path = NamingTemplate::default_for(&context).render(&context);
+
+        assert_eq!(
+            path,
+            PathBuf::from("Breaking Bad (2008)/Season 01/Breaking Bad - S01E02 - Cat's in the Bag")
+        );
+    }
+
+    #[test]
+    fn test_missing_tokens_are_dropped() {
+        let context = NamingContext {
+            title: "  Some   Show ".to_string(),
+            ..Default::default()
+        }
+        .with_episode_numbers(3, 7);
+
+        let path = NamingTemplate::default_for(&context).render(&context);
+        assert_eq!(
+            path,
+            PathBuf::from("Some Show/Season 03/Some Show - S03E07")
+        );
+
+        let path = NamingTemplate::new(MOVIE_TEMPLATE).render(&context);
+        assert_eq!(path, PathBuf::from("Some Show/Some Show"));
+    }
+
+    #[test]
+    fn test_episode_marker_parsing() {
+        assert_eq!(parse_episode_marker("Show.Name.s02e10.720p"), Some((2, 10)));
+        assert_eq!(strip_episode_marker("Show.Name.s02e10.720p"), "Show.Name");
+        assert_eq!(parse_episode_marker("The Matrix (1999)"), None);
+    }
+
+    #[test]
+    fn test_absolute_episode_parsing() {
+        assert_eq!(
+            parse_absolute_episode("[Group] Cowboy Bebop - 24 [1080p]"),
+            Some(24)
+        );
+        assert_eq!(parse_absolute_episode("One.Piece.E1071.720p"), Some(1071));
+        assert_eq!(parse_absolute_episode("Show - 05v2"), Some(5));
+        assert_eq!(
+            strip_episode_marker("Cowboy Bebop - 24 [1080p]"),
+            "Cowboy Bebop"
+        );
+        assert_eq!(parse_absolute_episode("Show.Name.s02e10.720p"), None);
+        assert_eq!(parse_absolute_episode("Show - 1080p"), None);
+        assert_eq!(parse_absolute_episode("The Matrix (1999)"), None);
+    }
+
+    #[test]
+    fn test_absolute_to_season_episode() {
+        let season = |season_number, episode_count| SeasonInfo {
+            season_number,
+            episode_count,
+        };
+        // Listed out of order, with specials that must not count
+        let seasons = [season(2, 12), season(0, 3), season(1, 25), season(3, 13)];
+
+        assert_eq!(absolute_to_season_episode(&seasons, 1), Some((1, 1)));
+        assert_eq!(absolute_to_season_episode(&seasons, 25), Some((1, 25)));
+        assert_eq!(absolute_to_season_episode(&seasons, 26), Some((2, 1)));
+        assert_eq!(absolute_to_season_episode(&seasons, 38), Some((3, 1)));
+        assert_eq!(absolute_to_season_episode(&seasons, 50), Some((3, 13)));
+        assert_eq!(absolute_to_season_episode(&seasons, 51), None);
+        assert_eq!(absolute_to_season_episode(&seasons, 0), None);
+
+        // Unknown counts fall back to season 1
+        assert_eq!(absolute_to_season_episode(&[], 38), Some((1, 38)));
+        assert_eq!(
+            absolute_to_season_episode(&[season(0, 4)], 38),
+            Some((1, 38))
+        );
+    }
+}
@@ -1,11 +1,12 @@
 use super::{ProviderBase, ProviderConfig};
 use crate::scraper::{
     AnimeMetadata, AnimeSearchResult, EpisodeMetadata, ExternalIds, MediaDetails,
-    MediaSearchResult, MetadataProvider, Result, ScraperError,
+    MediaSearchResult, MediaType, MetadataProvider, ProviderCapabilities, RateLimitConfig,
+    RateLimitStrategy, Result, ScraperError, SearchOptions, SearchPage,
 };
 use async_trait::async_trait;
 use serde::Deserialize;
-use std::sync::Arc;
+use std::{sync::Arc, time::Duration};
 
 const ANILIST_API_URL: &str = "https://graphql.anilist.co";
 
@@ -16,15 +17,45 @@ pub struct AniListProvider {
 
 impl AniListProvider {
     /// Create a new `AniList` provider (no API key required)
+    ///
+    /// `rate_limit` overrides the default of 90 requests per minute.
     #[must_use]
-    pub fn new(cache: Arc<crate::scraper::ScraperCache>) -> Self {
-        let config = ProviderConfig::new(ANILIST_API_URL).with_cache_ttl(86400); // 24 hours
+    pub fn new(
+        cache: Arc<crate::scraper::ScraperCache>,
+        rate_limit: Option<RateLimitConfig>,
+    ) -> Self {
+        Self::with_config(Self::default_config(rate_limit), cache)
+    }
+
+    /// Configuration used by [`new`](Self::new), to adjust before `with_config`
+    #[must_use]
+    pub fn default_config(rate_limit: Option<RateLimitConfig>) -> ProviderConfig {
+        ProviderConfig::new(ANILIST_API_URL)
+            .with_rate_limit(rate_limit.unwrap_or_else(Self::default_rate_limit))
+            .with_cache_ttl(86400) // 24 hours
+    }
 
+    /// Create a new `AniList` provider with a custom configuration
+    #[must_use]
+    pub fn with_config(config: ProviderConfig, cache: Arc<crate::scraper::ScraperCache>) -> Self {
         Self {
             base: ProviderBase::new(config, cache),
         }
     }
 
+    /// `AniList` allows 90 requests per minute
+    #[must_use]
+    pub const fn default_rate_limit() -> RateLimitConfig {
+        RateLimitConfig {
+            max_concurrent: 5,
+            max_requests: 90,
+            window_seconds: 60,
+            daily_quota: None,
+            strategy: RateLimitStrategy::Sliding,
+            burst: None,
+        }
+    }
+
     /// Execute GraphQL query
     async fn query<T: for<'de> Deserialize<'de>>(
         &self,
@@ -67,14 +98,15 @@ impl AniListProvider {
     }
 
     // Private helper methods
-    async fn search_anime_internal(
-        &self,
-        query: &str,
-        year: Option<i32>,
-    ) -> Result<Vec<AnimeSearchResult>> {
+    async fn search_anime_internal(&self, options: &SearchOptions) -> Result<SearchPage> {
         let gql_query = r"
-            query ($search: String, $year: Int) {
-                Page(page: 1, perPage: 20) {
+            query ($search: String, $year: Int, $page: Int) {
+                Page(page: $page, perPage: 20) {
+                    pageInfo {
+                        total
+                        currentPage
+                        lastPage
+                    }
                     media(search: $search, seasonYear: $year, type: ANIME) {
                         id
                         title {
@@ -88,37 +120,58 @@ impl AniListProvider {
                         }
                         description
                         averageScore
+                        isAdult
                     }
                 }
             }
         ";
 
         let variables = serde_json::json!({
-            "search": query,
-            "year": year
+            "search": options.query,
+            "year": options.year,
+            "page": options.page.unwrap_or(1)
         });
 
         let response: AniListSearchData = self.query(gql_query, variables).await?;
+        let page_info = response.page.page_info;
 
-        Ok(response
+        let results = response
             .page
             .media
             .into_iter()
-            .map(|anime| AnimeSearchResult {
-                id: anime.id.to_string(),
-                title: anime.title.romaji,
-                title_english: anime.title.english,
-                title_japanese: Some(anime.title.native),
-                year: anime.season_year,
-                poster_path: Some(anime.cover_image.large),
-                overview: anime.description,
-                score: anime.average_score.map(|s| f64::from(s) / 10.0),
-                provider: "anilist".to_string(),
+            // Filtered after the fetch; the isAdult search argument is unreliable
+            .filter(|anime| options.include_adult || !anime.is_adult)
+            .map(|anime| {
+                MediaSearchResult::Anime(AnimeSearchResult {
+                    id: anime.id.to_string(),
+                    title: anime.title.preferred(options),
+                    title_english: anime.title.english,
+                    title_japanese: Some(anime.title.native),
+                    year: anime.season_year,
+                    poster_path: Some(anime.cover_image.large),
+                    overview: anime.description,
+                    score: anime.average_score.map(|s| f64::from(s) / 10.0),
+                    adult: anime.is_adult,
+                    provider: "anilist".to_string(),
+                    external_ids: ExternalIds::default(),
+                    also_matched: Vec::new(),
+                })
             })
-            .collect())
+            .collect();
+
+        Ok(SearchPage {
+            results,
+            page: page_info.current_page,
+            total_pages: page_info.last_page,
+            total_results: page_info.total,
+        })
     }
 
-    async fn get_anime_details_internal(&self, id: &str) -> Result<AnimeMetadata> {
+    async fn get_anime_details_internal(
+        &self,
+        id: &str,
+        options: &SearchOptions,
+    ) -> Result<AnimeMetadata> {
         let gql_query = r"
             query ($id: Int) {
                 Media(id: $id, type: ANIME) {
@@ -177,7 +230,7 @@ impl AniListProvider {
 
         Ok(AnimeMetadata {
             id: anime.id.to_string(),
-            title: anime.title.romaji,
+            title: anime.title.preferred(options),
             title_english: anime.title.english,
             title_japanese: Some(anime.title.native),
             start_date: format_date(anime.start_date.as_ref()),
@@ -190,6 +243,8 @@ impl AniListProvider {
             episodes: anime.episodes,
             status: Some(anime.status),
             format: Some(anime.format),
+            studios: Vec::new(),
+            staff: Vec::new(),
             provider: "anilist".to_string(),
             external_ids: ExternalIds {
                 anilist_id: Some(anime.id.to_string()),
@@ -206,20 +261,57 @@ impl MetadataProvider for AniListProvider {
         "anilist"
     }
 
+    fn capabilities(&self) -> ProviderCapabilities {
+        ProviderCapabilities::new([MediaType::Anime]).with_cjk_titles(true)
+    }
+
+    fn quota_exhausted(&self) -> Option<Duration> {
+        self.base.quota_exhausted(self.name())
+    }
+
     fn requires_api_key(&self) -> bool {
         false
     }
 
-    async fn search(&self, query: &str, year: Option<i32>) -> Result<Vec<MediaSearchResult>> {
+    /// Run the smallest useful GraphQL query
+    async fn ping(&self) -> Result<()> {
+        let request = self
+            .base
+            .client
+            .post(ANILIST_API_URL)
+            .header("Accept", "application/json")
+            .json(&serde_json::json!({ "query": "{ GenreCollection }" }));
+        self.base.verify(request).await
+    }
+
+    async fn search(&self, options: &SearchOptions) -> Result<Vec<MediaSearchResult>> {
+        self.search_page(options).await.map(|page| page.results)
+    }
+
+    async fn search_page(&self, options: &SearchOptions) -> Result<SearchPage> {
         // AniList only supports anime searches
-        let anime = self.search_anime_internal(query, year).await?;
-        Ok(anime.into_iter().map(MediaSearchResult::Anime).collect())
+        if !options.allows(MediaType::Anime) {
+            return Ok(SearchPage::single(Vec::new()));
+        }
+
+        self.search_anime_internal(&self.base.localize(options))
+            .await
     }
 
     async fn get_details(&self, result: &MediaSearchResult) -> Result<MediaDetails> {
+        self.get_details_with(result, &SearchOptions::default())
+            .await
+    }
+
+    async fn get_details_with(
+        &self,
+        result: &MediaSearchResult,
+        options: &SearchOptions,
+    ) -> Result<MediaDetails> {
+        let options = &*self.base.localize(options);
         match result {
             MediaSearchResult::Anime(a) => self
-                .get_anime_details_internal(&a.id)
+                .get_anime_details_internal(&a.id, options)
                 .await
                 .map(MediaDetails::Anime),
             MediaSearchResult::Movie(_) => Err(ScraperError::Config(
@@ -257,9 +349,19 @@ struct AniListSearchData {
 
 #[derive(Debug, Deserialize)]
 struct AniListPage {
+    #[serde(rename = "pageInfo")]
+    page_info: AniListPageInfo,
     media: Vec<AniListSearchMedia>,
 }
 
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AniListPageInfo {
+    total: u32,
+    current_page: u32,
+    last_page: u32,
+}
+
 #[derive(Debug, Deserialize)]
 struct AniListSearchMedia {
     id: i32,
@@ -271,6 +373,8 @@ struct AniListSearchMedia {
     description: Option<String>,
     #[serde(rename = "averageScore")]
     average_score: Option<i32>,
+    #[serde(rename = "isAdult", default)]
+    is_adult: bool,
 }
 
 #[derive(Debug, Deserialize)]
@@ -309,6 +413,18 @@ struct AniListTitle {
     native: String,
 }
 
+impl AniListTitle {
+    /// Title in the requested language: native for Japanese, English when
+    /// available for English, romaji otherwise
+    fn preferred(&self, options: &SearchOptions) -> String {
+        match options.primary_language().as_deref() {
+            Some("ja") => self.native.clone(),
+            Some("en") => self.english.clone().unwrap_or_else(|| self.romaji.clone()),
+            _ => self.romaji.clone(),
+        }
+    }
+}
+
 #[derive(Debug, Deserialize)]
 struct AniListCoverImage {
     large: String,
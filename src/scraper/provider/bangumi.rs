@@ -1,14 +1,30 @@
 use super::{ProviderBase, ProviderConfig};
 use crate::scraper::{
     AnimeMetadata, AnimeSearchResult, EpisodeMetadata, ExternalIds, MediaDetails,
-    MediaSearchResult, MetadataProvider, Result, ScraperError,
+    MediaSearchResult, MediaType, MetadataProvider, ProviderCapabilities, RateLimitConfig,
+    RateLimitStrategy, Result, ScraperError, SearchOptions, StaffCredit,
 };
 use async_trait::async_trait;
 use serde::Deserialize;
-use std::sync::Arc;
+use std::{sync::Arc, time::Duration};
 
 const BANGUMI_API_URL: &str = "https://api.bgm.tv";
 
+/// Results per search page
+const SEARCH_PAGE_SIZE: u32 = 25;
+
+/// Bangumi staff relations worth keeping, with their English role names
+const STAFF_ROLES: &[(&str, &str)] = &[
+    ("导演", "Director"),
+    ("原作", "Original Creator"),
+    ("系列构成", "Series Composition"),
+    ("人物设定", "Character Design"),
+    ("音乐", "Music"),
+];
+
+/// Relation used for the production studio
+const STUDIO_RELATION: &str = "动画制作";
+
 /// Bangumi Provider
 pub struct BangumiProvider {
     base: ProviderBase,
@@ -16,15 +32,45 @@ pub struct BangumiProvider {
 
 impl BangumiProvider {
     /// Create a new Bangumi provider (no API key required)
+    ///
+    /// `rate_limit` overrides the default of 10 requests per second.
     #[must_use]
-    pub fn new(cache: Arc<crate::scraper::ScraperCache>) -> Self {
-        let config = ProviderConfig::new(BANGUMI_API_URL).with_cache_ttl(86400); // 24 hours
+    pub fn new(
+        cache: Arc<crate::scraper::ScraperCache>,
+        rate_limit: Option<RateLimitConfig>,
+    ) -> Self {
+        Self::with_config(Self::default_config(rate_limit), cache)
+    }
+
+    /// Configuration used by [`new`](Self::new), to adjust before `with_config`
+    #[must_use]
+    pub fn default_config(rate_limit: Option<RateLimitConfig>) -> ProviderConfig {
+        ProviderConfig::new(BANGUMI_API_URL)
+            .with_rate_limit(rate_limit.unwrap_or_else(Self::default_rate_limit))
+            .with_cache_ttl(86400) // 24 hours
+    }
 
+    /// Create a new Bangumi provider with a custom configuration
+    #[must_use]
+    pub fn with_config(config: ProviderConfig, cache: Arc<crate::scraper::ScraperCache>) -> Self {
         Self {
             base: ProviderBase::new(config, cache),
         }
     }
 
+    /// Bangumi publishes no hard limit, so stay polite
+    #[must_use]
+    pub const fn default_rate_limit() -> RateLimitConfig {
+        RateLimitConfig {
+            max_concurrent: 3,
+            max_requests: 10,
+            window_seconds: 1,
+            daily_quota: None,
+            strategy: RateLimitStrategy::Sliding,
+            burst: None,
+        }
+    }
+
     /// Execute Bangumi API request
     async fn request<T: for<'de> Deserialize<'de>>(&self, endpoint: &str) -> Result<T> {
         let url = format!("{BANGUMI_API_URL}{endpoint}");
@@ -49,11 +95,13 @@ impl BangumiProvider {
     // Private helper methods
     async fn search_anime_internal(
         &self,
-        query: &str,
-        _year: Option<i32>,
+        options: &SearchOptions,
     ) -> Result<Vec<AnimeSearchResult>> {
-        let encoded_query = urlencoding::encode(query);
-        let endpoint = format!("/search/subject/{encoded_query}?type=2&responseGroup=small");
+        let encoded_query = urlencoding::encode(&options.query);
+        let start = (options.page.unwrap_or(1).max(1) - 1) * SEARCH_PAGE_SIZE;
+        let endpoint = format!(
+            "/search/subject/{encoded_query}?type=2&responseGroup=small&start={start}&max_results={SEARCH_PAGE_SIZE}"
+        );
 
         let response: BangumiSearchResponse = self.request(&endpoint).await?;
 
@@ -63,10 +111,7 @@ impl BangumiProvider {
             .into_iter()
             .map(|subject| AnimeSearchResult {
                 id: subject.id.to_string(),
-                title: subject
-                    .name_cn
-                    .clone()
-                    .unwrap_or_else(|| subject.name.clone()),
+                title: display_title(&subject.name, subject.name_cn.as_deref(), options),
                 title_english: None,
                 title_japanese: Some(subject.name),
                 year: subject
@@ -77,25 +122,41 @@ impl BangumiProvider {
                 poster_path: subject.images.as_ref().map(|i| i.large.clone()),
                 overview: subject.summary,
                 score: subject.score,
+                adult: false,
                 provider: "bangumi".to_string(),
+                external_ids: ExternalIds::default(),
+                also_matched: Vec::new(),
             })
             .collect())
     }
 
-    async fn get_anime_details_internal(&self, id: &str) -> Result<AnimeMetadata> {
+    async fn get_anime_details_internal(
+        &self,
+        id: &str,
+        options: &SearchOptions,
+    ) -> Result<AnimeMetadata> {
         let endpoint = format!("/v0/subjects/{id}");
         let subject: BangumiSubject = self.request(&endpoint).await?;
 
         // Extract titles
-        let title_cn = subject
-            .name_cn
-            .clone()
-            .unwrap_or_else(|| subject.name.clone());
+        let title = display_title(&subject.name, subject.name_cn.as_deref(), options);
         let title_jp = subject.name.clone();
 
         // Extract date
         let start_date = subject.date.clone();
 
+        // Staff is optional; a failed lookup shouldn't fail the whole scrape
+        let (studios, staff) = match self
+            .request::<Vec<BangumiPerson>>(&format!("/v0/subjects/{id}/persons"))
+            .await
+        {
+            Ok(persons) => extract_staff(persons),
+            Err(e) => {
+                tracing::debug!("Failed to fetch Bangumi staff for {id}: {e}");
+                (Vec::new(), Vec::new())
+            }
+        };
+
         // Extract format
         let format = match subject.type_ {
             2 => "TV".to_string(),
@@ -105,7 +166,7 @@ impl BangumiProvider {
 
         Ok(AnimeMetadata {
             id: subject.id.to_string(),
-            title: title_cn,
+            title,
             title_english: None,
             title_japanese: Some(title_jp),
             start_date,
@@ -118,6 +179,8 @@ impl BangumiProvider {
             episodes: subject.eps,
             status: None,
             format: Some(format),
+            studios,
+            staff,
             provider: "bangumi".to_string(),
             external_ids: ExternalIds {
                 bangumi_id: Some(subject.id.to_string()),
@@ -127,26 +190,90 @@ impl BangumiProvider {
     }
 }
 
+/// Split Bangumi subject persons into studios and key staff credits
+/// Pick the Chinese title unless another language was requested
+///
+/// Bangumi's `name` is the original (usually Japanese) title.
+fn display_title(name: &str, name_cn: Option<&str>, options: &SearchOptions) -> String {
+    let prefer_native = options
+        .primary_language()
+        .is_some_and(|language| language != "zh");
+
+    match name_cn {
+        Some(name_cn) if !prefer_native && !name_cn.is_empty() => name_cn.to_string(),
+        _ => name.to_string(),
+    }
+}
+
+fn extract_staff(persons: Vec<BangumiPerson>) -> (Vec<String>, Vec<StaffCredit>) {
+    let mut studios = Vec::new();
+    let mut staff = Vec::new();
+
+    for person in persons {
+        if person.relation == STUDIO_RELATION {
+            studios.push(person.name);
+        } else if let Some((_, role)) = STAFF_ROLES
+            .iter()
+            .find(|(relation, _)| *relation == person.relation)
+        {
+            staff.push(StaffCredit {
+                role: (*role).to_string(),
+                name: person.name,
+            });
+        }
+    }
+
+    (studios, staff)
+}
+
 #[async_trait]
 impl MetadataProvider for BangumiProvider {
     fn name(&self) -> &'static str {
         "bangumi"
     }
 
+    fn capabilities(&self) -> ProviderCapabilities {
+        ProviderCapabilities::new([MediaType::Anime]).with_cjk_titles(true)
+    }
+
+    fn quota_exhausted(&self) -> Option<Duration> {
+        self.base.quota_exhausted(self.name())
+    }
+
     fn requires_api_key(&self) -> bool {
         false
     }
 
-    async fn search(&self, query: &str, year: Option<i32>) -> Result<Vec<MediaSearchResult>> {
+    async fn ping(&self) -> Result<()> {
+        self.base.ping().await
+    }
+
+    async fn search(&self, options: &SearchOptions) -> Result<Vec<MediaSearchResult>> {
         // Bangumi only supports anime/manga searches
-        let anime = self.search_anime_internal(query, year).await?;
+        if !options.allows(MediaType::Anime) {
+            return Ok(Vec::new());
+        }
+
+        let anime = self
+            .search_anime_internal(&self.base.localize(options))
+            .await?;
         Ok(anime.into_iter().map(MediaSearchResult::Anime).collect())
     }
 
     async fn get_details(&self, result: &MediaSearchResult) -> Result<MediaDetails> {
+        self.get_details_with(result, &SearchOptions::default())
+            .await
+    }
+
+    async fn get_details_with(
+        &self,
+        result: &MediaSearchResult,
+        options: &SearchOptions,
+    ) -> Result<MediaDetails> {
+        let options = &*self.base.localize(options);
         match result {
             MediaSearchResult::Anime(a) => self
-                .get_anime_details_internal(&a.id)
+                .get_anime_details_internal(&a.id, options)
                 .await
                 .map(MediaDetails::Anime),
             MediaSearchResult::Movie(_) => Err(ScraperError::Config(
@@ -217,3 +344,34 @@ struct BangumiRating {
 struct BangumiTag {
     name: String,
 }
+
+#[derive(Debug, Deserialize)]
+struct BangumiPerson {
+    name: String,
+    relation: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_director_and_studio() {
+        let persons: Vec<BangumiPerson> =
+            serde_json::from_str(include_str!("fixtures/bangumi_persons.json")).unwrap();
+
+        let (studios, staff) = extract_staff(persons);
+
+        assert_eq!(studios, vec!["サンライズ".to_string()]);
+        assert!(staff.contains(&StaffCredit {
+            role: "Director".to_string(),
+            name: "渡辺信一郎".to_string(),
+        }));
+        assert!(staff.contains(&StaffCredit {
+            role: "Original Creator".to_string(),
+            name: "矢立肇".to_string(),
+        }));
+        // Per-episode credits such as key animation are dropped
+        assert!(staff.iter().all(|credit| credit.name != "川元利浩"));
+    }
+}
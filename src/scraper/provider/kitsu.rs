@@ -0,0 +1,540 @@
+use super::{ProviderBase, ProviderConfig};
+use crate::scraper::{
+    AnimeMetadata, AnimeSearchResult, EpisodeMetadata, ExternalIds, MediaDetails,
+    MediaSearchResult, MediaType, MetadataProvider, ProviderCapabilities, RateLimitConfig,
+    RateLimitStrategy, Result, ScraperError, SearchOptions, SearchPage, cache::CacheKey,
+};
+use async_trait::async_trait;
+use serde::Deserialize;
+use std::{sync::Arc, time::Duration};
+
+const KITSU_API_URL: &str = "https://kitsu.io/api/edge";
+
+/// Results per search page
+const SEARCH_PAGE_SIZE: u32 = 20;
+
+/// Episodes per page; Kitsu caps `page[limit]` at 20
+const EPISODE_PAGE_SIZE: u32 = 20;
+
+/// Upper bound on episode pages fetched for one series
+const MAX_EPISODE_PAGES: u32 = 100;
+
+/// Kitsu Provider
+pub struct KitsuProvider {
+    base: ProviderBase,
+}
+
+impl KitsuProvider {
+    /// Create a new Kitsu provider (no API key required)
+    ///
+    /// `rate_limit` overrides the default of 10 requests per second.
+    #[must_use]
+    pub fn new(
+        cache: Arc<crate::scraper::ScraperCache>,
+        rate_limit: Option<RateLimitConfig>,
+    ) -> Self {
+        Self::with_config(Self::default_config(rate_limit), cache)
+    }
+
+    /// Configuration used by [`new`](Self::new), to adjust before `with_config`
+    #[must_use]
+    pub fn default_config(rate_limit: Option<RateLimitConfig>) -> ProviderConfig {
+        ProviderConfig::new(KITSU_API_URL)
+            .with_rate_limit(rate_limit.unwrap_or_else(Self::default_rate_limit))
+            .with_cache_ttl(86400) // 24 hours
+    }
+
+    /// Create a new Kitsu provider with a custom configuration
+    #[must_use]
+    pub fn with_config(config: ProviderConfig, cache: Arc<crate::scraper::ScraperCache>) -> Self {
+        Self {
+            base: ProviderBase::new(config, cache),
+        }
+    }
+
+    /// Kitsu publishes no hard limit, so stay polite
+    #[must_use]
+    pub const fn default_rate_limit() -> RateLimitConfig {
+        RateLimitConfig {
+            max_concurrent: 3,
+            max_requests: 10,
+            window_seconds: 1,
+            daily_quota: None,
+            strategy: RateLimitStrategy::Sliding,
+            burst: None,
+        }
+    }
+
+    /// Execute Kitsu API request
+    async fn request<T: for<'de> Deserialize<'de>>(&self, endpoint: &str) -> Result<T> {
+        let url = format!("{}{endpoint}", self.base.config.base_url);
+
+        let response = self.base.get_with_rate_limit("kitsu", &url).await?;
+
+        if !response.status().is_success() {
+            let status = response.status().as_u16();
+            let text = response.text().await.unwrap_or_default();
+            return Err(ScraperError::Api {
+                status,
+                message: text,
+            });
+        }
+
+        response
+            .json::<T>()
+            .await
+            .map_err(|e| ScraperError::Parse(format!("Failed to parse Kitsu response: {e}")))
+    }
+
+    async fn search_anime_internal(&self, options: &SearchOptions) -> Result<SearchPage> {
+        let page = options.page.unwrap_or(1).max(1);
+        let mut endpoint = format!(
+            "/anime?filter[text]={}&page[limit]={SEARCH_PAGE_SIZE}&page[offset]={}",
+            urlencoding::encode(&options.query),
+            (page - 1) * SEARCH_PAGE_SIZE
+        );
+        if let Some(year) = options.year {
+            endpoint.push_str(&format!("&filter[seasonYear]={year}"));
+        }
+
+        let response: KitsuResponse<Vec<KitsuResource<KitsuAnime>>> =
+            self.request(&endpoint).await?;
+        let total_results = response.meta.map_or(0, |meta| meta.count);
+
+        let results = response
+            .data
+            .into_iter()
+            .filter(|anime| options.include_adult || !anime.attributes.nsfw)
+            .map(|anime| {
+                let attributes = anime.attributes;
+                MediaSearchResult::Anime(AnimeSearchResult {
+                    title: attributes.preferred_title(options),
+                    title_english: attributes.titles.en.clone(),
+                    title_japanese: attributes.titles.ja_jp.clone(),
+                    year: attributes.year(),
+                    poster_path: attributes.poster(),
+                    score: attributes.score(),
+                    adult: attributes.nsfw,
+                    overview: attributes.synopsis,
+                    id: anime.id,
+                    provider: "kitsu".to_string(),
+                    external_ids: ExternalIds::default(),
+                    also_matched: Vec::new(),
+                })
+            })
+            .collect();
+
+        Ok(SearchPage {
+            results,
+            page,
+            total_pages: total_results.div_ceil(SEARCH_PAGE_SIZE),
+            total_results,
+        })
+    }
+
+    async fn get_anime_details_internal(
+        &self,
+        id: &str,
+        options: &SearchOptions,
+    ) -> Result<AnimeMetadata> {
+        let endpoint = format!("/anime/{id}?include=categories,mappings");
+        let response: KitsuResponse<KitsuResource<KitsuAnime>> = self.request(&endpoint).await?;
+        let anime = response.data.attributes;
+
+        let mut genres = Vec::new();
+        let mut external_ids = ExternalIds::default();
+        for included in response.included {
+            match included {
+                KitsuIncluded::Category { attributes } => genres.push(attributes.title),
+                KitsuIncluded::Mapping { attributes } => {
+                    if let Some(provider) = mapped_provider(&attributes.external_site) {
+                        external_ids.set(provider, &attributes.external_id);
+                    }
+                }
+                KitsuIncluded::Other => {}
+            }
+        }
+
+        Ok(AnimeMetadata {
+            id: response.data.id,
+            title: anime.preferred_title(options),
+            title_english: anime.titles.en.clone(),
+            title_japanese: anime.titles.ja_jp.clone(),
+            poster_path: anime.poster(),
+            backdrop_path: anime
+                .cover_image
+                .as_ref()
+                .and_then(|image| image.large.clone().or_else(|| image.original.clone())),
+            score: anime.score(),
+            start_date: anime.start_date,
+            end_date: anime.end_date,
+            overview: anime.synopsis,
+            genres,
+            episodes: anime.episode_count,
+            status: anime.status,
+            format: anime.subtype,
+            studios: Vec::new(),
+            staff: Vec::new(),
+            provider: "kitsu".to_string(),
+            external_ids,
+        })
+    }
+
+    /// List every episode of a series, in episode order
+    ///
+    /// Kitsu pages episodes with `page[limit]`/`page[offset]`; the full list
+    /// is cached so later lookups in the same series skip the API.
+    pub async fn list_episodes(&self, series_id: &str) -> Result<Vec<EpisodeMetadata>> {
+        let key = CacheKey::new("kitsu", "episodes", series_id);
+        if let Some(episodes) = self.base.cache.get::<Vec<EpisodeMetadata>>(&key).await {
+            return Ok(episodes);
+        }
+
+        let mut episodes = Vec::new();
+        for page in 0..MAX_EPISODE_PAGES {
+            let endpoint = format!(
+                "/anime/{series_id}/episodes?sort=number&page[limit]={EPISODE_PAGE_SIZE}&page[offset]={}",
+                page * EPISODE_PAGE_SIZE
+            );
+            let response: KitsuResponse<Vec<KitsuResource<KitsuEpisode>>> =
+                self.request(&endpoint).await?;
+            let fetched = response.data.len();
+            episodes.extend(response.data.into_iter().map(KitsuResource::into_metadata));
+
+            let total = response.meta.map_or(0, |meta| meta.count);
+            if fetched < EPISODE_PAGE_SIZE as usize || episodes.len() >= total as usize {
+                break;
+            }
+        }
+
+        if let Err(e) = self.base.cache.set(key, &episodes).await {
+            tracing::debug!("Failed to cache Kitsu episodes for {series_id}: {e}");
+        }
+        Ok(episodes)
+    }
+}
+
+/// `ExternalIds` slot for a Kitsu mapping site, if there is one
+fn mapped_provider(external_site: &str) -> Option<&'static str> {
+    match external_site {
+        "myanimelist/anime" => Some("mal"),
+        "anilist/anime" => Some("anilist"),
+        "thetvdb" | "thetvdb/series" => Some("tvdb"),
+        _ => None,
+    }
+}
+
+#[async_trait]
+impl MetadataProvider for KitsuProvider {
+    fn name(&self) -> &'static str {
+        "kitsu"
+    }
+
+    fn capabilities(&self) -> ProviderCapabilities {
+        ProviderCapabilities::new([MediaType::Anime])
+            .with_episode_details(true)
+            .with_cjk_titles(true)
+    }
+
+    fn quota_exhausted(&self) -> Option<Duration> {
+        self.base.quota_exhausted(self.name())
+    }
+
+    fn requires_api_key(&self) -> bool {
+        false
+    }
+
+    async fn ping(&self) -> Result<()> {
+        self.base.ping().await
+    }
+
+    async fn search(&self, options: &SearchOptions) -> Result<Vec<MediaSearchResult>> {
+        self.search_page(options).await.map(|page| page.results)
+    }
+
+    async fn search_page(&self, options: &SearchOptions) -> Result<SearchPage> {
+        // Kitsu only covers anime here
+        if !options.allows(MediaType::Anime) {
+            return Ok(SearchPage::single(Vec::new()));
+        }
+
+        self.search_anime_internal(&self.base.localize(options))
+            .await
+    }
+
+    async fn get_details(&self, result: &MediaSearchResult) -> Result<MediaDetails> {
+        self.get_details_with(result, &SearchOptions::default())
+            .await
+    }
+
+    async fn get_details_with(
+        &self,
+        result: &MediaSearchResult,
+        options: &SearchOptions,
+    ) -> Result<MediaDetails> {
+        let options = &*self.base.localize(options);
+        match result {
+            MediaSearchResult::Anime(a) => self
+                .get_anime_details_internal(&a.id, options)
+                .await
+                .map(MediaDetails::Anime),
+            MediaSearchResult::Movie(_) | MediaSearchResult::Tv(_) => Err(ScraperError::Config(
+                "Kitsu specializes in anime".to_string(),
+            )),
+        }
+    }
+
+    /// Look an episode up by season and its number within the season
+    ///
+    /// Kitsu usually lists each season as its own series, so most IDs only
+    /// have a season 1.
+    async fn get_episode_details(
+        &self,
+        series_id: &str,
+        season: i32,
+        episode: i32,
+    ) -> Result<EpisodeMetadata> {
+        let episodes = self.list_episodes(series_id).await?;
+
+        episodes
+            .into_iter()
+            .find(|ep| ep.season_number == season && ep.episode_number == episode)
+            .ok_or_else(|| {
+                ScraperError::NotFound(format!("Episode {episode} not found in season {season}"))
+            })
+    }
+}
+
+// Kitsu API Response Types (JSON:API)
+#[derive(Debug, Deserialize)]
+struct KitsuResponse<T> {
+    data: T,
+    #[serde(default)]
+    included: Vec<KitsuIncluded>,
+    meta: Option<KitsuMeta>,
+}
+
+#[derive(Debug, Deserialize)]
+struct KitsuMeta {
+    count: u32,
+}
+
+#[derive(Debug, Deserialize)]
+struct KitsuResource<A> {
+    id: String,
+    attributes: A,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct KitsuAnime {
+    canonical_title: String,
+    #[serde(default)]
+    titles: KitsuTitles,
+    synopsis: Option<String>,
+    start_date: Option<String>,
+    end_date: Option<String>,
+    /// Percentage, as a string such as `"82.27"`
+    average_rating: Option<String>,
+    poster_image: Option<KitsuImage>,
+    cover_image: Option<KitsuImage>,
+    episode_count: Option<i32>,
+    status: Option<String>,
+    subtype: Option<String>,
+    #[serde(default)]
+    nsfw: bool,
+}
+
+impl KitsuAnime {
+    /// Title in the requested language, falling back to the canonical one
+    fn preferred_title(&self, options: &SearchOptions) -> String {
+        let localized = match options.primary_language().as_deref() {
+            Some("ja") => self.titles.ja_jp.as_ref(),
+            Some("en") => self.titles.en.as_ref(),
+            _ => None,
+        };
+        localized
+            .filter(|title| !title.is_empty())
+            .unwrap_or(&self.canonical_title)
+            .clone()
+    }
+
+    fn year(&self) -> Option<i32> {
+        self.start_date.as_ref()?.split('-').next()?.parse().ok()
+    }
+
+    fn poster(&self) -> Option<String> {
+        let image = self.poster_image.as_ref()?;
+        image.large.clone().or_else(|| image.original.clone())
+    }
+
+    /// Score out of 10, like the other anime providers
+    fn score(&self) -> Option<f64> {
+        self.average_rating
+            .as_ref()?
+            .parse::<f64>()
+            .ok()
+            .map(|rating| rating / 10.0)
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct KitsuTitles {
+    en: Option<String>,
+    ja_jp: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct KitsuImage {
+    large: Option<String>,
+    original: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type")]
+enum KitsuIncluded {
+    #[serde(rename = "categories")]
+    Category { attributes: KitsuCategory },
+    #[serde(rename = "mappings")]
+    Mapping { attributes: KitsuMapping },
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Debug, Deserialize)]
+struct KitsuCategory {
+    title: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct KitsuMapping {
+    external_site: String,
+    external_id: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct KitsuEpisode {
+    canonical_title: Option<String>,
+    season_number: Option<i32>,
+    number: Option<i32>,
+    relative_number: Option<i32>,
+    synopsis: Option<String>,
+    airdate: Option<String>,
+    /// Minutes
+    length: Option<i32>,
+    thumbnail: Option<KitsuImage>,
+}
+
+impl KitsuResource<KitsuEpisode> {
+    fn into_metadata(self) -> EpisodeMetadata {
+        let episode = self.attributes;
+        let number = episode.relative_number.or(episode.number).unwrap_or(0);
+
+        EpisodeMetadata {
+            id: self.id,
+            name: episode
+                .canonical_title
+                .filter(|title| !title.is_empty())
+                .unwrap_or_else(|| format!("Episode {number}")),
+            season_number: episode.season_number.unwrap_or(1),
+            episode_number: number,
+            air_date: episode.airdate,
+            overview: episode.synopsis,
+            still_path: episode.thumbnail.and_then(|image| image.original),
+            runtime: episode.length,
+            vote_average: None,
+            provider: "kitsu".to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::{
+        Mock, MockServer, ResponseTemplate,
+        matchers::{method, path, query_param},
+    };
+
+    fn provider(server: &MockServer) -> KitsuProvider {
+        KitsuProvider::with_config(
+            ProviderConfig::new(server.uri()),
+            Arc::new(crate::scraper::ScraperCache::new()),
+        )
+    }
+
+    fn fixture(json: &str) -> ResponseTemplate {
+        ResponseTemplate::new(200)
+            .set_body_raw(json.as_bytes().to_vec(), "application/vnd.api+json")
+    }
+
+    #[tokio::test]
+    async fn test_search_maps_anime_and_pages() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/anime"))
+            .and(query_param("filter[text]", "Cowboy Bebop"))
+            .and(query_param("page[limit]", "20"))
+            .and(query_param("page[offset]", "20"))
+            .respond_with(fixture(include_str!("fixtures/kitsu_search_anime.json")))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let options = SearchOptions::new("Cowboy Bebop").with_page(2);
+        let page = provider(&server).search_page(&options).await.unwrap();
+
+        assert_eq!(page.page, 2);
+        assert_eq!(page.total_results, 45);
+        assert_eq!(page.total_pages, 3);
+        // The nsfw entry is dropped
+        assert_eq!(page.results.len(), 1);
+        let MediaSearchResult::Anime(anime) = &page.results[0] else {
+            panic!("expected an anime result");
+        };
+        assert_eq!(anime.id, "1");
+        assert_eq!(anime.title, "Cowboy Bebop");
+        assert_eq!(anime.title_japanese.as_deref(), Some("カウボーイビバップ"));
+        assert_eq!(anime.year, Some(1998));
+        assert_eq!(
+            anime.poster_path.as_deref(),
+            Some("https://media.kitsu.app/anime/poster_images/1/large.jpg")
+        );
+        assert_eq!(anime.score, Some(8.227));
+    }
+
+    #[tokio::test]
+    async fn test_episode_lookup_follows_pagination() {
+        let server = MockServer::start().await;
+        for (offset, body) in [
+            ("0", include_str!("fixtures/kitsu_episodes_page1.json")),
+            ("20", include_str!("fixtures/kitsu_episodes_page2.json")),
+        ] {
+            Mock::given(method("GET"))
+                .and(path("/anime/1/episodes"))
+                .and(query_param("page[limit]", "20"))
+                .and(query_param("page[offset]", offset))
+                .respond_with(fixture(body))
+                .expect(1)
+                .mount(&server)
+                .await;
+        }
+        let provider = provider(&server);
+
+        let episode = provider.get_episode_details("1", 1, 24).await.unwrap();
+        assert_eq!(episode.name, "Hard Luck Woman");
+        assert_eq!(episode.season_number, 1);
+        assert_eq!(episode.episode_number, 24);
+        assert_eq!(episode.air_date.as_deref(), Some("1999-04-02"));
+        assert_eq!(episode.runtime, Some(24));
+
+        // Served from the cached listing
+        assert_eq!(provider.list_episodes("1").await.unwrap().len(), 26);
+        assert!(matches!(
+            provider.get_episode_details("1", 1, 27).await,
+            Err(ScraperError::NotFound(_))
+        ));
+    }
+}
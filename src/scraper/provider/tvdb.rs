@@ -1,48 +1,93 @@
 use super::{ProviderBase, ProviderConfig};
 use crate::scraper::{
-    EpisodeMetadata, ExternalIds, MediaDetails, MediaSearchResult, MetadataProvider, Result,
-    ScraperError, TvMetadata, TvSearchResult,
+    EpisodeMetadata, ExternalIds, MediaDetails, MediaSearchResult, MediaType, MetadataProvider,
+    ProviderCapabilities, RateLimitConfig, Result, ScraperError, SearchOptions, TvMetadata,
+    TvSearchResult,
 };
 use async_trait::async_trait;
 use serde::Deserialize;
-use std::sync::Arc;
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 const TVDB_API_URL: &str = "https://api4.thetvdb.com/v4";
 
+/// How long a login token is reused before logging in again
+const DEFAULT_TOKEN_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// TVDB login token and when it was issued
+struct TvdbToken {
+    value: String,
+    issued_at: Instant,
+}
+
 /// TVDB Provider
 pub struct TvdbProvider {
     base: ProviderBase,
     api_key: String,
-    token: parking_lot::RwLock<Option<String>>,
+    token: parking_lot::RwLock<Option<TvdbToken>>,
+    token_ttl: Duration,
 }
 
 impl TvdbProvider {
     /// Create a new TVDB provider
-    pub fn new(api_key: impl Into<String>, cache: Arc<crate::scraper::ScraperCache>) -> Self {
+    ///
+    /// `rate_limit` overrides the default of 40 requests per 10 seconds.
+    pub fn new(
+        api_key: impl Into<String>,
+        cache: Arc<crate::scraper::ScraperCache>,
+        rate_limit: Option<RateLimitConfig>,
+    ) -> Self {
         let api_key = api_key.into();
-        let config = ProviderConfig::new(TVDB_API_URL)
-            .with_api_key(api_key.clone())
-            .with_cache_ttl(86400); // 24 hours
+        let config = Self::default_config(rate_limit).with_api_key(api_key.clone());
+
+        Self::with_config(api_key, config, cache)
+    }
 
+    /// Configuration used by [`new`](Self::new), to adjust before `with_config`
+    #[must_use]
+    pub fn default_config(rate_limit: Option<RateLimitConfig>) -> ProviderConfig {
+        ProviderConfig::new(TVDB_API_URL)
+            .with_rate_limit(rate_limit.unwrap_or_default())
+            .with_cache_ttl(86400) // 24 hours
+    }
+
+    /// Create a new TVDB provider with a custom configuration
+    pub fn with_config(
+        api_key: impl Into<String>,
+        config: ProviderConfig,
+        cache: Arc<crate::scraper::ScraperCache>,
+    ) -> Self {
         Self {
             base: ProviderBase::new(config, cache),
-            api_key,
+            api_key: api_key.into(),
             token: parking_lot::RwLock::new(None),
+            token_ttl: DEFAULT_TOKEN_TTL,
         }
     }
 
-    /// Get authentication token
+    /// Set how long a login token is reused before it is refreshed
+    #[must_use]
+    pub const fn with_token_ttl(mut self, ttl: Duration) -> Self {
+        self.token_ttl = ttl;
+        self
+    }
+
+    /// Get authentication token, logging in again once the cached one has expired
     async fn get_token(&self) -> Result<String> {
-        // Check if token already exists
+        // Check if a fresh token already exists
         {
             let token = self.token.read();
-            if let Some(ref t) = *token {
-                return Ok(t.clone());
+            if let Some(ref t) = *token
+                && t.issued_at.elapsed() < self.token_ttl
+            {
+                return Ok(t.value.clone());
             }
         }
 
         // Login to get new token
-        let login_url = format!("{TVDB_API_URL}/login");
+        let login_url = format!("{}/login", self.base.config.base_url);
         let body = serde_json::json!({
             "apikey": self.api_key
         });
@@ -68,24 +113,39 @@ impl TvdbProvider {
         })?;
 
         let token = login_response.data.token;
-        *self.token.write() = Some(token.clone());
+        *self.token.write() = Some(TvdbToken {
+            value: token.clone(),
+            issued_at: Instant::now(),
+        });
 
         Ok(token)
     }
 
-    /// Execute TVDB API request
-    async fn request<T: for<'de> Deserialize<'de>>(&self, endpoint: &str) -> Result<T> {
-        let token = self.get_token().await?;
-        let url = format!("{TVDB_API_URL}{endpoint}");
-
-        let response = self
-            .base
+    /// Send an authenticated GET request
+    async fn send(&self, url: &str, token: &str) -> Result<reqwest::Response> {
+        self.base
             .client
-            .get(&url)
+            .get(url)
             .header("Authorization", format!("Bearer {token}"))
             .send()
             .await
-            .map_err(ScraperError::Network)?;
+            .map_err(ScraperError::Network)
+    }
+
+    /// Execute TVDB API request
+    ///
+    /// A `401` means the token was revoked or expired early, so the request is
+    /// retried once with a freshly issued token.
+    async fn request<T: for<'de> Deserialize<'de>>(&self, endpoint: &str) -> Result<T> {
+        let url = format!("{}{endpoint}", self.base.config.base_url);
+        let token = self.get_token().await?;
+        let mut response = self.send(&url, &token).await?;
+
+        if response.status() == reqwest::StatusCode::UNAUTHORIZED {
+            *self.token.write() = None;
+            let token = self.get_token().await?;
+            response = self.send(&url, &token).await?;
+        }
 
         if !response.status().is_success() {
             let status = response.status().as_u16();
@@ -103,13 +163,12 @@ impl TvdbProvider {
     }
 
     // Private helper methods
-    async fn search_tv_internal(
-        &self,
-        query: &str,
-        _year: Option<i32>,
-    ) -> Result<Vec<TvSearchResult>> {
-        let encoded_query = urlencoding::encode(query);
-        let endpoint = format!("/search?query={encoded_query}&type=series");
+    async fn search_tv_internal(&self, options: &SearchOptions) -> Result<Vec<TvSearchResult>> {
+        let encoded_query = urlencoding::encode(&options.query);
+        let mut endpoint = format!("/search?query={encoded_query}&type=series");
+        if let Some(year) = options.year {
+            endpoint.push_str(&format!("&year={year}"));
+        }
 
         let response: TvdbSearchResponse = self.request(&endpoint).await?;
 
@@ -124,7 +183,10 @@ impl TvdbProvider {
                 poster_path: series.image_url,
                 overview: series.overview,
                 vote_average: None,
+                adult: false,
                 provider: "tvdb".to_string(),
+                external_ids: ExternalIds::default(),
+                also_matched: Vec::new(),
             })
             .collect())
     }
@@ -153,10 +215,14 @@ impl TvdbProvider {
                 .collect(),
             number_of_seasons: None,
             number_of_episodes: None,
+            seasons: vec![],
             episode_run_time: vec![],
             status: Some(series.status.name),
             original_language: series.original_language,
             production_companies: vec![],
+            cast: vec![],
+            director: None,
+            writers: vec![],
             provider: "tvdb".to_string(),
             external_ids: ExternalIds {
                 tvdb_id: Some(series.id.to_string()),
@@ -172,13 +238,30 @@ impl MetadataProvider for TvdbProvider {
         "tvdb"
     }
 
+    fn capabilities(&self) -> ProviderCapabilities {
+        ProviderCapabilities::new([MediaType::Tv]).with_episode_details(true)
+    }
+
+    fn quota_exhausted(&self) -> Option<Duration> {
+        self.base.quota_exhausted(self.name())
+    }
+
     fn requires_api_key(&self) -> bool {
         true
     }
 
-    async fn search(&self, query: &str, year: Option<i32>) -> Result<Vec<MediaSearchResult>> {
+    /// Log in, which fails on a rejected API key
+    async fn ping(&self) -> Result<()> {
+        self.get_token().await.map(|_| ())
+    }
+
+    async fn search(&self, options: &SearchOptions) -> Result<Vec<MediaSearchResult>> {
         // TVDB only supports TV show searches
-        let tv_shows = self.search_tv_internal(query, year).await?;
+        if !options.allows(MediaType::Tv) {
+            return Ok(Vec::new());
+        }
+
+        let tv_shows = self.search_tv_internal(options).await?;
         Ok(tv_shows.into_iter().map(MediaSearchResult::Tv).collect())
     }
 
@@ -308,3 +391,59 @@ struct TvdbEpisode {
     image: Option<String>,
     runtime: Option<i32>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::{
+        Mock, MockServer, ResponseTemplate,
+        matchers::{header, method, path},
+    };
+
+    fn login_response(token: &str) -> ResponseTemplate {
+        ResponseTemplate::new(200).set_body_json(serde_json::json!({ "data": { "token": token } }))
+    }
+
+    #[tokio::test]
+    async fn test_unauthorized_response_triggers_relogin() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/login"))
+            .respond_with(login_response("stale"))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/login"))
+            .respond_with(login_response("fresh"))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/series/42/extended"))
+            .and(header("Authorization", "Bearer stale"))
+            .respond_with(ResponseTemplate::new(401))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/series/42/extended"))
+            .and(header("Authorization", "Bearer fresh"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "data": {
+                    "id": 42,
+                    "name": "Twin Peaks",
+                    "status": { "name": "Ended" }
+                }
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let cache = Arc::new(crate::scraper::ScraperCache::new());
+        let provider = TvdbProvider::with_config("key", ProviderConfig::new(server.uri()), cache);
+
+        let details = provider.get_tv_details_internal("42").await.unwrap();
+        assert_eq!(details.name, "Twin Peaks");
+    }
+}
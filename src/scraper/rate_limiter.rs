@@ -1,14 +1,49 @@
 use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tokio::sync::Semaphore;
 
+use super::ScraperError;
+
+/// Period a daily quota covers, starting from the first request in it
+const QUOTA_PERIOD: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// How requests are spread over a window
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RateLimitStrategy {
+    /// At most `max_requests` in any `window_seconds`
+    #[default]
+    Sliding,
+    /// Tokens refill at `max_requests` per `window_seconds` up to `burst`, so
+    /// an idle provider may take a burst and is then held to the refill rate
+    TokenBucket,
+}
+
 /// Rate limiter configuration
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct RateLimitConfig {
     pub max_concurrent: usize,
     pub max_requests: usize,
     pub window_seconds: u64,
+    /// Requests allowed per day; unlimited when unset
+    pub daily_quota: Option<usize>,
+    pub strategy: RateLimitStrategy,
+    /// Token bucket capacity; `max_requests` when unset
+    pub burst: Option<usize>,
+}
+
+impl RateLimitConfig {
+    /// Tokens added per second under [`RateLimitStrategy::TokenBucket`]
+    fn refill_per_second(&self) -> f64 {
+        self.max_requests as f64 / self.window_seconds as f64
+    }
+
+    fn burst(&self) -> f64 {
+        self.burst.unwrap_or(self.max_requests) as f64
+    }
 }
 
 impl Default for RateLimitConfig {
@@ -17,6 +52,9 @@ impl Default for RateLimitConfig {
             max_concurrent: 5,
             max_requests: 40,
             window_seconds: 10,
+            daily_quota: None,
+            strategy: RateLimitStrategy::Sliding,
+            burst: None,
         }
     }
 }
@@ -24,13 +62,44 @@ impl Default for RateLimitConfig {
 #[derive(Debug, Clone)]
 struct RequestRecord {
     timestamps: Vec<Instant>,
+    /// Start of the current quota period and requests made in it
+    quota_period: Option<(Instant, usize)>,
+    /// Tokens left and when they were last topped up; full until first used
+    bucket: Option<(f64, Instant)>,
+    /// Until when the provider asked to be left alone
+    penalty: Option<Instant>,
 }
 
 impl RequestRecord {
     const fn new() -> Self {
         Self {
             timestamps: Vec::new(),
+            quota_period: None,
+            bucket: None,
+            penalty: None,
+        }
+    }
+
+    /// Time left on an active penalty; a penalty that has passed is cleared
+    fn penalized(&mut self) -> Option<Duration> {
+        let remaining = self.penalty?.checked_duration_since(Instant::now());
+        if remaining.is_none_or(|remaining| remaining.is_zero()) {
+            self.penalty = None;
+            return None;
+        }
+        remaining
+    }
+
+    /// Time until the quota resets, if it is used up
+    fn quota_exhausted(&mut self, daily_quota: Option<usize>) -> Option<Duration> {
+        let quota = daily_quota?;
+        let (started, used) = self.quota_period?;
+        let elapsed = started.elapsed();
+        if elapsed >= QUOTA_PERIOD {
+            self.quota_period = None;
+            return None;
         }
+        (used >= quota).then(|| QUOTA_PERIOD - elapsed)
     }
 
     fn cleanup(&mut self, window: Duration) {
@@ -44,6 +113,29 @@ impl RequestRecord {
 
     fn record_request(&mut self) {
         self.timestamps.push(Instant::now());
+        self.count_toward_quota();
+    }
+
+    fn count_toward_quota(&mut self) {
+        let (_, used) = self.quota_period.get_or_insert((Instant::now(), 0));
+        *used += 1;
+    }
+
+    /// Take a token, or return how long until the next one refills
+    fn take_token(&mut self, burst: f64, per_second: f64) -> Option<Duration> {
+        let now = Instant::now();
+        let (tokens, refilled) = self.bucket.get_or_insert((burst, now));
+        *tokens = (*tokens + now.duration_since(*refilled).as_secs_f64() * per_second).min(burst);
+        *refilled = now;
+
+        if *tokens >= 1.0 {
+            *tokens -= 1.0;
+            return None;
+        }
+        Some(
+            Duration::try_from_secs_f64((1.0 - *tokens) / per_second)
+                .unwrap_or(Duration::from_secs(1)),
+        )
     }
 
     fn next_available(&self, window: Duration, max_requests: usize) -> Option<Duration> {
@@ -83,13 +175,21 @@ impl RateLimiter {
         }
     }
 
-    pub async fn acquire(&self, provider: &str) -> Result<RateLimitGuard, String> {
+    /// Wait out any penalty on `provider`, then for a request slot
+    ///
+    /// Fails with [`ScraperError::RateLimit`] carrying the time until reset
+    /// once the daily quota is used up, instead of waiting that long.
+    pub async fn acquire(&self, provider: &str) -> Result<RateLimitGuard, ScraperError> {
+        if let Some(reset) = self.exhausted(provider) {
+            return Err(ScraperError::RateLimit(reset));
+        }
+
         let permit = self
             .semaphore
             .clone()
             .acquire_owned()
             .await
-            .map_err(|e| format!("Failed to acquire semaphore: {e}"))?;
+            .map_err(|_| ScraperError::RateLimit(Duration::from_secs(1)))?;
 
         let window = Duration::from_secs(self.config.window_seconds);
         let key = provider.to_string();
@@ -103,13 +203,17 @@ impl RateLimiter {
 
                 record.cleanup(window);
 
-                if record.can_request(self.config.max_requests) {
-                    record.record_request();
-                    break;
+                if let Some(reset) = record.quota_exhausted(self.config.daily_quota) {
+                    return Err(ScraperError::RateLimit(reset));
+                }
+
+                match record
+                    .penalized()
+                    .or_else(|| self.admit(&mut record, window))
+                {
+                    Some(wait) => wait,
+                    None => break,
                 }
-                record
-                    .next_available(window, self.config.max_requests)
-                    .unwrap_or(Duration::from_millis(100))
             };
 
             tracing::debug!(
@@ -123,6 +227,52 @@ impl RateLimiter {
         Ok(RateLimitGuard { _permit: permit })
     }
 
+    /// Record a request under the configured strategy, or return how long to
+    /// wait before trying again
+    fn admit(&self, record: &mut RequestRecord, window: Duration) -> Option<Duration> {
+        match self.config.strategy {
+            RateLimitStrategy::Sliding => {
+                if record.can_request(self.config.max_requests) {
+                    record.record_request();
+                    return None;
+                }
+                Some(
+                    record
+                        .next_available(window, self.config.max_requests)
+                        .unwrap_or(Duration::from_millis(100)),
+                )
+            }
+            RateLimitStrategy::TokenBucket => {
+                let deficit =
+                    record.take_token(self.config.burst(), self.config.refill_per_second());
+                if deficit.is_none() {
+                    record.count_toward_quota();
+                }
+                deficit
+            }
+        }
+    }
+
+    /// Hold off all requests to `provider` until `until`
+    ///
+    /// For when the provider itself reported a rate limit, e.g. with a 429
+    /// and `Retry-After`. An earlier deadline never shortens an active one.
+    pub fn penalize(&self, provider: &str, until: Instant) {
+        let mut record = self
+            .records
+            .entry(provider.to_string())
+            .or_insert_with(RequestRecord::new);
+        record.penalty = record.penalty.max(Some(until));
+    }
+
+    /// Time until `provider`'s daily quota resets, if it is used up
+    #[must_use]
+    pub fn exhausted(&self, provider: &str) -> Option<Duration> {
+        self.records
+            .get_mut(provider)?
+            .quota_exhausted(self.config.daily_quota)
+    }
+
     pub fn reset(&self, provider: &str) {
         self.records.remove(provider);
     }
@@ -140,3 +290,93 @@ impl RateLimiter {
 pub struct RateLimitGuard {
     _permit: tokio::sync::OwnedSemaphorePermit,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_daily_quota_reports_exhaustion() {
+        let limiter = RateLimiter::new(RateLimitConfig {
+            daily_quota: Some(3),
+            ..RateLimitConfig::default()
+        });
+
+        for _ in 0..3 {
+            assert!(limiter.exhausted("tmdb").is_none());
+            limiter.acquire("tmdb").await.unwrap();
+        }
+
+        let reset = limiter.exhausted("tmdb").expect("quota should be used up");
+        assert!(reset <= QUOTA_PERIOD);
+        assert!(matches!(
+            limiter.acquire("tmdb").await,
+            Err(ScraperError::RateLimit(d)) if d <= QUOTA_PERIOD
+        ));
+
+        // Quotas are tracked per provider
+        assert!(limiter.exhausted("tvdb").is_none());
+        limiter.acquire("tvdb").await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_token_bucket_bursts_then_smooths_out() {
+        // Ten requests a second, at most five at once
+        let config = RateLimitConfig {
+            max_requests: 10,
+            window_seconds: 1,
+            burst: Some(5),
+            ..RateLimitConfig::default()
+        };
+        let sliding = RateLimiter::new(config.clone());
+        let bucket = RateLimiter::new(RateLimitConfig {
+            strategy: RateLimitStrategy::TokenBucket,
+            ..config
+        });
+
+        // Both let the first burst straight through
+        let start = Instant::now();
+        for _ in 0..5 {
+            sliding.acquire("tmdb").await.unwrap();
+            bucket.acquire("tmdb").await.unwrap();
+        }
+        assert!(start.elapsed() < Duration::from_millis(50));
+
+        // The bucket is empty and refills a token every 100ms
+        let refill = Instant::now();
+        for _ in 0..3 {
+            bucket.acquire("tmdb").await.unwrap();
+        }
+        assert!(refill.elapsed() >= Duration::from_millis(250));
+
+        // The sliding window lets the rest of its window through at once and
+        // then stalls until the window has passed
+        let rest = Instant::now();
+        for _ in 0..5 {
+            sliding.acquire("tmdb").await.unwrap();
+        }
+        assert!(rest.elapsed() < Duration::from_millis(50));
+        sliding.acquire("tmdb").await.unwrap();
+        assert!(start.elapsed() >= Duration::from_secs(1));
+    }
+
+    #[tokio::test]
+    async fn test_acquire_waits_out_penalty() {
+        let limiter = RateLimiter::default();
+        let start = Instant::now();
+        limiter.penalize("tmdb", start + Duration::from_millis(200));
+        // A shorter penalty doesn't cut the first one short
+        limiter.penalize("tmdb", start + Duration::from_millis(50));
+
+        limiter.acquire("tvdb").await.unwrap();
+        assert!(start.elapsed() < Duration::from_millis(50));
+
+        limiter.acquire("tmdb").await.unwrap();
+        assert!(start.elapsed() >= Duration::from_millis(200));
+        assert!(limiter.records.get("tmdb").unwrap().penalty.is_none());
+
+        let start = Instant::now();
+        limiter.acquire("tmdb").await.unwrap();
+        assert!(start.elapsed() < Duration::from_millis(50));
+    }
+}
@@ -1,5 +1,9 @@
+use std::{fmt, str::FromStr};
+
 use serde::{Deserialize, Serialize};
 
+use super::ScraperError;
+
 /// Media type
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
@@ -9,6 +13,119 @@ pub enum MediaType {
     Anime,
 }
 
+impl MediaType {
+    /// Lowercase name, as used in serialized payloads
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Movie => "movie",
+            Self::Tv => "tv",
+            Self::Anime => "anime",
+        }
+    }
+}
+
+/// A built-in metadata provider
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Provider {
+    Tmdb,
+    Tvdb,
+    AniList,
+    Bangumi,
+    Kitsu,
+}
+
+impl Provider {
+    /// Every built-in provider
+    pub const ALL: [Self; 5] = [
+        Self::Tmdb,
+        Self::Tvdb,
+        Self::AniList,
+        Self::Bangumi,
+        Self::Kitsu,
+    ];
+
+    /// Name the provider reports from `MetadataProvider::name`
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Tmdb => "tmdb",
+            Self::Tvdb => "tvdb",
+            Self::AniList => "anilist",
+            Self::Bangumi => "bangumi",
+            Self::Kitsu => "kitsu",
+        }
+    }
+}
+
+impl FromStr for Provider {
+    type Err = ScraperError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|p| p.as_str() == s)
+            .ok_or_else(|| ScraperError::Config(format!("Unknown provider: {s}")))
+    }
+}
+
+impl fmt::Display for Provider {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// What a provider is able to look up
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProviderCapabilities {
+    /// Media types the provider searches and serves details for
+    pub media_types: Vec<MediaType>,
+    /// Whether `get_episode_details` is available
+    pub episode_details: bool,
+    /// Whether searches match titles written in Chinese, Japanese or Korean
+    pub cjk_titles: bool,
+}
+
+impl ProviderCapabilities {
+    /// Capabilities covering `media_types`, without episode details
+    #[must_use]
+    pub fn new(media_types: impl Into<Vec<MediaType>>) -> Self {
+        Self {
+            media_types: media_types.into(),
+            episode_details: false,
+            cjk_titles: false,
+        }
+    }
+
+    /// Set whether episode details are available
+    #[must_use]
+    pub const fn with_episode_details(mut self, episode_details: bool) -> Self {
+        self.episode_details = episode_details;
+        self
+    }
+
+    /// Set whether titles in CJK script are searchable
+    #[must_use]
+    pub const fn with_cjk_titles(mut self, cjk_titles: bool) -> Self {
+        self.cjk_titles = cjk_titles;
+        self
+    }
+
+    /// Whether the provider handles `media_type`
+    #[must_use]
+    pub fn supports(&self, media_type: MediaType) -> bool {
+        self.media_types.contains(&media_type)
+    }
+}
+
+impl Default for ProviderCapabilities {
+    /// Every media type, with episode details
+    fn default() -> Self {
+        Self::new([MediaType::Movie, MediaType::Tv, MediaType::Anime]).with_episode_details(true)
+    }
+}
+
 /// Generic media search result (includes all types)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "media_type", rename_all = "lowercase")]
@@ -19,6 +136,57 @@ pub enum MediaSearchResult {
 }
 
 impl MediaSearchResult {
+    /// Build a minimal search result pointing at a known provider ID
+    ///
+    /// Only the ID and provider are meaningful; this is enough for
+    /// `get_details` to resolve the full metadata.
+    #[must_use]
+    pub fn from_id(media_type: MediaType, provider: &str, id: &str) -> Self {
+        let (id, provider) = (id.to_string(), provider.to_string());
+        match media_type {
+            MediaType::Movie => Self::Movie(MovieSearchResult {
+                id,
+                title: String::new(),
+                original_title: None,
+                year: None,
+                poster_path: None,
+                overview: None,
+                vote_average: None,
+                adult: false,
+                provider,
+                external_ids: ExternalIds::default(),
+                also_matched: Vec::new(),
+            }),
+            MediaType::Tv => Self::Tv(TvSearchResult {
+                id,
+                name: String::new(),
+                original_name: None,
+                first_air_date: None,
+                poster_path: None,
+                overview: None,
+                vote_average: None,
+                adult: false,
+                provider,
+                external_ids: ExternalIds::default(),
+                also_matched: Vec::new(),
+            }),
+            MediaType::Anime => Self::Anime(AnimeSearchResult {
+                id,
+                title: String::new(),
+                title_english: None,
+                title_japanese: None,
+                year: None,
+                poster_path: None,
+                overview: None,
+                score: None,
+                adult: false,
+                provider,
+                external_ids: ExternalIds::default(),
+                also_matched: Vec::new(),
+            }),
+        }
+    }
+
     /// Get ID
     #[must_use]
     pub fn id(&self) -> &str {
@@ -58,6 +226,107 @@ impl MediaSearchResult {
             Self::Anime(a) => &a.provider,
         }
     }
+
+    /// Whether the provider flagged this result as adult content
+    #[must_use]
+    pub const fn is_adult(&self) -> bool {
+        match self {
+            Self::Movie(m) => m.adult,
+            Self::Tv(t) => t.adult,
+            Self::Anime(a) => a.adult,
+        }
+    }
+
+    /// Release or first air year
+    #[must_use]
+    pub fn year(&self) -> Option<i32> {
+        match self {
+            Self::Movie(m) => m.year,
+            Self::Tv(t) => t
+                .first_air_date
+                .as_deref()
+                .and_then(|d| d.split('-').next())
+                .and_then(|y| y.parse().ok()),
+            Self::Anime(a) => a.year,
+        }
+    }
+
+    /// Rating on a 0-10 scale
+    #[must_use]
+    pub const fn score(&self) -> Option<f64> {
+        match self {
+            Self::Movie(m) => m.vote_average,
+            Self::Tv(t) => t.vote_average,
+            Self::Anime(a) => a.score,
+        }
+    }
+
+    /// External IDs, including the result's own ID on its provider
+    #[must_use]
+    pub fn all_ids(&self) -> ExternalIds {
+        let mut ids = match self {
+            Self::Movie(m) => m.external_ids.clone(),
+            Self::Tv(t) => t.external_ids.clone(),
+            Self::Anime(a) => a.external_ids.clone(),
+        };
+        ids.set(self.provider(), self.id());
+        ids
+    }
+
+    /// Results from other providers collapsed into this one
+    #[must_use]
+    pub fn also_matched(&self) -> &[ProviderMatch] {
+        match self {
+            Self::Movie(m) => &m.also_matched,
+            Self::Tv(t) => &t.also_matched,
+            Self::Anime(a) => &a.also_matched,
+        }
+    }
+
+    const fn parts_mut(&mut self) -> (&mut ExternalIds, &mut Vec<ProviderMatch>) {
+        match self {
+            Self::Movie(m) => (&mut m.external_ids, &mut m.also_matched),
+            Self::Tv(t) => (&mut t.external_ids, &mut t.also_matched),
+            Self::Anime(a) => (&mut a.external_ids, &mut a.also_matched),
+        }
+    }
+
+    /// This result followed by the ones collapsed into it, ready for
+    /// `ScraperManager::get_merged_details`
+    #[must_use]
+    pub fn matched_results(&self) -> Vec<Self> {
+        let mut results = vec![self.clone()];
+        results.extend(
+            self.also_matched()
+                .iter()
+                .map(|m| Self::from_id(m.media_type, &m.provider, &m.id)),
+        );
+        results
+    }
+
+    /// Collapse `other` into this result, keeping this one's fields
+    pub fn absorb(&mut self, other: Self) {
+        let other_ids = other.all_ids();
+        let other_match = ProviderMatch {
+            provider: other.provider().to_string(),
+            id: other.id().to_string(),
+            media_type: other.media_type(),
+        };
+        let other_matches = other.also_matched().to_vec();
+
+        let (ids, matched) = self.parts_mut();
+        ids.merge(&other_ids);
+        matched.push(other_match);
+        matched.extend(other_matches);
+    }
+}
+
+/// A provider's result for a title, as recorded by deduplication
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProviderMatch {
+    pub provider: String,
+    pub id: String,
+    pub media_type: MediaType,
 }
 
 /// Generic media details (includes all types)
@@ -109,6 +378,194 @@ impl MediaDetails {
             Self::Anime(a) => &a.provider,
         }
     }
+
+    /// Get release year parsed from the first release/air date
+    #[must_use]
+    pub fn year(&self) -> Option<i32> {
+        let date = match self {
+            Self::Movie(m) => m.release_date.as_deref(),
+            Self::Tv(t) => t.first_air_date.as_deref(),
+            Self::Anime(a) => a.start_date.as_deref(),
+        };
+
+        date.and_then(|d| d.split('-').next())
+            .and_then(|y| y.parse().ok())
+    }
+
+    /// Get external IDs
+    #[must_use]
+    pub const fn external_ids(&self) -> &ExternalIds {
+        match self {
+            Self::Movie(m) => &m.external_ids,
+            Self::Tv(t) => &t.external_ids,
+            Self::Anime(a) => &a.external_ids,
+        }
+    }
+
+    /// External IDs including the provider's own ID for these details
+    #[must_use]
+    pub fn all_ids(&self) -> ExternalIds {
+        let mut ids = self.external_ids().clone();
+        ids.set(self.provider(), self.id());
+        ids
+    }
+
+    /// Fill fields missing here from lower-ranked `other`
+    ///
+    /// Values already present are kept. Details of another media type only
+    /// contribute the fields both types share.
+    pub fn merge_from(&mut self, other: &Self) {
+        match (self, other) {
+            (Self::Movie(m), Self::Movie(o)) => m.merge_from(o),
+            (Self::Tv(t), Self::Tv(o)) => t.merge_from(o),
+            (Self::Anime(a), Self::Anime(o)) => a.merge_from(o),
+            (this, other) => {
+                let (overview, poster, backdrop, genres) = this.shared_fields_mut();
+                let (o_overview, o_poster, o_backdrop, o_genres) = other.shared_fields();
+                fill(overview, o_overview);
+                fill(poster, o_poster);
+                fill(backdrop, o_backdrop);
+                fill_vec(genres, o_genres);
+                this.external_ids_mut().merge(other.external_ids());
+            }
+        }
+    }
+
+    /// Get external IDs for modification
+    pub const fn external_ids_mut(&mut self) -> &mut ExternalIds {
+        match self {
+            Self::Movie(m) => &mut m.external_ids,
+            Self::Tv(t) => &mut t.external_ids,
+            Self::Anime(a) => &mut a.external_ids,
+        }
+    }
+
+    /// Overview, poster, backdrop and genres
+    const fn shared_fields(
+        &self,
+    ) -> (
+        &Option<String>,
+        &Option<String>,
+        &Option<String>,
+        &Vec<String>,
+    ) {
+        match self {
+            Self::Movie(m) => (&m.overview, &m.poster_path, &m.backdrop_path, &m.genres),
+            Self::Tv(t) => (&t.overview, &t.poster_path, &t.backdrop_path, &t.genres),
+            Self::Anime(a) => (&a.overview, &a.poster_path, &a.backdrop_path, &a.genres),
+        }
+    }
+
+    const fn shared_fields_mut(
+        &mut self,
+    ) -> (
+        &mut Option<String>,
+        &mut Option<String>,
+        &mut Option<String>,
+        &mut Vec<String>,
+    ) {
+        match self {
+            Self::Movie(m) => (
+                &mut m.overview,
+                &mut m.poster_path,
+                &mut m.backdrop_path,
+                &mut m.genres,
+            ),
+            Self::Tv(t) => (
+                &mut t.overview,
+                &mut t.poster_path,
+                &mut t.backdrop_path,
+                &mut t.genres,
+            ),
+            Self::Anime(a) => (
+                &mut a.overview,
+                &mut a.poster_path,
+                &mut a.backdrop_path,
+                &mut a.genres,
+            ),
+        }
+    }
+}
+
+/// Set `target` from `source` if it is still empty
+fn fill<T: Clone>(target: &mut Option<T>, source: &Option<T>) {
+    if target.is_none() {
+        target.clone_from(source);
+    }
+}
+
+/// Replace an empty list with `source`
+fn fill_vec<T: Clone>(target: &mut Vec<T>, source: &[T]) {
+    if target.is_empty() {
+        target.extend_from_slice(source);
+    }
+}
+
+impl MovieMetadata {
+    fn merge_from(&mut self, other: &Self) {
+        fill(&mut self.original_title, &other.original_title);
+        fill(&mut self.release_date, &other.release_date);
+        fill(&mut self.runtime, &other.runtime);
+        fill(&mut self.overview, &other.overview);
+        fill(&mut self.poster_path, &other.poster_path);
+        fill(&mut self.backdrop_path, &other.backdrop_path);
+        fill(&mut self.vote_average, &other.vote_average);
+        fill(&mut self.vote_count, &other.vote_count);
+        fill_vec(&mut self.genres, &other.genres);
+        fill_vec(&mut self.production_companies, &other.production_companies);
+        fill_vec(&mut self.production_countries, &other.production_countries);
+        fill(&mut self.original_language, &other.original_language);
+        fill_vec(&mut self.cast, &other.cast);
+        fill(&mut self.director, &other.director);
+        fill_vec(&mut self.writers, &other.writers);
+        fill(&mut self.collection, &other.collection);
+        self.external_ids.merge(&other.external_ids);
+    }
+}
+
+impl TvMetadata {
+    fn merge_from(&mut self, other: &Self) {
+        fill(&mut self.original_name, &other.original_name);
+        fill(&mut self.first_air_date, &other.first_air_date);
+        fill(&mut self.last_air_date, &other.last_air_date);
+        fill(&mut self.overview, &other.overview);
+        fill(&mut self.poster_path, &other.poster_path);
+        fill(&mut self.backdrop_path, &other.backdrop_path);
+        fill(&mut self.vote_average, &other.vote_average);
+        fill(&mut self.vote_count, &other.vote_count);
+        fill_vec(&mut self.genres, &other.genres);
+        fill(&mut self.number_of_seasons, &other.number_of_seasons);
+        fill(&mut self.number_of_episodes, &other.number_of_episodes);
+        fill_vec(&mut self.seasons, &other.seasons);
+        fill_vec(&mut self.episode_run_time, &other.episode_run_time);
+        fill(&mut self.status, &other.status);
+        fill(&mut self.original_language, &other.original_language);
+        fill_vec(&mut self.production_companies, &other.production_companies);
+        fill_vec(&mut self.cast, &other.cast);
+        fill(&mut self.director, &other.director);
+        fill_vec(&mut self.writers, &other.writers);
+        self.external_ids.merge(&other.external_ids);
+    }
+}
+
+impl AnimeMetadata {
+    fn merge_from(&mut self, other: &Self) {
+        fill(&mut self.title_english, &other.title_english);
+        fill(&mut self.title_japanese, &other.title_japanese);
+        fill(&mut self.start_date, &other.start_date);
+        fill(&mut self.end_date, &other.end_date);
+        fill(&mut self.overview, &other.overview);
+        fill(&mut self.poster_path, &other.poster_path);
+        fill(&mut self.backdrop_path, &other.backdrop_path);
+        fill(&mut self.score, &other.score);
+        fill_vec(&mut self.genres, &other.genres);
+        fill(&mut self.episodes, &other.episodes);
+        fill(&mut self.status, &other.status);
+        fill(&mut self.format, &other.format);
+        fill_vec(&mut self.studios, &other.studios);
+        fill_vec(&mut self.staff, &other.staff);
+        self.external_ids.merge(&other.external_ids);
+    }
 }
 
 /// Movie search result
@@ -128,8 +585,17 @@ pub struct MovieSearchResult {
     pub overview: Option<String>,
     /// Vote average
     pub vote_average: Option<f64>,
+    /// Flagged as adult content by the provider
+    #[serde(default)]
+    pub adult: bool,
     /// Provider name
     pub provider: String,
+    /// IDs on other services, when the provider reports them with the result
+    #[serde(default)]
+    pub external_ids: ExternalIds,
+    /// Results from other providers collapsed into this one
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub also_matched: Vec<ProviderMatch>,
 }
 
 /// Movie metadata
@@ -163,12 +629,35 @@ pub struct MovieMetadata {
     pub production_countries: Vec<String>,
     /// Original language
     pub original_language: Option<String>,
+    /// Top-billed cast, in billing order
+    #[serde(default)]
+    pub cast: Vec<String>,
+    /// Director
+    #[serde(default)]
+    pub director: Option<String>,
+    /// Writers
+    #[serde(default)]
+    pub writers: Vec<String>,
+    /// Collection (franchise) the movie belongs to
+    #[serde(default)]
+    pub collection: Option<CollectionInfo>,
     /// Provider name
     pub provider: String,
     /// External IDs
     pub external_ids: ExternalIds,
 }
 
+/// Movie collection, such as a trilogy
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CollectionInfo {
+    /// Provider-specific ID
+    pub id: String,
+    /// Collection name
+    pub name: String,
+    /// Poster path/URL
+    pub poster_path: Option<String>,
+}
+
 /// TV show search result
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TvSearchResult {
@@ -186,8 +675,17 @@ pub struct TvSearchResult {
     pub overview: Option<String>,
     /// Vote average
     pub vote_average: Option<f64>,
+    /// Flagged as adult content by the provider
+    #[serde(default)]
+    pub adult: bool,
     /// Provider name
     pub provider: String,
+    /// IDs on other services, when the provider reports them with the result
+    #[serde(default)]
+    pub external_ids: ExternalIds,
+    /// Results from other providers collapsed into this one
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub also_matched: Vec<ProviderMatch>,
 }
 
 /// TV show metadata
@@ -219,6 +717,9 @@ pub struct TvMetadata {
     pub number_of_seasons: Option<i32>,
     /// Number of episodes
     pub number_of_episodes: Option<i32>,
+    /// Per-season episode counts
+    #[serde(default)]
+    pub seasons: Vec<SeasonInfo>,
     /// Episode runtime (minutes)
     pub episode_run_time: Vec<i32>,
     /// Status
@@ -227,12 +728,30 @@ pub struct TvMetadata {
     pub original_language: Option<String>,
     /// Production companies
     pub production_companies: Vec<String>,
+    /// Top-billed cast, in billing order
+    #[serde(default)]
+    pub cast: Vec<String>,
+    /// Director
+    #[serde(default)]
+    pub director: Option<String>,
+    /// Writers
+    #[serde(default)]
+    pub writers: Vec<String>,
     /// Provider name
     pub provider: String,
     /// External IDs
     pub external_ids: ExternalIds,
 }
 
+/// Season summary
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SeasonInfo {
+    /// Season number (0 for specials)
+    pub season_number: i32,
+    /// Number of episodes in the season
+    pub episode_count: i32,
+}
+
 /// Episode metadata
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EpisodeMetadata {
@@ -277,8 +796,17 @@ pub struct AnimeSearchResult {
     pub overview: Option<String>,
     /// Score
     pub score: Option<f64>,
+    /// Flagged as adult content by the provider
+    #[serde(default)]
+    pub adult: bool,
     /// Provider name
     pub provider: String,
+    /// IDs on other services, when the provider reports them with the result
+    #[serde(default)]
+    pub external_ids: ExternalIds,
+    /// Results from other providers collapsed into this one
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub also_matched: Vec<ProviderMatch>,
 }
 
 /// Anime metadata
@@ -312,12 +840,27 @@ pub struct AnimeMetadata {
     pub status: Option<String>,
     /// Anime format (TV, Movie, OVA, etc.)
     pub format: Option<String>,
+    /// Animation studios
+    #[serde(default)]
+    pub studios: Vec<String>,
+    /// Key staff credits (director, original creator, ...)
+    #[serde(default)]
+    pub staff: Vec<StaffCredit>,
     /// Provider name
     pub provider: String,
     /// External IDs
     pub external_ids: ExternalIds,
 }
 
+/// A staff member credited on a title
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StaffCredit {
+    /// Role, e.g. "Director"
+    pub role: String,
+    /// Person name
+    pub name: String,
+}
+
 /// External IDs
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ExternalIds {
@@ -334,3 +877,171 @@ pub struct ExternalIds {
     /// `MyAnimeList` ID
     pub mal_id: Option<String>,
 }
+
+impl ExternalIds {
+    /// Record `id` under the slot for `provider`, if it has one
+    pub fn set(&mut self, provider: &str, id: &str) {
+        let slot = match provider {
+            "imdb" => &mut self.imdb_id,
+            "tmdb" => &mut self.tmdb_id,
+            "tvdb" => &mut self.tvdb_id,
+            "anilist" => &mut self.anilist_id,
+            "bangumi" => &mut self.bangumi_id,
+            "mal" => &mut self.mal_id,
+            _ => return,
+        };
+        *slot = Some(id.to_string());
+    }
+
+    /// Fill IDs missing here from `other`
+    pub fn merge(&mut self, other: &Self) {
+        fill(&mut self.imdb_id, &other.imdb_id);
+        fill(&mut self.tmdb_id, &other.tmdb_id);
+        fill(&mut self.tvdb_id, &other.tvdb_id);
+        fill(&mut self.anilist_id, &other.anilist_id);
+        fill(&mut self.bangumi_id, &other.bangumi_id);
+        fill(&mut self.mal_id, &other.mal_id);
+    }
+
+    /// Whether both sets name the same ID for any provider
+    #[must_use]
+    pub fn overlaps(&self, other: &Self) -> bool {
+        let same = |a: &Option<String>, b: &Option<String>| a.is_some() && a == b;
+        same(&self.imdb_id, &other.imdb_id)
+            || same(&self.tmdb_id, &other.tmdb_id)
+            || same(&self.tvdb_id, &other.tvdb_id)
+            || same(&self.anilist_id, &other.anilist_id)
+            || same(&self.bangumi_id, &other.bangumi_id)
+            || same(&self.mal_id, &other.mal_id)
+    }
+}
+
+/// One page of search results, with the provider's paging totals
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SearchPage {
+    pub results: Vec<MediaSearchResult>,
+    /// 1-based page number
+    pub page: u32,
+    pub total_pages: u32,
+    pub total_results: u32,
+}
+
+impl SearchPage {
+    /// Wrap results from a provider that does not paginate
+    #[must_use]
+    pub fn single(results: Vec<MediaSearchResult>) -> Self {
+        Self {
+            total_results: u32::try_from(results.len()).unwrap_or(u32::MAX),
+            results,
+            page: 1,
+            total_pages: 1,
+        }
+    }
+
+    /// Whether the provider has pages after this one
+    #[must_use]
+    pub const fn has_next(&self) -> bool {
+        self.page < self.total_pages
+    }
+
+    /// Fold in the same page number from another endpoint of the provider
+    pub fn merge(&mut self, other: Self) {
+        self.results.extend(other.results);
+        self.total_pages = self.total_pages.max(other.total_pages);
+        self.total_results += other.total_results;
+    }
+}
+
+/// Options for a provider search
+///
+/// Built with [`SearchOptions::new`] and the `with_*` methods so that new
+/// filters can be added without touching every provider's signature. Details
+/// lookups only consult [`language`](Self::language).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SearchOptions {
+    pub query: String,
+    pub year: Option<i32>,
+    /// BCP-47 language tag such as `zh-CN` or `ja-JP`
+    pub language: Option<String>,
+    /// Include adult titles, for providers that filter them by default
+    pub include_adult: bool,
+    /// 1-based result page; providers use their first page when unset
+    pub page: Option<u32>,
+    /// Restrict results to these media types; empty means no restriction
+    pub media_types: Vec<MediaType>,
+    /// Only query the provider with this name; every provider when unset
+    pub provider: Option<String>,
+}
+
+impl SearchOptions {
+    /// Options searching for `query`
+    #[must_use]
+    pub fn new(query: impl Into<String>) -> Self {
+        Self {
+            query: query.into(),
+            ..Self::default()
+        }
+    }
+
+    #[must_use]
+    pub const fn with_year(mut self, year: Option<i32>) -> Self {
+        self.year = year;
+        self
+    }
+
+    /// Request results in `language`
+    #[must_use]
+    pub fn with_language(mut self, language: impl Into<String>) -> Self {
+        self.language = Some(language.into());
+        self
+    }
+
+    #[must_use]
+    pub const fn with_include_adult(mut self, include_adult: bool) -> Self {
+        self.include_adult = include_adult;
+        self
+    }
+
+    #[must_use]
+    pub const fn with_page(mut self, page: u32) -> Self {
+        self.page = Some(page);
+        self
+    }
+
+    #[must_use]
+    pub fn with_media_types(mut self, media_types: impl IntoIterator<Item = MediaType>) -> Self {
+        self.media_types = media_types.into_iter().collect();
+        self
+    }
+
+    /// Query only the provider named `provider`
+    #[must_use]
+    pub fn with_provider(mut self, provider: impl Into<String>) -> Self {
+        self.provider = Some(provider.into());
+        self
+    }
+
+    /// Whether results of `media_type` are wanted
+    #[must_use]
+    pub fn allows(&self, media_type: MediaType) -> bool {
+        self.media_types.is_empty() || self.media_types.contains(&media_type)
+    }
+
+    /// Primary language subtag, lowercased (`zh` for `zh-CN`)
+    #[must_use]
+    pub fn primary_language(&self) -> Option<String> {
+        let language = self.language.as_deref()?.split(['-', '_']).next()?;
+        (!language.is_empty()).then(|| language.to_ascii_lowercase())
+    }
+
+    /// Region subtag, uppercased (`CN` for `zh-CN`)
+    #[must_use]
+    pub fn region(&self) -> Option<String> {
+        self.language
+            .as_deref()?
+            .split(['-', '_'])
+            .skip(1)
+            .find(|tag| tag.len() == 2 && tag.chars().all(|c| c.is_ascii_alphabetic()))
+            .map(str::to_ascii_uppercase)
+    }
+}
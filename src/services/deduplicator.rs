@@ -0,0 +1,301 @@
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{self, BufReader, Read},
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info, warn};
+
+use crate::{entities::MediaItem, error::ScrapeError, services::file_scanner::content_hash};
+
+/// A duplicate file replaced (or to be replaced) by a hard link
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DedupAction {
+    pub canonical: String,
+    pub duplicate: String,
+    pub size: u64,
+}
+
+/// Deduplication report
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DedupReport {
+    pub dry_run: bool,
+    pub files_linked: usize,
+    pub reclaimed_bytes: u64,
+    pub actions: Vec<DedupAction>,
+    pub errors: Vec<String>,
+}
+
+/// Replace byte-identical media files with hard links to a single copy
+///
+/// Candidates are grouped by device, size and content hash, then compared
+/// byte-for-byte. Hashes stored by the scanner are reused; files scanned
+/// without hashing are hashed here. Files on different devices are never
+/// linked together, and files with other hard links are left alone since
+/// linking them would free nothing. With `dry_run` the planned actions are
+/// reported without touching the filesystem.
+pub async fn deduplicate(db: &sqlx::SqlitePool, dry_run: bool) -> Result<DedupReport, ScrapeError> {
+    let items = sqlx::query_as::<_, MediaItem>("SELECT * FROM media_items ORDER BY id")
+        .fetch_all(db)
+        .await
+        .map_err(|e| ScrapeError::ScanError(format!("Failed to load media items: {e}")))?;
+
+    let files: Vec<(PathBuf, Option<String>)> = items
+        .into_iter()
+        .map(|i| (PathBuf::from(i.file_path), i.content_hash))
+        .collect();
+
+    let report = tokio::task::spawn_blocking(move || deduplicate_paths(&files, dry_run)).await?;
+
+    info!(
+        "Deduplication {}: {} files, {} bytes reclaimed",
+        if report.dry_run {
+            "planned"
+        } else {
+            "complete"
+        },
+        report.files_linked,
+        report.reclaimed_bytes
+    );
+
+    Ok(report)
+}
+
+/// A file that may be replaced by a link, with its inode and link count
+#[cfg(unix)]
+struct Candidate {
+    path: PathBuf,
+    ino: u64,
+    nlink: u64,
+}
+
+#[cfg(unix)]
+fn deduplicate_paths(files: &[(PathBuf, Option<String>)], dry_run: bool) -> DedupReport {
+    use std::os::unix::fs::MetadataExt;
+
+    let mut report = DedupReport {
+        dry_run,
+        ..Default::default()
+    };
+
+    // Group by (device, size, hash); only files in the same group can be
+    // identical and linkable
+    let mut groups: HashMap<(u64, u64, String), Vec<Candidate>> = HashMap::new();
+    for (path, stored_hash) in files {
+        let meta = match std::fs::metadata(path) {
+            Ok(meta) if meta.is_file() && meta.len() > 0 => meta,
+            Ok(_) => continue,
+            Err(e) => {
+                report.errors.push(format!("{}: {e}", path.display()));
+                continue;
+            }
+        };
+        let hash = match stored_hash {
+            Some(hash) => hash.clone(),
+            None => match content_hash(path) {
+                Ok(hash) => hash,
+                Err(e) => {
+                    report.errors.push(format!("{}: {e}", path.display()));
+                    continue;
+                }
+            },
+        };
+        groups
+            .entry((meta.dev(), meta.len(), hash))
+            .or_default()
+            .push(Candidate {
+                path: path.clone(),
+                ino: meta.ino(),
+                nlink: meta.nlink(),
+            });
+    }
+
+    for ((_, size, _), files) in groups {
+        if files.len() < 2 {
+            continue;
+        }
+
+        // Each cluster holds a canonical file and the inode it lives on
+        let mut canonicals: Vec<(PathBuf, u64)> = Vec::new();
+
+        for Candidate { path, ino, nlink } in files {
+            let mut matched = None;
+            for (canonical, canonical_ino) in &canonicals {
+                if *canonical_ino == ino {
+                    // Already a hard link to the canonical copy
+                    matched = Some(None);
+                    break;
+                }
+                match files_equal(canonical, &path) {
+                    Ok(true) => {
+                        matched = Some(Some(canonical.clone()));
+                        break;
+                    }
+                    Ok(false) => {}
+                    Err(e) => {
+                        report.errors.push(format!("{}: {e}", path.display()));
+                        matched = Some(None);
+                        break;
+                    }
+                }
+            }
+
+            match matched {
+                None => canonicals.push((path, ino)),
+                Some(None) => {}
+                Some(Some(_)) if nlink > 1 => {
+                    debug!("Skipping {}: linked elsewhere", path.display());
+                }
+                Some(Some(canonical)) => {
+                    if !dry_run && let Err(e) = replace_with_link(&canonical, &path) {
+                        warn!("Failed to link {}: {}", path.display(), e);
+                        report.errors.push(format!("{}: {e}", path.display()));
+                        continue;
+                    }
+
+                    report.files_linked += 1;
+                    report.reclaimed_bytes += size;
+                    report.actions.push(DedupAction {
+                        canonical: canonical.to_string_lossy().to_string(),
+                        duplicate: path.to_string_lossy().to_string(),
+                        size,
+                    });
+                }
+            }
+        }
+    }
+
+    report
+}
+
+#[cfg(not(unix))]
+fn deduplicate_paths(_files: &[(PathBuf, Option<String>)], dry_run: bool) -> DedupReport {
+    DedupReport {
+        dry_run,
+        errors: vec!["Deduplication is only supported on Unix platforms".to_string()],
+        ..Default::default()
+    }
+}
+
+/// Compare two files byte-for-byte
+fn files_equal(a: &Path, b: &Path) -> io::Result<bool> {
+    let mut a = BufReader::new(File::open(a)?);
+    let mut b = BufReader::new(File::open(b)?);
+    let mut buf_a = [0u8; 64 * 1024];
+    let mut buf_b = [0u8; 64 * 1024];
+
+    loop {
+        let read_a = read_full(&mut a, &mut buf_a)?;
+        let read_b = read_full(&mut b, &mut buf_b)?;
+        if read_a != read_b || buf_a[..read_a] != buf_b[..read_b] {
+            return Ok(false);
+        }
+        if read_a == 0 {
+            return Ok(true);
+        }
+    }
+}
+
+/// Fill `buf` as far as possible, returning the number of bytes read
+fn read_full(reader: &mut impl Read, buf: &mut [u8]) -> io::Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match reader.read(&mut buf[filled..])? {
+            0 => break,
+            n => filled += n,
+        }
+    }
+    Ok(filled)
+}
+
+/// Atomically replace `duplicate` with a hard link to `canonical`
+#[cfg(unix)]
+fn replace_with_link(canonical: &Path, duplicate: &Path) -> io::Result<()> {
+    let file_name = duplicate
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let temp = duplicate.with_file_name(format!(".{file_name}.ayiah-dedup"));
+
+    std::fs::hard_link(canonical, &temp)?;
+    std::fs::rename(&temp, duplicate).inspect_err(|_| {
+        let _ = std::fs::remove_file(&temp);
+    })
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use std::os::unix::fs::MetadataExt;
+
+    use super::*;
+
+    fn ino(path: &Path) -> u64 {
+        std::fs::metadata(path).unwrap().ino()
+    }
+
+    #[test]
+    fn test_identical_files_are_linked() {
+        let dir = tempfile::tempdir().unwrap();
+        let a = dir.path().join("a.mkv");
+        let b = dir.path().join("b.mkv");
+        let c = dir.path().join("c.mkv");
+        std::fs::write(&a, b"same content").unwrap();
+        std::fs::write(&b, b"same content").unwrap();
+        std::fs::write(&c, b"diff content").unwrap();
+        let paths = vec![(a.clone(), None), (b.clone(), None), (c.clone(), None)];
+
+        let report = deduplicate_paths(&paths, true);
+        assert_eq!(report.files_linked, 1);
+        assert_ne!(ino(&a), ino(&b));
+
+        let report = deduplicate_paths(&paths, false);
+        assert_eq!(report.files_linked, 1);
+        assert_eq!(report.reclaimed_bytes, 12);
+        assert_eq!(ino(&a), ino(&b));
+        assert_ne!(ino(&a), ino(&c));
+        assert_eq!(std::fs::read(&b).unwrap(), b"same content");
+
+        // Already-linked files are not reported again
+        let report = deduplicate_paths(&paths, false);
+        assert_eq!(report.files_linked, 0);
+    }
+
+    #[test]
+    fn test_stored_hashes_split_candidates() {
+        let dir = tempfile::tempdir().unwrap();
+        let a = dir.path().join("a.mkv");
+        let b = dir.path().join("b.mkv");
+        std::fs::write(&a, b"same content").unwrap();
+        std::fs::write(&b, b"same content").unwrap();
+
+        // Differing stored hashes mean the files are never compared
+        let files = vec![
+            (a.clone(), Some("c-1".to_string())),
+            (b.clone(), Some("c-2".to_string())),
+        ];
+        assert_eq!(deduplicate_paths(&files, true).files_linked, 0);
+
+        let hash = content_hash(&a).unwrap();
+        let files = vec![(a, Some(hash.clone())), (b, Some(hash))];
+        assert_eq!(deduplicate_paths(&files, true).files_linked, 1);
+    }
+
+    #[test]
+    fn test_files_linked_elsewhere_are_skipped() {
+        let dir = tempfile::tempdir().unwrap();
+        let a = dir.path().join("a.mkv");
+        let b = dir.path().join("b.mkv");
+        std::fs::write(&a, b"same content").unwrap();
+        std::fs::write(&b, b"same content").unwrap();
+        // A seeding copy outside the library keeps b's data alive
+        std::fs::hard_link(&b, dir.path().join("seed.mkv")).unwrap();
+
+        let files = vec![(a.clone(), None), (b.clone(), None)];
+        let report = deduplicate_paths(&files, false);
+        assert_eq!(report.files_linked, 0);
+        assert_eq!(report.reclaimed_bytes, 0);
+        assert_ne!(ino(&a), ino(&b));
+    }
+}
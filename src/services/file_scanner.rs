@@ -1,12 +1,26 @@
-use crate::entities::{CreateMediaItem, LibraryFolder, MediaItem, MediaType};
+use crate::{
+    entities::{CreateMediaItem, CreateSubtitle, LibraryFolder, MediaItem, MediaType, Subtitle},
+    scraper::naming::{parse_absolute_episode, parse_episode_marker},
+};
+use chrono::{DateTime, Utc};
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
 use serde::{Deserialize, Serialize};
-use std::path::Path;
+use std::{
+    collections::HashSet,
+    fs::File,
+    io::{self, Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
+};
+use tokio::sync::mpsc;
 use tracing::{debug, error, info, warn};
 use walkdir::WalkDir;
+use xxhash_rust::xxh3::Xxh3;
 
 /// File scanner service for detecting media files
 pub struct FileScanner {
     db: sqlx::SqlitePool,
+    ignore_patterns: Vec<String>,
+    hash_contents: bool,
 }
 
 /// Scan result
@@ -16,18 +30,111 @@ pub struct ScanResult {
     pub new_items: usize,
     pub existing_items: usize,
     pub errors: usize,
+    /// Subtitle files newly attached to media items
+    #[serde(default)]
+    pub new_subtitles: usize,
+    /// Existing items whose file was replaced since the last scan
+    #[serde(default)]
+    pub changed_items: usize,
+}
+
+/// What indexing a file did to the library
+enum Indexed {
+    New,
+    Existing,
+    /// An existing item whose file has a different size or modification time
+    Changed,
+}
+
+/// Progress of an in-flight scan
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScanProgress {
+    pub processed: usize,
+    pub total: usize,
+    pub current: String,
+}
+
+/// Event emitted while a scan runs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum ScanEvent {
+    Progress(ScanProgress),
+    Complete(ScanResult),
+    Failed { message: String },
 }
 
 impl FileScanner {
     /// Create a new file scanner
     pub fn new(db: sqlx::SqlitePool) -> Self {
-        Self { db }
+        Self {
+            db,
+            ignore_patterns: Vec::new(),
+            hash_contents: false,
+        }
+    }
+
+    /// Fingerprint files with [`content_hash`] so moved files keep their item
+    ///
+    /// Costs a couple of small reads per new file.
+    #[must_use]
+    pub fn with_content_hashing(mut self, enabled: bool) -> Self {
+        self.hash_contents = enabled;
+        self
+    }
+
+    /// Skip files and folders matching these gitignore-style patterns, on top
+    /// of the defaults and each library's `.ayiahignore`
+    #[must_use]
+    pub fn with_ignore_patterns(mut self, patterns: Vec<String>) -> Self {
+        self.ignore_patterns = patterns;
+        self
+    }
+
+    /// Ignore rules for a library root
+    ///
+    /// The root's `.ayiahignore` is added last so it can re-include defaults
+    /// with `!pattern`. Invalid patterns are logged and skipped.
+    fn ignore_matcher(&self, root: &Path) -> Gitignore {
+        let mut builder = GitignoreBuilder::new(root);
+        let patterns = DEFAULT_IGNORE_PATTERNS
+            .iter()
+            .copied()
+            .chain(self.ignore_patterns.iter().map(String::as_str));
+        for pattern in patterns {
+            if let Err(e) = builder.add_line(None, pattern) {
+                warn!("Invalid ignore pattern {:?}: {}", pattern, e);
+            }
+        }
+
+        let ignore_file = root.join(IGNORE_FILE);
+        if ignore_file.is_file()
+            && let Some(e) = builder.add(&ignore_file)
+        {
+            warn!("Failed to read {}: {}", ignore_file.display(), e);
+        }
+
+        builder.build().unwrap_or_else(|e| {
+            warn!("Failed to build ignore rules for {}: {}", root.display(), e);
+            Gitignore::empty()
+        })
     }
 
     /// Scan a library folder for media files
     pub async fn scan_library_folder(
         &self,
         folder: &LibraryFolder,
+    ) -> Result<ScanResult, FileScannerError> {
+        self.scan_library_folder_with_progress(folder, None).await
+    }
+
+    /// Scan a library folder, reporting progress after each file
+    ///
+    /// If the receiving side goes away the scan stops with
+    /// [`FileScannerError::Cancelled`]; files indexed so far are kept.
+    pub async fn scan_library_folder_with_progress(
+        &self,
+        folder: &LibraryFolder,
+        progress: Option<mpsc::Sender<ScanEvent>>,
     ) -> Result<ScanResult, FileScannerError> {
         info!("Scanning library folder: {} ({})", folder.name, folder.path);
 
@@ -40,90 +147,153 @@ impl FileScanner {
             return Err(FileScannerError::NotADirectory(folder.path.clone()));
         }
 
-        let mut total_files = 0;
         let mut new_items = 0;
         let mut existing_items = 0;
+        let mut changed_items = 0;
         let mut errors = 0;
 
-        // Get supported extensions for this media type
-        let extensions = get_supported_extensions(folder.media_type);
+        // Get supported extensions, unless the folder names its own
+        let extensions = match &folder.extensions {
+            Some(extensions) => extensions.iter().map(String::as_str).collect(),
+            None => get_supported_extensions(folder.media_type),
+        };
+        let has_subtitles = matches!(folder.media_type, MediaType::Movie | MediaType::Tv);
+        let mut subtitle_paths = Vec::new();
+        let ignore = self.ignore_matcher(path);
+        let mut seen_files = HashSet::new();
 
-        // Walk through directory
-        for entry in WalkDir::new(path)
+        // Walk through directory and collect candidate files up front so the
+        // total is known before processing starts. Extras folders are skipped
+        // so trailers and featurettes don't become items of their own, and
+        // ignored folders are pruned without being walked. Symlinks are
+        // followed in a stable order; walkdir reports links back to an
+        // ancestor as errors, and files reachable through several links are
+        // only taken once.
+        let entries: Vec<_> = WalkDir::new(path)
             .follow_links(true)
+            .sort_by_file_name()
             .into_iter()
-            .filter_map(|e| e.ok())
-        {
-            let entry_path = entry.path();
-
-            // Skip directories
-            if entry_path.is_dir() {
-                continue;
-            }
+            .filter_entry(|entry| {
+                entry.depth() == 0
+                    || !(is_extras_folder(entry)
+                        || ignore
+                            .matched(entry.path(), entry.file_type().is_dir())
+                            .is_ignore())
+            })
+            .filter_map(|entry| match entry {
+                Ok(entry) => Some(entry),
+                Err(e) if e.loop_ancestor().is_some() => {
+                    warn!("Skipping symlink loop: {}", e);
+                    None
+                }
+                Err(e) => {
+                    warn!("Skipping unreadable entry: {}", e);
+                    None
+                }
+            })
+            .filter(|entry| {
+                let entry_path = entry.path();
 
-            // Check if file has supported extension
-            if let Some(ext) = entry_path.extension() {
-                let ext_str = ext.to_string_lossy().to_lowercase();
-                if !extensions.contains(&ext_str.as_str()) {
-                    continue;
+                // Skip directories
+                if entry_path.is_dir() {
+                    return false;
                 }
-            } else {
-                continue;
-            }
 
-            total_files += 1;
+                let Some(ext_str) = entry_path
+                    .extension()
+                    .map(|ext| ext.to_string_lossy().to_lowercase())
+                else {
+                    return false;
+                };
 
-            // Get file metadata
-            let file_path = entry_path.to_string_lossy().to_string();
-            let file_size = match entry.metadata() {
-                Ok(metadata) => metadata.len() as i64,
-                Err(e) => {
-                    error!("Failed to get metadata for {}: {}", file_path, e);
-                    errors += 1;
-                    continue;
+                let is_subtitle = has_subtitles && SUBTITLE_EXTENSIONS.contains(&ext_str.as_str());
+                if !is_subtitle && !extensions.contains(&ext_str.as_str()) {
+                    return false;
                 }
-            };
 
-            // Extract title from filename
-            let title = extract_title(entry_path);
+                let canonical = entry_path
+                    .canonicalize()
+                    .unwrap_or_else(|_| entry_path.to_path_buf());
+                if !seen_files.insert(canonical) {
+                    debug!(
+                        "Already reached through another path: {}",
+                        entry_path.display()
+                    );
+                    return false;
+                }
 
-            // Check if item already exists
-            match MediaItem::find_by_path(&self.db, &file_path).await {
-                Ok(Some(_)) => {
-                    debug!("Media item already exists: {}", file_path);
-                    existing_items += 1;
+                // Subtitles are attached to their media item after the scan
+                if is_subtitle {
+                    subtitle_paths.push(entry_path.to_path_buf());
+                    return false;
                 }
-                Ok(None) => {
-                    // Create new media item
-                    let create_item = CreateMediaItem {
-                        library_folder_id: folder.id,
-                        media_type: folder.media_type,
-                        title: title.clone(),
-                        file_path: file_path.clone(),
-                        file_size,
-                    };
-
-                    match MediaItem::create(&self.db, create_item).await {
-                        Ok(_) => {
-                            info!("Added new media item: {}", title);
-                            new_items += 1;
+
+                true
+            })
+            .collect();
+
+        let total_files = entries.len();
+        let media_paths: Vec<PathBuf> = entries.iter().map(|e| e.path().to_path_buf()).collect();
+
+        for (index, entry) in entries.into_iter().enumerate() {
+            let entry_path = entry.path();
+
+            // Get file metadata
+            let file_path = entry_path.to_string_lossy().to_string();
+            match entry.metadata() {
+                Ok(metadata) => {
+                    let modified_at = metadata.modified().ok().map(DateTime::<Utc>::from);
+                    match self
+                        .index_file(
+                            folder,
+                            entry_path,
+                            &file_path,
+                            metadata.len() as i64,
+                            modified_at,
+                        )
+                        .await
+                    {
+                        Ok(Indexed::New) => new_items += 1,
+                        Ok(Indexed::Existing) => existing_items += 1,
+                        Ok(Indexed::Changed) => {
+                            existing_items += 1;
+                            changed_items += 1;
                         }
                         Err(e) => {
-                            error!("Failed to create media item for {}: {}", file_path, e);
+                            error!("Failed to index {}: {}", file_path, e);
                             errors += 1;
                         }
                     }
                 }
                 Err(e) => {
-                    error!("Database error while checking {}: {}", file_path, e);
+                    error!("Failed to get metadata for {}: {}", file_path, e);
                     errors += 1;
                 }
             }
+
+            if let Some(tx) = &progress {
+                let event = ScanEvent::Progress(ScanProgress {
+                    processed: index + 1,
+                    total: total_files,
+                    current: entry.file_name().to_string_lossy().to_string(),
+                });
+                if tx.send(event).await.is_err() {
+                    info!(
+                        "Scan of {} stopped after {} of {} files: nobody is following it",
+                        folder.name,
+                        index + 1,
+                        total_files
+                    );
+                    return Err(FileScannerError::Cancelled);
+                }
+            }
         }
 
+        let new_subtitles = self.index_subtitles(&media_paths, subtitle_paths).await;
+
         info!(
-            "Scan complete: {} total files, {} new, {} existing, {} errors",
-            total_files, new_items, existing_items, errors
+            "Scan complete: {} total files, {} new, {} existing ({} changed), {} errors, {} new subtitles",
+            total_files, new_items, existing_items, changed_items, errors, new_subtitles
         );
 
         Ok(ScanResult {
@@ -131,9 +301,163 @@ impl FileScanner {
             new_items,
             existing_items,
             errors,
+            new_subtitles,
+            changed_items,
         })
     }
 
+    /// Attach subtitle files to the media files they belong to, returning how
+    /// many were newly recorded
+    async fn index_subtitles(
+        &self,
+        media_paths: &[PathBuf],
+        subtitle_paths: Vec<PathBuf>,
+    ) -> usize {
+        let mut new_subtitles = 0;
+
+        for subtitle_path in subtitle_paths {
+            let Some((media_path, language)) = match_subtitle(&subtitle_path, media_paths) else {
+                debug!("No media file for subtitle: {}", subtitle_path.display());
+                continue;
+            };
+
+            let media_item =
+                match MediaItem::find_by_path(&self.db, &media_path.to_string_lossy()).await {
+                    Ok(Some(item)) => item,
+                    Ok(None) => continue,
+                    Err(e) => {
+                        error!(
+                            "Database error while checking {}: {}",
+                            media_path.display(),
+                            e
+                        );
+                        continue;
+                    }
+                };
+
+            let create_subtitle = CreateSubtitle {
+                media_item_id: media_item.id,
+                file_path: subtitle_path.to_string_lossy().to_string(),
+                language,
+                format: subtitle_path
+                    .extension()
+                    .map(|ext| ext.to_string_lossy().to_lowercase())
+                    .unwrap_or_default(),
+            };
+            match Subtitle::create_if_missing(&self.db, create_subtitle).await {
+                Ok(Some(_)) => new_subtitles += 1,
+                Ok(None) => {}
+                Err(e) => error!(
+                    "Failed to record subtitle {}: {}",
+                    subtitle_path.display(),
+                    e
+                ),
+            }
+        }
+
+        new_subtitles
+    }
+
+    /// Record a single file
+    async fn index_file(
+        &self,
+        folder: &LibraryFolder,
+        entry_path: &Path,
+        file_path: &str,
+        file_size: i64,
+        modified_at: Option<DateTime<Utc>>,
+    ) -> Result<Indexed, sqlx::Error> {
+        // Extract title from filename
+        let title = extract_title(entry_path);
+
+        // Check if item already exists
+        match MediaItem::find_by_path(&self.db, file_path).await? {
+            Some(item) if is_replaced(&item, file_size, modified_at) => {
+                info!("Media item file changed: {}", file_path);
+                let content_hash = self.hash_file(entry_path);
+                MediaItem::mark_changed(
+                    &self.db,
+                    item.id,
+                    file_size,
+                    modified_at,
+                    content_hash.as_deref(),
+                )
+                .await?;
+                Ok(Indexed::Changed)
+            }
+            Some(item) => {
+                debug!("Media item already exists: {}", file_path);
+                if item.content_hash.is_none()
+                    && let Some(hash) = self.hash_file(entry_path)
+                    && let Err(e) = MediaItem::update_content_hash(&self.db, item.id, &hash).await
+                {
+                    error!("Failed to record content hash for {}: {}", file_path, e);
+                }
+                // Items indexed before modification times were kept
+                if item.modified_at.is_none()
+                    && let Some(modified_at) = modified_at
+                    && let Err(e) =
+                        MediaItem::update_modified_at(&self.db, item.id, modified_at).await
+                {
+                    error!(
+                        "Failed to record modification time for {}: {}",
+                        file_path, e
+                    );
+                }
+                Ok(Indexed::Existing)
+            }
+            None => {
+                let content_hash = self.hash_file(entry_path);
+                if let Some(hash) = &content_hash
+                    && let Some(moved) = self.find_moved(hash).await
+                {
+                    MediaItem::relocate(&self.db, moved.id, folder.id, file_path).await?;
+                    info!("Media item moved: {} -> {}", moved.file_path, file_path);
+                    return Ok(Indexed::Existing);
+                }
+
+                // Create new media item
+                let create_item = CreateMediaItem {
+                    library_folder_id: folder.id,
+                    media_type: folder.media_type,
+                    title: title.clone(),
+                    file_path: file_path.to_string(),
+                    file_size,
+                    content_hash,
+                    modified_at,
+                };
+
+                MediaItem::create(&self.db, create_item).await?;
+                info!("Added new media item: {}", title);
+                Ok(Indexed::New)
+            }
+        }
+    }
+
+    /// Content hash of a file, if hashing is enabled and the file is readable
+    fn hash_file(&self, path: &Path) -> Option<String> {
+        if !self.hash_contents {
+            return None;
+        }
+        content_hash(path)
+            .inspect_err(|e| warn!("Failed to hash {}: {}", path.display(), e))
+            .ok()
+    }
+
+    /// An item with this hash whose file is gone, i.e. one that was moved
+    async fn find_moved(&self, content_hash: &str) -> Option<MediaItem> {
+        match MediaItem::find_by_hash(&self.db, content_hash).await {
+            Ok(item) => item.filter(|item| !Path::new(&item.file_path).exists()),
+            Err(e) => {
+                error!(
+                    "Database error while looking up hash {}: {}",
+                    content_hash, e
+                );
+                None
+            }
+        }
+    }
+
     /// Scan all enabled library folders
     pub async fn scan_all_libraries(
         &self,
@@ -158,6 +482,8 @@ impl FileScanner {
                             new_items: 0,
                             existing_items: 0,
                             errors: 1,
+                            new_subtitles: 0,
+                            changed_items: 0,
                         },
                     ));
                 }
@@ -169,7 +495,7 @@ impl FileScanner {
 }
 
 /// Get supported file extensions for a media type
-fn get_supported_extensions(media_type: MediaType) -> Vec<&'static str> {
+pub fn get_supported_extensions(media_type: MediaType) -> Vec<&'static str> {
     match media_type {
         MediaType::Movie | MediaType::Tv => vec![
             "mkv", "mp4", "avi", "mov", "wmv", "flv", "webm", "m4v", "mpg", "mpeg", "m2ts", "ts",
@@ -179,6 +505,177 @@ fn get_supported_extensions(media_type: MediaType) -> Vec<&'static str> {
     }
 }
 
+/// Bytes hashed from each end of a file
+const HASH_CHUNK_SIZE: u64 = 64 * 1024;
+
+/// Fingerprint a file by its size and an xxHash of its first and last 64 KiB
+///
+/// This stays cheap for multi-gigabyte videos, at the cost of missing edits
+/// confined to the middle of a file.
+pub fn content_hash(path: &Path) -> io::Result<String> {
+    let mut file = File::open(path)?;
+    let size = file.metadata()?.len();
+    let mut hasher = Xxh3::new();
+    let mut chunk = Vec::with_capacity(HASH_CHUNK_SIZE as usize);
+
+    (&mut file).take(HASH_CHUNK_SIZE).read_to_end(&mut chunk)?;
+    hasher.update(&chunk);
+
+    if size > HASH_CHUNK_SIZE {
+        chunk.clear();
+        file.seek(SeekFrom::Start(
+            size.saturating_sub(HASH_CHUNK_SIZE).max(HASH_CHUNK_SIZE),
+        ))?;
+        file.take(HASH_CHUNK_SIZE).read_to_end(&mut chunk)?;
+        hasher.update(&chunk);
+    }
+
+    Ok(format!("{size:x}-{:016x}", hasher.digest()))
+}
+
+/// Per-library ignore file, in gitignore syntax
+pub const IGNORE_FILE: &str = ".ayiahignore";
+
+/// Files and folders never worth indexing: hidden entries, NAS metadata,
+/// partial downloads and sample clips
+const DEFAULT_IGNORE_PATTERNS: &[&str] = &[
+    ".*",
+    "@eaDir/",
+    "#recycle/",
+    "$RECYCLE.BIN/",
+    "lost+found/",
+    "Thumbs.db",
+    "*.part",
+    "*.crdownload",
+    "*.!qb",
+    "*.tmp",
+    "*.sample.*",
+    "*-sample.*",
+    "sample.*",
+];
+
+/// Extensions of external subtitle files
+const SUBTITLE_EXTENSIONS: &[&str] = &["srt", "ass", "ssa", "vtt", "sub"];
+
+/// Folder names holding trailers, featurettes and other bonus material
+const EXTRAS_FOLDERS: &[&str] = &[
+    "extras",
+    "featurettes",
+    "trailers",
+    "behind the scenes",
+    "deleted scenes",
+    "interviews",
+];
+
+/// Whether a walked directory holds extras rather than main media
+/// Whether the file at an item's path was replaced since it was indexed
+///
+/// Modification times are compared to the second, as file systems keep them
+/// at different precisions.
+fn is_replaced(item: &MediaItem, file_size: i64, modified_at: Option<DateTime<Utc>>) -> bool {
+    item.file_size != file_size
+        || matches!(
+            (item.modified_at, modified_at),
+            (Some(stored), Some(current)) if stored.timestamp() != current.timestamp()
+        )
+}
+
+fn is_extras_folder(entry: &walkdir::DirEntry) -> bool {
+    entry.file_type().is_dir()
+        && EXTRAS_FOLDERS.contains(&entry.file_name().to_string_lossy().to_lowercase().as_str())
+}
+
+/// Find the media file a subtitle belongs to and the subtitle's language
+///
+/// `Movie.srt` and `Movie.en.srt` both belong to `Movie.mkv` in the same
+/// folder; the segment after the media file's name is taken as the language.
+/// When several media names match, the longest wins.
+fn match_subtitle<'a>(
+    subtitle: &Path,
+    media_paths: &'a [PathBuf],
+) -> Option<(&'a Path, Option<String>)> {
+    let stem = subtitle.file_stem()?.to_string_lossy();
+
+    media_paths
+        .iter()
+        .filter(|media| media.parent() == subtitle.parent())
+        .filter_map(|media| {
+            let media_stem = media.file_stem()?.to_string_lossy();
+            let rest = stem.strip_prefix(media_stem.as_ref())?;
+            let language = if rest.is_empty() {
+                None
+            } else {
+                let tag = rest.strip_prefix('.')?.split('.').next()?;
+                Some(tag)
+                    .filter(|tag| is_language_tag(tag))
+                    .map(str::to_string)
+            };
+            Some((media_stem.len(), media.as_path(), language))
+        })
+        .max_by_key(|(len, _, _)| *len)
+        .map(|(_, media, language)| (media, language))
+}
+
+/// Loose check for tags like `en`, `eng`, `pt-BR` or `zh-Hans`
+fn is_language_tag(tag: &str) -> bool {
+    let mut parts = tag.split('-');
+    let primary = parts.next().unwrap_or_default();
+    (2..=3).contains(&primary.len())
+        && primary.chars().all(|c| c.is_ascii_alphabetic())
+        && parts.all(|part| {
+            (2..=4).contains(&part.len()) && part.chars().all(|c| c.is_ascii_alphanumeric())
+        })
+}
+
+/// Guess a file's media type from its extension and surrounding folders
+///
+/// Video files are TV when the file name has an `SxxEyy` marker or an absolute
+/// episode number like `Show - 12`, or sits in a season folder, and movies
+/// otherwise. Returns `None` for unknown extensions
+/// and for ones shared by several types, such as `pdf`; callers should then
+/// fall back to the library folder's type.
+pub(crate) fn detect_media_type(path: &Path) -> Option<MediaType> {
+    let ext = path.extension()?.to_string_lossy().to_lowercase();
+    let mut candidates = [MediaType::Movie, MediaType::Comic, MediaType::Book]
+        .into_iter()
+        .filter(|&media_type| get_supported_extensions(media_type).contains(&ext.as_str()));
+
+    let media_type = candidates.next()?;
+    if candidates.next().is_some() {
+        return None;
+    }
+    if media_type != MediaType::Movie {
+        return Some(media_type);
+    }
+
+    let is_episode = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy())
+        .is_some_and(|stem| {
+            parse_episode_marker(&stem).is_some() || parse_absolute_episode(&stem).is_some()
+        });
+    let in_season_folder = path
+        .parent()
+        .and_then(Path::file_name)
+        .is_some_and(|name| is_season_folder(&name.to_string_lossy()));
+
+    Some(if is_episode || in_season_folder {
+        MediaType::Tv
+    } else {
+        MediaType::Movie
+    })
+}
+
+/// Whether a folder name looks like `Season 1`, `Season 01` or `Specials`
+fn is_season_folder(name: &str) -> bool {
+    let name = name.to_lowercase();
+    name == "specials"
+        || name.strip_prefix("season").is_some_and(|number| {
+            let number = number.trim();
+            !number.is_empty() && number.chars().all(|c| c.is_ascii_digit())
+        })
+}
+
 /// Extract title from file path
 fn extract_title(path: &Path) -> String {
     path.file_stem()
@@ -201,4 +698,353 @@ pub enum FileScannerError {
 
     #[error("IO error: {0}")]
     IoError(#[from] std::io::Error),
+
+    #[error("Scan cancelled: progress receiver closed")]
+    Cancelled,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_media_type() {
+        let cases = [
+            ("/media/The Matrix (1999).mkv", Some(MediaType::Movie)),
+            ("/media/Breaking Bad S01E02.mkv", Some(MediaType::Tv)),
+            ("/media/breaking.bad.s05e16.720p.mp4", Some(MediaType::Tv)),
+            (
+                "/anime/[Group] Cowboy Bebop - 24 [1080p].mkv",
+                Some(MediaType::Tv),
+            ),
+            (
+                "/media/Breaking Bad/Season 01/Pilot.mkv",
+                Some(MediaType::Tv),
+            ),
+            (
+                "/media/Breaking Bad/Specials/Minisode.mkv",
+                Some(MediaType::Tv),
+            ),
+            ("/media/Seasons Greetings/Movie.mkv", Some(MediaType::Movie)),
+            ("/comics/Saga 001.CBZ", Some(MediaType::Comic)),
+            ("/books/Dune.epub", Some(MediaType::Book)),
+            ("/books/Dune.pdf", None),
+            ("/media/notes.txt", None),
+            ("/media/README", None),
+        ];
+
+        for (path, expected) in cases {
+            assert_eq!(detect_media_type(Path::new(path)), expected, "{path}");
+        }
+    }
+
+    #[test]
+    fn test_match_subtitle_reads_language_suffix() {
+        let media = vec![
+            PathBuf::from("/movies/Heat.mkv"),
+            PathBuf::from("/movies/Heat Director's Cut.mkv"),
+            PathBuf::from("/movies/other/Heat.mkv"),
+        ];
+        let cases = [
+            ("/movies/Heat.srt", Some(("/movies/Heat.mkv", None))),
+            (
+                "/movies/Heat.en.srt",
+                Some(("/movies/Heat.mkv", Some("en"))),
+            ),
+            (
+                "/movies/Heat.pt-BR.forced.ass",
+                Some(("/movies/Heat.mkv", Some("pt-BR"))),
+            ),
+            (
+                "/movies/Heat.commentary.srt",
+                Some(("/movies/Heat.mkv", None)),
+            ),
+            (
+                "/movies/Heat Director's Cut.ja.srt",
+                Some(("/movies/Heat Director's Cut.mkv", Some("ja"))),
+            ),
+            ("/movies/Heatwave.srt", None),
+            ("/movies/Ronin.srt", None),
+        ];
+
+        for (subtitle, expected) in cases {
+            let found = match_subtitle(Path::new(subtitle), &media);
+            let found = found
+                .as_ref()
+                .map(|(media, language)| (media.to_str().unwrap(), language.as_deref()));
+            assert_eq!(found, expected, "{subtitle}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_scan_attaches_subtitles_and_skips_extras() {
+        let db = crate::db::test_pool().await;
+        let dir = tempfile::tempdir().unwrap();
+        for name in [
+            "Heat (1995).mkv",
+            "Heat (1995).en.srt",
+            "Heat (1995).ja.ass",
+        ] {
+            std::fs::write(dir.path().join(name), b"data").unwrap();
+        }
+        let extras = dir.path().join("Extras");
+        std::fs::create_dir(&extras).unwrap();
+        std::fs::write(extras.join("Trailer.mkv"), b"data").unwrap();
+
+        let folder = LibraryFolder::create(
+            &db,
+            crate::entities::CreateLibraryFolder {
+                name: "Movies".to_string(),
+                path: dir.path().to_string_lossy().to_string(),
+                media_type: MediaType::Movie,
+                extensions: None,
+            },
+        )
+        .await
+        .unwrap();
+
+        let scanner = FileScanner::new(db.clone());
+        let result = scanner.scan_library_folder(&folder).await.unwrap();
+        assert_eq!(result.total_files, 1);
+        assert_eq!(result.new_items, 1);
+        assert_eq!(result.new_subtitles, 2);
+
+        let item =
+            MediaItem::find_by_path(&db, &dir.path().join("Heat (1995).mkv").to_string_lossy())
+                .await
+                .unwrap()
+                .unwrap();
+        let subtitles = Subtitle::list_by_media_item(&db, item.id).await.unwrap();
+        let tracks: Vec<_> = subtitles
+            .iter()
+            .map(|s| (s.language.as_deref(), s.format.as_str()))
+            .collect();
+        assert_eq!(tracks, [(Some("en"), "srt"), (Some("ja"), "ass")]);
+
+        // Rescanning doesn't record the same files twice
+        let result = scanner.scan_library_folder(&folder).await.unwrap();
+        assert_eq!((result.new_items, result.new_subtitles), (0, 0));
+    }
+
+    #[tokio::test]
+    async fn test_scan_skips_ignored_files_and_folders() {
+        let db = crate::db::test_pool().await;
+        let dir = tempfile::tempdir().unwrap();
+        for name in ["Heat (1995).mkv", "Heat (1995).sample.mkv", "Ronin.mkv"] {
+            std::fs::write(dir.path().join(name), b"data").unwrap();
+        }
+        let git = dir.path().join(".git");
+        std::fs::create_dir(&git).unwrap();
+        std::fs::write(git.join("Packed.mkv"), b"data").unwrap();
+        let junk = dir.path().join("Junk");
+        std::fs::create_dir(&junk).unwrap();
+        std::fs::write(junk.join("Old.mkv"), b"data").unwrap();
+        std::fs::write(dir.path().join(IGNORE_FILE), "Ronin.mkv\n").unwrap();
+
+        let folder = LibraryFolder::create(
+            &db,
+            crate::entities::CreateLibraryFolder {
+                name: "Movies".to_string(),
+                path: dir.path().to_string_lossy().to_string(),
+                media_type: MediaType::Movie,
+                extensions: None,
+            },
+        )
+        .await
+        .unwrap();
+
+        let scanner = FileScanner::new(db.clone()).with_ignore_patterns(vec!["Junk/".to_string()]);
+        let result = scanner.scan_library_folder(&folder).await.unwrap();
+        assert_eq!((result.total_files, result.new_items), (1, 1));
+        assert!(
+            MediaItem::find_by_path(&db, &dir.path().join("Heat (1995).mkv").to_string_lossy())
+                .await
+                .unwrap()
+                .is_some()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_scan_stops_when_progress_receiver_is_gone() {
+        let db = crate::db::test_pool().await;
+        let dir = tempfile::tempdir().unwrap();
+        for name in ["Alien.mkv", "Heat.mkv", "Ronin.mkv"] {
+            std::fs::write(dir.path().join(name), b"data").unwrap();
+        }
+        let folder = LibraryFolder::create(
+            &db,
+            crate::entities::CreateLibraryFolder {
+                name: "Movies".to_string(),
+                path: dir.path().to_string_lossy().to_string(),
+                media_type: MediaType::Movie,
+                extensions: None,
+            },
+        )
+        .await
+        .unwrap();
+
+        let (tx, rx) = mpsc::channel(1);
+        drop(rx);
+        let result = FileScanner::new(db.clone())
+            .scan_library_folder_with_progress(&folder, Some(tx))
+            .await;
+        assert!(matches!(result, Err(FileScannerError::Cancelled)));
+
+        // The first file is indexed before its progress event fails to send
+        for (name, indexed) in [("Alien.mkv", true), ("Heat.mkv", false)] {
+            let path = dir.path().join(name);
+            let item = MediaItem::find_by_path(&db, &path.to_string_lossy())
+                .await
+                .unwrap();
+            assert_eq!(item.is_some(), indexed, "{name}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_rescan_follows_moved_file_by_hash() {
+        let db = crate::db::test_pool().await;
+        let dir = tempfile::tempdir().unwrap();
+        let original = dir.path().join("Heat (1995).mkv");
+        std::fs::write(&original, b"heat").unwrap();
+
+        let folder = LibraryFolder::create(
+            &db,
+            crate::entities::CreateLibraryFolder {
+                name: "Movies".to_string(),
+                path: dir.path().to_string_lossy().to_string(),
+                media_type: MediaType::Movie,
+                extensions: None,
+            },
+        )
+        .await
+        .unwrap();
+
+        let scanner = FileScanner::new(db.clone()).with_content_hashing(true);
+        let result = scanner.scan_library_folder(&folder).await.unwrap();
+        assert_eq!(result.new_items, 1);
+
+        let moved = dir.path().join("Heat").join("Heat (1995).mkv");
+        std::fs::create_dir(moved.parent().unwrap()).unwrap();
+        std::fs::rename(&original, &moved).unwrap();
+
+        let result = scanner.scan_library_folder(&folder).await.unwrap();
+        assert_eq!((result.new_items, result.existing_items), (0, 1));
+
+        let items = MediaItem::list_by_folder(&db, folder.id).await.unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].file_path, moved.to_string_lossy());
+        assert_eq!(items[0].content_hash, Some(content_hash(&moved).unwrap()));
+    }
+
+    #[tokio::test]
+    async fn test_folder_extensions_override_defaults() {
+        let db = crate::db::test_pool().await;
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("Heat (1995).divx"), b"heat").unwrap();
+        std::fs::write(dir.path().join("Ronin (1998).mkv"), b"ronin").unwrap();
+
+        let folder = LibraryFolder::create(
+            &db,
+            crate::entities::CreateLibraryFolder {
+                name: "Movies".to_string(),
+                path: dir.path().to_string_lossy().to_string(),
+                media_type: MediaType::Movie,
+                extensions: crate::entities::normalize_extensions(&[".DivX", "mkv"]),
+            },
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            folder.extensions,
+            Some(vec!["divx".to_string(), "mkv".to_string()])
+        );
+
+        let result = FileScanner::new(db.clone())
+            .scan_library_folder(&folder)
+            .await
+            .unwrap();
+        assert_eq!((result.total_files, result.new_items), (2, 2));
+        let items = MediaItem::list_by_folder(&db, folder.id).await.unwrap();
+        assert!(items.iter().any(|item| item.file_path.ends_with(".divx")));
+    }
+
+    #[tokio::test]
+    async fn test_rescan_flags_replaced_file() {
+        let db = crate::db::test_pool().await;
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("Heat (1995).mkv");
+        std::fs::write(&file, b"heat").unwrap();
+
+        let folder = LibraryFolder::create(
+            &db,
+            crate::entities::CreateLibraryFolder {
+                name: "Movies".to_string(),
+                path: dir.path().to_string_lossy().to_string(),
+                media_type: MediaType::Movie,
+                extensions: None,
+            },
+        )
+        .await
+        .unwrap();
+
+        let scanner = FileScanner::new(db.clone());
+        scanner.scan_library_folder(&folder).await.unwrap();
+        let result = scanner.scan_library_folder(&folder).await.unwrap();
+        assert_eq!((result.existing_items, result.changed_items), (1, 0));
+
+        // Same path, new content
+        std::fs::write(&file, b"heat, director's cut").unwrap();
+        let result = scanner.scan_library_folder(&folder).await.unwrap();
+        assert_eq!((result.new_items, result.changed_items), (0, 1));
+
+        let items = MediaItem::list_by_folder(&db, folder.id).await.unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].file_size, 20);
+        assert!(items[0].metadata_stale);
+        assert!(items[0].modified_at.is_some());
+        // Flagged items are picked up by the next metadata fetch
+        assert_eq!(
+            MediaItem::list_without_metadata(&db, folder.id)
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_scan_survives_symlink_loops() {
+        use std::os::unix::fs::symlink;
+
+        let db = crate::db::test_pool().await;
+        let dir = tempfile::tempdir().unwrap();
+        let movies = dir.path().join("Movies");
+        std::fs::create_dir(&movies).unwrap();
+        std::fs::write(movies.join("Heat (1995).mkv"), b"data").unwrap();
+        std::fs::write(dir.path().join("Ronin (1998).mkv"), b"data").unwrap();
+
+        // A link back to the root, and a second route to the same movie
+        symlink(dir.path(), movies.join("Back")).unwrap();
+        symlink(&movies, dir.path().join("Shortcut")).unwrap();
+
+        let folder = LibraryFolder::create(
+            &db,
+            crate::entities::CreateLibraryFolder {
+                name: "Movies".to_string(),
+                path: dir.path().to_string_lossy().to_string(),
+                media_type: MediaType::Movie,
+                extensions: None,
+            },
+        )
+        .await
+        .unwrap();
+
+        let scanner = FileScanner::new(db.clone());
+        let result = scanner.scan_library_folder(&folder).await.unwrap();
+        assert_eq!((result.total_files, result.new_items), (2, 2));
+
+        let result = scanner.scan_library_folder(&folder).await.unwrap();
+        assert_eq!((result.new_items, result.existing_items), (0, 2));
+    }
 }
@@ -0,0 +1,247 @@
+//! Local copies of poster and backdrop images
+//!
+//! Image URLs in saved metadata are replaced with `/api/images/{hash}`, served
+//! from files in the image directory named after a hash of the remote URL.
+//! Depending on the mode, an image is downloaded right after its metadata is
+//! saved or on the first request for it. Whenever a download fails, the
+//! remote URL keeps being used.
+
+use std::{
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use reqwest::{Client, header::CONTENT_TYPE};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+use xxhash_rust::xxh3::xxh3_128;
+
+use crate::entities::{CachedImage, CreateVideoMetadata};
+
+/// Route prefix local image paths point at
+pub const IMAGE_ROUTE: &str = "/api/images";
+
+/// How long a single image download may take
+const DOWNLOAD_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// When referenced images are downloaded
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImageCacheMode {
+    /// Keep remote URLs in metadata
+    Off,
+    /// Download on the first request for the image
+    #[default]
+    Lazy,
+    /// Download as soon as metadata is saved
+    Eager,
+}
+
+/// Get the image directory, next to the main database
+#[must_use]
+pub fn default_image_dir() -> PathBuf {
+    std::env::var("AYIAH_DATA_DIR").map_or_else(
+        |_| {
+            dirs::data_dir()
+                .unwrap_or_else(|| PathBuf::from("."))
+                .join("ayiah")
+                .join("images")
+        },
+        |data_dir| PathBuf::from(data_dir).join("images"),
+    )
+}
+
+/// Hash identifying a remote image URL
+#[must_use]
+pub fn url_hash(url: &str) -> String {
+    format!("{:032x}", xxh3_128(url.as_bytes()))
+}
+
+/// Local route an image URL is served from once localized
+#[must_use]
+pub fn local_route(url: &str) -> String {
+    format!("{IMAGE_ROUTE}/{}", url_hash(url))
+}
+
+/// Image cache errors
+#[derive(Debug, thiserror::Error)]
+pub enum ImageCacheError {
+    #[error("Image not found")]
+    NotFound,
+
+    #[error("Failed to download {url}: {message}")]
+    Download { url: String, message: String },
+
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
+
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// An image available on disk
+#[derive(Debug, Clone)]
+pub struct StoredImage {
+    pub path: PathBuf,
+    pub content_type: Option<String>,
+}
+
+/// Downloads remote images and serves them from the image directory
+pub struct ImageCache {
+    db: sqlx::SqlitePool,
+    dir: PathBuf,
+    mode: ImageCacheMode,
+    client: Client,
+}
+
+impl ImageCache {
+    #[must_use]
+    pub fn new(db: sqlx::SqlitePool, dir: impl Into<PathBuf>, mode: ImageCacheMode) -> Self {
+        Self {
+            db,
+            dir: dir.into(),
+            mode,
+            client: Client::builder()
+                .user_agent("Ayiah/0.1.0")
+                .timeout(DOWNLOAD_TIMEOUT)
+                .build()
+                .expect("Failed to build HTTP client"),
+        }
+    }
+
+    #[must_use]
+    pub const fn mode(&self) -> ImageCacheMode {
+        self.mode
+    }
+
+    /// Directory images are stored in
+    #[must_use]
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Replace a remote image URL with its local route
+    ///
+    /// Anything that isn't an `http(s)` URL is returned unchanged, as is the
+    /// URL itself when the cache is off or an eager download fails.
+    pub async fn localize(&self, url: Option<String>) -> Option<String> {
+        let url = url?;
+        if self.mode == ImageCacheMode::Off
+            || !(url.starts_with("http://") || url.starts_with("https://"))
+        {
+            return Some(url);
+        }
+
+        let route = local_route(&url);
+        let hash = url_hash(&url);
+        if let Err(e) = CachedImage::register(&self.db, &hash, &url).await {
+            warn!("Failed to register image {}: {}", url, e);
+            return Some(url);
+        }
+        if self.mode == ImageCacheMode::Eager
+            && let Err(e) = self.fetch(&hash).await
+        {
+            warn!("Keeping remote image {}: {}", url, e);
+            return Some(url);
+        }
+
+        Some(route)
+    }
+
+    /// Localize the poster and backdrop of metadata about to be saved
+    pub async fn localize_metadata(&self, metadata: &mut CreateVideoMetadata) {
+        metadata.poster_path = self.localize(metadata.poster_path.take()).await;
+        metadata.backdrop_path = self.localize(metadata.backdrop_path.take()).await;
+    }
+
+    /// Get an image from disk, downloading it first if needed
+    pub async fn fetch(&self, hash: &str) -> Result<StoredImage, ImageCacheError> {
+        // Hashes double as file names, so nothing else may reach the filesystem
+        if hash.len() != 32 || !hash.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return Err(ImageCacheError::NotFound);
+        }
+        let image = CachedImage::find_by_hash(&self.db, hash)
+            .await?
+            .ok_or(ImageCacheError::NotFound)?;
+
+        let path = self.dir.join(hash);
+        if image.downloaded_at.is_some() && tokio::fs::try_exists(&path).await? {
+            return Ok(StoredImage {
+                path,
+                content_type: image.content_type,
+            });
+        }
+
+        self.download(&image, path).await
+    }
+
+    async fn download(
+        &self,
+        image: &CachedImage,
+        path: PathBuf,
+    ) -> Result<StoredImage, ImageCacheError> {
+        let failed = |e: reqwest::Error| ImageCacheError::Download {
+            url: image.url.clone(),
+            message: e.to_string(),
+        };
+        let response = self
+            .client
+            .get(&image.url)
+            .send()
+            .await
+            .and_then(reqwest::Response::error_for_status)
+            .map_err(failed)?;
+        let content_type = response
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_string);
+        let bytes = response.bytes().await.map_err(failed)?;
+
+        // Write under a unique name first so concurrent fetches never serve a
+        // partial file
+        tokio::fs::create_dir_all(&self.dir).await?;
+        let partial = self
+            .dir
+            .join(format!("{}.{}.part", image.hash, uuid::Uuid::new_v4()));
+        tokio::fs::write(&partial, &bytes).await?;
+        tokio::fs::rename(&partial, &path).await?;
+
+        CachedImage::mark_downloaded(&self.db, &image.hash, content_type.as_deref()).await?;
+
+        Ok(StoredImage { path, content_type })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_failed_eager_download_keeps_remote_url() {
+        use wiremock::{Mock, MockServer, ResponseTemplate, matchers::method};
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(404))
+            .mount(&server)
+            .await;
+        let dir = tempfile::tempdir().unwrap();
+        let cache = ImageCache::new(
+            crate::db::test_pool().await,
+            dir.path(),
+            ImageCacheMode::Eager,
+        );
+
+        let url = format!("{}/missing.jpg", server.uri());
+        assert_eq!(cache.localize(Some(url.clone())).await, Some(url));
+        assert_eq!(
+            cache
+                .localize(Some("/relative.jpg".to_string()))
+                .await
+                .as_deref(),
+            Some("/relative.jpg")
+        );
+        assert_eq!(cache.localize(None).await, None);
+    }
+}
@@ -0,0 +1,507 @@
+//! In-memory queue for background work such as scans and metadata refreshes
+//!
+//! Jobs run on the tokio runtime, at most `workers` at a time. Their status is
+//! kept in memory only, so it does not survive a restart. Every change is also
+//! broadcast as a [`JobEvent`] to whoever subscribes.
+//!
+//! Jobs can be cancelled with [`JobQueue::cancel`]. A queued job then never
+//! starts; a running one is expected to check [`JobHandle::is_cancelled`]
+//! between units of work and return early, keeping whatever it finished.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    future::Future,
+    panic::AssertUnwindSafe,
+    sync::{
+        Arc,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::Duration,
+};
+
+use chrono::{DateTime, Utc};
+use futures::FutureExt;
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use tokio::sync::{Semaphore, broadcast};
+use tokio_util::{sync::CancellationToken, task::TaskTracker};
+use tracing::Instrument;
+
+use crate::services::ScanEvent;
+
+/// Number of jobs allowed to run at once by default
+pub const DEFAULT_JOB_WORKERS: usize = 2;
+
+/// Finished jobs kept for status queries; older ones are forgotten
+const MAX_FINISHED_JOBS: usize = 100;
+
+/// Events buffered per subscriber; slower subscribers start missing events
+const EVENT_CAPACITY: usize = 256;
+
+/// How long [`JobQueue::drain`] waits for running jobs by default
+pub const DEFAULT_DRAIN_TIMEOUT: Duration = Duration::from_secs(30);
+
+pub type JobId = u64;
+
+/// Lifecycle of a job
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum JobStatus {
+    Queued,
+    Running,
+    Done,
+    Failed,
+    Cancelled,
+}
+
+impl JobStatus {
+    /// Whether the job has stopped, successfully, with an error or cancelled
+    #[must_use]
+    pub const fn is_finished(self) -> bool {
+        matches!(self, Self::Done | Self::Failed | Self::Cancelled)
+    }
+}
+
+/// Snapshot of a job's state
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobInfo {
+    pub id: JobId,
+    /// What the job does, e.g. `scan` or `refresh_metadata`
+    pub kind: String,
+    pub status: JobStatus,
+    /// Units of work finished so far
+    pub processed: usize,
+    /// Total units of work, once the job knows it
+    pub total: Option<usize>,
+    pub error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+}
+
+/// Change broadcast to job subscribers
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum JobEvent {
+    /// A job was queued, started, made progress or finished
+    Job(JobInfo),
+    /// Per-file progress of a scan job
+    Scan { job_id: JobId, event: ScanEvent },
+}
+
+type Jobs = Arc<RwLock<BTreeMap<JobId, JobInfo>>>;
+
+/// Cancellation tokens of jobs that have not finished yet
+type Tokens = Arc<Mutex<HashMap<JobId, CancellationToken>>>;
+
+/// Handle given to a running job for reporting progress
+#[derive(Clone)]
+pub struct JobHandle {
+    id: JobId,
+    jobs: Jobs,
+    events: broadcast::Sender<JobEvent>,
+    cancel: CancellationToken,
+}
+
+impl JobHandle {
+    #[must_use]
+    pub const fn id(&self) -> JobId {
+        self.id
+    }
+
+    /// Whether the job has been asked to stop
+    #[must_use]
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel.is_cancelled()
+    }
+
+    /// Token cancelled when the job is asked to stop, for passing on to
+    /// services that check it themselves
+    #[must_use]
+    pub const fn cancellation_token(&self) -> &CancellationToken {
+        &self.cancel
+    }
+
+    /// Record how much of the job is done
+    pub fn set_progress(&self, processed: usize, total: usize) {
+        self.update(|job| {
+            job.processed = processed;
+            job.total = Some(total);
+        });
+    }
+
+    /// Pass a scan event on to subscribers
+    pub fn send_scan_event(&self, event: ScanEvent) {
+        let _ = self.events.send(JobEvent::Scan {
+            job_id: self.id,
+            event,
+        });
+    }
+
+    fn update(&self, f: impl FnOnce(&mut JobInfo)) {
+        let updated = self.jobs.write().get_mut(&self.id).map(|job| {
+            f(job);
+            job.clone()
+        });
+        // Nobody listening is fine
+        if let Some(job) = updated {
+            let _ = self.events.send(JobEvent::Job(job));
+        }
+    }
+
+    fn finish(&self, result: Result<(), String>) {
+        let cancelled = self.is_cancelled();
+        self.update(|job| {
+            job.status = match result {
+                // However the job ended, it stopped because it was asked to
+                _ if cancelled => JobStatus::Cancelled,
+                Ok(()) => JobStatus::Done,
+                Err(error) => {
+                    job.error = Some(error);
+                    JobStatus::Failed
+                }
+            };
+            job.finished_at = Some(Utc::now());
+        });
+    }
+}
+
+/// Outcome of draining the queue at shutdown
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DrainOutcome {
+    /// Running jobs that finished in time
+    pub drained: usize,
+    /// Running jobs still going when the timeout ran out
+    pub abandoned: usize,
+}
+
+/// Background job queue with a bounded number of workers
+pub struct JobQueue {
+    jobs: Jobs,
+    next_id: AtomicU64,
+    workers: Arc<Semaphore>,
+    events: broadcast::Sender<JobEvent>,
+    tasks: TaskTracker,
+    tokens: Tokens,
+}
+
+impl JobQueue {
+    /// Create a queue running at most `workers` jobs at once
+    #[must_use]
+    pub fn new(workers: usize) -> Self {
+        Self {
+            jobs: Arc::default(),
+            next_id: AtomicU64::new(1),
+            workers: Arc::new(Semaphore::new(workers.max(1))),
+            events: broadcast::channel(EVENT_CAPACITY).0,
+            tasks: TaskTracker::new(),
+            tokens: Tokens::default(),
+        }
+    }
+
+    /// Queue `job` and return its ID
+    ///
+    /// The job starts once a worker is free. An `Err` or a panic marks it as
+    /// failed, unless the job was cancelled. Must be called from within a tokio runtime. Jobs queued after
+    /// [`drain`](Self::drain) never start.
+    pub fn enqueue<F, Fut>(&self, kind: impl Into<String>, job: F) -> JobId
+    where
+        F: FnOnce(JobHandle) -> Fut + Send + 'static,
+        Fut: Future<Output = Result<(), String>> + Send + 'static,
+    {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let info = JobInfo {
+            id,
+            kind: kind.into(),
+            status: JobStatus::Queued,
+            processed: 0,
+            total: None,
+            error: None,
+            created_at: Utc::now(),
+            finished_at: None,
+        };
+        {
+            let mut jobs = self.jobs.write();
+            prune_finished(&mut jobs);
+            jobs.insert(id, info.clone());
+        }
+        let _ = self.events.send(JobEvent::Job(info));
+
+        let cancel = CancellationToken::new();
+        self.tokens.lock().insert(id, cancel.clone());
+        let handle = JobHandle {
+            id,
+            jobs: self.jobs.clone(),
+            events: self.events.clone(),
+            cancel,
+        };
+        let workers = self.workers.clone();
+        let tokens = self.tokens.clone();
+        self.tasks.spawn(
+            async move {
+                let permit = tokio::select! {
+                    permit = workers.acquire_owned() => permit,
+                    () = handle.cancel.cancelled() => {
+                        tokens.lock().remove(&id);
+                        handle.finish(Ok(()));
+                        return;
+                    }
+                };
+                let Ok(_permit) = permit else {
+                    return;
+                };
+                handle.update(|job| job.status = JobStatus::Running);
+
+                let result = AssertUnwindSafe(job(handle.clone()))
+                    .catch_unwind()
+                    .await
+                    .unwrap_or_else(|_| Err("Job panicked".to_string()));
+                tokens.lock().remove(&id);
+                match &result {
+                    _ if handle.is_cancelled() => tracing::info!("Job {id} cancelled"),
+                    Err(e) => tracing::warn!("Job {id} failed: {e}"),
+                    Ok(()) => {}
+                }
+                handle.finish(result);
+            }
+            .in_current_span(),
+        );
+
+        id
+    }
+
+    /// Look up a job by ID
+    #[must_use]
+    pub fn get(&self, id: JobId) -> Option<JobInfo> {
+        self.jobs.read().get(&id).cloned()
+    }
+
+    /// Ask a queued or running job to stop
+    ///
+    /// Returns `false` if the job is unknown or has already finished. A queued
+    /// job never starts; a running one is marked cancelled once it returns.
+    pub fn cancel(&self, id: JobId) -> bool {
+        let Some(token) = self.tokens.lock().get(&id).cloned() else {
+            return false;
+        };
+        token.cancel();
+        true
+    }
+
+    /// Receive every job change from now on
+    ///
+    /// A subscriber more than a few hundred events behind gets
+    /// [`RecvError::Lagged`](broadcast::error::RecvError::Lagged).
+    #[must_use]
+    pub fn subscribe(&self) -> broadcast::Receiver<JobEvent> {
+        self.events.subscribe()
+    }
+
+    /// All known jobs, newest first
+    #[must_use]
+    pub fn list(&self) -> Vec<JobInfo> {
+        self.jobs.read().values().rev().cloned().collect()
+    }
+
+    /// Stop starting jobs and wait up to `timeout` for running ones to finish
+    ///
+    /// Meant for shutdown: queued jobs are left queued, and jobs still running
+    /// after the timeout are dropped wherever they are.
+    pub async fn drain(&self, timeout: Duration) -> DrainOutcome {
+        self.workers.close();
+        self.tasks.close();
+
+        let running = self.running();
+        let abandoned = match tokio::time::timeout(timeout, self.tasks.wait()).await {
+            Ok(()) => 0,
+            Err(_) => self.running(),
+        };
+
+        DrainOutcome {
+            drained: running.saturating_sub(abandoned),
+            abandoned,
+        }
+    }
+
+    fn running(&self) -> usize {
+        self.jobs
+            .read()
+            .values()
+            .filter(|job| job.status == JobStatus::Running)
+            .count()
+    }
+}
+
+impl Default for JobQueue {
+    fn default() -> Self {
+        Self::new(DEFAULT_JOB_WORKERS)
+    }
+}
+
+/// Drop the oldest finished jobs once more than `MAX_FINISHED_JOBS` are kept
+fn prune_finished(jobs: &mut BTreeMap<JobId, JobInfo>) {
+    let finished: Vec<JobId> = jobs
+        .values()
+        .filter(|job| job.status.is_finished())
+        .map(|job| job.id)
+        .collect();
+
+    for id in finished
+        .iter()
+        .take(finished.len().saturating_sub(MAX_FINISHED_JOBS - 1))
+    {
+        jobs.remove(id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    async fn wait_for(queue: &JobQueue, id: JobId) -> JobInfo {
+        tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                let job = queue.get(id).unwrap();
+                if job.status.is_finished() {
+                    return job;
+                }
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("job did not finish")
+    }
+
+    #[tokio::test]
+    async fn test_job_runs_to_completion_with_progress() {
+        let queue = JobQueue::new(1);
+        let (release, wait) = tokio::sync::oneshot::channel::<()>();
+
+        let id = queue.enqueue("scan", |handle| async move {
+            handle.set_progress(1, 3);
+            wait.await.map_err(|e| e.to_string())?;
+            handle.set_progress(3, 3);
+            Ok(())
+        });
+
+        let job = queue.get(id).unwrap();
+        assert_eq!(job.kind, "scan");
+        assert!(!job.status.is_finished());
+
+        release.send(()).unwrap();
+        let job = wait_for(&queue, id).await;
+        assert_eq!(job.status, JobStatus::Done);
+        assert_eq!((job.processed, job.total), (3, Some(3)));
+        assert!(job.finished_at.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_failed_and_panicking_jobs_are_marked_failed() {
+        let queue = JobQueue::new(2);
+
+        let failed = queue.enqueue("refresh_metadata", |_| async { Err("boom".to_string()) });
+        let panicked = queue.enqueue("refresh_metadata", |_| async { panic!("oops") });
+
+        let job = wait_for(&queue, failed).await;
+        assert_eq!(job.status, JobStatus::Failed);
+        assert_eq!(job.error.as_deref(), Some("boom"));
+        assert_eq!(wait_for(&queue, panicked).await.status, JobStatus::Failed);
+
+        let ids: Vec<_> = queue.list().iter().map(|job| job.id).collect();
+        assert_eq!(ids, [panicked, failed]);
+    }
+
+    #[tokio::test]
+    async fn test_drain_lets_running_jobs_finish() {
+        let queue = JobQueue::new(1);
+        let (started, wait_started) = tokio::sync::oneshot::channel::<()>();
+        let running = queue.enqueue("scan", |_| async move {
+            let _ = started.send(());
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            Ok(())
+        });
+        let queued = queue.enqueue("scan", |_| async { Ok(()) });
+        wait_started.await.unwrap();
+
+        let outcome = queue.drain(Duration::from_secs(5)).await;
+        assert_eq!(
+            outcome,
+            DrainOutcome {
+                drained: 1,
+                abandoned: 0
+            }
+        );
+        assert_eq!(queue.get(running).unwrap().status, JobStatus::Done);
+        assert_eq!(queue.get(queued).unwrap().status, JobStatus::Queued);
+
+        let stuck = JobQueue::new(1);
+        stuck.enqueue("scan", |_| std::future::pending());
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        let outcome = stuck.drain(Duration::from_millis(10)).await;
+        assert_eq!(outcome.abandoned, 1);
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_jobs_stop_and_are_marked_cancelled() {
+        let queue = JobQueue::new(1);
+        let (started, wait_started) = tokio::sync::oneshot::channel::<()>();
+        let running = queue.enqueue("refresh_metadata", |handle| async move {
+            let _ = started.send(());
+            let mut processed = 0;
+            while !handle.is_cancelled() {
+                processed += 1;
+                handle.set_progress(processed, 1000);
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+            Ok(())
+        });
+        let queued = queue.enqueue("scan", |_| async { panic!("cancelled job started") });
+        wait_started.await.unwrap();
+
+        assert!(queue.cancel(queued));
+        assert!(queue.cancel(running));
+
+        let job = wait_for(&queue, running).await;
+        assert_eq!(job.status, JobStatus::Cancelled);
+        assert!(job.processed > 0);
+        assert!(job.error.is_none());
+        assert_eq!(wait_for(&queue, queued).await.status, JobStatus::Cancelled);
+
+        // Finished and unknown jobs cannot be cancelled
+        assert!(!queue.cancel(running));
+        assert!(!queue.cancel(999));
+    }
+
+    #[test]
+    fn test_prune_keeps_recent_finished_jobs() {
+        let mut jobs = BTreeMap::new();
+        for id in 1..=(MAX_FINISHED_JOBS as u64 + 5) {
+            jobs.insert(
+                id,
+                JobInfo {
+                    id,
+                    kind: "scan".to_string(),
+                    status: if id == 1 {
+                        JobStatus::Running
+                    } else {
+                        JobStatus::Done
+                    },
+                    processed: 0,
+                    total: None,
+                    error: None,
+                    created_at: Utc::now(),
+                    finished_at: None,
+                },
+            );
+        }
+
+        prune_finished(&mut jobs);
+
+        // Room is left for the job about to be inserted
+        assert_eq!(jobs.len(), MAX_FINISHED_JOBS);
+        assert!(jobs.contains_key(&1));
+        assert!(!jobs.contains_key(&2));
+        assert!(jobs.contains_key(&(MAX_FINISHED_JOBS as u64 + 5)));
+    }
+}
@@ -0,0 +1,328 @@
+use std::{collections::HashSet, fmt::Display, path::Path};
+
+use futures::{Stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use sqlx::{Connection, QueryBuilder, Sqlite, SqliteConnection};
+use tracing::{info, warn};
+
+use crate::entities::{LibraryFolder, MediaItem, MediaItemWithMetadata, VideoMetadata};
+
+/// Lines written per multi-row insert
+const BATCH_SIZE: usize = 500;
+
+/// How imported items whose `file_path` already exists are handled
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportMode {
+    /// Leave the existing item and its metadata alone
+    #[default]
+    Skip,
+    /// Overwrite the existing item and its metadata
+    Upsert,
+}
+
+/// Outcome of an import
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ImportReport {
+    pub inserted: usize,
+    pub updated: usize,
+    pub skipped: usize,
+    pub errored: usize,
+    /// One message per failed line or batch
+    pub errors: Vec<String>,
+}
+
+/// A validated line waiting to be written
+struct ImportRow {
+    line: usize,
+    library_folder_id: i64,
+    media_item: MediaItem,
+    metadata: Option<VideoMetadata>,
+}
+
+/// Import newline-delimited JSON in the format of the library export
+///
+/// Everything runs in one transaction, written in batches of multi-row
+/// inserts. A line that fails to parse, or whose file isn't inside any
+/// library folder, is reported and skipped without aborting the rest; item
+/// IDs are reassigned, and items are attached to the folder containing them.
+pub async fn import_ndjson<S, B, E>(
+    db: &sqlx::SqlitePool,
+    body: S,
+    mode: ImportMode,
+) -> Result<ImportReport, sqlx::Error>
+where
+    S: Stream<Item = Result<B, E>>,
+    B: AsRef<[u8]>,
+    E: Display,
+{
+    let folders = LibraryFolder::list_all(db).await?;
+    let mut tx = db.begin().await?;
+    let mut report = ImportReport::default();
+    let mut batch = Vec::with_capacity(BATCH_SIZE);
+    let mut buffer = Vec::new();
+    let mut line_number = 0;
+
+    let mut body = std::pin::pin!(body);
+    loop {
+        let chunk = match body.next().await {
+            Some(Ok(chunk)) => Some(chunk),
+            Some(Err(e)) => {
+                report
+                    .errors
+                    .push(format!("Failed to read request body: {e}"));
+                None
+            }
+            None => None,
+        };
+        let done = chunk.is_none();
+        if let Some(chunk) = chunk {
+            buffer.extend_from_slice(chunk.as_ref());
+        }
+
+        // Take complete lines, plus the unterminated last one at the end
+        while let Some(end) = buffer
+            .iter()
+            .position(|&b| b == b'\n')
+            .or_else(|| (done && !buffer.is_empty()).then(|| buffer.len() - 1))
+        {
+            let line: Vec<u8> = buffer.drain(..=end).collect();
+            line_number += 1;
+            if let Some(row) = parse_line(&line, line_number, &folders, &mut report) {
+                batch.push(row);
+            }
+            if batch.len() >= BATCH_SIZE {
+                write_batch(&mut tx, std::mem::take(&mut batch), mode, &mut report).await?;
+            }
+        }
+
+        if done {
+            break;
+        }
+    }
+
+    write_batch(&mut tx, batch, mode, &mut report).await?;
+    tx.commit().await?;
+
+    info!(
+        "Import complete: {} inserted, {} updated, {} skipped, {} errors",
+        report.inserted, report.updated, report.skipped, report.errored
+    );
+    Ok(report)
+}
+
+/// Validate one line, recording why it was rejected
+fn parse_line(
+    line: &[u8],
+    line_number: usize,
+    folders: &[LibraryFolder],
+    report: &mut ImportReport,
+) -> Option<ImportRow> {
+    if line.trim_ascii().is_empty() {
+        return None;
+    }
+
+    let mut reject = |message: String| {
+        report.errored += 1;
+        report.errors.push(format!("Line {line_number}: {message}"));
+        None
+    };
+
+    let record = match serde_json::from_slice::<MediaItemWithMetadata>(line) {
+        Ok(record) => record,
+        Err(e) => return reject(format!("Invalid record: {e}")),
+    };
+    let media_item = record.media_item;
+    if media_item.title.trim().is_empty() || media_item.file_path.trim().is_empty() {
+        return reject("title and file_path must not be empty".to_string());
+    }
+
+    // Prefer the most specific (longest) folder when folders are nested
+    let path = Path::new(&media_item.file_path);
+    let Some(folder) = folders
+        .iter()
+        .filter(|f| path.starts_with(&f.path))
+        .max_by_key(|f| f.path.len())
+    else {
+        return reject(format!(
+            "{} is not inside any library folder",
+            media_item.file_path
+        ));
+    };
+
+    Some(ImportRow {
+        line: line_number,
+        library_folder_id: folder.id,
+        metadata: record.metadata,
+        media_item,
+    })
+}
+
+/// Write a batch inside a savepoint, so a failing batch leaves no partial rows
+async fn write_batch(
+    tx: &mut SqliteConnection,
+    batch: Vec<ImportRow>,
+    mode: ImportMode,
+    report: &mut ImportReport,
+) -> Result<(), sqlx::Error> {
+    let (Some(first), Some(last)) = (batch.first(), batch.last()) else {
+        return Ok(());
+    };
+    let lines = format!("Lines {}-{}", first.line, last.line);
+
+    let mut savepoint = tx.begin().await?;
+    match insert_rows(&mut savepoint, &batch, mode).await {
+        Ok(counts) => {
+            savepoint.commit().await?;
+            report.inserted += counts.inserted;
+            report.updated += counts.updated;
+            report.skipped += counts.skipped;
+        }
+        Err(e) => {
+            savepoint.rollback().await?;
+            warn!("Failed to import {}: {}", lines.to_lowercase(), e);
+            report.errored += batch.len();
+            report.errors.push(format!("{lines}: {e}"));
+        }
+    }
+
+    Ok(())
+}
+
+/// Insert a batch with one statement per table
+async fn insert_rows(
+    conn: &mut SqliteConnection,
+    batch: &[ImportRow],
+    mode: ImportMode,
+) -> Result<ImportReport, sqlx::Error> {
+    let mut existing =
+        QueryBuilder::<Sqlite>::new("SELECT file_path FROM media_items WHERE file_path IN (");
+    let mut paths = existing.separated(", ");
+    for row in batch {
+        paths.push_bind(&row.media_item.file_path);
+    }
+    existing.push(")");
+    let existing: HashSet<String> = existing
+        .build_query_scalar()
+        .fetch_all(&mut *conn)
+        .await?
+        .into_iter()
+        .collect();
+
+    let rows: Vec<_> = match mode {
+        ImportMode::Skip => batch
+            .iter()
+            .filter(|row| !existing.contains(&row.media_item.file_path))
+            .collect(),
+        ImportMode::Upsert => batch.iter().collect(),
+    };
+    let mut counts = ImportReport {
+        skipped: batch.len() - rows.len(),
+        ..ImportReport::default()
+    };
+    if rows.is_empty() {
+        return Ok(counts);
+    }
+
+    let mut items = QueryBuilder::<Sqlite>::new(
+        "INSERT INTO media_items \
+         (library_folder_id, media_type, title, file_path, file_size, content_hash, added_at) ",
+    );
+    items.push_values(&rows, |mut values, row| {
+        let item = &row.media_item;
+        values
+            .push_bind(row.library_folder_id)
+            .push_bind(item.media_type)
+            .push_bind(&item.title)
+            .push_bind(&item.file_path)
+            .push_bind(item.file_size)
+            .push_bind(&item.content_hash)
+            .push_bind(item.added_at);
+    });
+    items.push(match mode {
+        ImportMode::Skip => " ON CONFLICT(file_path) DO NOTHING",
+        ImportMode::Upsert => {
+            " ON CONFLICT(file_path) DO UPDATE SET \
+             library_folder_id = excluded.library_folder_id, \
+             media_type = excluded.media_type, \
+             title = excluded.title, \
+             file_size = excluded.file_size, \
+             content_hash = excluded.content_hash, \
+             updated_at = CURRENT_TIMESTAMP"
+        }
+    });
+    items.push(" RETURNING id, file_path");
+    let ids: Vec<(i64, String)> = items.build_query_as().fetch_all(&mut *conn).await?;
+
+    for (_, file_path) in &ids {
+        if existing.contains(file_path) {
+            counts.updated += 1;
+        } else {
+            counts.inserted += 1;
+        }
+    }
+    // Repeated paths within the batch collapse into one row
+    counts.skipped += rows.len() - ids.len();
+
+    let with_metadata: Vec<_> = ids
+        .iter()
+        .filter_map(|(id, file_path)| {
+            let row = rows
+                .iter()
+                .rev()
+                .find(|row| &row.media_item.file_path == file_path)?;
+            Some((*id, row.metadata.as_ref()?))
+        })
+        .collect();
+    if with_metadata.is_empty() {
+        return Ok(counts);
+    }
+
+    let mut metadata = QueryBuilder::<Sqlite>::new(
+        "INSERT INTO video_metadata (\
+         media_item_id, tmdb_id, tvdb_id, imdb_id, overview, poster_path, backdrop_path, \
+         release_date, runtime, vote_average, vote_count, genres, \
+         anilist_id, mal_id, bangumi_id, episode_count) ",
+    );
+    metadata.push_values(&with_metadata, |mut values, (id, metadata)| {
+        values
+            .push_bind(*id)
+            .push_bind(metadata.tmdb_id)
+            .push_bind(metadata.tvdb_id)
+            .push_bind(&metadata.imdb_id)
+            .push_bind(&metadata.overview)
+            .push_bind(&metadata.poster_path)
+            .push_bind(&metadata.backdrop_path)
+            .push_bind(&metadata.release_date)
+            .push_bind(metadata.runtime)
+            .push_bind(metadata.vote_average)
+            .push_bind(metadata.vote_count)
+            .push_bind(&metadata.genres)
+            .push_bind(metadata.anilist_id)
+            .push_bind(metadata.mal_id)
+            .push_bind(metadata.bangumi_id)
+            .push_bind(metadata.episode_count);
+    });
+    metadata.push(
+        " ON CONFLICT(media_item_id) DO UPDATE SET \
+         tmdb_id = excluded.tmdb_id, \
+         tvdb_id = excluded.tvdb_id, \
+         imdb_id = excluded.imdb_id, \
+         overview = excluded.overview, \
+         poster_path = excluded.poster_path, \
+         backdrop_path = excluded.backdrop_path, \
+         release_date = excluded.release_date, \
+         runtime = excluded.runtime, \
+         vote_average = excluded.vote_average, \
+         vote_count = excluded.vote_count, \
+         genres = excluded.genres, \
+         anilist_id = excluded.anilist_id, \
+         mal_id = excluded.mal_id, \
+         bangumi_id = excluded.bangumi_id, \
+         episode_count = excluded.episode_count, \
+         updated_at = CURRENT_TIMESTAMP",
+    );
+    metadata.build().execute(&mut *conn).await?;
+
+    Ok(counts)
+}
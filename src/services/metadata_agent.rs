@@ -1,14 +1,65 @@
 use crate::{
-    entities::{CreateVideoMetadata, MediaItem, MediaType, VideoMetadata},
-    scraper::{MediaDetails, ScraperManager},
+    entities::{
+        CreateEpisodeMetadata, CreateVideoMetadata, EpisodeMetadata, MatchOverride, MediaItem,
+        MediaType, MetadataChange, MetadataFetchAttempt, VideoMetadata,
+    },
+    scraper::{
+        ExternalIds, MediaDetails, MediaSearchResult, Provider, ScraperManager, SearchOptions,
+    },
+    services::{ImageCache, image_cache, nfo},
+    utils::title::{cjk_language, clean_title},
 };
-use std::sync::Arc;
+use chrono::{DateTime, Utc};
+use futures::{StreamExt, future, stream};
+use std::{sync::Arc, time::Duration};
+use tokio_util::sync::CancellationToken;
 use tracing::{debug, error, info, warn};
 
+/// Default number of items fetched concurrently by `batch_fetch_metadata`
+pub const DEFAULT_BATCH_CONCURRENCY: usize = 4;
+
+/// How failed lookups are retried before an item needs a manual match
+#[derive(Debug, Clone)]
+pub struct FetchRetryPolicy {
+    /// Total attempts, including the first lookup
+    pub max_attempts: u32,
+    /// Delay before the first retry, doubled for each subsequent one
+    pub base_delay: Duration,
+    /// Upper bound for a single retry delay
+    pub max_delay: Duration,
+}
+
+impl Default for FetchRetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 5,
+            base_delay: Duration::from_secs(15 * 60),
+            max_delay: Duration::from_secs(24 * 60 * 60),
+        }
+    }
+}
+
+impl FetchRetryPolicy {
+    /// When to retry after `attempts` failures, or `None` to give up
+    fn next_retry(&self, attempts: u32) -> Option<DateTime<Utc>> {
+        if attempts >= self.max_attempts {
+            return None;
+        }
+        let delay = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(attempts.saturating_sub(1)))
+            .min(self.max_delay);
+        Some(Utc::now() + chrono::Duration::from_std(delay).unwrap_or(chrono::Duration::MAX))
+    }
+}
+
 /// Metadata agent service for fetching and saving metadata
 pub struct MetadataAgent {
     scraper_manager: Arc<ScraperManager>,
     db: sqlx::SqlitePool,
+    concurrency: usize,
+    retry: FetchRetryPolicy,
+    image_cache: Option<Arc<ImageCache>>,
 }
 
 impl MetadataAgent {
@@ -17,46 +68,201 @@ impl MetadataAgent {
         Self {
             scraper_manager,
             db,
+            concurrency: DEFAULT_BATCH_CONCURRENCY,
+            retry: FetchRetryPolicy::default(),
+            image_cache: None,
         }
     }
 
+    /// Set how many items `batch_fetch_metadata` processes at once
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Set how failed lookups are retried
+    #[must_use]
+    pub fn with_retry_policy(mut self, retry: FetchRetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Point saved posters and backdrops at local copies
+    #[must_use]
+    pub fn with_image_cache(mut self, image_cache: Arc<ImageCache>) -> Self {
+        self.image_cache = Some(image_cache);
+        self
+    }
+
     /// Fetch and save metadata for a media item
+    ///
+    /// A failure is recorded and schedules a retry; see [`retry_due`](Self::retry_due).
+    /// Items with locked metadata are skipped unless `force` is set.
     pub async fn fetch_and_save_metadata(
         &self,
         media_item: &MediaItem,
+        force: bool,
+    ) -> Result<VideoMetadata, MetadataAgentError> {
+        if media_item.metadata_locked && !force {
+            return Err(MetadataAgentError::MetadataLocked);
+        }
+
+        let result = self.match_and_save(media_item).await;
+        if let Err(e) = &result {
+            self.record_failure(media_item, e).await;
+        }
+        result
+    }
+
+    /// Retry the failed items whose backoff has elapsed
+    pub async fn retry_due(
+        &self,
+    ) -> Result<Vec<Result<VideoMetadata, MetadataAgentError>>, MetadataAgentError> {
+        let items = MetadataFetchAttempt::list_due(&self.db, Utc::now())
+            .await
+            .map_err(|e| MetadataAgentError::DatabaseError(e.to_string()))?;
+        if !items.is_empty() {
+            info!("Retrying metadata for {} items", items.len());
+        }
+
+        Ok(self.batch_fetch_metadata(items, false).await)
+    }
+
+    /// Count a failed lookup and schedule the next one, if any are left
+    async fn record_failure(&self, media_item: &MediaItem, error: &MetadataAgentError) {
+        let recorded = async {
+            let attempts = MetadataFetchAttempt::find_by_media_item_id(&self.db, media_item.id)
+                .await?
+                .map_or(0, |attempt| attempt.attempts)
+                + 1;
+            let next_retry_at = self
+                .retry
+                .next_retry(u32::try_from(attempts).unwrap_or(u32::MAX));
+            if next_retry_at.is_none() {
+                warn!(
+                    "Giving up on metadata for {} after {} attempts; it needs a manual match",
+                    media_item.title, attempts
+                );
+            }
+            MetadataFetchAttempt::upsert(
+                &self.db,
+                media_item.id,
+                attempts,
+                &error.to_string(),
+                next_retry_at,
+            )
+            .await
+        };
+
+        if let Err(e) = recorded.await {
+            error!(
+                "Failed to record metadata failure for {} (ID: {}): {}",
+                media_item.title, media_item.id, e
+            );
+        }
+    }
+
+    /// Look a media item up and save the best match
+    async fn match_and_save(
+        &self,
+        media_item: &MediaItem,
     ) -> Result<VideoMetadata, MetadataAgentError> {
+        let details = self.find_details(media_item).await?;
+
+        // Convert to database format and save
+        let metadata = self.save_metadata(media_item.id, details).await?;
+
+        info!(
+            "Successfully saved metadata for {} (ID: {})",
+            media_item.title, media_item.id
+        );
+
+        Ok(metadata)
+    }
+
+    /// Look a media item up and fetch details of the best match
+    async fn find_details(
+        &self,
+        media_item: &MediaItem,
+    ) -> Result<MediaDetails, MetadataAgentError> {
         info!(
             "Fetching metadata for {} (ID: {})",
             media_item.title, media_item.id
         );
 
-        // Extract year from title if present (e.g., "Movie Title (2023)")
-        let (title, year) = self.parse_title_and_year(&media_item.title);
+        // A match pinned by the user wins over both the NFO and the search
+        if let Some(candidate) = self.override_candidate(media_item).await {
+            debug!(
+                "Matched {} via override ({} {})",
+                media_item.title,
+                candidate.provider(),
+                candidate.id()
+            );
+            return self
+                .scraper_manager
+                .get_details(&candidate)
+                .await
+                .map_err(|e| {
+                    error!("Failed to get details for override: {}", e);
+                    MetadataAgentError::DetailsFailed(e.to_string())
+                });
+        }
+
+        // IDs from an existing NFO sidecar skip the search entirely
+        let nfo = read_nfo(media_item);
+        if let Some(candidate) = nfo
+            .as_ref()
+            .and_then(|nfo| nfo_candidate(media_item.media_type, &nfo.external_ids))
+        {
+            match self.scraper_manager.get_details(&candidate).await {
+                Ok(details) => {
+                    debug!(
+                        "Matched {} via NFO ({} {})",
+                        media_item.title,
+                        candidate.provider(),
+                        candidate.id()
+                    );
+                    return Ok(details);
+                }
+                Err(e) => debug!("NFO match failed for {}: {}", media_item.title, e),
+            }
+        }
+
+        // Strip release noise and pull out the year, e.g. "Movie.Title.2023.1080p"
+        let (title, year) = clean_title(&media_item.title);
 
         // Search for the media
         let search_results = self
             .scraper_manager
-            .search(&title, year)
-            .await
-            .map_err(|e| {
+            .search(
+                &SearchOptions::new(title.clone())
+                    .with_year(year)
+                    .with_include_adult(true),
+            )
+            .await;
+        let found = search_results
+            .as_ref()
+            .ok()
+            .and_then(|results| self.best_match(media_item.media_type, results));
+        let found = match found {
+            Some(result) => Some(result),
+            None => {
+                let original_title = nfo.and_then(|nfo| nfo.original_title);
+                self.search_fallback(media_item.media_type, &title, original_title, year)
+                    .await
+            }
+        };
+        let matching_result = match (found, search_results) {
+            (Some(result), _) => result,
+            (None, Err(e)) => {
                 error!("Failed to search for {}: {}", title, e);
-                MetadataAgentError::SearchFailed(e.to_string())
-            })?;
-
-        // Filter results by media type
-        let matching_result = search_results
-            .into_iter()
-            .find(|result| {
-                matches!(
-                    (media_item.media_type, result.media_type()),
-                    (MediaType::Movie, crate::scraper::MediaType::Movie)
-                        | (MediaType::Tv, crate::scraper::MediaType::Tv)
-                )
-            })
-            .ok_or_else(|| {
+                return Err(MetadataAgentError::SearchFailed(e.to_string()));
+            }
+            (None, Ok(_)) => {
                 warn!("No matching results found for {}", title);
-                MetadataAgentError::NoMatchingResults
-            })?;
+                return Err(MetadataAgentError::NoMatchingResults);
+            }
+        };
 
         debug!(
             "Found matching result: {} (Provider: {})",
@@ -65,129 +271,369 @@ impl MetadataAgent {
         );
 
         // Get detailed metadata
-        let details = self
-            .scraper_manager
+        self.scraper_manager
             .get_details(&matching_result)
             .await
             .map_err(|e| {
                 error!("Failed to get details: {}", e);
                 MetadataAgentError::DetailsFailed(e.to_string())
-            })?;
+            })
+    }
 
-        // Convert to database format and save
-        let metadata = self.save_metadata(media_item.id, details).await?;
+    /// Search result pointing at the override pinned for an item's title
+    ///
+    /// The ID is taken to be of the first kind of title the provider serves
+    /// that the item can be stored as.
+    async fn override_candidate(&self, media_item: &MediaItem) -> Option<MediaSearchResult> {
+        let pinned =
+            match MatchOverride::find(&self.db, &media_item.title, media_item.media_type).await {
+                Ok(pinned) => pinned?,
+                Err(e) => {
+                    warn!(
+                        "Failed to look up match override for {}: {}",
+                        media_item.title, e
+                    );
+                    return None;
+                }
+            };
 
-        info!(
-            "Successfully saved metadata for {} (ID: {})",
-            media_item.title, media_item.id
-        );
+        let Some(kind) = self
+            .scraper_manager
+            .providers()
+            .iter()
+            .find(|provider| provider.name() == pinned.provider)
+            .and_then(|provider| {
+                provider
+                    .capabilities()
+                    .media_types
+                    .into_iter()
+                    .find(|&kind| stored_as(media_item.media_type, kind))
+            })
+        else {
+            warn!(
+                "Ignoring match override for {}: provider {} can't serve it",
+                media_item.title, pinned.provider
+            );
+            return None;
+        };
 
-        Ok(metadata)
+        Some(MediaSearchResult::from_id(
+            kind,
+            &pinned.provider,
+            &pinned.media_id,
+        ))
+    }
+
+    /// Retry a missed search across languages
+    ///
+    /// The title and the NFO's original title are each tried: one written in
+    /// CJK script goes to the providers able to search that script, in its
+    /// language, and a different original title goes to every provider.
+    async fn search_fallback(
+        &self,
+        media_type: MediaType,
+        title: &str,
+        original_title: Option<String>,
+        year: Option<i32>,
+    ) -> Option<MediaSearchResult> {
+        let original_title = original_title.filter(|original| original != title);
+        let cjk_providers: Vec<String> = self
+            .scraper_manager
+            .providers()
+            .iter()
+            .filter(|provider| provider.capabilities().cjk_titles)
+            .map(|provider| provider.name().to_string())
+            .collect();
+
+        for query in std::iter::once(title).chain(original_title.as_deref()) {
+            let searches: Vec<SearchOptions> = match cjk_language(query) {
+                Some(language) => cjk_providers
+                    .iter()
+                    .map(|provider| {
+                        let mut options = SearchOptions::new(query)
+                            .with_year(year)
+                            .with_include_adult(true);
+                        options.language = Some(language.to_string());
+                        options.provider = Some(provider.clone());
+                        options
+                    })
+                    .collect(),
+                // Latin titles already went to every provider
+                None if query == title => continue,
+                None => vec![
+                    SearchOptions::new(query)
+                        .with_year(year)
+                        .with_include_adult(true),
+                ],
+            };
+
+            for options in searches {
+                let Ok(results) = self.scraper_manager.search(&options).await else {
+                    continue;
+                };
+                if let Some(result) = self.best_match(media_type, &results) {
+                    debug!(
+                        "Matched {} on fallback via {} ({})",
+                        query,
+                        result.provider(),
+                        options.language.as_deref().unwrap_or("default language")
+                    );
+                    return Some(result);
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Search result of a kind stored under `media_type` from the most
+    /// preferred provider
+    ///
+    /// Providers are preferred in the scraper manager's priority order, and
+    /// unlisted ones only when no listed one matched, in search order.
+    /// Results collapsed by deduplication count too, so a preferred
+    /// provider's match isn't hidden behind another provider's.
+    fn best_match(
+        &self,
+        media_type: MediaType,
+        results: &[MediaSearchResult],
+    ) -> Option<MediaSearchResult> {
+        results
+            .iter()
+            .flat_map(|result| {
+                std::iter::once(result.clone()).chain(
+                    result
+                        .also_matched()
+                        .iter()
+                        .map(|m| MediaSearchResult::from_id(m.media_type, &m.provider, &m.id)),
+                )
+            })
+            .filter(|result| stored_as(media_type, result.media_type()))
+            .min_by_key(|result| {
+                self.scraper_manager
+                    .preference(result.provider())
+                    .unwrap_or(usize::MAX)
+            })
     }
 
     /// Save metadata to database
-    async fn save_metadata(
+    pub async fn save_metadata(
         &self,
         media_item_id: i64,
         details: MediaDetails,
     ) -> Result<VideoMetadata, MetadataAgentError> {
-        let create_metadata = match details {
-            MediaDetails::Movie(movie) => CreateVideoMetadata {
-                media_item_id,
-                tmdb_id: movie
-                    .external_ids
-                    .tmdb_id
-                    .and_then(|id| id.parse().ok()),
-                tvdb_id: movie
-                    .external_ids
-                    .tvdb_id
-                    .and_then(|id| id.parse().ok()),
-                imdb_id: movie.external_ids.imdb_id,
-                overview: movie.overview,
-                poster_path: movie.poster_path,
-                backdrop_path: movie.backdrop_path,
-                release_date: movie.release_date,
-                runtime: movie.runtime,
-                vote_average: movie.vote_average,
-                vote_count: movie.vote_count,
-                genres: movie.genres,
-            },
-            MediaDetails::Tv(tv) => CreateVideoMetadata {
-                media_item_id,
-                tmdb_id: tv.external_ids.tmdb_id.and_then(|id| id.parse().ok()),
-                tvdb_id: tv.external_ids.tvdb_id.and_then(|id| id.parse().ok()),
-                imdb_id: tv.external_ids.imdb_id,
-                overview: tv.overview,
-                poster_path: tv.poster_path,
-                backdrop_path: tv.backdrop_path,
-                release_date: tv.first_air_date,
-                runtime: tv.episode_run_time.first().copied(),
-                vote_average: tv.vote_average,
-                vote_count: tv.vote_count,
-                genres: tv.genres,
-            },
-            MediaDetails::Anime(_) => {
-                return Err(MetadataAgentError::UnsupportedMediaType(
-                    "Anime not yet supported".to_string(),
-                ))
-            }
-        };
+        let mut create_metadata = CreateVideoMetadata::from((media_item_id, details));
+        if let Some(image_cache) = &self.image_cache {
+            image_cache.localize_metadata(&mut create_metadata).await;
+        }
 
-        VideoMetadata::upsert(&self.db, create_metadata)
+        let metadata = VideoMetadata::upsert(&self.db, create_metadata)
             .await
             .map_err(|e| {
                 error!("Failed to save metadata to database: {}", e);
                 MetadataAgentError::DatabaseError(e.to_string())
-            })
-    }
-
-    /// Parse title and year from a string like "Movie Title (2023)"
-    fn parse_title_and_year(&self, title: &str) -> (String, Option<i32>) {
-        let re = regex::Regex::new(r"^(.+?)\s*\((\d{4})\)\s*$").expect("Invalid regex");
+            })?;
 
-        if let Some(captures) = re.captures(title) {
-            let title = captures.get(1).map(|m| m.as_str().to_string()).unwrap_or_else(|| title.to_string());
-            let year = captures
-                .get(2)
-                .and_then(|m| m.as_str().parse().ok());
-            (title, year)
-        } else {
-            (title.to_string(), None)
+        // Earlier failures no longer matter once the item is matched
+        if let Err(e) = MetadataFetchAttempt::clear(&self.db, media_item_id).await {
+            warn!(
+                "Failed to clear metadata failures for {}: {}",
+                media_item_id, e
+            );
+        }
+        if let Err(e) = MediaItem::clear_metadata_stale(&self.db, media_item_id).await {
+            warn!(
+                "Failed to clear stale metadata flag for {}: {}",
+                media_item_id, e
+            );
         }
+
+        Ok(metadata)
     }
 
     /// Refresh metadata for an existing media item
     pub async fn refresh_metadata(
         &self,
         media_item_id: i64,
+        force: bool,
     ) -> Result<VideoMetadata, MetadataAgentError> {
         let media_item = MediaItem::find_by_id(&self.db, media_item_id)
             .await
             .map_err(|e| MetadataAgentError::DatabaseError(e.to_string()))?
             .ok_or(MetadataAgentError::MediaItemNotFound)?;
 
-        self.fetch_and_save_metadata(&media_item).await
+        self.fetch_and_save_metadata(&media_item, force).await
+    }
+
+    /// Fetch fresh metadata for a media item and list the fields a refresh
+    /// would change, without saving anything
+    ///
+    /// A failed lookup isn't recorded, as a refresh's would be.
+    pub async fn preview_refresh(
+        &self,
+        media_item_id: i64,
+    ) -> Result<Vec<MetadataChange>, MetadataAgentError> {
+        let media_item = MediaItem::find_by_id(&self.db, media_item_id)
+            .await
+            .map_err(|e| MetadataAgentError::DatabaseError(e.to_string()))?
+            .ok_or(MetadataAgentError::MediaItemNotFound)?;
+        let details = self.find_details(&media_item).await?;
+        let existing = VideoMetadata::find_by_media_item_id(&self.db, media_item_id)
+            .await
+            .map_err(|e| MetadataAgentError::DatabaseError(e.to_string()))?;
+
+        let mut metadata = CreateVideoMetadata::from((media_item_id, details));
+        // Saved images may be the local copies of the very same URLs
+        if let Some(existing) = &existing {
+            for (new, old) in [
+                (&mut metadata.poster_path, &existing.poster_path),
+                (&mut metadata.backdrop_path, &existing.backdrop_path),
+            ] {
+                if new.as_deref().map(image_cache::local_route) == *old {
+                    new.clone_from(old);
+                }
+            }
+        }
+
+        Ok(metadata.changes_from(existing.as_ref()))
+    }
+
+    /// Fetch and save metadata for a single episode of a media item
+    ///
+    /// The series must already have metadata, whose provider IDs are used for the lookup.
+    pub async fn fetch_episode_metadata(
+        &self,
+        media_item_id: i64,
+        season: i32,
+        episode: i32,
+    ) -> Result<EpisodeMetadata, MetadataAgentError> {
+        let series = VideoMetadata::find_by_media_item_id(&self.db, media_item_id)
+            .await
+            .map_err(|e| MetadataAgentError::DatabaseError(e.to_string()))?
+            .ok_or(MetadataAgentError::SeriesMetadataMissing)?;
+
+        let ids = ExternalIds::from(&series);
+        let (provider, series_id) = match (ids.tmdb_id, ids.tvdb_id) {
+            (Some(id), _) => (Provider::Tmdb, id),
+            (None, Some(id)) => (Provider::Tvdb, id),
+            (None, None) => return Err(MetadataAgentError::SeriesMetadataMissing),
+        };
+
+        let details = self
+            .scraper_manager
+            .get_episode_details(provider.as_str(), &series_id, season, episode)
+            .await
+            .map_err(|e| {
+                error!("Failed to get episode details: {}", e);
+                MetadataAgentError::DetailsFailed(e.to_string())
+            })?;
+
+        EpisodeMetadata::upsert(
+            &self.db,
+            CreateEpisodeMetadata::from((media_item_id, details)),
+        )
+        .await
+        .map_err(|e| {
+            error!("Failed to save episode metadata to database: {}", e);
+            MetadataAgentError::DatabaseError(e.to_string())
+        })
     }
 
     /// Batch fetch metadata for multiple media items
+    ///
+    /// Items are fetched concurrently; provider rate limiters provide backpressure.
+    /// Results are returned in the same order as the input.
     pub async fn batch_fetch_metadata(
         &self,
         media_items: Vec<MediaItem>,
+        force: bool,
+    ) -> Vec<Result<VideoMetadata, MetadataAgentError>> {
+        self.batch_fetch_metadata_with_progress(
+            media_items,
+            force,
+            &CancellationToken::new(),
+            |_| {},
+        )
+        .await
+    }
+
+    /// Batch fetch metadata, calling `on_progress` with the number of items
+    /// finished so far after each one completes
+    ///
+    /// Once `cancel` is cancelled no more items are started; items already
+    /// being fetched are finished and saved. Results cover only the items that
+    /// were started.
+    pub async fn batch_fetch_metadata_with_progress(
+        &self,
+        media_items: Vec<MediaItem>,
+        force: bool,
+        cancel: &CancellationToken,
+        mut on_progress: impl FnMut(usize),
     ) -> Vec<Result<VideoMetadata, MetadataAgentError>> {
-        let mut results = Vec::new();
+        let mut finished = 0;
+        let mut results: Vec<_> = stream::iter(media_items.into_iter().enumerate())
+            .take_while(|_| future::ready(!cancel.is_cancelled()))
+            .map(|(index, item)| async move {
+                (index, self.fetch_and_save_metadata(&item, force).await)
+            })
+            .buffer_unordered(self.concurrency)
+            .inspect(|_| {
+                finished += 1;
+                on_progress(finished);
+            })
+            .collect()
+            .await;
 
-        for item in media_items {
-            let result = self.fetch_and_save_metadata(&item).await;
-            results.push(result);
+        results.sort_by_key(|(index, _)| *index);
+        results.into_iter().map(|(_, result)| result).collect()
+    }
+}
 
-            // Add a small delay to respect rate limits
-            tokio::time::sleep(tokio::time::Duration::from_millis(250)).await;
-        }
+/// Read a media item's NFO sidecar, if it has a readable one
+fn read_nfo(media_item: &MediaItem) -> Option<nfo::NfoMetadata> {
+    let path = nfo::find_sidecar(std::path::Path::new(&media_item.file_path))?;
+    let parsed = match media_item.media_type {
+        MediaType::Movie => nfo::parse_movie_nfo(&path),
+        MediaType::Tv => nfo::parse_tvshow_nfo(&path),
+        _ => return None,
+    };
 
-        results
+    parsed
+        .inspect_err(|e| warn!("Ignoring unreadable NFO {}: {}", path.display(), e))
+        .ok()
+}
+
+/// Build a lookup from the provider IDs in a media item's NFO sidecar, if any
+fn nfo_candidate(media_type: MediaType, ids: &ExternalIds) -> Option<MediaSearchResult> {
+    let media_type = match media_type {
+        MediaType::Movie => crate::scraper::MediaType::Movie,
+        MediaType::Tv => crate::scraper::MediaType::Tv,
+        _ => return None,
+    };
+
+    match (&ids.tmdb_id, &ids.tvdb_id) {
+        (Some(id), _) => Some(MediaSearchResult::from_id(
+            media_type,
+            Provider::Tmdb.as_str(),
+            id,
+        )),
+        (None, Some(id)) if media_type == crate::scraper::MediaType::Tv => Some(
+            MediaSearchResult::from_id(media_type, Provider::Tvdb.as_str(), id),
+        ),
+        _ => None,
     }
 }
 
+/// Whether results of `found` kind are stored under `media_type`
+const fn stored_as(media_type: MediaType, found: crate::scraper::MediaType) -> bool {
+    matches!(
+        (media_type, found),
+        (MediaType::Movie, crate::scraper::MediaType::Movie)
+            | (MediaType::Tv, crate::scraper::MediaType::Tv)
+            | (MediaType::Tv, crate::scraper::MediaType::Anime)
+    )
+}
+
 /// Metadata agent errors
 #[derive(Debug, thiserror::Error)]
 pub enum MetadataAgentError {
@@ -208,4 +654,283 @@ pub enum MetadataAgentError {
 
     #[error("Unsupported media type: {0}")]
     UnsupportedMediaType(String),
+
+    #[error("Series metadata must be fetched before episode metadata")]
+    SeriesMetadataMissing,
+
+    #[error("Metadata is locked")]
+    MetadataLocked,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        app::config::ScraperConfig,
+        entities::{CreateLibraryFolder, CreateMediaItem, LibraryFolder},
+        scraper::{
+            ProviderCapabilities,
+            mock::{FakeProvider, anime_details, movie_details},
+        },
+    };
+
+    async fn seed_item(db: &sqlx::SqlitePool, media_type: MediaType, title: &str) -> MediaItem {
+        let folder = LibraryFolder::create(
+            db,
+            CreateLibraryFolder {
+                name: "Library".to_string(),
+                path: format!("/library/{title}"),
+                media_type,
+                extensions: None,
+            },
+        )
+        .await
+        .unwrap();
+
+        MediaItem::create(
+            db,
+            CreateMediaItem {
+                library_folder_id: folder.id,
+                media_type,
+                title: title.to_string(),
+                file_path: format!("/library/{title}/{title}.mkv"),
+                file_size: 1,
+                content_hash: None,
+                modified_at: None,
+            },
+        )
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_anime_metadata_is_saved() {
+        let db = crate::db::test_pool().await;
+        let mut manager = ScraperManager::new();
+        manager.add_provider(Box::new(FakeProvider::new("anilist").with_details(
+            MediaDetails::Anime(anime_details("anilist", "1", "Cowboy Bebop", 1998)),
+        )));
+        let agent = MetadataAgent::new(Arc::new(manager), db.clone());
+
+        let item = seed_item(&db, MediaType::Tv, "Cowboy Bebop").await;
+        let metadata = agent.fetch_and_save_metadata(&item, false).await.unwrap();
+
+        assert_eq!(metadata.anilist_id, Some(1));
+        assert_eq!(metadata.episode_count, Some(26));
+        assert_eq!(metadata.release_date.as_deref(), Some("1998-04-03"));
+    }
+
+    #[tokio::test]
+    async fn test_batch_fetch_processes_all_items_in_order() {
+        let db = crate::db::test_pool().await;
+        let titles = ["Alien", "Blade Runner", "Heat", "Ronin", "Solaris"];
+        let mut provider = FakeProvider::new("tmdb");
+        for (i, title) in titles.iter().enumerate() {
+            provider = provider.with_movie(&i.to_string(), title, 1980 + i as i32);
+        }
+        let mut manager = ScraperManager::new();
+        manager.add_provider(Box::new(provider));
+        let agent = MetadataAgent::new(Arc::new(manager), db.clone()).with_concurrency(2);
+
+        let mut items = Vec::new();
+        for title in titles {
+            items.push(seed_item(&db, MediaType::Movie, title).await);
+        }
+        // An item with no match must not abort the rest of the batch
+        items.insert(2, seed_item(&db, MediaType::Movie, "Nonexistent").await);
+
+        let results = agent.batch_fetch_metadata(items.clone(), false).await;
+
+        assert_eq!(results.len(), items.len());
+        assert!(results[2].is_err());
+        for (item, result) in items.iter().zip(&results) {
+            if let Ok(metadata) = result {
+                assert_eq!(metadata.media_item_id, item.id);
+            }
+        }
+        assert_eq!(results.iter().filter(|r| r.is_ok()).count(), titles.len());
+    }
+
+    #[tokio::test]
+    async fn test_locked_items_are_only_refreshed_when_forced() {
+        let db = crate::db::test_pool().await;
+        let mut manager = ScraperManager::new();
+        manager.add_provider(Box::new(
+            FakeProvider::new("tmdb").with_movie("348", "Alien", 1979),
+        ));
+        let agent = MetadataAgent::new(Arc::new(manager), db.clone());
+
+        let item = seed_item(&db, MediaType::Movie, "Alien").await;
+        MediaItem::set_metadata_locked(&db, item.id, true)
+            .await
+            .unwrap();
+        let item = MediaItem::find_by_id(&db, item.id).await.unwrap().unwrap();
+        assert!(item.metadata_locked);
+
+        let results = agent.batch_fetch_metadata(vec![item.clone()], false).await;
+        assert!(matches!(
+            results[0],
+            Err(MetadataAgentError::MetadataLocked)
+        ));
+        assert!(
+            VideoMetadata::find_by_media_item_id(&db, item.id)
+                .await
+                .unwrap()
+                .is_none()
+        );
+        // Skipping a locked item is not a failure worth retrying
+        assert!(
+            MetadataFetchAttempt::find_by_media_item_id(&db, item.id)
+                .await
+                .unwrap()
+                .is_none()
+        );
+
+        let results = agent.batch_fetch_metadata(vec![item.clone()], true).await;
+        assert_eq!(results[0].as_ref().unwrap().media_item_id, item.id);
+        assert!(
+            VideoMetadata::find_by_media_item_id(&db, item.id)
+                .await
+                .unwrap()
+                .is_some()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_override_forces_pinned_id() {
+        let db = crate::db::test_pool().await;
+        let mut manager = ScraperManager::new();
+        manager.add_provider(Box::new(
+            FakeProvider::new("tmdb")
+                .with_movie("593", "Solaris", 1972)
+                .with_movie("2103", "Solaris", 2002),
+        ));
+        let agent = MetadataAgent::new(Arc::new(manager), db.clone());
+        let item = seed_item(&db, MediaType::Movie, "Solaris").await;
+
+        // Search ranking alone picks the first result
+        let metadata = agent.fetch_and_save_metadata(&item, false).await.unwrap();
+        assert!(metadata.release_date.unwrap().starts_with("1972"));
+
+        MatchOverride::upsert(
+            &db,
+            crate::entities::CreateMatchOverride {
+                title: "Solaris.1080p.BluRay".to_string(),
+                media_type: MediaType::Movie,
+                provider: "tmdb".to_string(),
+                media_id: "2103".to_string(),
+            },
+        )
+        .await
+        .unwrap();
+        let metadata = agent.refresh_metadata(item.id, false).await.unwrap();
+        assert!(metadata.release_date.unwrap().starts_with("2002"));
+    }
+
+    #[tokio::test]
+    async fn test_nfo_ids_seed_matching() {
+        let db = crate::db::test_pool().await;
+        let mut movie = movie_details("tmdb", "603", "The Matrix", 1999);
+        movie.external_ids.tmdb_id = Some("603".to_string());
+        let mut manager = ScraperManager::new();
+        manager.add_provider(Box::new(
+            FakeProvider::new("tmdb").with_details(MediaDetails::Movie(movie)),
+        ));
+        let agent = MetadataAgent::new(Arc::new(manager), db.clone());
+
+        // The file name alone would never match, but the sidecar names the TMDB ID
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("mtrx_1080p.mkv");
+        std::fs::write(&file, b"").unwrap();
+        std::fs::write(
+            dir.path().join("mtrx_1080p.nfo"),
+            r#"<movie><uniqueid type="tmdb" default="true">603</uniqueid></movie>"#,
+        )
+        .unwrap();
+
+        let mut item = seed_item(&db, MediaType::Movie, "mtrx_1080p").await;
+        item.file_path = file.display().to_string();
+        let metadata = agent.fetch_and_save_metadata(&item, false).await.unwrap();
+
+        assert_eq!(metadata.tmdb_id, Some(603));
+    }
+
+    #[tokio::test]
+    async fn test_cjk_original_title_falls_back_to_cjk_providers() {
+        let db = crate::db::test_pool().await;
+        let mut manager = ScraperManager::new();
+        manager.add_provider(Box::new(
+            FakeProvider::new("tmdb")
+                .with_capabilities(ProviderCapabilities::new([
+                    crate::scraper::MediaType::Movie,
+                    crate::scraper::MediaType::Tv,
+                ]))
+                .with_details(MediaDetails::Movie(movie_details(
+                    "tmdb",
+                    "1",
+                    "Frieren: Beyond Journey's End",
+                    2023,
+                ))),
+        ));
+        let mut frieren = anime_details("bangumi", "400602", "葬送のフリーレン", 2023);
+        frieren.external_ids.bangumi_id = Some("400602".to_string());
+        manager.add_provider(Box::new(
+            FakeProvider::new("bangumi")
+                .with_capabilities(
+                    ProviderCapabilities::new([crate::scraper::MediaType::Anime])
+                        .with_cjk_titles(true),
+                )
+                .with_details(MediaDetails::Anime(frieren)),
+        ));
+        let agent = MetadataAgent::new(Arc::new(manager), db.clone());
+
+        // Nobody knows the romaji title; the sidecar has the Japanese one
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("Sousou no Frieren.mkv");
+        std::fs::write(&file, b"").unwrap();
+        std::fs::write(
+            dir.path().join("tvshow.nfo"),
+            "<tvshow><originaltitle>葬送のフリーレン</originaltitle></tvshow>",
+        )
+        .unwrap();
+
+        let mut item = seed_item(&db, MediaType::Tv, "Sousou no Frieren").await;
+        item.file_path = file.display().to_string();
+        let metadata = agent.fetch_and_save_metadata(&item, false).await.unwrap();
+
+        assert_eq!(metadata.bangumi_id, Some(400602));
+    }
+
+    #[tokio::test]
+    async fn test_default_provider_match_is_preferred() {
+        let db = crate::db::test_pool().await;
+        let fallback = FakeProvider::new("bangumi").with_movie("b1", "Heat", 1995);
+        let default = FakeProvider::new("tmdb").with_movie("949", "Heat", 1995);
+        let other = FakeProvider::new("kitsu").with_movie("k1", "Heat", 1995);
+        let calls = [
+            fallback.details_calls(),
+            default.details_calls(),
+            other.details_calls(),
+        ];
+        let config = ScraperConfig {
+            default_provider: Some("tmdb".to_string()),
+            fallback_providers: vec!["bangumi".to_string()],
+            ..ScraperConfig::default()
+        };
+
+        // Registered last, and collapsed into the first provider's result by
+        // deduplication
+        let mut manager = ScraperManager::new();
+        manager.add_provider(Box::new(other));
+        manager.add_provider(Box::new(fallback));
+        manager.add_provider(Box::new(default));
+        manager.set_priority(config.provider_order());
+        let agent = MetadataAgent::new(Arc::new(manager), db.clone());
+
+        let item = seed_item(&db, MediaType::Movie, "Heat").await;
+        agent.fetch_and_save_metadata(&item, false).await.unwrap();
+
+        let calls = calls.map(|calls| calls.load(std::sync::atomic::Ordering::SeqCst));
+        assert_eq!(calls, [0, 1, 0]);
+    }
 }
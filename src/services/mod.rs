@@ -1,5 +1,18 @@
+pub mod deduplicator;
 pub mod file_scanner;
+pub mod image_cache;
+pub mod job_queue;
+pub mod library_import;
 pub mod metadata_agent;
+pub mod nfo;
+pub mod organizer;
+pub mod scan_scheduler;
+pub mod webhook_notifier;
 
-pub use file_scanner::{FileScanner, FileScannerError, ScanResult};
+pub use file_scanner::{FileScanner, FileScannerError, ScanEvent, ScanProgress, ScanResult};
+pub use image_cache::{ImageCache, ImageCacheMode};
+pub use job_queue::{JobQueue, JobStatus};
 pub use metadata_agent::{MetadataAgent, MetadataAgentError};
+pub use organizer::{ConflictPolicy, OrganizeMethod};
+pub use scan_scheduler::ScanScheduler;
+pub use webhook_notifier::{WebhookEvent, WebhookNotifier};
@@ -0,0 +1,352 @@
+//! Kodi-style `.nfo` sidecar files
+//!
+//! Jellyfin, Emby and Kodi keep metadata in XML files next to the media. We read
+//! them to seed matching with known IDs, and write them so other tools can reuse
+//! what we scraped.
+
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::ScrapeError,
+    scraper::{EpisodeMetadata, ExternalIds, MovieMetadata, TvMetadata},
+};
+
+/// Metadata recovered from an NFO file; every field is optional
+#[derive(Debug, Clone, Default)]
+pub struct NfoMetadata {
+    pub title: Option<String>,
+    pub original_title: Option<String>,
+    pub year: Option<i32>,
+    pub plot: Option<String>,
+    pub premiered: Option<String>,
+    pub runtime: Option<i32>,
+    pub rating: Option<f64>,
+    pub genres: Vec<String>,
+    pub external_ids: ExternalIds,
+}
+
+/// Read a `<movie>` NFO
+pub fn parse_movie_nfo(path: &Path) -> Result<NfoMetadata, ScrapeError> {
+    parse_video_nfo(path)
+}
+
+/// Read a `<tvshow>` NFO
+pub fn parse_tvshow_nfo(path: &Path) -> Result<NfoMetadata, ScrapeError> {
+    parse_video_nfo(path)
+}
+
+/// Write a `<movie>` NFO for the given metadata
+pub fn write_movie_nfo(path: &Path, movie: &MovieMetadata) -> Result<(), ScrapeError> {
+    let nfo = VideoNfo {
+        title: Some(movie.title.clone()),
+        originaltitle: movie.original_title.clone(),
+        year: year_of(movie.release_date.as_deref()),
+        plot: movie.overview.clone(),
+        runtime: movie.runtime.map(|r| r.to_string()),
+        premiered: movie.release_date.clone(),
+        rating: movie.vote_average.map(|r| r.to_string()),
+        genre: movie.genres.clone(),
+        uniqueid: unique_ids(&movie.provider, &movie.id, &movie.external_ids),
+        ..Default::default()
+    };
+
+    write_nfo(path, "movie", &nfo)
+}
+
+/// Write a `<tvshow>` NFO for the given metadata
+pub fn write_tvshow_nfo(path: &Path, tv: &TvMetadata) -> Result<(), ScrapeError> {
+    let nfo = VideoNfo {
+        title: Some(tv.name.clone()),
+        originaltitle: tv.original_name.clone(),
+        year: year_of(tv.first_air_date.as_deref()),
+        plot: tv.overview.clone(),
+        premiered: tv.first_air_date.clone(),
+        rating: tv.vote_average.map(|r| r.to_string()),
+        genre: tv.genres.clone(),
+        uniqueid: unique_ids(&tv.provider, &tv.id, &tv.external_ids),
+        ..Default::default()
+    };
+
+    write_nfo(path, "tvshow", &nfo)
+}
+
+/// Write an `<episodedetails>` NFO for the given episode
+pub fn write_episode_nfo(path: &Path, episode: &EpisodeMetadata) -> Result<(), ScrapeError> {
+    let nfo = EpisodeNfo {
+        title: episode.name.clone(),
+        season: episode.season_number,
+        episode: episode.episode_number,
+        plot: episode.overview.clone(),
+        aired: episode.air_date.clone(),
+        runtime: episode.runtime,
+        rating: episode.vote_average,
+        thumb: episode.still_path.clone(),
+    };
+
+    write_nfo(path, "episodedetails", &nfo)
+}
+
+/// Locate the sidecar describing a media file
+///
+/// Checks `<name>.nfo` next to the file, then the folder-level `movie.nfo` or
+/// `tvshow.nfo`. For directories only the folder-level files are considered.
+pub fn find_sidecar(media_path: &Path) -> Option<PathBuf> {
+    let (dir, own) = if media_path.is_dir() {
+        (media_path, None)
+    } else {
+        (media_path.parent()?, Some(media_path.with_extension("nfo")))
+    };
+
+    own.into_iter()
+        .chain(["movie.nfo", "tvshow.nfo"].map(|name| dir.join(name)))
+        .find(|path| path.is_file())
+}
+
+fn parse_video_nfo(path: &Path) -> Result<NfoMetadata, ScrapeError> {
+    let xml = std::fs::read_to_string(path)?;
+    let nfo: VideoNfo = quick_xml::de::from_str(&xml)
+        .map_err(|e| ScrapeError::NfoError(format!("{}: {e}", path.display())))?;
+
+    let mut external_ids = ExternalIds::default();
+    for id in nfo.uniqueid {
+        set_external_id(&mut external_ids, &id.kind, id.value);
+    }
+    // Older scrapers write bare <id>/<imdbid>/<tmdbid> tags instead of <uniqueid>
+    if let Some(id) = nfo.id.filter(|id| id.starts_with("tt")) {
+        external_ids.imdb_id.get_or_insert(id);
+    }
+    if let Some(id) = nfo.imdbid {
+        external_ids.imdb_id.get_or_insert(id);
+    }
+    if let Some(id) = nfo.tmdbid {
+        external_ids.tmdb_id.get_or_insert(id);
+    }
+
+    Ok(NfoMetadata {
+        year: parse_trimmed(nfo.year.as_deref())
+            .or_else(|| parse_trimmed(year_of(nfo.premiered.as_deref()).as_deref())),
+        runtime: parse_trimmed(nfo.runtime.as_deref()),
+        rating: parse_trimmed(nfo.rating.as_deref()),
+        title: non_empty(nfo.title),
+        original_title: non_empty(nfo.originaltitle),
+        plot: non_empty(nfo.plot),
+        premiered: non_empty(nfo.premiered),
+        genres: nfo.genre,
+        external_ids,
+    })
+}
+
+fn write_nfo<T: Serialize>(path: &Path, root: &str, value: &T) -> Result<(), ScrapeError> {
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n");
+    let mut serializer = quick_xml::se::Serializer::with_root(&mut xml, Some(root))
+        .map_err(|e| ScrapeError::NfoError(e.to_string()))?;
+    serializer.indent(' ', 2);
+    value
+        .serialize(serializer)
+        .map_err(|e| ScrapeError::NfoError(e.to_string()))?;
+    xml.push('\n');
+
+    std::fs::write(path, xml)?;
+    Ok(())
+}
+
+/// `<uniqueid>` entries for all known IDs, with the source provider's as default
+fn unique_ids(provider: &str, id: &str, ids: &ExternalIds) -> Vec<UniqueId> {
+    let mut ids = ids.clone();
+    set_external_id(&mut ids, provider, id.to_string());
+
+    [
+        ("tmdb", &ids.tmdb_id),
+        ("imdb", &ids.imdb_id),
+        ("tvdb", &ids.tvdb_id),
+        ("anilist", &ids.anilist_id),
+        ("bangumi", &ids.bangumi_id),
+        ("mal", &ids.mal_id),
+    ]
+    .into_iter()
+    .filter_map(|(kind, id)| {
+        id.as_ref().map(|value| UniqueId {
+            kind: kind.to_string(),
+            default: (kind == provider).then_some(true),
+            value: value.clone(),
+        })
+    })
+    .collect()
+}
+
+fn set_external_id(ids: &mut ExternalIds, kind: &str, value: String) {
+    let slot = match kind.to_ascii_lowercase().as_str() {
+        "tmdb" | "themoviedb" => &mut ids.tmdb_id,
+        "imdb" => &mut ids.imdb_id,
+        "tvdb" => &mut ids.tvdb_id,
+        "anilist" => &mut ids.anilist_id,
+        "bangumi" => &mut ids.bangumi_id,
+        "mal" | "myanimelist" => &mut ids.mal_id,
+        _ => return,
+    };
+    if !value.trim().is_empty() {
+        *slot = Some(value.trim().to_string());
+    }
+}
+
+fn year_of(date: Option<&str>) -> Option<String> {
+    date?
+        .get(..4)
+        .filter(|y| y.parse::<i32>().is_ok())
+        .map(ToOwned::to_owned)
+}
+
+fn parse_trimmed<T: std::str::FromStr>(value: Option<&str>) -> Option<T> {
+    value?.trim().parse().ok()
+}
+
+fn non_empty(value: Option<String>) -> Option<String> {
+    value.filter(|v| !v.trim().is_empty())
+}
+
+/// Shared layout of `<movie>` and `<tvshow>`; numbers are kept as text since
+/// hand-edited files often leave tags empty
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct VideoNfo {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    originaltitle: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    year: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    plot: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    runtime: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    premiered: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    rating: Option<String>,
+    #[serde(default)]
+    genre: Vec<String>,
+    #[serde(default)]
+    uniqueid: Vec<UniqueId>,
+    #[serde(default, skip_serializing)]
+    id: Option<String>,
+    #[serde(default, skip_serializing)]
+    imdbid: Option<String>,
+    #[serde(default, skip_serializing)]
+    tmdbid: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct UniqueId {
+    #[serde(rename = "@type")]
+    kind: String,
+    #[serde(rename = "@default", skip_serializing_if = "Option::is_none")]
+    default: Option<bool>,
+    #[serde(rename = "$text")]
+    value: String,
+}
+
+#[derive(Debug, Serialize)]
+struct EpisodeNfo {
+    title: String,
+    season: i32,
+    episode: i32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    plot: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    aired: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    runtime: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    rating: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    thumb: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scraper::mock::movie_details;
+
+    #[test]
+    fn test_movie_nfo_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("The Matrix.nfo");
+        let mut movie = movie_details("tmdb", "603", "The Matrix", 1999);
+        movie.runtime = Some(136);
+        movie.genres = vec!["Action".to_string(), "Science Fiction".to_string()];
+        movie.external_ids.imdb_id = Some("tt0133093".to_string());
+
+        write_movie_nfo(&path, &movie).unwrap();
+        let parsed = parse_movie_nfo(&path).unwrap();
+
+        assert_eq!(parsed.title.as_deref(), Some("The Matrix"));
+        assert_eq!(parsed.year, Some(1999));
+        assert_eq!(parsed.runtime, Some(136));
+        assert_eq!(parsed.genres, movie.genres);
+        assert_eq!(parsed.external_ids.tmdb_id.as_deref(), Some("603"));
+        assert_eq!(parsed.external_ids.imdb_id.as_deref(), Some("tt0133093"));
+    }
+
+    #[test]
+    fn test_tvshow_nfo_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tvshow.nfo");
+        let tv = TvMetadata {
+            id: "1396".to_string(),
+            name: "Breaking Bad".to_string(),
+            original_name: None,
+            first_air_date: Some("2008-01-20".to_string()),
+            last_air_date: None,
+            overview: Some("A chemistry teacher turns to crime.".to_string()),
+            poster_path: None,
+            backdrop_path: None,
+            vote_average: Some(8.9),
+            vote_count: None,
+            genres: vec!["Drama".to_string()],
+            number_of_seasons: Some(5),
+            number_of_episodes: Some(62),
+            seasons: Vec::new(),
+            episode_run_time: Vec::new(),
+            status: None,
+            original_language: None,
+            production_companies: Vec::new(),
+            cast: Vec::new(),
+            director: None,
+            writers: Vec::new(),
+            provider: "tmdb".to_string(),
+            external_ids: ExternalIds {
+                tmdb_id: Some("1396".to_string()),
+                tvdb_id: Some("81189".to_string()),
+                ..Default::default()
+            },
+        };
+
+        write_tvshow_nfo(&path, &tv).unwrap();
+        let parsed = parse_tvshow_nfo(&path).unwrap();
+
+        assert_eq!(parsed.title.as_deref(), Some("Breaking Bad"));
+        assert_eq!(parsed.year, Some(2008));
+        assert_eq!(parsed.plot, tv.overview);
+        assert_eq!(parsed.rating, Some(8.9));
+        assert_eq!(parsed.external_ids.tvdb_id.as_deref(), Some("81189"));
+    }
+
+    #[test]
+    fn test_parse_legacy_id_tags() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("movie.nfo");
+        std::fs::write(
+            &path,
+            "<movie><title>Heat</title><year></year><id>tt0113277</id><tmdbid>949</tmdbid></movie>",
+        )
+        .unwrap();
+
+        let parsed = parse_movie_nfo(&path).unwrap();
+
+        assert_eq!(parsed.year, None);
+        assert_eq!(parsed.external_ids.imdb_id.as_deref(), Some("tt0113277"));
+        assert_eq!(parsed.external_ids.tmdb_id.as_deref(), Some("949"));
+        assert_eq!(find_sidecar(&dir.path().join("Heat.mkv")), Some(path));
+    }
+}
@@ -0,0 +1,239 @@
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use tracing::info;
+
+use crate::{
+    error::ScrapeError,
+    scraper::{NamingContext, NamingTemplate},
+};
+
+/// How a file is placed into the organized library
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OrganizeMethod {
+    /// Create a symbolic link pointing at the original file
+    #[default]
+    Symlink,
+    /// Create a hard link to the original file (same filesystem only)
+    HardLink,
+    /// Copy the file, leaving the original in place
+    Copy,
+    /// Move the file into the library
+    Move,
+}
+
+/// What to do when the organize target already exists
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConflictPolicy {
+    /// Leave the existing file alone and report its path
+    #[default]
+    Skip,
+    /// Replace the existing file
+    Overwrite,
+    /// Pick a free name by appending ` (1)`, ` (2)`, ...
+    Rename,
+}
+
+/// Compute where a file goes under `target_dir`, keeping its extension
+///
+/// Uses the built-in template for the context when none is given.
+pub fn target_path(
+    target_dir: &Path,
+    template: Option<&NamingTemplate>,
+    context: &NamingContext,
+    source: &Path,
+) -> PathBuf {
+    let relative = match template {
+        Some(template) => template.render(context),
+        None => NamingTemplate::default_for(context).render(context),
+    };
+
+    let mut target = target_dir.join(relative);
+    if let Some(extension) = source.extension() {
+        let mut file_name = target.file_name().unwrap_or_default().to_os_string();
+        file_name.push(".");
+        file_name.push(extension);
+        target.set_file_name(file_name);
+    }
+    target
+}
+
+/// Place `source` at `target` using the given method, returning the final path
+///
+/// An existing target is handled according to `conflict`. With `dry_run` the
+/// same checks run but nothing on disk is changed.
+pub async fn organize_file(
+    source: &Path,
+    target: &Path,
+    method: OrganizeMethod,
+    conflict: ConflictPolicy,
+    dry_run: bool,
+) -> Result<PathBuf, ScrapeError> {
+    if !source.is_file() {
+        return Err(ScrapeError::FileNotFound(source.display().to_string()));
+    }
+
+    let mut target = target.to_path_buf();
+    if let Ok(existing) = tokio::fs::symlink_metadata(&target).await {
+        match conflict {
+            ConflictPolicy::Skip => {
+                info!("Skipping {}: target already exists", source.display());
+                return Ok(target);
+            }
+            ConflictPolicy::Overwrite if existing.is_dir() => {
+                return Err(ScrapeError::PathExists(target.display().to_string()));
+            }
+            ConflictPolicy::Overwrite => {
+                if !dry_run {
+                    tokio::fs::remove_file(&target).await?;
+                }
+            }
+            ConflictPolicy::Rename => target = free_path(&target).await,
+        }
+    }
+    let target = target.as_path();
+
+    if dry_run {
+        info!(
+            "Would organize {} -> {} ({:?})",
+            source.display(),
+            target.display(),
+            method
+        );
+        return Ok(target.to_path_buf());
+    }
+
+    if let Some(parent) = target.parent() {
+        tokio::fs::create_dir_all(parent).await.map_err(|e| {
+            ScrapeError::DirectoryCreationError(format!("{}: {e}", parent.display()))
+        })?;
+    }
+
+    match method {
+        OrganizeMethod::Symlink => create_symlink(source, target)
+            .await
+            .map_err(|e| ScrapeError::SymlinkError(format!("{}: {e}", target.display())))?,
+        OrganizeMethod::HardLink => tokio::fs::hard_link(source, target)
+            .await
+            .map_err(|e| ScrapeError::HardLinkError(format!("{}: {e}", target.display())))?,
+        OrganizeMethod::Copy => {
+            tokio::fs::copy(source, target)
+                .await
+                .map_err(|e| ScrapeError::CopyError(format!("{}: {e}", target.display())))?;
+        }
+        OrganizeMethod::Move => move_file(source, target)
+            .await
+            .map_err(|e| ScrapeError::MoveError(format!("{}: {e}", target.display())))?,
+    }
+
+    info!(
+        "Organized {} -> {} ({:?})",
+        source.display(),
+        target.display(),
+        method
+    );
+
+    Ok(target.to_path_buf())
+}
+
+#[cfg(unix)]
+async fn create_symlink(source: &Path, target: &Path) -> std::io::Result<()> {
+    let source = tokio::fs::canonicalize(source).await?;
+    tokio::fs::symlink(source, target).await
+}
+
+#[cfg(windows)]
+async fn create_symlink(source: &Path, target: &Path) -> std::io::Result<()> {
+    let source = tokio::fs::canonicalize(source).await?;
+    tokio::fs::symlink_file(source, target).await
+}
+
+/// First `name (n).ext` next to `path` that doesn't exist yet
+async fn free_path(path: &Path) -> PathBuf {
+    let stem = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let extension = path
+        .extension()
+        .map(|e| format!(".{}", e.to_string_lossy()))
+        .unwrap_or_default();
+
+    let mut n = 1;
+    loop {
+        let candidate = path.with_file_name(format!("{stem} ({n}){extension}"));
+        if tokio::fs::symlink_metadata(&candidate).await.is_err() {
+            return candidate;
+        }
+        n += 1;
+    }
+}
+
+/// Rename, falling back to copy + remove when crossing filesystems
+async fn move_file(source: &Path, target: &Path) -> std::io::Result<()> {
+    if tokio::fs::rename(source, target).await.is_ok() {
+        return Ok(());
+    }
+
+    tokio::fs::copy(source, target).await?;
+    tokio::fs::remove_file(source).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Fixture {
+        _dir: tempfile::TempDir,
+        source: PathBuf,
+        target: PathBuf,
+    }
+
+    fn fixture() -> Fixture {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("new.mkv");
+        let target = dir.path().join("library/Movie.mkv");
+        std::fs::write(&source, b"new").unwrap();
+        std::fs::create_dir_all(target.parent().unwrap()).unwrap();
+        std::fs::write(&target, b"old").unwrap();
+        Fixture {
+            _dir: dir,
+            source,
+            target,
+        }
+    }
+
+    async fn organize(f: &Fixture, conflict: ConflictPolicy) -> PathBuf {
+        organize_file(&f.source, &f.target, OrganizeMethod::Copy, conflict, false)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_skip_keeps_existing_target() {
+        let f = fixture();
+        assert_eq!(organize(&f, ConflictPolicy::Skip).await, f.target);
+        assert_eq!(std::fs::read(&f.target).unwrap(), b"old");
+    }
+
+    #[tokio::test]
+    async fn test_overwrite_replaces_existing_target() {
+        let f = fixture();
+        assert_eq!(organize(&f, ConflictPolicy::Overwrite).await, f.target);
+        assert_eq!(std::fs::read(&f.target).unwrap(), b"new");
+    }
+
+    #[tokio::test]
+    async fn test_rename_finds_free_name() {
+        let f = fixture();
+        std::fs::write(f.target.with_file_name("Movie (1).mkv"), b"older").unwrap();
+
+        let organized = organize(&f, ConflictPolicy::Rename).await;
+
+        assert_eq!(organized, f.target.with_file_name("Movie (2).mkv"));
+        assert_eq!(std::fs::read(&organized).unwrap(), b"new");
+        assert_eq!(std::fs::read(&f.target).unwrap(), b"old");
+    }
+}
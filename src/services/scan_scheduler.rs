@@ -0,0 +1,242 @@
+//! Periodic rescans of every enabled library folder
+//!
+//! Each run is pushed back by a random jitter and goes through the job queue
+//! like a manual scan. A run that comes due while the previous one is still
+//! going is skipped rather than queued behind it.
+
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::Duration,
+};
+
+use chrono::{DateTime, Utc};
+use parking_lot::RwLock;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use tokio::task::JoinHandle;
+use tracing::info;
+
+use crate::{
+    app::config::{ScanScheduleConfig, ScannerConfig},
+    entities::MediaItem,
+    services::{FileScanner, MetadataAgent, WebhookEvent, WebhookNotifier, job_queue::JobHandle},
+};
+
+/// Current state of the scan schedule
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduleInfo {
+    pub interval_seconds: u64,
+    pub jitter_seconds: u64,
+    /// Whether a scheduled scan is in progress
+    pub running: bool,
+    /// When the next run is due, jitter included
+    pub next_run: Option<DateTime<Utc>>,
+}
+
+/// Marks a scheduled scan as in progress until dropped
+pub struct ScheduledRun {
+    running: Arc<AtomicBool>,
+}
+
+impl ScheduledRun {
+    fn start(running: &Arc<AtomicBool>) -> Option<Self> {
+        (!running.swap(true, Ordering::AcqRel)).then(|| Self {
+            running: running.clone(),
+        })
+    }
+}
+
+impl Drop for ScheduledRun {
+    fn drop(&mut self) {
+        self.running.store(false, Ordering::Release);
+    }
+}
+
+/// Triggers rescans at a fixed interval plus jitter
+pub struct ScanScheduler {
+    interval: Duration,
+    jitter: Duration,
+    next_run: RwLock<Option<DateTime<Utc>>>,
+    running: Arc<AtomicBool>,
+}
+
+impl ScanScheduler {
+    #[must_use]
+    pub fn new(interval: Duration, jitter: Duration) -> Self {
+        Self {
+            interval,
+            jitter,
+            next_run: RwLock::new(None),
+            running: Arc::default(),
+        }
+    }
+
+    /// Build a scheduler from the config, or `None` when scheduling is off
+    ///
+    /// The schedule is read once; changing it requires a restart.
+    #[must_use]
+    pub fn from_config(config: &ScanScheduleConfig) -> Option<Self> {
+        let minutes = config.interval_minutes.filter(|&minutes| minutes > 0)?;
+        Some(Self::new(
+            Duration::from_secs(minutes * 60),
+            Duration::from_secs(config.jitter_seconds),
+        ))
+    }
+
+    #[must_use]
+    pub fn info(&self) -> ScheduleInfo {
+        ScheduleInfo {
+            interval_seconds: self.interval.as_secs(),
+            jitter_seconds: self.jitter.as_secs(),
+            running: self.running.load(Ordering::Acquire),
+            next_run: *self.next_run.read(),
+        }
+    }
+
+    /// Call `scan` on every tick until the returned task is aborted
+    ///
+    /// `scan` should hand the [`ScheduledRun`] to whatever does the work and
+    /// drop it once the scan is over; ticks arriving before then are skipped.
+    pub fn spawn<F>(self: Arc<Self>, scan: F) -> JoinHandle<()>
+    where
+        F: Fn(ScheduledRun) + Send + 'static,
+    {
+        tokio::spawn(async move {
+            loop {
+                let delay = self.next_delay();
+                *self.next_run.write() = chrono::Duration::from_std(delay)
+                    .ok()
+                    .map(|delay| Utc::now() + delay);
+                tokio::time::sleep(delay).await;
+
+                match ScheduledRun::start(&self.running) {
+                    Some(run) => {
+                        info!("Starting scheduled library scan");
+                        scan(run);
+                    }
+                    None => info!("Skipping scheduled scan; the previous one is still running"),
+                }
+            }
+        })
+    }
+
+    fn next_delay(&self) -> Duration {
+        let jitter_ms = u64::try_from(self.jitter.as_millis()).unwrap_or(u64::MAX);
+        self.interval + Duration::from_millis(rand::rng().random_range(0..=jitter_ms))
+    }
+}
+
+/// Scan every enabled library folder, then fetch metadata for whatever is
+/// still missing it and retry earlier failures that are due
+pub async fn scan_and_fetch_metadata(
+    db: sqlx::SqlitePool,
+    scanner_config: ScannerConfig,
+    metadata_agent: Option<Arc<MetadataAgent>>,
+    webhooks: Arc<WebhookNotifier>,
+    job: JobHandle,
+) -> Result<(), String> {
+    let scanner = FileScanner::new(db.clone())
+        .with_ignore_patterns(scanner_config.ignore_patterns)
+        .with_content_hashing(scanner_config.hash_files);
+    let results = scanner
+        .scan_all_libraries()
+        .await
+        .map_err(|e| format!("Failed to scan libraries: {e}"))?;
+
+    let total = results.len();
+    for (done, (folder, result)) in results.into_iter().enumerate() {
+        webhooks.notify(WebhookEvent::ScanComplete, folder.id, result.new_items);
+        if job.is_cancelled() {
+            return Ok(());
+        }
+
+        if let Some(metadata_agent) = &metadata_agent {
+            let items = MediaItem::list_without_metadata(&db, folder.id)
+                .await
+                .map_err(|e| format!("Failed to fetch items without metadata: {e}"))?;
+            let succeeded = metadata_agent
+                .batch_fetch_metadata_with_progress(items, false, job.cancellation_token(), |_| {})
+                .await
+                .iter()
+                .filter(|r| r.is_ok())
+                .count();
+            webhooks.notify(WebhookEvent::MetadataComplete, folder.id, succeeded);
+        }
+
+        job.set_progress(done + 1, total);
+    }
+
+    if let Some(metadata_agent) = &metadata_agent
+        && !job.is_cancelled()
+    {
+        metadata_agent
+            .retry_due()
+            .await
+            .map_err(|e| format!("Failed to retry metadata: {e}"))?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicUsize;
+
+    use super::*;
+
+    #[tokio::test(start_paused = true)]
+    async fn test_runs_on_schedule_and_skips_overlaps() {
+        let scheduler = Arc::new(ScanScheduler::new(
+            Duration::from_secs(60),
+            Duration::from_secs(5),
+        ));
+        assert!(scheduler.info().next_run.is_none());
+
+        let started = Arc::new(AtomicUsize::new(0));
+        let scan_time = Arc::new(RwLock::new(Duration::ZERO));
+        let task = scheduler.clone().spawn({
+            let started = started.clone();
+            let scan_time = scan_time.clone();
+            move |run| {
+                started.fetch_add(1, Ordering::SeqCst);
+                let scan_time = *scan_time.read();
+                tokio::spawn(async move {
+                    tokio::time::sleep(scan_time).await;
+                    drop(run);
+                });
+            }
+        });
+
+        // Quick scans: one per tick, each 60-65 seconds apart
+        tokio::time::sleep(Duration::from_secs(200)).await;
+        assert_eq!(started.load(Ordering::SeqCst), 3);
+        assert!(
+            scheduler
+                .info()
+                .next_run
+                .is_some_and(|next| next > Utc::now())
+        );
+
+        // A scan outlasting two ticks keeps them from starting another
+        *scan_time.write() = Duration::from_secs(150);
+        tokio::time::sleep(Duration::from_secs(70)).await;
+        assert_eq!(started.load(Ordering::SeqCst), 4);
+        assert!(scheduler.info().running);
+        tokio::time::sleep(Duration::from_secs(130)).await;
+        assert_eq!(started.load(Ordering::SeqCst), 4);
+
+        task.abort();
+    }
+
+    #[test]
+    fn test_zero_interval_disables_schedule() {
+        let config = ScanScheduleConfig {
+            interval_minutes: Some(0),
+            jitter_seconds: 30,
+        };
+        assert!(ScanScheduler::from_config(&config).is_none());
+    }
+}
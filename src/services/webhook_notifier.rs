@@ -0,0 +1,188 @@
+//! Webhook notifications for library events
+//!
+//! Each event is POSTed as JSON to every configured URL. Delivery happens in
+//! the background and never blocks or fails the operation that triggered it.
+
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::app::config::WebhooksConfig;
+
+/// How long a single delivery attempt may take
+const DELIVERY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Library events webhooks can subscribe to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookEvent {
+    /// A library folder scan finished
+    ScanComplete,
+    /// Metadata fetching for a library folder finished
+    MetadataComplete,
+}
+
+/// Body POSTed to webhook URLs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookPayload {
+    pub event: WebhookEvent,
+    pub folder_id: i64,
+    /// Items added by a scan, or items that received metadata
+    pub new_items: usize,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Sends library events to the configured webhook URLs
+#[derive(Debug, Clone)]
+pub struct WebhookNotifier {
+    client: reqwest::Client,
+    config: WebhooksConfig,
+}
+
+impl WebhookNotifier {
+    #[must_use]
+    pub fn new(config: WebhooksConfig) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(DELIVERY_TIMEOUT)
+            .build()
+            .unwrap_or_default();
+
+        Self { client, config }
+    }
+
+    /// Notify every subscribed URL of `event`
+    ///
+    /// Returns immediately; each delivery is retried once and failures are
+    /// only logged. Must be called from within a tokio runtime.
+    pub fn notify(&self, event: WebhookEvent, folder_id: i64, new_items: usize) {
+        if !self.config.subscribes_to(event) {
+            return;
+        }
+
+        let payload = WebhookPayload {
+            event,
+            folder_id,
+            new_items,
+            timestamp: Utc::now(),
+        };
+        for url in &self.config.urls {
+            let client = self.client.clone();
+            let url = url.clone();
+            let payload = payload.clone();
+            tokio::spawn(async move {
+                if let Err(e) = deliver(&client, &url, &payload).await {
+                    tracing::warn!("Retrying webhook delivery to {url}: {e}");
+                    if let Err(e) = deliver(&client, &url, &payload).await {
+                        tracing::warn!("Webhook delivery to {url} failed: {e}");
+                    }
+                }
+            });
+        }
+    }
+}
+
+async fn deliver(
+    client: &reqwest::Client,
+    url: &str,
+    payload: &WebhookPayload,
+) -> Result<(), reqwest::Error> {
+    client
+        .post(url)
+        .json(payload)
+        .send()
+        .await?
+        .error_for_status()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use wiremock::{
+        Mock, MockServer, Request, ResponseTemplate,
+        matchers::{method, path},
+    };
+
+    use super::*;
+
+    async fn received(server: &MockServer, count: usize) -> Vec<Request> {
+        tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                let requests = server.received_requests().await.unwrap_or_default();
+                if requests.len() >= count {
+                    return requests;
+                }
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("webhook was not delivered")
+    }
+
+    fn notifier(server: &MockServer, events: Vec<WebhookEvent>) -> WebhookNotifier {
+        WebhookNotifier::new(WebhooksConfig {
+            urls: vec![format!("{}/hook", server.uri())],
+            events,
+        })
+    }
+
+    #[tokio::test]
+    async fn test_scan_complete_posts_payload() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/hook"))
+            .respond_with(ResponseTemplate::new(204))
+            .mount(&server)
+            .await;
+
+        notifier(&server, Vec::new()).notify(WebhookEvent::ScanComplete, 7, 3);
+
+        let requests = received(&server, 1).await;
+        let body: serde_json::Value = requests[0].body_json().unwrap();
+        assert_eq!(body["event"], "scan_complete");
+        assert_eq!(body["folder_id"], 7);
+        assert_eq!(body["new_items"], 3);
+        assert!(
+            body["timestamp"]
+                .as_str()
+                .is_some_and(|t| t.parse::<DateTime<Utc>>().is_ok())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_failed_delivery_is_retried_once() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(500))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&server)
+            .await;
+
+        notifier(&server, Vec::new()).notify(WebhookEvent::MetadataComplete, 1, 2);
+
+        assert_eq!(received(&server, 2).await.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_unsubscribed_events_are_not_sent() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&server)
+            .await;
+
+        let notifier = notifier(&server, vec![WebhookEvent::MetadataComplete]);
+        notifier.notify(WebhookEvent::ScanComplete, 1, 1);
+        notifier.notify(WebhookEvent::MetadataComplete, 1, 1);
+
+        let requests = received(&server, 1).await;
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(server.received_requests().await.unwrap().len(), 1);
+        let body: serde_json::Value = requests[0].body_json().unwrap();
+        assert_eq!(body["event"], "metadata_complete");
+    }
+}
@@ -1,2 +1,3 @@
 pub mod graceful_shutdown;
 pub mod logger;
+pub mod title;
@@ -0,0 +1,177 @@
+//! Cleaning noisy file names into searchable titles
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+static BRACKETED: Lazy<Regex> = Lazy::new(|| Regex::new(r"\[([^\]]*)\]|\{([^}]*)\}").unwrap());
+static PARENTHESIZED: Lazy<Regex> = Lazy::new(|| Regex::new(r"\(([^)]*)\)").unwrap());
+static YEAR: Lazy<Regex> = Lazy::new(|| Regex::new(r"\b(?:19|20)\d{2}\b").unwrap());
+static EMPTY_BRACKETS: Lazy<Regex> = Lazy::new(|| Regex::new(r"\(\s*\)|\[\s*\]").unwrap());
+/// Quality, source and codec tags; everything from the first one on is noise
+static TAG: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"(?i)\b(?:2160p|1080p|720p|576p|480p|4k|uhd|hdr(?:10)?|blu-?ray|bdrip|brrip|bdremux|remux|web-?dl|webrip|hdtv|dvdrip|hdrip|x264|x265|h\.?264|h\.?265|hevc|avc|10-?bit|aac(?:2\.0)?|ac3|e-?ac3|dts(?:-hd)?|ddp?5\.1|truehd|atmos|flac|repack)\b",
+    )
+    .unwrap()
+});
+
+/// Turn a file name into a title and year suitable for provider searches
+///
+/// Release group brackets and quality, source and codec tags are dropped,
+/// along with everything after the first tag. Dots and underscores become
+/// spaces, and a year anywhere in the name is extracted, cutting off what
+/// follows it as in scene-style `The.Matrix.1999.1080p.BluRay.x264-GROUP`. A
+/// leading number such as `1917` is kept as the title.
+#[must_use]
+pub fn clean_title(raw: &str) -> (String, Option<i32>) {
+    // Keep a bracketed year, drop every other bracketed tag
+    let unbracketed = BRACKETED.replace_all(raw, |caps: &regex::Captures| {
+        let inner = caps
+            .get(1)
+            .or_else(|| caps.get(2))
+            .map_or("", |m| m.as_str());
+        if YEAR.find(inner).is_some_and(|m| m.as_str() == inner.trim()) {
+            format!(" {} ", inner.trim())
+        } else {
+            " ".to_string()
+        }
+    });
+
+    let unbracketed = PARENTHESIZED.replace_all(&unbracketed, |caps: &regex::Captures| {
+        if TAG.is_match(&caps[1]) {
+            " ".to_string()
+        } else {
+            caps[0].to_string()
+        }
+    });
+
+    // Dots only separate words when the name has no spaces, unlike `Mr. Robot`
+    let mut name = unbracketed.replace('_', " ");
+    if !name.trim().contains(' ') {
+        name = name.replace('.', " ");
+    }
+
+    let name = TAG
+        .find(&name)
+        .map_or(name.as_str(), |m| &name[..m.start()]);
+    let year = YEAR
+        .find_iter(name)
+        .filter(|m| !name[..m.start()].trim_matches(is_separator).is_empty())
+        .last();
+    let (title, year) = match year {
+        Some(m) => (&name[..m.start()], m.as_str().parse().ok()),
+        None => (name, None),
+    };
+
+    let title = EMPTY_BRACKETS.replace_all(title, " ");
+    let title = title
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .trim_matches(is_separator)
+        .to_string();
+    if title.is_empty() {
+        return (raw.trim().to_string(), year);
+    }
+
+    (title, year)
+}
+
+/// Guess the language of a title written in CJK script
+///
+/// Any kana means Japanese and any Hangul Korean; Han characters alone are
+/// taken as Chinese. Returns `None` for titles without CJK characters.
+#[must_use]
+pub fn cjk_language(title: &str) -> Option<&'static str> {
+    let (mut han, mut kana, mut hangul) = (false, false, false);
+    for c in title.chars() {
+        match c {
+            '\u{3040}'..='\u{30FF}' | '\u{31F0}'..='\u{31FF}' | '\u{FF66}'..='\u{FF9F}' => {
+                kana = true;
+            }
+            '\u{1100}'..='\u{11FF}' | '\u{3130}'..='\u{318F}' | '\u{AC00}'..='\u{D7AF}' => {
+                hangul = true;
+            }
+            '\u{3400}'..='\u{4DBF}'
+            | '\u{4E00}'..='\u{9FFF}'
+            | '\u{F900}'..='\u{FAFF}'
+            | '\u{20000}'..='\u{2FA1F}' => han = true,
+            _ => {}
+        }
+    }
+
+    if kana {
+        Some("ja-JP")
+    } else if hangul {
+        Some("ko-KR")
+    } else if han {
+        Some("zh-CN")
+    } else {
+        None
+    }
+}
+
+fn is_separator(c: char) -> bool {
+    matches!(c, ' ' | '-' | '.' | '_' | '(' | '[')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clean_title() {
+        let cases = [
+            ("The Matrix (1999)", "The Matrix", Some(1999)),
+            (
+                "The.Matrix.1999.1080p.BluRay.x264-GROUP",
+                "The Matrix",
+                Some(1999),
+            ),
+            (
+                "Blade_Runner_2049_2017_2160p_UHD_HDR",
+                "Blade Runner 2049",
+                Some(2017),
+            ),
+            (
+                "2001.A.Space.Odyssey.1968.REMUX",
+                "2001 A Space Odyssey",
+                Some(1968),
+            ),
+            ("1917.2019.WEB-DL.x265", "1917", Some(2019)),
+            ("Mr. Robot (2015) [1080p]", "Mr. Robot", Some(2015)),
+            ("Breaking.Bad.720p.HDTV.x264", "Breaking Bad", None),
+            ("Charlotte's Web", "Charlotte's Web", None),
+            (
+                "[SubsPlease] Sousou no Frieren [1080p]",
+                "Sousou no Frieren",
+                None,
+            ),
+            (
+                "[Erai-raws] Spy x Family [2022][1080p][HEVC][Multiple Subtitle]",
+                "Spy x Family",
+                Some(2022),
+            ),
+            ("[Group] Cowboy Bebop (BD 1080p AAC)", "Cowboy Bebop", None),
+            ("Akira {tmdb-149} (1988)", "Akira", Some(1988)),
+            (
+                "[Lilith-Raws] 葬送のフリーレン [WebRip 1080p]",
+                "葬送のフリーレン",
+                None,
+            ),
+        ];
+
+        for (raw, title, year) in cases {
+            assert_eq!(clean_title(raw), (title.to_string(), year), "{raw}");
+        }
+    }
+
+    #[test]
+    fn test_cjk_language() {
+        assert_eq!(cjk_language("葬送のフリーレン"), Some("ja-JP"));
+        assert_eq!(cjk_language("進撃の巨人 Season 2"), Some("ja-JP"));
+        assert_eq!(cjk_language("鬼灭之刃"), Some("zh-CN"));
+        assert_eq!(cjk_language("오징어 게임"), Some("ko-KR"));
+        assert_eq!(cjk_language("Sousou no Frieren"), None);
+    }
+}
@@ -0,0 +1,17 @@
+import type { ApiResponse } from "../types/api";
+import { alovaInstance } from "./client";
+
+export interface CacheStats {
+	hits: number;
+	misses: number;
+	entry_count: number;
+	hit_rate: number;
+}
+
+export const getCacheStats = () => {
+	return alovaInstance.Get<ApiResponse<CacheStats>>("/cache/stats");
+};
+
+export const clearCache = () => {
+	return alovaInstance.Post<ApiResponse<CacheStats>>("/cache/clear");
+};
@@ -4,6 +4,17 @@ import { alovaInstance } from "./client";
 export interface HealthResponse {
 	status: string;
 	database: string;
+	components: ComponentHealth[];
+	cache_entries: number | null;
+	config_loaded: boolean;
+}
+
+export interface ComponentHealth {
+	name: string;
+	critical: boolean;
+	healthy: boolean;
+	latency_ms: number;
+	error: string | null;
 }
 
 export const getHealth = () => {
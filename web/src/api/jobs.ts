@@ -0,0 +1,27 @@
+import type { ApiResponse } from "../types/api";
+import { alovaInstance } from "./client";
+
+export type JobStatus = "queued" | "running" | "done" | "failed" | "cancelled";
+
+export interface JobInfo {
+	id: number;
+	kind: string;
+	status: JobStatus;
+	processed: number;
+	total: number | null;
+	error: string | null;
+	created_at: string;
+	finished_at: string | null;
+}
+
+export const getJobs = () => {
+	return alovaInstance.Get<ApiResponse<JobInfo[]>>("/jobs");
+};
+
+export const getJob = (id: number) => {
+	return alovaInstance.Get<ApiResponse<JobInfo>>(`/jobs/${id}`);
+};
+
+export const cancelJob = (id: number) => {
+	return alovaInstance.Post<ApiResponse<JobInfo>>(`/jobs/${id}/cancel`);
+};
@@ -2,7 +2,9 @@ import type { ApiResponse } from "../types/api";
 import type {
 	CreateLibraryFolderRequest,
 	LibraryFolder,
+	RefreshMetadataResponse,
 	ScanResponse,
+	UpdateLibraryFolderRequest,
 } from "../types/library-folder";
 import { alovaInstance } from "./client";
 
@@ -23,6 +25,16 @@ export const createLibraryFolder = (data: CreateLibraryFolderRequest) => {
 	);
 };
 
+export const updateLibraryFolder = (
+	id: number,
+	data: UpdateLibraryFolderRequest,
+) => {
+	return alovaInstance.Put<ApiResponse<LibraryFolder>>(
+		`/library-folders/${id}`,
+		data,
+	);
+};
+
 export const deleteLibraryFolder = (id: number) => {
 	return alovaInstance.Delete<ApiResponse<string>>(`/library-folders/${id}`);
 };
@@ -33,6 +45,14 @@ export const scanLibraryFolder = (id: number) => {
 	);
 };
 
+export const refreshLibraryFolderMetadata = (id: number, sync = false) => {
+	return alovaInstance.Post<ApiResponse<RefreshMetadataResponse>>(
+		`/library-folders/${id}/refresh-metadata`,
+		undefined,
+		{ params: { sync } },
+	);
+};
+
 export const scanAllLibraryFolders = () => {
 	return alovaInstance.Post<ApiResponse<ScanResponse[]>>(
 		"/library-folders/scan-all",
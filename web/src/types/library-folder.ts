@@ -16,14 +16,30 @@ export interface CreateLibraryFolderRequest {
 	media_type: MediaType;
 }
 
+export interface UpdateLibraryFolderRequest {
+	name?: string;
+	path?: string;
+	media_type?: MediaType;
+	enabled?: boolean;
+}
+
 export interface ScanResult {
-	added: number;
-	updated: number;
-	skipped: number;
+	total_files: number;
+	new_items: number;
+	existing_items: number;
 	errors: number;
+	new_subtitles: number;
 }
 
 export interface ScanResponse {
 	folder: LibraryFolder;
 	result: ScanResult;
+	metadata_job_id?: number;
+}
+
+export interface RefreshMetadataResponse {
+	total: number;
+	succeeded: number | null;
+	failed: number | null;
+	job_id?: number;
 }
@@ -27,6 +27,54 @@ export interface VideoMetadata {
 	genres: string | null;
 	created_at: string;
 	updated_at: string;
+	anilist_id: number | null;
+	mal_id: number | null;
+	bangumi_id: number | null;
+	episode_count: number | null;
+}
+
+export interface BookMetadata {
+	id: number;
+	media_item_id: number;
+	isbn: string | null;
+	authors: string | null;
+	publisher: string | null;
+	published_date: string | null;
+	page_count: number | null;
+	language: string | null;
+	overview: string | null;
+	cover_path: string | null;
+	genres: string | null;
+	rating: number | null;
+	created_at: string;
+	updated_at: string;
+}
+
+export interface ComicMetadata {
+	id: number;
+	media_item_id: number;
+	series: string | null;
+	volume: number | null;
+	issue_number: string | null;
+	authors: string | null;
+	publisher: string | null;
+	published_date: string | null;
+	page_count: number | null;
+	overview: string | null;
+	cover_path: string | null;
+	genres: string | null;
+	rating: number | null;
+	created_at: string;
+	updated_at: string;
+}
+
+export interface Subtitle {
+	id: number;
+	media_item_id: number;
+	file_path: string;
+	language: string | null;
+	format: string;
+	created_at: string;
 }
 
 export interface MediaItemWithMetadata {
@@ -39,9 +87,14 @@ export interface MediaItemWithMetadata {
 	added_at: string;
 	updated_at: string;
 	metadata: VideoMetadata | null;
+	book_metadata?: BookMetadata;
+	comic_metadata?: ComicMetadata;
+	subtitles?: Subtitle[];
 }
 
 export interface LibraryResponse {
 	items: MediaItemWithMetadata[];
 	total: number;
+	page: number;
+	per_page: number;
 }